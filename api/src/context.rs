@@ -8,8 +8,10 @@ use crate::{
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
         block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        ForbiddenError, GoneError, InternalError, NotFoundError, ServiceUnavailableError,
+        StdApiError,
     },
+    streaming::CommitNotifier,
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{
@@ -19,9 +21,13 @@ use aptos_api_types::{
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
 use aptos_db_indexer::table_info_reader::TableInfoReader;
+use aptos_event_notifications::EventNotificationListener;
 use aptos_gas_schedule::{AptosGasParameters, FromOnChainGasSchedule};
 use aptos_logger::{error, info, Schema};
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolStats, PendingAccountInfo,
+    PendingTransactionInfo, SubmissionStatus,
+};
 use aptos_storage_interface::{
     state_view::{DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView},
     DbReader, Order, MAX_REQUEST_LIMIT,
@@ -64,6 +70,7 @@ use std::{
     },
     time::Instant,
 };
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -77,6 +84,9 @@ pub struct Context {
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
     view_function_stats: Arc<FunctionStats>,
     simulate_txn_stats: Arc<FunctionStats>,
+    commit_notifier: Arc<CommitNotifier>,
+    /// Bounds the number of open streams by `max_streaming_connections`
+    stream_slots: Arc<Semaphore>,
     pub table_info_reader: Option<Arc<dyn TableInfoReader>>,
 }
 
@@ -107,6 +117,7 @@ impl Context {
                 )),
             )
         };
+        let stream_slots = Arc::new(Semaphore::new(node_config.api.max_streaming_connections));
         Self {
             chain_id,
             db,
@@ -129,6 +140,8 @@ impl Context {
             })),
             view_function_stats,
             simulate_txn_stats,
+            commit_notifier: Arc::new(CommitNotifier::new()),
+            stream_slots,
             table_info_reader,
         }
     }
@@ -149,7 +162,16 @@ impl Context {
         self.node_config.api.max_account_modules_page_size
     }
 
+    /// Whether the node is a read replica, serving the DB of another node.
+    pub fn is_read_replica(&self) -> bool {
+        self.node_config.storage.read_replica.is_some()
+    }
+
     pub fn latest_state_view(&self) -> Result<DbStateView> {
+        if self.is_read_replica() {
+            let latest_ledger_info = self.db.get_latest_ledger_info()?;
+            return self.state_view_at_version(latest_ledger_info.ledger_info().version());
+        }
         Ok(self.db.latest_state_checkpoint_view()?)
     }
 
@@ -157,8 +179,14 @@ impl Context {
         &self,
         ledger_info: &LedgerInfo,
     ) -> Result<DbStateView, E> {
-        self.db
-            .latest_state_checkpoint_view()
+        // The latest state checkpoint of a read replica only advances with the snapshots the
+        // primary persists, the state at the latest version is read from the K/V DB instead.
+        let state_view = if self.is_read_replica() {
+            self.state_view_at_version(ledger_info.version())
+        } else {
+            self.db.latest_state_checkpoint_view().map_err(Into::into)
+        };
+        state_view
             .context("Failed to read latest state checkpoint from DB")
             .map_err(|e| E::internal_with_code(e, AptosErrorCode::InternalError, ledger_info))
     }
@@ -777,6 +805,104 @@ impl Context {
             .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
     }
 
+    /// Returns the transactions touching the account, starting at `start_version` in the given
+    /// order. The transactions are returned in ascending order of version either way.
+    pub fn get_account_touched_transactions<E: InternalError>(
+        &self,
+        address: AccountAddress,
+        start_version: u64,
+        order: Order,
+        limit: u16,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<TransactionOnChainData>, E> {
+        let txns = self
+            .db
+            .get_account_touched_transactions(
+                address,
+                start_version,
+                order,
+                limit as u64,
+                true,
+                ledger_version,
+            )
+            .context("Failed to retrieve account touched transactions")
+            .map_err(|err| {
+                E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+            })?;
+        let mut txns = txns
+            .into_inner()
+            .into_iter()
+            .map(|t| self.convert_into_transaction_on_chain_data(t))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse account touched transactions")
+            .map_err(|err| {
+                E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+            })?;
+        if order == Order::Descending {
+            txns.reverse();
+        }
+        Ok(txns)
+    }
+
+    /// Returns the changes of the state value, starting at `start_version` or, when no start
+    /// version is given, the latest ones, in the order of versions
+    ///
+    /// State values are pruned separately from the ledger, so the start version is checked
+    /// against the oldest state value version rather than the oldest ledger version
+    pub fn get_state_value_history<E: InternalError + GoneError>(
+        &self,
+        state_key: &StateKey,
+        start_version: Option<u64>,
+        limit: u16,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<(Version, Option<StateValue>)>, E> {
+        let (start_version, order) = match start_version {
+            Some(start_version) => (start_version, Order::Ascending),
+            None => (ledger_version, Order::Descending),
+        };
+
+        let error_if_pruned = || -> Result<(), E> {
+            let first_version = self
+                .db
+                .get_first_state_value_version()
+                .context("Failed to retrieve the oldest state value version")
+                .map_err(|err| {
+                    E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+                })?
+                .unwrap_or(0);
+            if start_version < first_version {
+                return Err(version_pruned(start_version, ledger_info));
+            }
+            Ok(())
+        };
+        error_if_pruned()?;
+
+        let mut history = self
+            .db
+            .get_state_value_history(
+                state_key,
+                start_version,
+                order,
+                limit as u64,
+                ledger_version,
+            )
+            .or_else(|err| {
+                // The pruner can catch up with the start version in the meantime
+                error_if_pruned()?;
+                Err(E::internal_with_code(
+                    anyhow!(err).context("Failed to retrieve state value history"),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                ))
+            })?;
+        if order == Order::Descending {
+            history.reverse();
+        }
+        Ok(history)
+    }
+
     pub fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -803,6 +929,52 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_pending_transactions(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<PendingTransactionInfo>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetPendingTransactions(
+                limit, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_pending_account_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<PendingAccountInfo> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetPendingAccountTransactions(
+                address, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_mempool_stats(&self) -> Result<MempoolStats> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetMempoolStats(req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -1314,6 +1486,23 @@ impl Context {
     pub fn simulate_txn_stats(&self) -> &FunctionStats {
         &self.simulate_txn_stats
     }
+
+    pub(crate) fn commit_notifier(&self) -> &CommitNotifier {
+        &self.commit_notifier
+    }
+
+    /// Reserves a slot for a new stream, or returns None if `max_streaming_connections` streams
+    /// are already open. The slot is released when the permit is dropped.
+    pub(crate) fn try_reserve_stream_slot(&self) -> Option<OwnedSemaphorePermit> {
+        self.stream_slots.clone().try_acquire_owned().ok()
+    }
+
+    /// Sets the subscription to new block events that the streaming APIs are notified of
+    /// commits from
+    pub fn set_commit_notifications(&self, commit_notifications: EventNotificationListener) {
+        self.commit_notifier
+            .set_commit_notifications(commit_notifications);
+    }
 }
 
 pub struct GasScheduleCache {
@@ -16,12 +16,15 @@ mod events;
 mod failpoint;
 mod index;
 mod log;
+mod mempool;
 pub mod metrics;
 mod page;
 mod response;
 mod runtime;
 mod set_failpoints;
+mod simulation;
 mod state;
+mod streaming;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
@@ -44,6 +47,12 @@ pub enum ApiTags {
     /// General information
     General,
 
+    /// Access to pending transactions in mempool
+    Mempool,
+
+    /// Streams of committed transactions and events
+    Streaming,
+
     /// Access to tables
     Tables,
 
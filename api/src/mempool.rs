@@ -0,0 +1,319 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    context::{api_spawn_blocking, Context},
+    failpoint::fail_point_poem,
+    page::Page,
+    response::{
+        api_disabled, BasicError, BasicResponse, BasicResponseStatus, BasicResult, InternalError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, LedgerInfo, MempoolAccountTransactions,
+    MempoolAccountTransactionsBcs, MempoolStats, MempoolTransaction, MempoolTransactionBcs,
+    MoveConverter, SequenceNumberGap,
+};
+use aptos_mempool::{PendingAccountInfo, PendingTransactionInfo};
+use aptos_vm::data_cache::AsMoveResolver;
+use move_core_types::resolver::ModuleResolver;
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// API for inspecting the pending transactions in the node's mempool
+#[derive(Clone)]
+pub struct MempoolApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl MempoolApi {
+    /// Get mempool transactions
+    ///
+    /// Retrieves the transactions pending in the node's mempool. Transactions that can be
+    /// included in the next blocks are returned first, in the order they are pulled into
+    /// blocks, followed by parked transactions.
+    ///
+    /// This API is disabled unless `mempool_introspection_enabled` is set in the API config.
+    #[oai(
+        path = "/mempool/transactions",
+        method = "get",
+        operation_id = "get_mempool_transactions",
+        tag = "ApiTags::Mempool"
+    )]
+    async fn get_mempool_transactions(
+        &self,
+        accept_type: AcceptType,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResult<Vec<MempoolTransaction>> {
+        fail_point_poem("endpoint_get_mempool_transactions")?;
+        self.check_mempool_introspection_enabled("Get mempool transactions", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let page = Page::new(None, limit.0, self.context.max_transactions_page_size());
+        let limit = page.limit(&ledger_info)?;
+
+        let txns = self
+            .context
+            .get_pending_transactions(limit as usize)
+            .await
+            .context("Failed to get transactions from mempool")
+            .map_err(|err| {
+                BasicError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+            })?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.render_transactions(&accept_type, txns, &ledger_info)).await
+    }
+
+    /// Get mempool transactions of an account
+    ///
+    /// Retrieves the transactions of the given account pending in the node's mempool, ordered
+    /// by sequence number, along with the gaps in sequence numbers that cause transactions to
+    /// be parked.
+    ///
+    /// This API is disabled unless `mempool_introspection_enabled` is set in the API config.
+    #[oai(
+        path = "/mempool/accounts/:address",
+        method = "get",
+        operation_id = "get_mempool_account_transactions",
+        tag = "ApiTags::Mempool"
+    )]
+    async fn get_mempool_account_transactions(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+    ) -> BasicResult<MempoolAccountTransactions> {
+        fail_point_poem("endpoint_get_mempool_account_transactions")?;
+        self.check_mempool_introspection_enabled("Get mempool account transactions", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+
+        let account_info = self
+            .context
+            .get_pending_account_transactions(address.0.into())
+            .await
+            .context("Failed to get account transactions from mempool")
+            .map_err(|err| {
+                BasicError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+            })?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.render_account_transactions(&accept_type, account_info, &ledger_info)
+        })
+        .await
+    }
+
+    /// Get mempool stats
+    ///
+    /// Retrieves the number of transactions in the node's mempool, its size and its capacity.
+    ///
+    /// This API is disabled unless `mempool_introspection_enabled` is set in the API config.
+    #[oai(
+        path = "/mempool/stats",
+        method = "get",
+        operation_id = "get_mempool_stats",
+        tag = "ApiTags::Mempool"
+    )]
+    async fn get_mempool_stats(&self, accept_type: AcceptType) -> BasicResult<MempoolStats> {
+        fail_point_poem("endpoint_get_mempool_stats")?;
+        self.check_mempool_introspection_enabled("Get mempool stats", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+
+        let stats = self
+            .context
+            .get_mempool_stats()
+            .await
+            .context("Failed to get stats from mempool")
+            .map_err(|err| {
+                BasicError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+            })?;
+        let stats = MempoolStats {
+            num_transactions: (stats.num_transactions as u64).into(),
+            num_ready_transactions: (stats.num_ready_transactions as u64).into(),
+            num_parked_transactions: (stats.num_parked_transactions as u64).into(),
+            num_accounts: (stats.num_accounts as u64).into(),
+            size_bytes: (stats.size_bytes as u64).into(),
+            capacity: (stats.capacity as u64).into(),
+            capacity_bytes: (stats.capacity_bytes as u64).into(),
+            capacity_per_user: (stats.capacity_per_user as u64).into(),
+        };
+
+        match accept_type {
+            AcceptType::Json => {
+                BasicResponse::try_from_json((stats, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((stats, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+}
+
+impl MempoolApi {
+    fn check_mempool_introspection_enabled(
+        &self,
+        api_name: &'static str,
+        accept_type: &AcceptType,
+    ) -> Result<(), BasicError> {
+        if !self.context.node_config.api.mempool_introspection_enabled {
+            return Err(api_disabled(api_name));
+        }
+        self.context.check_api_output_enabled(api_name, accept_type)
+    }
+
+    fn render_transactions(
+        &self,
+        accept_type: &AcceptType,
+        txns: Vec<PendingTransactionInfo>,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResult<Vec<MempoolTransaction>> {
+        let now = SystemTime::now();
+        match accept_type {
+            AcceptType::Json => {
+                let state_view = self.context.latest_state_view_poem(ledger_info)?;
+                let resolver = state_view.as_move_resolver();
+                let converter = resolver.as_converter(
+                    self.context.db.clone(),
+                    self.context.table_info_reader.clone(),
+                );
+                let txns = txns
+                    .into_iter()
+                    .map(|txn| render_transaction(&converter, txn, now, ledger_info))
+                    .collect::<Result<Vec<_>, _>>()?;
+                BasicResponse::try_from_json((txns, ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                let txns: Vec<_> = txns
+                    .into_iter()
+                    .map(|txn| render_transaction_bcs(txn, now))
+                    .collect();
+                BasicResponse::try_from_bcs((txns, ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
+    fn render_account_transactions(
+        &self,
+        accept_type: &AcceptType,
+        account_info: PendingAccountInfo,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResult<MempoolAccountTransactions> {
+        let now = SystemTime::now();
+        match accept_type {
+            AcceptType::Json => {
+                let state_view = self.context.latest_state_view_poem(ledger_info)?;
+                let resolver = state_view.as_move_resolver();
+                let converter = resolver.as_converter(
+                    self.context.db.clone(),
+                    self.context.table_info_reader.clone(),
+                );
+                let transactions = account_info
+                    .transactions
+                    .into_iter()
+                    .map(|txn| render_transaction(&converter, txn, now, ledger_info))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let account_transactions = MempoolAccountTransactions {
+                    address: account_info.address.into(),
+                    sequence_number: account_info.sequence_number.map(Into::into),
+                    transactions,
+                    sequence_number_gaps: account_info
+                        .sequence_number_gaps
+                        .into_iter()
+                        .map(|gap| SequenceNumberGap {
+                            start: gap.start.into(),
+                            end: gap.end.into(),
+                        })
+                        .collect(),
+                };
+                BasicResponse::try_from_json((
+                    account_transactions,
+                    ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                let account_transactions = MempoolAccountTransactionsBcs {
+                    address: account_info.address,
+                    sequence_number: account_info.sequence_number,
+                    transactions: account_info
+                        .transactions
+                        .into_iter()
+                        .map(|txn| render_transaction_bcs(txn, now))
+                        .collect(),
+                    sequence_number_gaps: account_info
+                        .sequence_number_gaps
+                        .into_iter()
+                        .map(|gap| (gap.start, gap.end))
+                        .collect(),
+                };
+                BasicResponse::try_from_bcs((
+                    account_transactions,
+                    ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+        }
+    }
+}
+
+fn render_transaction<R: ModuleResolver + ?Sized>(
+    converter: &MoveConverter<'_, R>,
+    txn: PendingTransactionInfo,
+    now: SystemTime,
+    ledger_info: &LedgerInfo,
+) -> Result<MempoolTransaction, BasicError> {
+    let (insertion_timestamp_usecs, time_in_mempool_usecs) = mempool_times(&txn, now);
+    let transaction = converter
+        .try_into_pending_transaction_poem(txn.txn)
+        .context("Failed to convert mempool transaction to PendingTransaction")
+        .map_err(|err| {
+            BasicError::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })?;
+    Ok(MempoolTransaction {
+        transaction,
+        priority_score: txn.priority_score.into(),
+        system_expiration_timestamp_usecs: (txn.system_expiration_time.as_micros() as u64).into(),
+        insertion_timestamp_usecs: insertion_timestamp_usecs.into(),
+        time_in_mempool_usecs: time_in_mempool_usecs.into(),
+        parked: txn.parked,
+    })
+}
+
+fn render_transaction_bcs(txn: PendingTransactionInfo, now: SystemTime) -> MempoolTransactionBcs {
+    let (insertion_timestamp_usecs, time_in_mempool_usecs) = mempool_times(&txn, now);
+    MempoolTransactionBcs {
+        transaction: txn.txn,
+        priority_score: txn.priority_score,
+        system_expiration_timestamp_usecs: txn.system_expiration_time.as_micros() as u64,
+        insertion_timestamp_usecs,
+        time_in_mempool_usecs,
+        parked: txn.parked,
+    }
+}
+
+/// Returns the insertion timestamp and the time spent in mempool of the transaction, in
+/// microseconds
+fn mempool_times(txn: &PendingTransactionInfo, now: SystemTime) -> (u64, u64) {
+    let insertion_timestamp = txn
+        .insertion_time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let time_in_mempool = now.duration_since(txn.insertion_time).unwrap_or_default();
+    (
+        insertion_timestamp.as_micros() as u64,
+        time_in_mempool.as_micros() as u64,
+    )
+}
@@ -4,8 +4,8 @@
 
 use aptos_global_constants::DEFAULT_BUCKETS;
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -87,3 +87,21 @@ pub static GAS_USED: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static ACTIVE_STREAMS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_api_active_streams",
+        "Number of open streams of the streaming APIs grouped by stream",
+        &["stream"]
+    )
+    .unwrap()
+});
+
+pub static STREAMED_ITEMS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_streamed_items",
+        "Number of items pushed by the streaming APIs grouped by stream",
+        &["stream"]
+    )
+    .unwrap()
+});
@@ -5,12 +5,13 @@
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
     context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    log::middleware_log, mempool::MempoolApi, set_failpoints, state::StateApi,
+    streaming::StreamingApi, transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
 use aptos_db_indexer::table_info_reader::TableInfoReader;
+use aptos_event_notifications::EventNotificationListener;
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::DbReader;
@@ -36,11 +37,15 @@ pub fn bootstrap(
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     table_info_reader: Option<Arc<dyn TableInfoReader>>,
+    commit_notifications: Option<EventNotificationListener>,
 ) -> anyhow::Result<Runtime> {
     let max_runtime_workers = get_max_runtime_workers(&config.api);
     let runtime = aptos_runtimes::spawn_named_runtime("api".into(), Some(max_runtime_workers));
 
     let context = Context::new(chain_id, db, mp_sender, config.clone(), table_info_reader);
+    if let Some(commit_notifications) = commit_notifications {
+        context.set_commit_notifications(commit_notifications);
+    }
 
     attach_poem_to_runtime(runtime.handle(), context.clone(), config, false)
         .context("Failed to attach poem to runtime")?;
@@ -105,7 +110,9 @@ pub fn get_api_service(
         BlocksApi,
         EventsApi,
         IndexApi,
+        MempoolApi,
         StateApi,
+        StreamingApi,
         TransactionsApi,
         ViewFunctionApi,
     ),
@@ -128,9 +135,15 @@ pub fn get_api_service(
         IndexApi {
             context: context.clone(),
         },
+        MempoolApi {
+            context: context.clone(),
+        },
         StateApi {
             context: context.clone(),
         },
+        StreamingApi {
+            context: context.clone(),
+        },
         TransactionsApi {
             context: context.clone(),
         },
@@ -342,6 +355,7 @@ mod tests {
             context.db.clone(),
             context.mempool.ac_client.clone(),
             None,
+            None,
         );
         assert!(ret.is_ok());
 
@@ -0,0 +1,179 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::context::Context;
+use anyhow::{bail, format_err, Context as AnyhowContext};
+use aptos_api_types::{AsConverter, MoveModuleBytecode, StateOverrides};
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::CORE_CODE_ADDRESS,
+    state_store::{
+        errors::StateviewError,
+        state_key::{StateKey, StateKeyInner},
+        state_storage_usage::StateStorageUsage,
+        state_value::StateValue,
+        table::TableHandle,
+        StateView, StateViewId, TStateView,
+    },
+    write_set::WriteSet,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// A view over a base state view with some of its state values overridden, used to simulate
+/// transactions against hypothetical state and on top of each other
+pub(crate) struct OverriddenStateView<'a, S> {
+    base_view: &'a S,
+    overrides: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl<'a, S: StateView> OverriddenStateView<'a, S> {
+    pub fn new(base_view: &'a S) -> Self {
+        Self {
+            base_view,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Sets the bytes under the state key, or deletes the state value when `None`. The metadata
+    /// of an existing state value is kept.
+    ///
+    /// The modules and resources of the framework account can't be overridden, as the VM reads
+    /// its configuration from the on-chain configs, and runs the prologue and epilogue of the
+    /// framework, before executing any transaction.
+    pub fn set(&mut self, state_key: StateKey, bytes: Option<Vec<u8>>) -> anyhow::Result<()> {
+        if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+            if access_path.address == CORE_CODE_ADDRESS {
+                bail!(
+                    "The state of the framework account {} can't be overridden",
+                    CORE_CODE_ADDRESS.to_hex_literal()
+                )
+            }
+        }
+        let state_value = match bytes {
+            Some(bytes) => Some(match self.get_state_value(&state_key)? {
+                Some(state_value) => state_value.map_bytes(|_| Ok(bytes.into()))?,
+                None => StateValue::new_legacy(bytes.into()),
+            }),
+            None => None,
+        };
+        self.overrides.insert(state_key, state_value);
+        Ok(())
+    }
+
+    /// Applies the changes of a simulated transaction, so that the next transactions see them
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set.iter() {
+            let state_value = write_op.bytes().map(|bytes| {
+                StateValue::new_with_metadata(bytes.clone(), write_op.metadata().clone())
+            });
+            self.overrides.insert(state_key.clone(), state_value);
+        }
+    }
+
+    /// Applies the overrides given in JSON. Modules are applied first, so that resources and
+    /// table items can be of the types they define.
+    pub fn apply_state_overrides(
+        &mut self,
+        context: &Context,
+        state_overrides: StateOverrides,
+    ) -> anyhow::Result<()> {
+        for (index, module) in state_overrides.modules.into_iter().enumerate() {
+            let bytecode = module.bytecode.0;
+            let abi = MoveModuleBytecode::new(bytecode.clone())
+                .try_parse_abi()?
+                .abi
+                .ok_or_else(|| format_err!("Invalid bytecode of module override {}", index))?;
+            let module_id = ModuleId::new(abi.address.into(), abi.name.into());
+            let state_key = StateKey::access_path(AccessPath::code_access_path(module_id));
+            self.set(state_key, Some(bytecode))?;
+        }
+
+        let changes = {
+            let resolver = self.as_move_resolver();
+            let converter =
+                resolver.as_converter(context.db.clone(), context.table_info_reader.clone());
+            let mut changes = Vec::new();
+            for (index, resource) in state_overrides.resources.into_iter().enumerate() {
+                let struct_tag = StructTag::try_from(resource.typ)
+                    .with_context(|| format!("Invalid type of resource override {}", index))?;
+                let bytes = resource
+                    .data
+                    .map(|data| {
+                        converter
+                            .try_into_vm_value(
+                                &TypeTag::Struct(Box::new(struct_tag.clone())),
+                                data,
+                            )?
+                            .undecorate()
+                            .simple_serialize()
+                            .ok_or_else(|| format_err!("Failed to serialize resource"))
+                    })
+                    .transpose()
+                    .with_context(|| format!("Invalid data of resource override {}", index))?;
+                let access_path =
+                    AccessPath::resource_access_path(resource.address.into(), struct_tag)?;
+                changes.push((StateKey::access_path(access_path), bytes));
+            }
+
+            for (index, table_item) in state_overrides.table_items.into_iter().enumerate() {
+                let key_type = TypeTag::try_from(table_item.key_type).with_context(|| {
+                    format!("Invalid key type of table item override {}", index)
+                })?;
+                let value_type = TypeTag::try_from(table_item.value_type).with_context(|| {
+                    format!("Invalid value type of table item override {}", index)
+                })?;
+                let key = converter
+                    .try_into_vm_value(&key_type, table_item.key)
+                    .and_then(|key| {
+                        key.undecorate()
+                            .simple_serialize()
+                            .ok_or_else(|| format_err!("Failed to serialize table key"))
+                    })
+                    .with_context(|| format!("Invalid key of table item override {}", index))?;
+                let value = table_item
+                    .value
+                    .map(|value| {
+                        converter
+                            .try_into_vm_value(&value_type, value)?
+                            .undecorate()
+                            .simple_serialize()
+                            .ok_or_else(|| format_err!("Failed to serialize table value"))
+                    })
+                    .transpose()
+                    .with_context(|| format!("Invalid value of table item override {}", index))?;
+                let state_key = StateKey::table_item(TableHandle(table_item.handle.into()), key);
+                changes.push((state_key, value));
+            }
+            changes
+        };
+
+        for (state_key, bytes) in changes {
+            self.set(state_key, bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, S: StateView> TStateView for OverriddenStateView<'a, S> {
+    type Key = StateKey;
+
+    /// Returns the id of the base view, as there is no id for overridden state. Nothing keyed
+    /// by the id may be cached from this view, which is why the simulations against it use VMs
+    /// with an empty code cache.
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        match self.overrides.get(state_key) {
+            Some(state_value) => Ok(state_value.clone()),
+            None => self.base_view.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        self.base_view.get_usage()
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     accept_type::AcceptType,
     context::api_spawn_blocking,
     failpoint::fail_point_poem,
+    page::Page,
     response::{
         api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
         BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
@@ -14,13 +15,15 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
+    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
-    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    RawTableItemRequest, StateValueChange, StateValueChangeBcs, TableItemRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
 };
 use aptos_types::{
     access_path::AccessPath,
-    state_store::{state_key::StateKey, table::TableHandle, TStateView},
+    state_store::{state_key::StateKey, state_value::StateValue, table::TableHandle, TStateView},
+    transaction::Version,
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use move_core_types::{
@@ -90,6 +93,62 @@ impl StateApi {
         .await
     }
 
+    /// Get account resource history
+    ///
+    /// Retrieves the changes of an individual resource of a given account, i.e., the versions of
+    /// the transactions writing or deleting it along with the values written, ordered by version.
+    /// Resources in resource groups are not supported.
+    ///
+    /// If no start version is given, it will return the latest changes.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the start version has been pruned, the server responds with a 410. Changes before the
+    /// oldest available version are not returned.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/history",
+        method = "get",
+        operation_id = "get_account_resource_history",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_history(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Ledger version to start list of changes
+        ///
+        /// If not provided, defaults to showing the latest changes
+        start: Query<Option<U64>>,
+        /// Max number of changes to retrieve
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<StateValueChange>> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_history")?;
+        self.context
+            .check_api_output_enabled("Get account resource history", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.resource_history(&accept_type, address.0, resource_type.0, page)
+        })
+        .await
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -182,6 +241,62 @@ impl StateApi {
         .await
     }
 
+    /// Get table item history
+    ///
+    /// Get the changes of a table item from the table identified by {table_handle} in the path
+    /// and the "key" (TableItemRequest) provided in the request body, i.e., the versions of the
+    /// transactions writing or deleting it along with the values written, ordered by version.
+    ///
+    /// If no start version is given, it will return the latest changes.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the start version has been pruned, the server responds with a 410. Changes before the
+    /// oldest available version are not returned.
+    #[oai(
+        path = "/tables/:table_handle/item/history",
+        method = "post",
+        operation_id = "get_table_item_history",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_item_history(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<TableItemRequest>,
+        /// Ledger version to start list of changes
+        ///
+        /// If not provided, defaults to showing the latest changes
+        start: Query<Option<U64>>,
+        /// Max number of changes to retrieve
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<StateValueChange>> {
+        table_item_request
+            .0
+            .verify()
+            .context("'table_item_request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_table_item_history")?;
+        self.context
+            .check_api_output_enabled("Get table item history", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.table_item_history(&accept_type, table_handle.0, table_item_request.0, page)
+        })
+        .await
+    }
+
     /// Get raw table item
     ///
     /// Get a table item at a specific ledger version from the table identified by {table_handle}
@@ -339,6 +454,43 @@ impl StateApi {
         }
     }
 
+    /// Read the changes of a resource in the page
+    ///
+    /// JSON: Convert each value to a MoveValue of the resource
+    /// BCS: Leave each value encoded as the resource
+    fn resource_history(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTag,
+        page: Page,
+    ) -> BasicResultWith404<Vec<StateValueChange>> {
+        let resource_type: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let access_path = AccessPath::resource_access_path(address.into(), resource_type.clone())
+            .context("Failed to build the resource path")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let (ledger_info, _, state_view) = self.context.state_view(None)?;
+        let history = self.history(&StateKey::access_path(access_path), page, &ledger_info)?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        );
+        Self::render_history(accept_type, &ledger_info, history, |bytes| {
+            Ok(MoveValue::Struct(
+                converter.try_into_resource(&resource_type, bytes)?.data,
+            ))
+        })
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
@@ -489,6 +641,63 @@ impl StateApi {
         }
     }
 
+    /// Retrieve the changes of a table item in the page
+    pub fn table_item_history(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        table_item_request: TableItemRequest,
+        page: Page,
+    ) -> BasicResultWith404<Vec<StateValueChange>> {
+        // Parse the key and value types for the table
+        let key_type = table_item_request
+            .key_type
+            .try_into()
+            .context("Failed to parse key_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let value_type = table_item_request
+            .value_type
+            .try_into()
+            .context("Failed to parse value_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        // The latest state is used for converting both the key and the values
+        let (ledger_info, _, state_view) = self.context.state_view(None)?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        );
+
+        // Convert key to lookup version for DB
+        let vm_key = converter
+            .try_into_vm_value(&key_type, table_item_request.key)
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
+            BasicErrorWith404::bad_request_with_code(
+                "Failed to serialize table key",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+
+        let state_key = StateKey::table_item(TableHandle(table_handle.into()), raw_key);
+        let history = self.history(&state_key, page, &ledger_info)?;
+        Self::render_history(accept_type, &ledger_info, history, |bytes| {
+            converter.try_into_move_value(&value_type, bytes)
+        })
+    }
+
     /// Retrieve table item for a specific ledger version
     pub fn raw_table_item(
         &self,
@@ -612,4 +821,73 @@ impl StateApi {
             },
         }
     }
+
+    /// Retrieve the changes of a state value in the page
+    fn history(
+        &self,
+        state_key: &StateKey,
+        page: Page,
+        latest_ledger_info: &LedgerInfo,
+    ) -> Result<Vec<(Version, Option<StateValue>)>, BasicErrorWith404> {
+        let ledger_version = latest_ledger_info.version();
+        let limit = page.limit(latest_ledger_info)?;
+        let start_version = page
+            .start_option()
+            .map(|_| page.compute_start(limit, ledger_version, latest_ledger_info))
+            .transpose()?;
+
+        self.context.get_state_value_history(
+            state_key,
+            start_version,
+            limit,
+            ledger_version,
+            latest_ledger_info,
+        )
+    }
+
+    /// JSON: Convert each value with `to_move_value`
+    /// BCS: Leave each value encoded
+    fn render_history(
+        accept_type: &AcceptType,
+        ledger_info: &LedgerInfo,
+        history: Vec<(Version, Option<StateValue>)>,
+        to_move_value: impl Fn(&[u8]) -> anyhow::Result<MoveValue>,
+    ) -> BasicResultWith404<Vec<StateValueChange>> {
+        match accept_type {
+            AcceptType::Json => {
+                let changes = history
+                    .into_iter()
+                    .map(|(version, value)| {
+                        Ok(StateValueChange {
+                            version: version.into(),
+                            value: value
+                                .map(|value| to_move_value(value.bytes()))
+                                .transpose()?,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to deserialize state values retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((changes, ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                let changes = history
+                    .into_iter()
+                    .map(|(version, value)| StateValueChangeBcs {
+                        version,
+                        value: value.map(|value| value.bytes().to_vec()),
+                    })
+                    .collect::<Vec<_>>();
+
+                BasicResponse::try_from_bcs((changes, ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
 }
@@ -0,0 +1,587 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streams of committed transactions and events, pushed to clients as server-sent events.
+//!
+//! Each stream starts at a given ledger version (or at the next committed version) and follows
+//! the ledger as new transactions are committed, so clients can resume a stream from the version
+//! after the last item they received instead of polling the paginated APIs.
+
+use crate::{
+    context::Context,
+    failpoint::fail_point_poem,
+    metrics,
+    response::{
+        api_disabled, version_pruned, BadRequestError, BasicErrorWith404, ServiceUnavailableError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, EntryFunctionId, LedgerInfo, MoveStructTag, Transaction,
+    TransactionOnChainData, VersionedEvent, U64,
+};
+use aptos_event_notifications::EventNotificationListener;
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, EventWithVersion},
+    transaction::{TransactionPayload, Version},
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use futures::{stream::BoxStream, StreamExt};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use poem_openapi::{param::Query, payload::EventStream, OpenApi};
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+
+/// Interval at which keep-alive comments are sent on idle streams
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+pub type StreamResult<T> = poem::Result<EventStream<BoxStream<'static, T>>, BasicErrorWith404>;
+
+/// API for streaming committed transactions and events
+#[derive(Clone)]
+pub struct StreamingApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl StreamingApi {
+    /// Stream transactions
+    ///
+    /// Streams committed transactions as server-sent events, in version order. The stream
+    /// starts at `start` and keeps following the ledger as new transactions are committed.
+    /// To resume an interrupted stream, reconnect with `start` set to the version after the
+    /// last received transaction.
+    ///
+    /// All given filters must match for a transaction to be streamed.
+    ///
+    /// This API is disabled unless `streaming_enabled` is set in the API config.
+    #[oai(
+        path = "/stream/transactions",
+        method = "get",
+        operation_id = "stream_transactions",
+        tag = "ApiTags::Streaming"
+    )]
+    async fn stream_transactions(
+        &self,
+        /// Ledger version to start streaming from
+        ///
+        /// If not provided, defaults to the next committed version
+        start: Query<Option<U64>>,
+        /// Only stream transactions sent by this account
+        account: Query<Option<Address>>,
+        /// Only stream transactions calling this entry function, e.g. `0x1::coin::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream transactions that emitted an event of this type
+        ///
+        /// If the type has no generic type parameters, events of all instantiations match.
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> StreamResult<Transaction> {
+        fail_point_poem("endpoint_stream_transactions")?;
+        let (filter, start_version, commits, slot) =
+            self.prepare_stream(start.0, account.0, entry_function.0, event_type.0)?;
+        let items = committed_transaction_stream(
+            self.context.clone(),
+            "transactions",
+            filter,
+            start_version,
+            commits,
+            slot,
+            render_transactions,
+        );
+        Ok(EventStream::new(items.boxed()).keep_alive(KEEP_ALIVE_INTERVAL))
+    }
+
+    /// Stream events
+    ///
+    /// Streams the events of committed transactions as server-sent events, in version order.
+    /// The stream starts at `start` and keeps following the ledger as new transactions are
+    /// committed. Every event contains the version of its transaction, so an interrupted
+    /// stream can be resumed by reconnecting with `start` set to the version after the last
+    /// fully received transaction.
+    ///
+    /// All given filters must match for an event to be streamed.
+    ///
+    /// This API is disabled unless `streaming_enabled` is set in the API config.
+    #[oai(
+        path = "/stream/events",
+        method = "get",
+        operation_id = "stream_events",
+        tag = "ApiTags::Streaming"
+    )]
+    async fn stream_events(
+        &self,
+        /// Ledger version to start streaming from
+        ///
+        /// If not provided, defaults to the next committed version
+        start: Query<Option<U64>>,
+        /// Only stream events emitted by transactions sent by this account
+        account: Query<Option<Address>>,
+        /// Only stream events emitted by transactions calling this entry function, e.g.
+        /// `0x1::coin::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream events of this type
+        ///
+        /// If the type has no generic type parameters, events of all instantiations match.
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> StreamResult<VersionedEvent> {
+        fail_point_poem("endpoint_stream_events")?;
+        let (filter, start_version, commits, slot) =
+            self.prepare_stream(start.0, account.0, entry_function.0, event_type.0)?;
+        let items = committed_transaction_stream(
+            self.context.clone(),
+            "events",
+            filter,
+            start_version,
+            commits,
+            slot,
+            render_events,
+        );
+        Ok(EventStream::new(items.boxed()).keep_alive(KEEP_ALIVE_INTERVAL))
+    }
+}
+
+impl StreamingApi {
+    /// Checks that a new stream can be opened, and returns its filter, its start version, the
+    /// receiver of commit notifications and the stream's slot among the open streams
+    fn prepare_stream(
+        &self,
+        start: Option<U64>,
+        account: Option<Address>,
+        entry_function: Option<EntryFunctionId>,
+        event_type: Option<MoveStructTag>,
+    ) -> Result<
+        (
+            StreamFilter,
+            Version,
+            watch::Receiver<Version>,
+            OwnedSemaphorePermit,
+        ),
+        BasicErrorWith404,
+    > {
+        if !self.context.node_config.api.streaming_enabled {
+            return Err(api_disabled("Streaming"));
+        }
+        // Reserve the slot before subscribing, so concurrent requests can't exceed the limit
+        let slot = self.context.try_reserve_stream_slot().ok_or_else(|| {
+            BasicErrorWith404::service_unavailable_with_code_no_info(
+                "Too many open streams, try again later",
+                AptosErrorCode::TooManyStreams,
+            )
+        })?;
+        let commits = self.context.commit_notifier().subscribe().ok_or_else(|| {
+            BasicErrorWith404::service_unavailable_with_code_no_info(
+                "This node doesn't publish commit notifications",
+                AptosErrorCode::ApiDisabled,
+            )
+        })?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let filter = StreamFilter::new(account, entry_function, event_type).map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                format!("{:#}", err),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+        let start_version = match start {
+            Some(start) => {
+                let start = start.0;
+                if start < ledger_info.oldest_ledger_version.0 {
+                    return Err(version_pruned(start, &ledger_info));
+                }
+                start
+            },
+            None => ledger_info.version() + 1,
+        };
+        Ok((filter, start_version, commits, slot))
+    }
+}
+
+/// Filters the transactions and events pushed to a stream
+#[derive(Clone, Debug)]
+struct StreamFilter {
+    account: Option<AccountAddress>,
+    entry_function: Option<(ModuleId, Identifier)>,
+    event_type: Option<StructTag>,
+}
+
+impl StreamFilter {
+    fn new(
+        account: Option<Address>,
+        entry_function: Option<EntryFunctionId>,
+        event_type: Option<MoveStructTag>,
+    ) -> anyhow::Result<Self> {
+        let event_type = event_type
+            .map(StructTag::try_from)
+            .transpose()
+            .context("Invalid event type")?;
+        Ok(Self {
+            account: account.map(Into::into),
+            entry_function: entry_function
+                .map(|function| (function.module.into(), function.name.into())),
+            event_type,
+        })
+    }
+
+    /// Returns true if the transaction matches the account and entry function filters
+    fn matches_transaction(&self, txn: &TransactionOnChainData) -> bool {
+        if self.account.is_none() && self.entry_function.is_none() {
+            return true;
+        }
+        let Some(signed_txn) = txn.transaction.try_as_signed_user_txn() else {
+            return false;
+        };
+        if let Some(account) = self.account {
+            if signed_txn.sender() != account {
+                return false;
+            }
+        }
+        if let Some((module, function)) = &self.entry_function {
+            match signed_txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    if entry_function.module() != module
+                        || entry_function.function() != function.as_ident_str()
+                    {
+                        return false;
+                    }
+                },
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Returns true if the event matches the event type filter
+    fn matches_event(&self, event: &ContractEvent) -> bool {
+        let Some(event_type) = &self.event_type else {
+            return true;
+        };
+        match event.type_tag() {
+            TypeTag::Struct(struct_tag) => {
+                struct_tag.address == event_type.address
+                    && struct_tag.module == event_type.module
+                    && struct_tag.name == event_type.name
+                    && (event_type.type_params.is_empty()
+                        || struct_tag.type_params == event_type.type_params)
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Converts the matching transactions of a batch into stream items. Each transaction comes
+/// with the timestamp of its block.
+type RenderFn<T> = fn(
+    &Context,
+    &LedgerInfo,
+    &StreamFilter,
+    Vec<(u64, TransactionOnChainData)>,
+) -> anyhow::Result<Vec<T>>;
+
+fn render_transactions(
+    context: &Context,
+    ledger_info: &LedgerInfo,
+    filter: &StreamFilter,
+    txns: Vec<(u64, TransactionOnChainData)>,
+) -> anyhow::Result<Vec<Transaction>> {
+    let txns: Vec<_> = txns
+        .into_iter()
+        .filter(|(_, txn)| {
+            filter.event_type.is_none() || txn.events.iter().any(|e| filter.matches_event(e))
+        })
+        .collect();
+    if txns.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let state_view = context.state_view_at_version(ledger_info.version())?;
+    let resolver = state_view.as_move_resolver();
+    let converter = resolver.as_converter(context.db.clone(), context.table_info_reader.clone());
+    txns.into_iter()
+        .map(|(timestamp, txn)| converter.try_into_onchain_transaction(timestamp, txn))
+        .collect::<anyhow::Result<_>>()
+        .context("Failed to convert transaction data from storage")
+}
+
+fn render_events(
+    context: &Context,
+    ledger_info: &LedgerInfo,
+    filter: &StreamFilter,
+    txns: Vec<(u64, TransactionOnChainData)>,
+) -> anyhow::Result<Vec<VersionedEvent>> {
+    let events: Vec<_> = txns
+        .into_iter()
+        .flat_map(|(_, txn)| {
+            let version = txn.version;
+            txn.events
+                .into_iter()
+                .filter(|event| filter.matches_event(event))
+                .map(move |event| EventWithVersion::new(version, event))
+        })
+        .collect();
+    if events.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let state_view = context.state_view_at_version(ledger_info.version())?;
+    let resolver = state_view.as_move_resolver();
+    let converter = resolver.as_converter(context.db.clone(), context.table_info_reader.clone());
+    converter
+        .try_into_versioned_events(&events)
+        .context("Failed to convert events from storage")
+}
+
+/// Tracks the number of open streams of a kind in the metrics
+struct ActiveStream(&'static str);
+
+impl ActiveStream {
+    fn new(stream: &'static str) -> Self {
+        metrics::ACTIVE_STREAMS.with_label_values(&[stream]).inc();
+        Self(stream)
+    }
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        metrics::ACTIVE_STREAMS.with_label_values(&[self.0]).dec();
+    }
+}
+
+/// The state of a stream between two batches
+struct StreamState<T> {
+    _active_stream: ActiveStream,
+    /// Released when the stream is dropped
+    _slot: OwnedSemaphorePermit,
+    context: Arc<Context>,
+    filter: StreamFilter,
+    next_version: Version,
+    commits: watch::Receiver<Version>,
+    render: RenderFn<T>,
+}
+
+impl<T: Send + 'static> StreamState<T> {
+    /// Returns the items of the next batch of committed transactions, waiting for new commits if
+    /// the stream has caught up with the ledger. The returned items are empty if no transaction
+    /// of the batch matched the filter.
+    async fn next_items(&mut self) -> anyhow::Result<Vec<T>> {
+        let context = self.context.clone();
+        let filter = self.filter.clone();
+        let next_version = self.next_version;
+        let render = self.render;
+        let batch = tokio::task::spawn_blocking(move || {
+            read_batch(&context, &filter, next_version, render)
+        })
+        .await??;
+
+        match batch {
+            Some((items, next_version)) => {
+                self.next_version = next_version;
+                Ok(items)
+            },
+            None => {
+                self.commits
+                    .changed()
+                    .await
+                    .context("Commit notifier was dropped")?;
+                Ok(vec![])
+            },
+        }
+    }
+}
+
+/// Reads and renders the batch of committed transactions starting at `start_version`. Returns
+/// the rendered items and the version to continue from, or None if `start_version` isn't
+/// committed yet.
+fn read_batch<T>(
+    context: &Context,
+    filter: &StreamFilter,
+    start_version: Version,
+    render: RenderFn<T>,
+) -> anyhow::Result<Option<(Vec<T>, Version)>> {
+    let ledger_info = context.get_latest_ledger_info_wrapped()?;
+    let ledger_version = ledger_info.version();
+    if start_version > ledger_version {
+        return Ok(None);
+    }
+
+    let limit = std::cmp::min(
+        context.max_transactions_page_size() as u64,
+        ledger_version - start_version + 1,
+    ) as u16;
+    let txns = context.get_transactions(start_version, limit, ledger_version)?;
+    let next_version = start_version + txns.len() as u64;
+
+    // Track the block timestamps before filtering, as the filter may drop the block metadata
+    // transactions
+    let mut timestamp = context.db.get_block_timestamp(start_version)?;
+    let txns = txns
+        .into_iter()
+        .filter_map(|txn| {
+            if let Some(block_metadata) = txn.transaction.try_as_block_metadata_ext() {
+                timestamp = block_metadata.timestamp_usecs();
+            } else if let Some(block_metadata) = txn.transaction.try_as_block_metadata() {
+                timestamp = block_metadata.timestamp_usecs();
+            }
+            filter.matches_transaction(&txn).then_some((timestamp, txn))
+        })
+        .collect();
+
+    let items = render(context, &ledger_info, filter, txns)?;
+    Ok(Some((items, next_version)))
+}
+
+/// Returns a stream of the items rendered from the committed transactions, starting at
+/// `start_version` and following the ledger as new transactions are committed. The stream ends
+/// if reading from the DB fails, in which case clients are expected to resume it.
+fn committed_transaction_stream<T: Send + 'static>(
+    context: Arc<Context>,
+    stream: &'static str,
+    filter: StreamFilter,
+    start_version: Version,
+    commits: watch::Receiver<Version>,
+    slot: OwnedSemaphorePermit,
+    render: RenderFn<T>,
+) -> impl futures::Stream<Item = T> + Send + 'static {
+    let state = StreamState {
+        _active_stream: ActiveStream::new(stream),
+        _slot: slot,
+        context,
+        filter,
+        next_version: start_version,
+        commits,
+        render,
+    };
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            match state.next_items().await {
+                Ok(items) if items.is_empty() => continue,
+                Ok(items) => {
+                    metrics::STREAMED_ITEMS
+                        .with_label_values(&[state._active_stream.0])
+                        .inc_by(items.len() as u64);
+                    return Some((items, state));
+                },
+                Err(error) => {
+                    warn!(
+                        "Ending stream at version {}: {:#}",
+                        state.next_version, error
+                    );
+                    return None;
+                },
+            }
+        }
+    })
+    .flat_map(futures::stream::iter)
+}
+
+/// Notifies streams of newly committed transactions.
+///
+/// Streams are woken up by the new block events that state sync publishes after every commit.
+/// A single task relays these notifications to all streams. The task is started when the first
+/// stream subscribes, and stops when the last stream is dropped.
+pub struct CommitNotifier {
+    sender: Arc<watch::Sender<Version>>,
+    relay: Arc<Mutex<RelayState>>,
+}
+
+/// The state of the task relaying commit notifications to streams
+enum RelayState {
+    /// The node doesn't publish commit notifications
+    Unavailable,
+    /// No stream is open, the listener is held until the next stream subscribes
+    Idle(EventNotificationListener),
+    Running,
+}
+
+impl CommitNotifier {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(0);
+        Self {
+            sender: Arc::new(sender),
+            relay: Arc::new(Mutex::new(RelayState::Unavailable)),
+        }
+    }
+
+    /// Sets the subscription to new block events that streams are notified from
+    pub fn set_commit_notifications(&self, commit_notifications: EventNotificationListener) {
+        *self.relay.lock().unwrap() = RelayState::Idle(commit_notifications);
+    }
+
+    /// Returns the number of open streams
+    pub fn num_subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Returns a receiver that is notified of every newly committed version, or None if the
+    /// node doesn't publish commit notifications
+    pub fn subscribe(&self) -> Option<watch::Receiver<Version>> {
+        // Subscribe while holding the lock, so the relay task can't stop concurrently
+        let mut relay = self.relay.lock().unwrap();
+        match mem::replace(&mut *relay, RelayState::Running) {
+            RelayState::Unavailable => {
+                *relay = RelayState::Unavailable;
+                None
+            },
+            RelayState::Idle(listener) => {
+                let receiver = self.sender.subscribe();
+                tokio::spawn(relay_commit_notifications(
+                    listener,
+                    self.sender.clone(),
+                    self.relay.clone(),
+                ));
+                Some(receiver)
+            },
+            RelayState::Running => Some(self.sender.subscribe()),
+        }
+    }
+}
+
+impl Default for CommitNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Relays commit notifications to the open streams until the last stream is dropped, and then
+/// hands the listener back so that the next stream restarts the relay.
+async fn relay_commit_notifications(
+    mut listener: EventNotificationListener,
+    sender: Arc<watch::Sender<Version>>,
+    relay: Arc<Mutex<RelayState>>,
+) {
+    loop {
+        tokio::select! {
+            notification = listener.next() => {
+                let Some(notification) = notification else {
+                    warn!("Commit notifications ended, streams will no longer be notified");
+                    *relay.lock().unwrap() = RelayState::Unavailable;
+                    return;
+                };
+                sender.send_if_modified(|latest_version| {
+                    if *latest_version >= notification.version {
+                        return false;
+                    }
+                    *latest_version = notification.version;
+                    true
+                });
+            },
+            _ = sender.closed() => {
+                let mut relay = relay.lock().unwrap();
+                if sender.receiver_count() == 0 {
+                    *relay = RelayState::Idle(listener);
+                    return;
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config};
+use aptos_api_test_context::current_function_name;
+use aptos_config::config::NodeConfig;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_mempool_introspection_disabled_by_default() {
+    let context = new_test_context(current_function_name!());
+    context.expect_status_code(403).get("/mempool/stats").await;
+    context
+        .expect_status_code(403)
+        .get("/mempool/transactions")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_mempool_transactions() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.mempool_introspection_enabled = true;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    let body = bcs::to_bytes(&txn).unwrap();
+    let pending_txn = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", body)
+        .await;
+
+    let stats = context.get("/mempool/stats").await;
+    assert_eq!(stats["num_transactions"], "1");
+    assert_eq!(stats["num_ready_transactions"], "1");
+    assert_eq!(stats["num_parked_transactions"], "0");
+    assert_eq!(stats["num_accounts"], "1");
+
+    let txns = context.get("/mempool/transactions").await;
+    let txns = txns.as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(txns[0]["transaction"]["hash"], pending_txn["hash"]);
+    assert_eq!(txns[0]["parked"], false);
+
+    let sender = pending_txn["sender"].as_str().unwrap();
+    let account_txns = context.get(&format!("/mempool/accounts/{}", sender)).await;
+    assert_eq!(account_txns["transactions"].as_array().unwrap().len(), 1);
+    assert!(account_txns["sequence_number_gaps"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
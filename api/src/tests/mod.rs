@@ -8,6 +8,7 @@ mod converter_test;
 mod events_test;
 mod index_test;
 mod invalid_post_request_test;
+mod mempool_test;
 mod modules;
 mod multisig_transactions_test;
 mod objects;
@@ -15,6 +16,7 @@ mod resource_groups;
 mod secp256k1_ecdsa;
 mod simulation_test;
 mod state_test;
+mod streaming_test;
 mod string_resource_test;
 mod transaction_vector_test;
 mod transactions_test;
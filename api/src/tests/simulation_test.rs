@@ -3,9 +3,15 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::HexEncodedBytes;
 use aptos_crypto::ed25519::Ed25519Signature;
-use aptos_types::transaction::{
-    authenticator::TransactionAuthenticator, EntryFunction, TransactionPayload,
+use aptos_sdk::transaction_builder::aptos_stdlib::aptos_token_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{
+        authenticator::TransactionAuthenticator, EntryFunction, SignedTransaction,
+        TransactionPayload,
+    },
 };
 use move_core_types::{ident_str, language_storage::ModuleId};
 use serde_json::json;
@@ -140,3 +146,361 @@ async fn test_simulate_txn_with_aggregator() {
         unreachable!("Simulation uses Ed25519 authenticator.");
     }
 }
+
+/// Returns the request to simulate the transaction with a dummy signature
+fn simulation_request(txn: &SignedTransaction, payload: serde_json::Value) -> serde_json::Value {
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        json!({
+            "sender": txn.sender().to_string(),
+            "sequence_number": txn.sequence_number().to_string(),
+            "max_gas_amount": txn.max_gas_amount().to_string(),
+            "gas_unit_price": txn.gas_unit_price().to_string(),
+            "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+            "payload": payload,
+            "signature": {
+                "type": "ed25519_signature",
+                "public_key": public_key.to_string(),
+                "signature": Ed25519Signature::dummy_signature().to_string(),
+            }
+        })
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+}
+
+fn transfer_payload(receiver: AccountAddress, amount: u64) -> serde_json::Value {
+    json!({
+        "type": "entry_function_payload",
+        "function": "0x1::aptos_account::transfer",
+        "type_arguments": [],
+        "arguments": [receiver.to_standard_string(), amount.to_string()]
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // The second transfer only succeeds if it is simulated after the first one
+    let first = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let second = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({
+                "transactions": [
+                    simulation_request(&first, transfer_payload(bob.address(), SMALL_TRANSFER_AMOUNT)),
+                    simulation_request(&second, transfer_payload(bob.address(), SMALL_TRANSFER_AMOUNT)),
+                ]
+            }),
+        )
+        .await;
+    assert_eq!(resp.as_array().unwrap().len(), 2);
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+    assert!(resp[1]["success"].as_bool().is_some_and(|v| v));
+    assert_eq!(resp[1]["sequence_number"], "1");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence_with_resource_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let coin_store = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+    let mut data = context
+        .get(&format!(
+            "/accounts/{}/resource/{}",
+            alice.address(),
+            coin_store
+        ))
+        .await["data"]
+        .clone();
+    data["coin"]["value"] = json!((2 * LARGE_TRANSFER_AMOUNT).to_string());
+
+    let txn = context.account_transfer_to(alice, bob.address(), LARGE_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, transfer_payload(bob.address(), LARGE_TRANSFER_AMOUNT));
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({ "transactions": [request.clone()] }),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({
+                "transactions": [request],
+                "state_overrides": {
+                    "resources": [{
+                        "address": alice.address().to_hex_literal(),
+                        "type": coin_store,
+                        "data": data,
+                    }]
+                }
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence_invalid_size() {
+    let mut context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({ "transactions": [] }),
+        )
+        .await;
+
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let max_sequence_size = context.context.node_config.api.max_simulation_sequence_size;
+    let transactions: Vec<_> = (0..=max_sequence_size)
+        .map(|_| {
+            let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+            simulation_request(&txn, transfer_payload(bob.address(), SMALL_TRANSFER_AMOUNT))
+        })
+        .collect();
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({ "transactions": transactions }),
+        )
+        .await;
+}
+
+/// Returns the bytecode of the single module of the package published by the payload
+fn module_bytecode(payload: TransactionPayload) -> Vec<u8> {
+    if let TransactionPayload::EntryFunction(entry_function) = payload {
+        let mut code: Vec<Vec<u8>> = bcs::from_bytes(&entry_function.args()[1]).unwrap();
+        code.remove(0)
+    } else {
+        unreachable!("Packages are published with an entry function.");
+    }
+}
+
+/// Returns the transaction with a dummy signature, to be simulated in BCS
+fn with_dummy_signature(txn: &SignedTransaction) -> SignedTransaction {
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        SignedTransaction::new(
+            txn.clone().into_raw_transaction(),
+            public_key.clone(),
+            Ed25519Signature::dummy_signature(),
+        )
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence_with_module_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("src/tests/move/pack_abi");
+    let bytecode = module_bytecode(TestContext::build_package(path, vec![(
+        "abi".to_string(),
+        alice.address(),
+    )]));
+
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(alice.address(), ident_str!("test").to_owned()),
+        ident_str!("private_entry_function").to_owned(),
+        vec![],
+        vec![bcs::to_bytes(&5u64).unwrap()],
+    ));
+    let txn = alice.sign_with_transaction_builder(context.transaction_factory().payload(payload));
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({
+                "transactions": [simulation_request(&txn, json!({
+                    "type": "entry_function_payload",
+                    "function": format!("{}::test::private_entry_function", alice.address()),
+                    "type_arguments": [],
+                    "arguments": ["5"]
+                }))],
+                "state_overrides": {
+                    "modules": [{ "bytecode": HexEncodedBytes::from(bytecode).to_string() }]
+                }
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+
+    // The overridden module must not be visible to the simulations against the state in
+    // storage, where it doesn't exist
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn(
+            "/transactions/simulate",
+            bcs::to_bytes(&with_dummy_signature(&txn)).unwrap(),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence_with_table_item_override() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    let collection_txn =
+        creator.sign_with_transaction_builder(context.transaction_factory().payload(
+            aptos_token_stdlib::token_create_collection_script(
+                b"collection".to_vec(),
+                b"description".to_vec(),
+                b"uri".to_vec(),
+                1,
+                vec![false, false, false],
+            ),
+        ));
+    context.commit_block(&vec![txn, collection_txn]).await;
+
+    let creator_address = creator.address().to_hex_literal();
+    let token_payload = |name: &str| {
+        json!({
+            "type": "entry_function_payload",
+            "function": "0x3::token::create_token_script",
+            "type_arguments": [],
+            "arguments": [
+                "collection", name, "description", "1", "1", "uri",
+                creator_address, "1", "0",
+                [false, false, false, false, false], [], [], []
+            ]
+        })
+    };
+    let first = context.account_transfer_to(creator, creator.address(), SMALL_TRANSFER_AMOUNT);
+    let second = context.account_transfer_to(creator, creator.address(), SMALL_TRANSFER_AMOUNT);
+    let transactions = json!([
+        simulation_request(&first, token_payload("first")),
+        simulation_request(&second, token_payload("second")),
+    ]);
+
+    // The collection holds a single token
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({ "transactions": transactions }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+    assert!(!resp[1]["success"].as_bool().is_some_and(|v| v));
+
+    let collections = context
+        .get(&format!(
+            "/accounts/{}/resource/0x3::token::Collections",
+            creator.address()
+        ))
+        .await;
+    let handle = collections["data"]["collection_data"]["handle"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let mut collection_data = context
+        .post(
+            &format!("/tables/{}/item", handle),
+            json!({
+                "key_type": "0x1::string::String",
+                "value_type": "0x3::token::CollectionData",
+                "key": "collection",
+            }),
+        )
+        .await;
+    collection_data["maximum"] = json!("2");
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({
+                "transactions": transactions,
+                "state_overrides": {
+                    "table_items": [{
+                        "handle": handle,
+                        "key_type": "0x1::string::String",
+                        "value_type": "0x3::token::CollectionData",
+                        "key": "collection",
+                        "value": collection_data,
+                    }]
+                }
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+    assert!(resp[1]["success"].as_bool().is_some_and(|v| v));
+
+    // The simulations against the state in storage still see the original maximum
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_sequence",
+            json!({ "transactions": transactions }),
+        )
+        .await;
+    assert!(!resp[1]["success"].as_bool().is_some_and(|v| v));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_sequence_rejects_framework_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, transfer_payload(bob.address(), SMALL_TRANSFER_AMOUNT));
+    let module = context.get("/accounts/0x1/module/aptos_account").await;
+    let chain_id = context
+        .get("/accounts/0x1/resource/0x1::chain_id::ChainId")
+        .await;
+    for state_overrides in [
+        json!({ "modules": [{ "bytecode": module["bytecode"] }] }),
+        json!({
+            "resources": [{
+                "address": "0x1",
+                "type": "0x1::chain_id::ChainId",
+                "data": chain_id["data"],
+            }]
+        }),
+    ] {
+        context
+            .expect_status_code(400)
+            .post(
+                "/transactions/simulate_sequence",
+                json!({
+                    "transactions": [request.clone()],
+                    "state_overrides": state_overrides,
+                }),
+            )
+            .await;
+    }
+}
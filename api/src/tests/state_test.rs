@@ -95,6 +95,116 @@ async fn test_get_account_resource_with_version_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_history() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resource =
+        get_account_resource(&account.address().to_hex_literal(), "0x1::account::Account");
+    let history = context.get(&format!("{}/history", resource)).await;
+    let changes = history.as_array().unwrap();
+    assert_eq!(1, changes.len());
+    let latest = context.get(&resource).await;
+    assert_eq!(changes[0]["value"], latest["data"]);
+
+    let version: u64 = changes[0]["version"].as_str().unwrap().parse().unwrap();
+    let history = context
+        .get(&format!("{}/history?start={}", resource, version + 1))
+        .await;
+    assert_eq!(history, json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_history() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let creator = &mut ctx.gen_account();
+    let txn = ctx.mint_user_account(creator).await;
+    ctx.commit_block(&vec![txn]).await;
+
+    let collection_name = "collection name".to_owned();
+    let collection_txn = creator.sign_with_transaction_builder(ctx.transaction_factory().payload(
+        aptos_token_stdlib::token_create_collection_script(
+            collection_name.clone().into_bytes(),
+            "description".to_owned().into_bytes(),
+            "uri".to_owned().into_bytes(),
+            20_000_000,
+            vec![false, false, false],
+        ),
+    ));
+    ctx.commit_block(&vec![collection_txn]).await;
+
+    // Creating a token in the collection increases its supply
+    let token_txn = creator.sign_with_transaction_builder(ctx.transaction_factory().payload(
+        aptos_token_stdlib::token_create_token_script(
+            collection_name.clone().into_bytes(),
+            "token name".to_owned().into_bytes(),
+            "token description".to_owned().into_bytes(),
+            1,
+            1,
+            "uri".to_owned().into_bytes(),
+            creator.address(),
+            1,
+            0,
+            vec![false, false, false, false, false],
+            vec![],
+            vec![],
+            vec![],
+        ),
+    ));
+    ctx.commit_block(&vec![token_txn]).await;
+
+    let collections = ctx
+        .get(&get_account_resource(
+            &creator.address().to_hex_literal(),
+            "0x3::token::Collections",
+        ))
+        .await;
+    let handle: AccountAddress = collections["data"]["collection_data"]["handle"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let request = json!({
+        "key_type": "0x1::string::String",
+        "value_type": "0x3::token::CollectionData",
+        "key": collection_name,
+    });
+
+    let history = ctx
+        .post(
+            &format!("{}/history", get_table_item(handle)),
+            request.clone(),
+        )
+        .await;
+    let changes = history.as_array().unwrap();
+    assert_eq!(2, changes.len());
+    assert_eq!(changes[0]["value"]["supply"], json!("0"));
+    assert_eq!(changes[1]["value"]["supply"], json!("1"));
+    let latest = ctx.post(&get_table_item(handle), request.clone()).await;
+    assert_eq!(changes[1]["value"], latest);
+
+    let version: u64 = changes[0]["version"].as_str().unwrap().parse().unwrap();
+    let history = ctx
+        .post(
+            &format!("{}/history?start={}", get_table_item(handle), version + 1),
+            request.clone(),
+        )
+        .await;
+    assert_eq!(history, json!([changes[1]]));
+
+    let history = ctx
+        .post(
+            &format!("{}/history?limit=1", get_table_item(handle)),
+            request,
+        )
+        .await;
+    assert_eq!(history, json!([changes[1]]));
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config};
+use aptos_api_test_context::{current_function_name, ApiSpecificConfig, TestContext};
+use aptos_config::config::NodeConfig;
+use serde_json::Value;
+use std::time::Duration;
+
+fn new_streaming_test_context(test_name: String) -> TestContext {
+    let mut node_config = NodeConfig::default();
+    node_config.api.streaming_enabled = true;
+    new_test_context_with_config(test_name, node_config)
+}
+
+/// Opens a stream and checks that it was accepted
+async fn open_stream(context: &TestContext, path: &str) -> reqwest::Response {
+    let ApiSpecificConfig::V1(address) = context.api_specific_config;
+    let response = reqwest::get(format!("http://{}/v1{}", address, path))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    response
+}
+
+/// Returns the data of the next event of an open stream
+async fn next_stream_event(response: &mut reqwest::Response) -> Value {
+    let mut body = String::new();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while !body.contains("\n\n") {
+            let chunk = response.chunk().await.unwrap().expect("stream ended");
+            body.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    })
+    .await
+    .expect("timed out waiting for a stream event");
+
+    let event = body.split("\n\n").next().unwrap();
+    let data: String = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    serde_json::from_str(&data).unwrap()
+}
+
+/// Opens a stream and returns the data of its first event
+async fn first_stream_event(context: &TestContext, path: &str) -> Value {
+    let mut response = open_stream(context, path).await;
+    next_stream_event(&mut response).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_streaming_disabled_by_default() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(403)
+        .get("/stream/transactions")
+        .await;
+    context.expect_status_code(403).get("/stream/events").await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions_by_account() {
+    let mut context = new_streaming_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let event = first_stream_event(
+        &context,
+        &format!("/stream/transactions?start=0&account={}", txn.sender()),
+    )
+    .await;
+    assert_eq!(event["type"], "user_transaction");
+    assert_eq!(event["hash"], txn.clone().committed_hash().to_hex_literal());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_events_by_type() {
+    let mut context = new_streaming_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let event = first_stream_event(
+        &context,
+        &format!(
+            "/stream/events?start=0&account={}&event_type=0x1::transaction_fee::FeeStatement",
+            txn.sender()
+        ),
+    )
+    .await;
+    assert_eq!(event["type"], "0x1::transaction_fee::FeeStatement");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_max_connections() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.streaming_enabled = true;
+    node_config.api.max_streaming_connections = 0;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+    let resp = context
+        .expect_status_code(503)
+        .get("/stream/transactions")
+        .await;
+    assert_eq!(resp["error_code"], "too_many_streams");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_max_connections_concurrent() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.streaming_enabled = true;
+    node_config.api.max_streaming_connections = 2;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+    let ApiSpecificConfig::V1(address) = context.api_specific_config;
+
+    // Open more streams at once than allowed, only the allowed number of them is accepted
+    let responses = futures::future::join_all(
+        (0..6).map(|_| reqwest::get(format!("http://{}/v1/stream/transactions", address))),
+    )
+    .await;
+    let statuses: Vec<_> = responses
+        .iter()
+        .map(|response| response.as_ref().unwrap().status())
+        .collect();
+    assert_eq!(statuses.iter().filter(|status| **status == 200).count(), 2);
+    assert_eq!(statuses.iter().filter(|status| **status == 503).count(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_follows_new_commits() {
+    let mut context = new_streaming_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+
+    // The stream starts at the next committed version, so it only sees the new block
+    let mut response = open_stream(
+        &context,
+        &format!("/stream/transactions?account={}", txn.sender()),
+    )
+    .await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let event = next_stream_event(&mut response).await;
+    assert_eq!(event["type"], "user_transaction");
+    assert_eq!(event["hash"], txn.clone().committed_hash().to_hex_literal());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_commit_notifier_restarts_after_last_subscriber() {
+    let mut context = new_streaming_test_context(current_function_name!());
+    let commit_notifier = context.context.commit_notifier();
+
+    let commits = commit_notifier.subscribe().unwrap();
+    assert_eq!(commit_notifier.num_subscribers(), 1);
+    drop(commits);
+    assert_eq!(commit_notifier.num_subscribers(), 0);
+    // Let the relay task notice that the last subscriber is gone
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // A new subscriber is still notified of new commits
+    let mut commits = context.context.commit_notifier().subscribe().unwrap();
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+    tokio::time::timeout(Duration::from_secs(30), commits.changed())
+        .await
+        .expect("timed out waiting for a commit notification")
+        .unwrap();
+    assert_eq!(
+        *commits.borrow(),
+        context.get_latest_ledger_info().version()
+    );
+}
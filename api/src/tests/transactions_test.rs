@@ -5,7 +5,7 @@
 use super::new_test_context;
 use crate::tests::new_test_context_with_config;
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
-use aptos_config::config::{GasEstimationStaticOverride, NodeConfig};
+use aptos_config::config::{GasEstimationStaticOverride, NodeConfig, ReadReplicaConfig};
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
@@ -144,6 +144,40 @@ async fn test_post_bcs_format_transaction() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_read_replica() {
+    // As set up for the read replica role
+    let mut node_config = NodeConfig::default();
+    node_config.storage.read_replica = Some(ReadReplicaConfig::default());
+    node_config.api.transaction_submission_enabled = false;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    // The state at the latest version is served
+    let resources = context
+        .get(&format!(
+            "/accounts/{}/resources",
+            account.address().to_hex_literal()
+        ))
+        .await;
+    assert!(resources
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|resource| resource["type"] == json!("0x1::account::Account")));
+
+    // Transactions can't be submitted without a mempool
+    let mut root_account = context.root_account().await;
+    let txn = context.account_transfer_to(&mut root_account, account.address(), 1);
+    let resp = context
+        .expect_status_code(403)
+        .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+        .await;
+    assert_eq!(resp["error_code"], json!("api_disabled"));
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_post_invalid_bcs_format_transaction() {
     let mut context = new_test_context(current_function_name!());
@@ -675,6 +709,105 @@ async fn test_get_account_transactions() {
     assert_json(txns, expected_txns);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_touched_transactions() {
+    let mut context = aptos_api_test_context::new_test_context(
+        current_function_name!(),
+        NodeConfig::default(),
+        true,
+    );
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    // The created account never sent a transaction but is touched by its creation
+    let txns = context
+        .get(format!("/accounts/{}/touched_transactions", account.address()).as_str())
+        .await;
+    assert_eq!(1, txns.as_array().unwrap().len());
+    let expected_txns = context.get("/transactions?start=2&limit=1").await;
+    assert_json(txns, expected_txns);
+
+    let txns = context
+        .get(
+            format!(
+                "/accounts/{}/touched_transactions?start=3",
+                account.address()
+            )
+            .as_str(),
+        )
+        .await;
+    assert_json(txns, json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_touched_transactions_backward() {
+    let mut context = aptos_api_test_context::new_test_context(
+        current_function_name!(),
+        NodeConfig::default(),
+        true,
+    );
+    let root = context.root_account().await.address();
+    let account1 = context.gen_account();
+    let txn1 = context.create_user_account(&account1).await;
+    context.commit_block(&vec![txn1]).await;
+    let account2 = context.gen_account();
+    let txn2 = context.create_user_account(&account2).await;
+    context.commit_block(&vec![txn2]).await;
+
+    // The latest transaction of the root account is the creation of the second account
+    let latest = context
+        .get(format!("/accounts/{}/touched_transactions?limit=1", root).as_str())
+        .await;
+    assert_eq!(1, latest.as_array().unwrap().len());
+    let latest_version: u64 = latest[0]["version"].as_str().unwrap().parse().unwrap();
+
+    // Paging backward from it returns the creation of the first account
+    let previous = context
+        .get(
+            format!(
+                "/accounts/{}/touched_transactions?end={}&limit=1",
+                root, latest_version
+            )
+            .as_str(),
+        )
+        .await;
+    assert_eq!(1, previous.as_array().unwrap().len());
+    let previous_version: u64 = previous[0]["version"].as_str().unwrap().parse().unwrap();
+    assert!(previous_version < latest_version);
+    let expected_txns = context
+        .get(format!("/transactions?start={}&limit=1", previous_version).as_str())
+        .await;
+    assert_json(previous, expected_txns);
+
+    // A page in each direction can't be requested at once
+    context
+        .expect_status_code(400)
+        .get(
+            format!(
+                "/accounts/{}/touched_transactions?start=0&end={}",
+                root, latest_version
+            )
+            .as_str(),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_touched_transactions_disabled() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(403)
+        .get(
+            format!(
+                "/accounts/{}/touched_transactions",
+                context.root_account().await.address()
+            )
+            .as_str(),
+        )
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_transactions_filter_transactions_by_start_sequence_number() {
     let mut context = new_test_context(current_function_name!());
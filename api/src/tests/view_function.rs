@@ -221,3 +221,99 @@ async fn test_view_aggregator() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let resp = context
+        .post(
+            "/view/batch",
+            json!([
+                build_coin_balance_request(&owner.address()),
+                json!({
+                    "function": "0x1::coin::balance",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": vec!["0x1::aptos_coin::NotACoin"],
+                }),
+                build_coin_decimals_request(),
+            ]),
+        )
+        .await;
+
+    // A failing function doesn't fail the rest of the batch
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[0]["error"].is_null());
+    assert!(results[1]["values"].is_null());
+    assert!(results[1]["error"].is_string());
+    assert_eq!(results[2]["values"], json!([8]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_budget_exhausted() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_gas_view_function_batch = 0;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = context
+        .post("/view/batch", json!([build_coin_decimals_request()]))
+        .await;
+
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0]["values"].is_null());
+    assert!(results[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("budget of the batch is exhausted"));
+    assert_eq!(results[0]["gas_used"], "0");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_too_large() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_view_function_batch_size = 1;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/batch",
+            json!([build_coin_decimals_request(), build_coin_decimals_request()]),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_too_large_bcs() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_view_function_batch_size = 1;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // The batch is rejected from its length prefix, before its items are decoded
+    let resp = context
+        .expect_status_code(400)
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path(&context.prepend_path("/view/batch"))
+                .header(
+                    "Content-Type",
+                    "application/x.aptos.view_function_batch+bcs",
+                )
+                .body(vec![0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+        )
+        .await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .contains("exceeds the limit of 1"));
+}
@@ -16,24 +16,28 @@ use crate::{
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         ForbiddenError, InsufficientStorageError, InternalError,
     },
+    simulation::OverriddenStateView,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SimulateTransactionsRequest,
+    SimulateTransactionsRequestBcs, SubmitTransactionRequest, Transaction, TransactionData,
+    TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_storage_interface::Order;
 use aptos_types::{
     account_config::CoinStoreResource,
     mempool_status::MempoolStatusCode,
+    state_store::StateView,
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload, TransactionStatus,
+        RawTransactionWithData, SignedTransaction, TransactionPayload, TransactionStatus, Version,
     },
     vm_status::StatusCode,
 };
@@ -128,6 +132,26 @@ impl VerifyInput for SubmitTransactionsBatchPost {
     }
 }
 
+// We need a custom type here because we use different types for each of the
+// content types possible for the POST data.
+#[derive(ApiRequest, Debug)]
+pub enum SimulateTransactionsPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<SimulateTransactionsRequest>),
+
+    #[oai(content_type = "application/x.aptos.simulate_transactions+bcs")]
+    Bcs(Bcs),
+}
+
+impl VerifyInput for SimulateTransactionsPost {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            SimulateTransactionsPost::Json(inner) => inner.0.verify(),
+            SimulateTransactionsPost::Bcs(_) => Ok(()),
+        }
+    }
+}
+
 /// API for interacting with transactions
 #[derive(Clone)]
 pub struct TransactionsApi {
@@ -275,6 +299,64 @@ impl TransactionsApi {
         api_spawn_blocking(move || api.list_by_account(&accept_type, page, address.0)).await
     }
 
+    /// Get account touched transactions
+    ///
+    /// Retrieves on-chain committed transactions touching an account, in which the
+    /// account is the sender, a secondary signer or the fee payer, owns the key of an
+    /// emitted event, is referred to by an emitted event of the framework, or owns a
+    /// resource or a fungible asset store written by the transaction. This includes
+    /// incoming transfers. If the start version is too far in the past, a 410 will be
+    /// returned.
+    ///
+    /// If no start version is given, it will return the latest transactions. To page
+    /// backward from the latest transactions, set `end` to the version of the oldest
+    /// transaction received.
+    ///
+    /// This API is disabled unless `enable_account_transactions_index` is set in the
+    /// storage config, along with the internal indexer.
+    #[oai(
+        path = "/accounts/:address/touched_transactions",
+        method = "get",
+        operation_id = "get_account_touched_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_account_touched_transactions(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to start list of transactions
+        ///
+        /// If not provided, defaults to showing the latest transactions
+        start: Query<Option<U64>>,
+        /// Ledger version to end list of transactions, exclusive
+        ///
+        /// If provided, the latest transactions before this version are shown. Can't be
+        /// combined with `start`.
+        end: Query<Option<U64>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_get_account_touched_transactions")?;
+        if !self.context.db.account_transactions_index_enabled() {
+            return Err(api_disabled("Get account touched transactions"));
+        }
+        self.context
+            .check_api_output_enabled("Get account touched transactions", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.list_touched_by_account(&accept_type, page, end.0.map(|v| v.0), address.0)
+        })
+        .await
+    }
+
     /// Submit transaction
     ///
     /// This endpoint accepts transaction submissions in two formats.
@@ -530,6 +612,49 @@ impl TransactionsApi {
         .await
     }
 
+    /// Simulate a sequence of transactions
+    ///
+    /// Simulates the transactions in order, each one against the state left by the previous
+    /// ones, e.g., to simulate a transaction depending on another one that isn't committed yet.
+    /// Like with /transactions/simulate, the transactions must have zero-padded signatures.
+    ///
+    /// The state can be overridden before simulating the transactions, to see what would
+    /// happen if an account had different resources, or if a module were upgraded. Overrides
+    /// are given in JSON, except with BCS where they are raw state values, or `None` to delete
+    /// them, under state keys.
+    ///
+    /// To use this endpoint with BCS, you must submit a SimulateTransactionsRequestBcs
+    /// encoded as BCS. See SimulateTransactionsRequestBcs in api/types/src/simulation.rs.
+    #[oai(
+        path = "/transactions/simulate_sequence",
+        method = "post",
+        operation_id = "simulate_transaction_sequence",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_sequence(
+        &self,
+        accept_type: AcceptType,
+        data: SimulateTransactionsPost,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.verify()
+            .context("Simulated transactions invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_sequence")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction sequence"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction sequence", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.simulate_sequence(&accept_type, data)).await
+    }
+
     /// Encode submission
     ///
     /// This endpoint accepts an EncodeSubmissionRequest, which internally is a
@@ -880,6 +1005,63 @@ impl TransactionsApi {
         }
     }
 
+    fn list_touched_by_account(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        end: Option<u64>,
+        address: Address,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_version = latest_ledger_info.version();
+        let oldest_version = latest_ledger_info.oldest_ledger_version.0;
+        let limit = page.limit(&latest_ledger_info)?;
+        let (start_version, order) = match (page.start_option(), end) {
+            (Some(_), Some(_)) => {
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    "Only one of start and end can be given",
+                    AptosErrorCode::InvalidInput,
+                    &latest_ledger_info,
+                ));
+            },
+            (Some(_), None) => {
+                let start_version =
+                    page.compute_start(limit, ledger_version, &latest_ledger_info)?;
+                if start_version < oldest_version {
+                    return Err(version_pruned(start_version, &latest_ledger_info));
+                }
+                (start_version, Order::Ascending)
+            },
+            (None, Some(end)) => {
+                if end <= oldest_version {
+                    return Err(version_pruned(end, &latest_ledger_info));
+                }
+                (end - 1, Order::Descending)
+            },
+            (None, None) => (ledger_version, Order::Descending),
+        };
+
+        let data = self.context.get_account_touched_transactions(
+            address.into(),
+            start_version,
+            order,
+            limit,
+            ledger_version,
+            &latest_ledger_info,
+        )?;
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                self.context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Parses a single signed transaction
     fn get_signed_transaction(
         &self,
@@ -1101,6 +1283,10 @@ impl TransactionsApi {
                 mempool_status.message,
                 AptosErrorCode::InvalidTransactionUpdate,
             )),
+            MempoolStatusCode::RejectedByFilter => Err(AptosError::new_with_error_code(
+                "Transaction was rejected by the node's transaction filter",
+                AptosErrorCode::RejectedByFilter,
+            )),
             MempoolStatusCode::UnknownStatus => Err(AptosError::new_with_error_code(
                 format!("Transaction was rejected with status {}", mempool_status,),
                 AptosErrorCode::InternalError,
@@ -1171,6 +1357,9 @@ impl TransactionsApi {
                         ledger_info,
                     ),
                 ),
+                AptosErrorCode::RejectedByFilter => Err(
+                    SubmitTransactionError::forbidden_from_aptos_error(error, ledger_info),
+                ),
                 _ => Err(SubmitTransactionError::internal_from_aptos_error(
                     error,
                     ledger_info,
@@ -1240,9 +1429,181 @@ impl TransactionsApi {
 
         // Simulate transaction
         let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
-        let version = ledger_info.version();
+        let (vm_status, simulated_txn) = self.simulate_on_state_view(
+            txn,
+            &state_view,
+            ledger_info.version(),
+            /*use_uncached_vm=*/ false,
+        );
+
+        match accept_type {
+            AcceptType::Json => {
+                let user_transactions = self.render_simulated_transactions(
+                    &ledger_info,
+                    &state_view,
+                    vec![(vm_status, simulated_txn)],
+                )?;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
+    /// Simulate transactions in the VM in order, on top of overridden state
+    fn simulate_sequence(
+        &self,
+        accept_type: &AcceptType,
+        data: SimulateTransactionsPost,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let bad_request = |err: anyhow::Error| {
+            SubmitTransactionError::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        };
+
+        let base_state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let mut state_view = OverriddenStateView::new(&base_state_view);
+        let txns: Vec<SignedTransaction> = match data {
+            SimulateTransactionsPost::Bcs(data) => {
+                let request: SimulateTransactionsRequestBcs =
+                    bcs::from_bytes_with_limit(&data.0, MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                        .context("Failed to deserialize input into SimulateTransactionsRequestBcs")
+                        .map_err(bad_request)?;
+                for (state_key, bytes) in request.state_overrides {
+                    state_view
+                        .set(state_key, bytes)
+                        .context("Failed to apply state overrides")
+                        .map_err(bad_request)?;
+                }
+                request.transactions
+            },
+            SimulateTransactionsPost::Json(data) => {
+                let request = data.0;
+                if let Some(state_overrides) = request.state_overrides {
+                    state_view
+                        .apply_state_overrides(&self.context, state_overrides)
+                        .context("Failed to apply state overrides")
+                        .map_err(bad_request)?;
+                }
+                // Transactions are converted against the overridden state, as they may call
+                // functions of overridden modules
+                let resolver = state_view.as_move_resolver();
+                let converter = resolver.as_converter(
+                    self.context.db.clone(),
+                    self.context.table_info_reader.clone(),
+                );
+                request
+                    .transactions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, txn)| {
+                        converter
+                            .try_into_signed_transaction_poem(txn, self.context.chain_id())
+                            .context(format!(
+                                "Failed to create SignedTransaction from SubmitTransactionRequest at position {}",
+                                index
+                            ))
+                            .map_err(bad_request)
+                    })
+                    .collect::<Result<_, _>>()?
+            },
+        };
+
+        let max_sequence_size = self.context.node_config.api.max_simulation_sequence_size;
+        if txns.is_empty() || txns.len() > max_sequence_size {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                format!(
+                    "Simulated {} transactions, while it must be between 1 and {}",
+                    txns.len(),
+                    max_sequence_size,
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        for txn in txns.iter() {
+            // The caller must ensure that the signatures are not valid, as otherwise
+            // a malicious actor could execute the transactions without their knowledge
+            if txn.verify_signature().is_ok() {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "Simulated transactions must not have a valid signature",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+            if !self.context.node_config.api.simulation_filter.allows(
+                aptos_crypto::HashValue::zero(),
+                ledger_info.timestamp(),
+                txn,
+            ) {
+                return Err(SubmitTransactionError::forbidden_with_code(
+                    "Transaction not allowed by simulation filter",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+        }
+
+        let mut simulated_txns = Vec::with_capacity(txns.len());
+        for txn in txns {
+            // Modules may be overridden, or published by the previous transactions, so none of
+            // them may be loaded into the code cache shared with the other simulations
+            let (vm_status, simulated_txn) = self.simulate_on_state_view(
+                txn,
+                &state_view,
+                ledger_info.version(),
+                /*use_uncached_vm=*/ true,
+            );
+            state_view.apply_write_set(&simulated_txn.changes);
+            simulated_txns.push((vm_status, simulated_txn));
+        }
+
+        match accept_type {
+            AcceptType::Json => {
+                let user_transactions =
+                    self.render_simulated_transactions(&ledger_info, &state_view, simulated_txns)?;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                let simulated_txns: Vec<_> = simulated_txns
+                    .into_iter()
+                    .map(|(_, simulated_txn)| simulated_txn)
+                    .collect();
+                BasicResponse::try_from_bcs((simulated_txns, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
+    /// Simulate a transaction in the VM against the given state
+    ///
+    /// All state hashes of the resulting transaction are invalid, and filled with 0s. A VM with
+    /// an empty code cache must be used when the modules of the state may differ from the ones
+    /// in storage.
+    fn simulate_on_state_view(
+        &self,
+        txn: SignedTransaction,
+        state_view: &impl StateView,
+        version: Version,
+        use_uncached_vm: bool,
+    ) -> (VMStatus, TransactionOnChainData) {
+        let (vm_status, output) = if use_uncached_vm {
+            AptosSimulationVM::create_uncached_vm_and_simulate_signed_transaction(&txn, state_view)
+        } else {
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, state_view)
+        };
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
         let exe_status = match output.status().clone() {
@@ -1299,53 +1660,68 @@ impl TransactionsApi {
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
         };
+        (vm_status, simulated_txn)
+    }
 
-        match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => {
-                            let mut txn = *user_txn;
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    txn.info.vm_status +=
-                                        format!("\nExecution failed with status: {}", msg).as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
+    /// Renders simulated transactions in JSON, using the state they were simulated against
+    fn render_simulated_transactions(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        simulated_txns: Vec<(VMStatus, TransactionOnChainData)>,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        );
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the transactions into UserTransactions.
+        let mut user_transactions = Vec::new();
+        for (vm_status, simulated_txn) in simulated_txns {
+            let transaction = self
+                .context
+                .db
+                .get_block_timestamp(simulated_txn.version)
+                .and_then(|timestamp| {
+                    converter.try_into_onchain_transaction(timestamp, simulated_txn)
+                })
+                .context("Failed to convert simulated transaction")
+                .map_err(|err| {
+                    SubmitTransactionError::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?;
+            match transaction {
+                Transaction::UserTransaction(user_txn) => {
+                    let mut txn = *user_txn;
+                    match &vm_status {
+                        VMStatus::Error {
+                            message: Some(msg), ..
+                        }
+                        | VMStatus::ExecutionFailure {
+                            message: Some(msg), ..
+                        } => {
+                            txn.info.vm_status +=
+                                format!("\nExecution failed with status: {}", msg).as_str();
                         },
+                        _ => (),
                     }
-                }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
+                    user_transactions.push(txn);
+                },
+                _ => {
+                    return Err(SubmitTransactionError::internal_with_code(
+                        "Simulation transaction resulted in a non-UserTransaction",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    ))
+                },
+            }
         }
+        Ok(user_transactions)
     }
 
     /// Encode message as BCS
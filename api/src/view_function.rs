@@ -12,17 +12,21 @@ use crate::{
     },
     ApiTags, Context,
 };
-use anyhow::Context as anyhowContext;
+use anyhow::{anyhow, Context as anyhowContext};
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewFunctionResult,
+    ViewFunctionResultBcs, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
-use aptos_bcs_utils::serialize_uleb128;
+use aptos_bcs_utils::{deserialize_uleb128, serialize_uleb128};
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
 use poem_openapi::{param::Query, payload::Json, ApiRequest, OpenApi};
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// API for executing Move view function.
 #[derive(Clone)]
@@ -39,6 +43,15 @@ pub enum ViewFunctionRequest {
     Bcs(Bcs),
 }
 
+#[derive(ApiRequest, Debug)]
+pub enum ViewFunctionBatchRequest {
+    #[oai(content_type = "application/json")]
+    Json(Json<Vec<ViewRequest>>),
+
+    #[oai(content_type = "application/x.aptos.view_function_batch+bcs")]
+    Bcs(Bcs),
+}
+
 #[OpenApi]
 impl ViewFunctionApi {
     /// Execute view function of a module
@@ -71,6 +84,45 @@ impl ViewFunctionApi {
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
             .await
     }
+
+    /// Execute a batch of view functions
+    ///
+    /// Execute the Move functions with the given parameters against a single ledger version
+    /// and return their execution results, in the order of the requests. A function that
+    /// fails doesn't fail the batch, its result holds the error instead.
+    ///
+    /// The functions of a batch share a gas and time budget. A function still running when
+    /// the time budget runs out is aborted. Once the budget is exhausted, the remaining
+    /// functions are not executed and their results hold an error.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        request: ViewFunctionBatchRequest,
+        /// Ledger version to get state of accounts
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ViewFunctionResult>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            view_batch_request(context, accept_type, request, ledger_version)
+        })
+        .await
+    }
 }
 
 fn view_request(
@@ -121,11 +173,7 @@ fn view_request(
     };
 
     // Reject the request if it's not allowed by the filter.
-    if !context.node_config.api.view_filter.allows(
-        view_function.module.address(),
-        view_function.module.name().as_str(),
-        view_function.function.as_str(),
-    ) {
+    if !is_allowed(&context, &view_function) {
         return Err(BasicErrorWith404::forbidden_with_code_no_info(
             format!(
                 "Function {}::{} is not allowed",
@@ -169,32 +217,7 @@ fn view_request(
             BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let resolver = state_view.as_move_resolver();
-            let return_types = resolver
-                .as_converter(context.db.clone(), context.table_info_reader.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.into_iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = values
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    resolver
-                        .as_converter(context.db.clone(), context.table_info_reader.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
+            let move_vals = convert_return_values(&context, &state_view, &view_function, values)
                 .map_err(|err| {
                     BasicErrorWith404::bad_request_with_code(
                         err,
@@ -212,3 +235,200 @@ fn view_request(
     );
     result
 }
+
+fn view_batch_request(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: ViewFunctionBatchRequest,
+    ledger_version: Query<Option<U64>>,
+) -> BasicResultWith404<Vec<ViewFunctionResult>> {
+    // Retrieve the state of the chain all the functions are executed against
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let state_view = context
+        .state_view_at_version(requested_version)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+    let api_config = &context.node_config.api;
+    let check_batch_size = |batch_size: usize| {
+        if batch_size > api_config.max_view_function_batch_size {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Batch of {} view functions exceeds the limit of {}",
+                    batch_size, api_config.max_view_function_batch_size
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        Ok(())
+    };
+
+    // Requests that can't be converted fail on their own, without failing the batch
+    let view_functions: Vec<anyhow::Result<ViewFunction>> = match request {
+        ViewFunctionBatchRequest::Json(data) => {
+            check_batch_size(data.0.len())?;
+            let resolver = state_view.as_move_resolver();
+            let converter =
+                resolver.as_converter(context.db.clone(), context.table_info_reader.clone());
+            data.0
+                .into_iter()
+                .map(|request| converter.convert_view_function(request))
+                .collect()
+        },
+        ViewFunctionBatchRequest::Bcs(data) => {
+            // Check the length prefix of the batch before decoding its items
+            if let Ok((batch_size, _)) = deserialize_uleb128(data.0.as_slice()) {
+                check_batch_size(usize::try_from(batch_size).unwrap_or(usize::MAX))?;
+            }
+            let view_functions: Vec<ViewFunction> =
+                bcs::from_bytes_with_limit(data.0.as_slice(), MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                    .context("Failed to deserialize input into a batch of ViewRequest")
+                    .map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+            view_functions.into_iter().map(Ok).collect()
+        },
+    };
+
+    let deadline =
+        Instant::now() + Duration::from_millis(api_config.view_function_batch_timeout_ms);
+    let mut gas_remaining = api_config.max_gas_view_function_batch;
+    let mut outputs = Vec::with_capacity(view_functions.len());
+    for view_function in view_functions {
+        let output = view_function.and_then(|view_function| {
+            if !is_allowed(&context, &view_function) {
+                return Err(anyhow!(
+                    "Function {}::{} is not allowed",
+                    view_function.module,
+                    view_function.function
+                ));
+            }
+            if gas_remaining == 0 || Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Function {}::{} not executed, the gas or time budget of the batch is exhausted",
+                    view_function.module,
+                    view_function.function
+                ));
+            }
+
+            let output = AptosVM::execute_view_function_with_deadline(
+                &state_view,
+                view_function.module.clone(),
+                view_function.function.clone(),
+                view_function.ty_args.clone(),
+                view_function.args.clone(),
+                std::cmp::min(api_config.max_gas_view_function, gas_remaining),
+                deadline,
+            );
+            gas_remaining = gas_remaining.saturating_sub(output.gas_used);
+            context.view_function_stats().increment(
+                FunctionStats::function_to_key(&view_function.module, &view_function.function),
+                output.gas_used,
+            );
+            Ok((view_function, output))
+        });
+        outputs.push(output);
+    }
+
+    match accept_type {
+        AcceptType::Bcs => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|output| match output {
+                    Ok((_, output)) => match output.values {
+                        Ok(values) => ViewFunctionResultBcs {
+                            values: Some(values),
+                            error: None,
+                            gas_used: output.gas_used,
+                        },
+                        Err(err) => ViewFunctionResultBcs {
+                            values: None,
+                            error: Some(format!("{:#}", err)),
+                            gas_used: output.gas_used,
+                        },
+                    },
+                    Err(err) => ViewFunctionResultBcs {
+                        values: None,
+                        error: Some(format!("{:#}", err)),
+                        gas_used: 0,
+                    },
+                })
+                .collect();
+            BasicResponse::try_from_bcs((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+        AcceptType::Json => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|output| {
+                    let (values, gas_used) = match output {
+                        Ok((view_function, output)) => (
+                            output.values.and_then(|values| {
+                                convert_return_values(&context, &state_view, &view_function, values)
+                            }),
+                            output.gas_used,
+                        ),
+                        Err(err) => (Err(err), 0),
+                    };
+                    match values {
+                        Ok(values) => ViewFunctionResult {
+                            values: Some(values),
+                            error: None,
+                            gas_used: gas_used.into(),
+                        },
+                        Err(err) => ViewFunctionResult {
+                            values: None,
+                            error: Some(format!("{:#}", err)),
+                            gas_used: gas_used.into(),
+                        },
+                    }
+                })
+                .collect();
+            BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+    }
+}
+
+/// Returns whether the view function is allowed by the view filter of the node
+fn is_allowed(context: &Context, view_function: &ViewFunction) -> bool {
+    context.node_config.api.view_filter.allows(
+        view_function.module.address(),
+        view_function.module.name().as_str(),
+        view_function.function.as_str(),
+    )
+}
+
+/// Converts the BCS encoded return values of a view function to JSON values
+fn convert_return_values(
+    context: &Context,
+    state_view: &DbStateView,
+    view_function: &ViewFunction,
+    values: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<MoveValue>> {
+    let resolver = state_view.as_move_resolver();
+    let converter = resolver.as_converter(context.db.clone(), context.table_info_reader.clone());
+    let return_types = converter
+        .function_return_types(view_function)
+        .and_then(|tys| {
+            tys.into_iter()
+                .map(TypeTag::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+
+    values
+        .into_iter()
+        .zip(return_types.into_iter())
+        .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+        .collect()
+}
@@ -17,9 +17,11 @@ use aptos_config::{
 };
 use aptos_crypto::{ed25519::Ed25519PrivateKey, hash::HashValue, SigningKey};
 use aptos_db::AptosDB;
+use aptos_event_notifications::{EventNotificationSender, EventSubscriptionService};
 use aptos_executor::{block_executor::BlockExecutor, db_bootstrapper};
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_framework::BuiltPackage;
+use aptos_infallible::{Mutex, RwLock};
 use aptos_mempool::mocks::MockSharedMempool;
 use aptos_mempool_notifications::MempoolNotificationSender;
 use aptos_sdk::{
@@ -34,6 +36,7 @@ use aptos_storage_interface::{state_view::DbStateView, DbReaderWriter};
 use aptos_temppath::TempPath;
 use aptos_types::{
     account_address::{create_multisig_account_address, AccountAddress},
+    account_config::new_block_event_key,
     aggregate_signature::AggregateSignature,
     block_executor::config::BlockExecutorConfigFromOnchain,
     block_info::BlockInfo,
@@ -148,6 +151,15 @@ pub fn new_test_context(
         None, /* table info reader */
     );
 
+    // Notify the streaming APIs of the blocks committed by the tests, as state sync does
+    let mut event_subscription_service =
+        EventSubscriptionService::new(Arc::new(RwLock::new(db_rw.clone())));
+    context.set_commit_notifications(
+        event_subscription_service
+            .subscribe_to_events(vec![new_block_event_key()], vec![])
+            .unwrap(),
+    );
+
     // Configure the testing depending on which API version we're testing.
     let runtime_handle = tokio::runtime::Handle::current();
     let poem_address = attach_poem_to_runtime(&runtime_handle, context.clone(), &node_config, true)
@@ -160,6 +172,7 @@ pub fn new_test_context(
         root_key,
         validator_owner,
         Box::new(BlockExecutor::<AptosVM>::new(db_rw)),
+        event_subscription_service,
         mempool,
         db,
         test_name,
@@ -176,6 +189,7 @@ pub struct TestContext {
     rng: rand::rngs::StdRng,
     root_key: ConfigKey<Ed25519PrivateKey>,
     executor: Arc<dyn BlockExecutorTrait>,
+    event_subscription_service: Arc<Mutex<EventSubscriptionService>>,
     expect_status_code: u16,
     test_name: String,
     golden_output: Option<GoldenOutputs>,
@@ -190,6 +204,7 @@ impl TestContext {
         root_key: Ed25519PrivateKey,
         validator_owner: AccountAddress,
         executor: Box<dyn BlockExecutorTrait>,
+        event_subscription_service: EventSubscriptionService,
         mempool: MockSharedMempool,
         db: Arc<AptosDB>,
         test_name: String,
@@ -201,6 +216,7 @@ impl TestContext {
             root_key: ConfigKey::new(root_key),
             validator_owner,
             executor: executor.into(),
+            event_subscription_service: Arc::new(Mutex::new(event_subscription_service)),
             mempool: Arc::new(mempool),
             expect_status_code: 200,
             db,
@@ -635,6 +651,10 @@ impl TestContext {
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len() + 1),
             )
             .unwrap();
+        self.event_subscription_service
+            .lock()
+            .notify_events(result.version(), result.subscribable_events().to_vec())
+            .unwrap();
 
         self.mempool
             .mempool_notifier
@@ -96,11 +96,15 @@ pub enum AptosErrorCode {
     SequenceNumberTooOld = 402,
     /// The submitted transaction failed VM checks.
     VmError = 403,
+    /// The submitted transaction was rejected by the node's transaction filter.
+    RejectedByFilter = 404,
 
     /// Health check failed.
     HealthCheckFailed = 500,
     /// The mempool is full, no new transactions can be submitted.
     MempoolIsFull = 501,
+    /// The maximum number of streams is open, no new streams can be opened.
+    TooManyStreams = 502,
 
     /// Internal server error
     InternalError = 600,
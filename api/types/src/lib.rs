@@ -15,8 +15,10 @@ mod hash;
 mod headers;
 mod index;
 mod ledger_info;
+mod mempool;
 pub mod mime_types;
 mod move_types;
+mod simulation;
 mod state;
 mod table;
 pub mod transaction;
@@ -33,6 +35,10 @@ pub use hash::HashValue;
 pub use headers::*;
 pub use index::{IndexResponse, IndexResponseBcs};
 pub use ledger_info::LedgerInfo;
+pub use mempool::{
+    MempoolAccountTransactions, MempoolAccountTransactionsBcs, MempoolStats, MempoolTransaction,
+    MempoolTransactionBcs, SequenceNumberGap,
+};
 pub use move_types::{
     verify_field_identifier, verify_function_identifier, verify_module_identifier, EntryFunctionId,
     HexEncodedBytes, MoveAbility, MoveFunction, MoveFunctionGenericTypeParam,
@@ -41,7 +47,11 @@ pub use move_types::{
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
 use serde::{Deserialize, Deserializer};
-pub use state::RawStateValueRequest;
+pub use simulation::{
+    ModuleOverride, ResourceOverride, SimulateTransactionsRequest, SimulateTransactionsRequestBcs,
+    StateOverrides, TableItemOverride,
+};
+pub use state::{RawStateValueRequest, StateValueChange, StateValueChangeBcs};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
@@ -57,7 +67,7 @@ pub use transaction::{
     UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
     WriteSetPayload, WriteTableItem,
 };
-pub use view::{ViewFunction, ViewRequest};
+pub use view::{ViewFunction, ViewFunctionResult, ViewFunctionResultBcs, ViewRequest};
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
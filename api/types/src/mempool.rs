@@ -0,0 +1,89 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, PendingTransaction, U64};
+use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// A transaction in mempool, along with its mempool metadata
+///
+/// Ready transactions are pulled into blocks in the order of their highest `priority_score`,
+/// then their earliest `system_expiration_timestamp_usecs`, then the highest sender address
+/// and the lowest sequence number.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MempoolTransaction {
+    pub transaction: PendingTransaction,
+    /// The score assigned to the transaction by the node's mempool ordering policy
+    pub priority_score: U64,
+    /// The time mempool evicts the transaction in Unix epoch microseconds
+    pub system_expiration_timestamp_usecs: U64,
+    /// The time the transaction was inserted into mempool in Unix epoch microseconds
+    pub insertion_timestamp_usecs: U64,
+    /// The time the transaction has spent in mempool in microseconds
+    pub time_in_mempool_usecs: U64,
+    /// Whether the transaction is parked, i.e., it can't be included in a block until the
+    /// transactions of the same account with lower sequence numbers are
+    pub parked: bool,
+}
+
+/// A transaction in mempool, along with its mempool metadata for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolTransactionBcs {
+    pub transaction: SignedTransaction,
+    pub priority_score: u64,
+    pub system_expiration_timestamp_usecs: u64,
+    pub insertion_timestamp_usecs: u64,
+    pub time_in_mempool_usecs: u64,
+    pub parked: bool,
+}
+
+/// A range of missing sequence numbers, from `start` (inclusive) to `end` (exclusive)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SequenceNumberGap {
+    pub start: U64,
+    pub end: U64,
+}
+
+/// The transactions of an account in mempool
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MempoolAccountTransactions {
+    pub address: Address,
+    /// The sequence number of the account known to mempool, if any
+    pub sequence_number: Option<U64>,
+    /// The transactions of the account in mempool, ordered by sequence number
+    pub transactions: Vec<MempoolTransaction>,
+    /// The sequence numbers missing before the transactions, which cause them to be parked
+    pub sequence_number_gaps: Vec<SequenceNumberGap>,
+}
+
+/// The transactions of an account in mempool for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolAccountTransactionsBcs {
+    pub address: AccountAddress,
+    pub sequence_number: Option<u64>,
+    pub transactions: Vec<MempoolTransactionBcs>,
+    /// The missing sequence number ranges, as `(start, end)` with `end` exclusive
+    pub sequence_number_gaps: Vec<(u64, u64)>,
+}
+
+/// Statistics of the node's mempool
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MempoolStats {
+    /// The number of transactions in mempool
+    pub num_transactions: U64,
+    /// The number of transactions that can be included in the next blocks
+    pub num_ready_transactions: U64,
+    /// The number of transactions waiting for transactions with lower sequence numbers
+    pub num_parked_transactions: U64,
+    /// The number of accounts with transactions in mempool
+    pub num_accounts: U64,
+    /// The estimated size of mempool in bytes
+    pub size_bytes: U64,
+    /// The maximum number of transactions in mempool
+    pub capacity: U64,
+    /// The maximum size of mempool in bytes
+    pub capacity_bytes: U64,
+    /// The maximum number of transactions in mempool per account
+    pub capacity_per_user: U64,
+}
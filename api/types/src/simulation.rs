@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    Address, HexEncodedBytes, MoveStructTag, MoveType, SubmitTransactionRequest, VerifyInput,
+    VerifyInputWithRecursion,
+};
+use anyhow::bail;
+use aptos_types::{state_store::state_key::StateKey, transaction::SignedTransaction};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request to simulate a sequence of transactions against overridden state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionsRequest {
+    /// Transactions to simulate in order, each one seeing the changes of the previous ones
+    pub transactions: Vec<SubmitTransactionRequest>,
+    /// Changes applied to the state before simulating the transactions
+    pub state_overrides: Option<StateOverrides>,
+}
+
+impl VerifyInput for SimulateTransactionsRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        if self.transactions.is_empty() {
+            bail!("No transactions to simulate")
+        }
+        for transaction in self.transactions.iter() {
+            transaction.verify()?;
+        }
+        if let Some(state_overrides) = &self.state_overrides {
+            state_overrides.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Request to simulate a sequence of transactions against overridden state for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulateTransactionsRequestBcs {
+    pub transactions: Vec<SignedTransaction>,
+    /// Raw state values to set under the state keys, or to delete when `None`
+    pub state_overrides: Vec<(StateKey, Option<Vec<u8>>)>,
+}
+
+/// Changes applied to the state before simulating transactions
+///
+/// Resources stored in resource groups, e.g., the resources of objects, can't be overridden.
+/// Neither can the modules and resources of the framework account 0x1, e.g., the on-chain
+/// configs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// Modules to publish or upgrade
+    #[serde(default)]
+    #[oai(default)]
+    pub modules: Vec<ModuleOverride>,
+    /// Resources to set or delete
+    #[serde(default)]
+    #[oai(default)]
+    pub resources: Vec<ResourceOverride>,
+    /// Table items to set or delete
+    #[serde(default)]
+    #[oai(default)]
+    pub table_items: Vec<TableItemOverride>,
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        for module in self.modules.iter() {
+            if module.bytecode.is_empty() {
+                bail!("Move module bytecode is empty")
+            }
+        }
+        for resource in self.resources.iter() {
+            resource.typ.verify(0)?;
+        }
+        for table_item in self.table_items.iter() {
+            table_item.key_type.verify(0)?;
+            table_item.value_type.verify(0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Module published at the address of the module, replacing any existing one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub bytecode: HexEncodedBytes,
+}
+
+/// Resource set under an account, replacing any existing one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: MoveStructTag,
+    /// Value of the resource in JSON, as returned by the resource APIs, or `null` to delete it
+    pub data: Option<Value>,
+}
+
+/// Item set in a table, replacing any existing one
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableItemOverride {
+    pub handle: Address,
+    pub key_type: MoveType,
+    pub value_type: MoveType,
+    /// The value of the table item's key
+    pub key: Value,
+    /// The value of the table item, or `null` to delete it
+    pub value: Option<Value>,
+}
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HexEncodedBytes;
+use crate::{HexEncodedBytes, MoveValue, U64};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -10,3 +10,20 @@ use serde::{Deserialize, Serialize};
 pub struct RawStateValueRequest {
     pub key: HexEncodedBytes,
 }
+
+/// A change of a state value, e.g., a resource or a table item
+#[derive(Clone, Debug, PartialEq, Serialize, Object)]
+pub struct StateValueChange {
+    /// The version of the transaction that changed the value
+    pub version: U64,
+    /// The value written by the transaction, or none if it deleted the value
+    pub value: Option<MoveValue>,
+}
+
+/// A change of a state value for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateValueChangeBcs {
+    pub version: u64,
+    /// The BCS encoded value, or none if it was deleted
+    pub value: Option<Vec<u8>>,
+}
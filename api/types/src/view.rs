@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{EntryFunctionId, MoveType, MoveValue, U64};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -28,3 +28,23 @@ pub struct ViewFunction {
     #[serde(with = "vec_bytes")]
     pub args: Vec<Vec<u8>>,
 }
+
+/// Result of a view function executed as part of a batch
+#[derive(Clone, Debug, PartialEq, Serialize, Object)]
+pub struct ViewFunctionResult {
+    /// Return values of the function, if it succeeded
+    pub values: Option<Vec<MoveValue>>,
+    /// Reason the function failed or wasn't executed
+    pub error: Option<String>,
+    /// Gas used by the function
+    pub gas_used: U64,
+}
+
+/// Result of a view function executed as part of a batch for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewFunctionResultBcs {
+    /// BCS encoded return values of the function, if it succeeded
+    pub values: Option<Vec<Vec<u8>>>,
+    pub error: Option<String>,
+    pub gas_used: u64,
+}
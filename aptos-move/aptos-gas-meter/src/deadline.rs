@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::traits::GasAlgebra;
+use aptos_gas_algebra::{Fee, FeePerGasUnit, GasExpression, Octa};
+use aptos_gas_schedule::VMGasParameters;
+use aptos_vm_types::storage::{io_pricing::IoPricing, space_pricing::DiskSpacePricing};
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_core_types::{
+    gas_algebra::{InternalGas, InternalGasUnit, NumBytes},
+    vm_status::StatusCode,
+};
+use std::{fmt::Debug, time::Instant};
+
+/// Number of charges between two checks of the clock.
+const CHARGES_PER_CLOCK_CHECK: u32 = 1024;
+
+/// A gas algebra that stops the execution once a deadline has passed, on top of the charges
+/// of the underlying algebra. Used to bound the wall-clock time of executions that are not
+/// part of a block, such as view functions.
+pub struct DeadlineGasAlgebra<A> {
+    base: A,
+    deadline: Instant,
+    charges_until_clock_check: u32,
+}
+
+impl<A> DeadlineGasAlgebra<A> {
+    pub fn new(base: A, deadline: Instant) -> Self {
+        Self {
+            base,
+            deadline,
+            charges_until_clock_check: 0,
+        }
+    }
+
+    fn check_deadline(&mut self) -> PartialVMResult<()> {
+        if self.charges_until_clock_check > 0 {
+            self.charges_until_clock_check -= 1;
+            return Ok(());
+        }
+        self.charges_until_clock_check = CHARGES_PER_CLOCK_CHECK;
+        if Instant::now() >= self.deadline {
+            return Err(PartialVMError::new(StatusCode::EXECUTION_LIMIT_REACHED)
+                .with_message("Execution deadline exceeded".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl<A: GasAlgebra> GasAlgebra for DeadlineGasAlgebra<A> {
+    fn feature_version(&self) -> u64 {
+        self.base.feature_version()
+    }
+
+    fn vm_gas_params(&self) -> &VMGasParameters {
+        self.base.vm_gas_params()
+    }
+
+    fn io_pricing(&self) -> &IoPricing {
+        self.base.io_pricing()
+    }
+
+    fn disk_space_pricing(&self) -> &DiskSpacePricing {
+        self.base.disk_space_pricing()
+    }
+
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn check_consistency(&self) -> PartialVMResult<()> {
+        self.base.check_consistency()
+    }
+
+    fn charge_execution(
+        &mut self,
+        abstract_amount: impl GasExpression<VMGasParameters, Unit = InternalGasUnit> + Debug,
+    ) -> PartialVMResult<()> {
+        self.base.charge_execution(abstract_amount)?;
+        self.check_deadline()
+    }
+
+    fn charge_io(
+        &mut self,
+        abstract_amount: impl GasExpression<VMGasParameters, Unit = InternalGasUnit>,
+    ) -> PartialVMResult<()> {
+        self.base.charge_io(abstract_amount)?;
+        self.check_deadline()
+    }
+
+    fn charge_storage_fee(
+        &mut self,
+        abstract_amount: impl GasExpression<VMGasParameters, Unit = Octa>,
+        gas_unit_price: FeePerGasUnit,
+    ) -> PartialVMResult<()> {
+        self.base
+            .charge_storage_fee(abstract_amount, gas_unit_price)
+    }
+
+    fn count_dependency(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.base.count_dependency(size)
+    }
+
+    fn execution_gas_used(&self) -> InternalGas {
+        self.base.execution_gas_used()
+    }
+
+    fn io_gas_used(&self) -> InternalGas {
+        self.base.io_gas_used()
+    }
+
+    fn storage_fee_used_in_gas_units(&self) -> InternalGas {
+        self.base.storage_fee_used_in_gas_units()
+    }
+
+    fn storage_fee_used(&self) -> Fee {
+        self.base.storage_fee_used()
+    }
+}
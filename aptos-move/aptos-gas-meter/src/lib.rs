@@ -5,9 +5,11 @@
 //! It also defines traits that enable composability of gas meters and algebra.
 
 mod algebra;
+mod deadline;
 mod meter;
 mod traits;
 
 pub use algebra::StandardGasAlgebra;
+pub use deadline::DeadlineGasAlgebra;
 pub use meter::StandardGasMeter;
 pub use traits::{AptosGasMeter, GasAlgebra};
@@ -27,7 +27,9 @@ use aptos_framework::{
     RuntimeModuleMetadataV1,
 };
 use aptos_gas_algebra::{Gas, GasQuantity, NumBytes, Octa};
-use aptos_gas_meter::{AptosGasMeter, GasAlgebra, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_meter::{
+    AptosGasMeter, DeadlineGasAlgebra, GasAlgebra, StandardGasAlgebra, StandardGasMeter,
+};
 use aptos_gas_schedule::{AptosGasParameters, VMGasParameters};
 use aptos_logger::{enabled, prelude::*, Level};
 use aptos_memory_usage_tracker::MemoryTrackedGasMeter;
@@ -101,6 +103,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     marker::Sync,
     sync::Arc,
+    time::Instant,
 };
 
 static EXECUTION_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
@@ -201,6 +204,18 @@ impl AptosVM {
     pub fn new(
         resolver: &impl AptosMoveResolver,
         override_is_delayed_field_optimization_capable: Option<bool>,
+    ) -> Self {
+        Self::new_impl(
+            resolver,
+            override_is_delayed_field_optimization_capable,
+            /*use_warm_vm_cache=*/ true,
+        )
+    }
+
+    fn new_impl(
+        resolver: &impl AptosMoveResolver,
+        override_is_delayed_field_optimization_capable: Option<bool>,
+        use_warm_vm_cache: bool,
     ) -> Self {
         let _timer = TIMER.timer_with(&["AptosVM::new"]);
 
@@ -234,16 +249,29 @@ impl AptosVM {
         let aggregator_v2_type_tagging = override_is_delayed_field_optimization_capable
             && features.is_aggregator_v2_delayed_fields_enabled();
 
-        let move_vm = MoveVmExt::new(
-            native_gas_params,
-            misc_gas_params,
-            gas_feature_version,
-            chain_id.id(),
-            features,
-            timed_features.clone(),
-            resolver,
-            aggregator_v2_type_tagging,
-        )
+        let move_vm = if use_warm_vm_cache {
+            MoveVmExt::new(
+                native_gas_params,
+                misc_gas_params,
+                gas_feature_version,
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        } else {
+            MoveVmExt::new_uncached(
+                native_gas_params,
+                misc_gas_params,
+                gas_feature_version,
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        }
         .expect("should be able to create Move VM; check if there are duplicated natives");
 
         Self {
@@ -1454,17 +1482,25 @@ impl AptosVM {
         log_context: &AdapterLogSchema,
     ) -> Result<MemoryTrackedGasMeter<StandardGasMeter<StandardGasAlgebra>>, VMStatus> {
         Ok(MemoryTrackedGasMeter::new(StandardGasMeter::new(
-            StandardGasAlgebra::new(
-                self.gas_feature_version,
-                get_or_vm_startup_failure(&self.gas_params, log_context)?
-                    .vm
-                    .clone(),
-                get_or_vm_startup_failure(&self.storage_gas_params, log_context)?.clone(),
-                balance,
-            ),
+            self.make_standard_gas_algebra(balance, log_context)?,
         )))
     }
 
+    fn make_standard_gas_algebra(
+        &self,
+        balance: Gas,
+        log_context: &AdapterLogSchema,
+    ) -> Result<StandardGasAlgebra, VMStatus> {
+        Ok(StandardGasAlgebra::new(
+            self.gas_feature_version,
+            get_or_vm_startup_failure(&self.gas_params, log_context)?
+                .vm
+                .clone(),
+            get_or_vm_startup_failure(&self.storage_gas_params, log_context)?.clone(),
+            balance,
+        ))
+    }
+
     fn validate_signed_transaction(
         &self,
         session: &mut SessionExt,
@@ -1988,6 +2024,55 @@ impl AptosVM {
         type_args: Vec<TypeTag>,
         arguments: Vec<Vec<u8>>,
         max_gas_amount: u64,
+    ) -> ViewFunctionOutput {
+        Self::execute_view_function_with_gas_meter(
+            state_view,
+            module_id,
+            func_name,
+            type_args,
+            arguments,
+            max_gas_amount,
+            |vm, log_context| vm.make_standard_gas_meter(max_gas_amount.into(), log_context),
+        )
+    }
+
+    /// Executes a view function like [`AptosVM::execute_view_function`], and aborts the
+    /// execution once `deadline` has passed.
+    pub fn execute_view_function_with_deadline(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        max_gas_amount: u64,
+        deadline: Instant,
+    ) -> ViewFunctionOutput {
+        Self::execute_view_function_with_gas_meter(
+            state_view,
+            module_id,
+            func_name,
+            type_args,
+            arguments,
+            max_gas_amount,
+            |vm, log_context| {
+                Ok(MemoryTrackedGasMeter::new(StandardGasMeter::new(
+                    DeadlineGasAlgebra::new(
+                        vm.make_standard_gas_algebra(max_gas_amount.into(), log_context)?,
+                        deadline,
+                    ),
+                )))
+            },
+        )
+    }
+
+    fn execute_view_function_with_gas_meter<G: AptosGasMeter>(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        max_gas_amount: u64,
+        make_gas_meter: impl FnOnce(&AptosVM, &AdapterLogSchema) -> Result<G, VMStatus>,
     ) -> ViewFunctionOutput {
         let resolver = state_view.as_move_resolver();
         let vm = AptosVM::new(
@@ -1995,7 +2080,7 @@ impl AptosVM {
             /*override_is_delayed_field_optimization_capable=*/ Some(false),
         );
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
-        let mut gas_meter = match vm.make_standard_gas_meter(max_gas_amount.into(), &log_context) {
+        let mut gas_meter = match make_gas_meter(&vm, &log_context) {
             Ok(gas_meter) => gas_meter,
             Err(e) => return ViewFunctionOutput::new(Err(anyhow::Error::msg(format!("{}", e))), 0),
         };
@@ -2407,9 +2492,20 @@ pub struct AptosSimulationVM(AptosVM);
 
 impl AptosSimulationVM {
     pub fn new(resolver: &impl AptosMoveResolver) -> Self {
-        let mut vm = AptosVM::new(
+        Self::new_impl(resolver, /*use_warm_vm_cache=*/ true)
+    }
+
+    /// Creates a simulation VM that doesn't take its code cache from the warm VM cache, so that
+    /// modules loaded from overridden state can't leak into other executions.
+    pub fn new_uncached(resolver: &impl AptosMoveResolver) -> Self {
+        Self::new_impl(resolver, /*use_warm_vm_cache=*/ false)
+    }
+
+    fn new_impl(resolver: &impl AptosMoveResolver, use_warm_vm_cache: bool) -> Self {
+        let mut vm = AptosVM::new_impl(
             resolver,
             /*override_is_delayed_field_optimization_capable=*/ Some(false),
+            use_warm_vm_cache,
         );
         vm.is_simulation = true;
         Self(vm)
@@ -2429,12 +2525,40 @@ impl AptosSimulationVM {
 
         let resolver = state_view.as_move_resolver();
         let vm = Self::new(&resolver);
+        vm.simulate_signed_transaction(transaction, state_view, &resolver)
+    }
+
+    /// Simulates a signed transaction on a newly created VM instance with an empty code cache.
+    /// To be used with state views overriding modules, as the code cache of the warm VMs is
+    /// shared with the executions against the state in storage.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn create_uncached_vm_and_simulate_signed_transaction(
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        let resolver = state_view.as_move_resolver();
+        let vm = Self::new_uncached(&resolver);
+        vm.simulate_signed_transaction(transaction, state_view, &resolver)
+    }
+
+    fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        resolver: &impl AptosMoveResolver,
+    ) -> (VMStatus, TransactionOutput) {
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
         let (vm_status, vm_output) =
-            vm.0.execute_user_transaction(&resolver, transaction, &log_context);
+            self.0
+                .execute_user_transaction(resolver, transaction, &log_context);
         let txn_output = vm_output
-            .try_materialize_into_transaction_output(&resolver)
+            .try_materialize_into_transaction_output(resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
         (vm_status, txn_output)
     }
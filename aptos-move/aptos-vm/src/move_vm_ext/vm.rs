@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId};
+use crate::{
+    move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId},
+    natives::aptos_natives_with_builder,
+};
 use aptos_framework::natives::{
     aggregator_natives::NativeAggregatorContext,
     code::NativeCodeContext,
@@ -71,6 +74,7 @@ impl MoveVmExt {
         gas_hook: Option<F>,
         resolver: &impl AptosMoveResolver,
         aggregator_v2_type_tagging: bool,
+        use_warm_vm_cache: bool,
     ) -> VMResult<Self>
     where
         F: Fn(DynamicExpression) + Send + Sync + 'static,
@@ -111,26 +115,29 @@ impl MoveVmExt {
             builder.set_gas_hook(hook);
         }
 
+        let vm_config = VMConfig {
+            verifier: verifier_config,
+            deserializer_config: DeserializerConfig::new(
+                max_binary_format_version,
+                max_identifier_size,
+            ),
+            paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
+            enable_invariant_violation_check_in_swap_loc,
+            type_size_limit,
+            max_value_nest_depth: Some(128),
+            type_max_cost,
+            type_base_cost,
+            type_byte_cost,
+            aggregator_v2_type_tagging,
+        };
+        let inner = if use_warm_vm_cache {
+            WarmVmCache::get_warm_vm(builder, vm_config, resolver)?
+        } else {
+            MoveVM::new_with_config(aptos_natives_with_builder(&mut builder), vm_config)?
+        };
+
         Ok(Self {
-            inner: WarmVmCache::get_warm_vm(
-                builder,
-                VMConfig {
-                    verifier: verifier_config,
-                    deserializer_config: DeserializerConfig::new(
-                        max_binary_format_version,
-                        max_identifier_size,
-                    ),
-                    paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
-                    enable_invariant_violation_check_in_swap_loc,
-                    type_size_limit,
-                    max_value_nest_depth: Some(128),
-                    type_max_cost,
-                    type_base_cost,
-                    type_byte_cost,
-                    aggregator_v2_type_tagging,
-                },
-                resolver,
-            )?,
+            inner,
             chain_id,
             features,
         })
@@ -156,6 +163,34 @@ impl MoveVmExt {
             None,
             resolver,
             aggregator_v2_type_tagging,
+            true,
+        )
+    }
+
+    /// Creates a VM with an empty code cache, instead of taking one from the warm VM cache, for
+    /// executions against state whose modules may differ from the ones in storage. Modules
+    /// loaded by the VM are not shared with any other VM.
+    pub fn new_uncached(
+        native_gas_params: NativeGasParameters,
+        misc_gas_params: MiscGasParameters,
+        gas_feature_version: u64,
+        chain_id: u8,
+        features: Features,
+        timed_features: TimedFeatures,
+        resolver: &impl AptosMoveResolver,
+        aggregator_v2_type_tagging: bool,
+    ) -> VMResult<Self> {
+        Self::new_impl::<fn(DynamicExpression)>(
+            native_gas_params,
+            misc_gas_params,
+            gas_feature_version,
+            chain_id,
+            features,
+            timed_features,
+            None,
+            resolver,
+            aggregator_v2_type_tagging,
+            false,
         )
     }
 
@@ -183,6 +218,7 @@ impl MoveVmExt {
             gas_hook,
             resolver,
             aggregator_v2_type_tagging,
+            true,
         )
     }
 
@@ -7,6 +7,7 @@
 mod indexer;
 mod logger;
 mod network;
+mod read_replica;
 mod services;
 mod state_sync;
 mod storage;
@@ -27,11 +28,13 @@ use aptos_dkg_runtime::start_dkg_runtime;
 use aptos_framework::ReleaseBundle;
 use aptos_jwk_consensus::start_jwk_consensus_runtime;
 use aptos_logger::{prelude::*, telemetry_log_writer::TelemetryLog, Level, LoggerFilterUpdater};
+use aptos_mempool::MempoolPersistenceHandle;
 use aptos_safety_rules::safety_rules_manager::load_consensus_key_from_secure_storage;
 use aptos_state_sync_driver::driver_factory::StateSyncRuntimes;
 use aptos_types::chain_id::ChainId;
 use aptos_validator_transaction_pool::VTxnPoolState;
 use clap::Parser;
+use either::Either;
 use futures::channel::mpsc;
 use hex::{FromHex, FromHexError};
 use rand::{rngs::StdRng, SeedableRng};
@@ -39,10 +42,7 @@ use std::{
     fs,
     io::Write,
     path::{Path, PathBuf},
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    sync::Arc,
     thread,
 };
 use tokio::runtime::Runtime;
@@ -197,13 +197,23 @@ pub struct AptosHandle {
     _indexer_runtime: Option<Runtime>,
     _indexer_table_info_runtime: Option<Runtime>,
     _jwk_consensus_runtime: Option<Runtime>,
-    _mempool_runtime: Runtime,
+    mempool_persistence: MempoolPersistenceHandle,
+    mempool_runtime: Runtime,
     _network_runtimes: Vec<Runtime>,
     _peer_monitoring_service_runtime: Runtime,
     _state_sync_runtimes: StateSyncRuntimes,
     _telemetry_runtime: Option<Runtime>,
 }
 
+impl AptosHandle {
+    /// Persists the state that should survive a node restart (e.g., the mempool snapshot).
+    /// The runtimes are stopped when the handle is dropped.
+    pub fn shutdown(mut self) {
+        self.mempool_runtime
+            .block_on(self.mempool_persistence.shutdown());
+    }
+}
+
 /// Start an Aptos node
 pub fn start(
     config: NodeConfig,
@@ -246,12 +256,45 @@ pub fn start(
         warn!("Failpoints is set in the node config, but the binary didn't compile with this feature!");
     }
 
+    // Stop the node gracefully when it is terminated. Setting the handler fails if one is already
+    // installed (e.g., when the node is embedded), the node then runs until the process exits.
+    let (term_sender, term_receiver) = std::sync::mpsc::channel();
+    let term_receiver = match ctrlc::set_handler(move || {
+        let _ = term_sender.send(());
+    }) {
+        Ok(()) => Some(term_receiver),
+        Err(error) => {
+            error!(
+                "Failed to set the termination handler, the mempool won't be persisted on shutdown: {}",
+                error
+            );
+            None
+        },
+    };
+
     // Set up the node environment and start it
-    let _node_handle =
-        setup_environment_and_start_node(config, remote_log_receiver, Some(logger_filter_update))?;
-    let term = Arc::new(AtomicBool::new(false));
-    while !term.load(Ordering::Acquire) {
-        thread::park();
+    let node_handle = if config.storage.read_replica.is_some() {
+        Either::Right(read_replica::setup_environment_and_start_read_replica(
+            config,
+            remote_log_receiver,
+            Some(logger_filter_update),
+        )?)
+    } else {
+        Either::Left(setup_environment_and_start_node(
+            config,
+            remote_log_receiver,
+            Some(logger_filter_update),
+        )?)
+    };
+    let Some(term_receiver) = term_receiver else {
+        loop {
+            thread::park();
+        }
+    };
+    let _ = term_receiver.recv();
+    info!("Received a termination signal, shutting down the node.");
+    if let Either::Left(node_handle) = node_handle {
+        node_handle.shutdown();
     }
 
     Ok(())
@@ -597,6 +640,7 @@ pub fn setup_environment_and_start_node(
         consensus_reconfig_subscription,
         dkg_subscriptions,
         jwk_consensus_subscriptions,
+        api_commit_subscription,
     ) = state_sync::create_event_subscription_service(&node_config, &db_rw);
 
     // Set up the networks and gather the application network handles
@@ -647,10 +691,15 @@ pub fn setup_environment_and_start_node(
         indexer_table_info_runtime,
         indexer_runtime,
         indexer_grpc_runtime,
-    ) = services::bootstrap_api_and_indexer(&node_config, db_rw.clone(), chain_id)?;
+    ) = services::bootstrap_api_and_indexer(
+        &node_config,
+        db_rw.clone(),
+        chain_id,
+        api_commit_subscription,
+    )?;
 
     // Create mempool and get the consensus to mempool sender
-    let (mempool_runtime, consensus_to_mempool_sender) =
+    let (mempool_runtime, consensus_to_mempool_sender, mempool_txn_filter, mempool_persistence) =
         services::start_mempool_runtime_and_get_consensus_sender(
             &mut node_config,
             &db_rw,
@@ -660,6 +709,7 @@ pub fn setup_environment_and_start_node(
             mempool_client_receiver,
             peers_and_metadata,
         );
+    admin_service.set_mempool_transaction_filter(mempool_txn_filter);
 
     // Ensure consensus key in secure DB.
     if !matches!(
@@ -738,16 +788,18 @@ pub fn setup_environment_and_start_node(
         debug!("State sync initialization complete.");
 
         // Initialize and start consensus
-        let (runtime, consensus_db, quorum_store_db) = services::start_consensus_runtime(
-            &mut node_config,
-            db_rw,
-            consensus_reconfig_subscription,
-            consensus_network_interfaces,
-            consensus_notifier,
-            consensus_to_mempool_sender,
-            vtxn_pool,
-        );
+        let (runtime, consensus_db, quorum_store_db, txn_filter) =
+            services::start_consensus_runtime(
+                &mut node_config,
+                db_rw,
+                consensus_reconfig_subscription,
+                consensus_network_interfaces,
+                consensus_notifier,
+                consensus_to_mempool_sender,
+                vtxn_pool,
+            );
         admin_service.set_consensus_dbs(consensus_db, quorum_store_db);
+        admin_service.set_transaction_filter(txn_filter);
         runtime
     });
 
@@ -761,7 +813,8 @@ pub fn setup_environment_and_start_node(
         _indexer_runtime: indexer_runtime,
         _indexer_table_info_runtime: indexer_table_info_runtime,
         _jwk_consensus_runtime: jwk_consensus_runtime,
-        _mempool_runtime: mempool_runtime,
+        mempool_persistence,
+        mempool_runtime,
         _network_runtimes: network_runtimes,
         _peer_monitoring_service_runtime: peer_monitoring_service_runtime,
         _state_sync_runtimes: state_sync_runtimes,
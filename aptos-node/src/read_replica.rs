@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{services, utils};
+use anyhow::anyhow;
+use aptos_admin_service::AdminService;
+use aptos_config::config::NodeConfig;
+use aptos_db::AptosDB;
+use aptos_logger::{prelude::*, telemetry_log_writer::TelemetryLog, LoggerFilterUpdater};
+use aptos_storage_interface::DbReaderWriter;
+use futures::channel::mpsc;
+use std::{sync::Arc, thread, time::Duration};
+use tokio::runtime::Runtime;
+
+/// Runtime handle to ensure that all inner runtimes of a read replica stay in scope
+pub struct ReadReplicaHandle {
+    _admin_service: AdminService,
+    _api_runtime: Option<Runtime>,
+    _indexer_grpc_runtime: Option<Runtime>,
+    _indexer_runtime: Option<Runtime>,
+    _telemetry_runtime: Option<Runtime>,
+}
+
+/// Starts a read replica: the primary's DB is opened as a RocksDB secondary instance which is
+/// periodically caught up, and only the API (and the indexers reading from it) are served.
+/// Networking, state sync, mempool and consensus are not started, so transaction submissions
+/// are rejected.
+pub fn setup_environment_and_start_read_replica(
+    mut node_config: NodeConfig,
+    remote_log_rx: Option<mpsc::Receiver<TelemetryLog>>,
+    logger_filter_update_job: Option<LoggerFilterUpdater>,
+) -> anyhow::Result<ReadReplicaHandle> {
+    let read_replica_config = node_config
+        .storage
+        .read_replica
+        .clone()
+        .ok_or_else(|| anyhow!("storage.read_replica is not set."))?;
+
+    // There is no mempool to submit transactions to, so reject submissions upfront instead of
+    // failing them on the way to the mempool.
+    node_config.api.transaction_submission_enabled = false;
+
+    // Log the node config at node startup
+    node_config.log_all_configs();
+
+    // Starts the admin service
+    let admin_service = services::start_admin_service(&node_config);
+
+    // Open the primary's DB as a secondary instance
+    let aptos_db = AptosDB::open_secondary(
+        node_config.storage.get_dir_paths(),
+        &read_replica_config.secondary_dir,
+        node_config.storage.rocksdb_configs,
+        node_config.storage.max_num_nodes_per_lru_cache_shard,
+    )
+    .map_err(|err| anyhow!("DB failed to open as secondary {}", err))?;
+    let (aptos_db, db_rw) = DbReaderWriter::wrap(aptos_db);
+    start_catch_up_thread(
+        aptos_db,
+        Duration::from_millis(read_replica_config.catch_up_interval_ms),
+    );
+
+    admin_service.set_aptos_db(db_rw.clone().into());
+
+    // Set the Aptos VM configurations
+    utils::set_aptos_vm_configurations(&node_config);
+
+    // Obtain the chain_id from the DB
+    let chain_id = utils::fetch_chain_id(&db_rw)?;
+
+    // Set the chain_id in global AptosNodeIdentity
+    aptos_node_identity::set_chain_id(chain_id)?;
+
+    // Start the telemetry service
+    let telemetry_runtime = services::start_telemetry_service(
+        &node_config,
+        remote_log_rx,
+        logger_filter_update_job,
+        chain_id,
+    );
+
+    // Bootstrap the API and indexer. There is no mempool behind the API, submissions are
+    // disabled above. Nothing commits on a replica either, so the streaming APIs get no commit
+    // notifications.
+    let (
+        _mempool_client_receiver,
+        api_runtime,
+        _indexer_table_info_runtime,
+        indexer_runtime,
+        indexer_grpc_runtime,
+    ) = services::bootstrap_api_and_indexer(&node_config, db_rw, chain_id, None)?;
+
+    Ok(ReadReplicaHandle {
+        _admin_service: admin_service,
+        _api_runtime: api_runtime,
+        _indexer_grpc_runtime: indexer_grpc_runtime,
+        _indexer_runtime: indexer_runtime,
+        _telemetry_runtime: telemetry_runtime,
+    })
+}
+
+fn start_catch_up_thread(aptos_db: Arc<AptosDB>, interval: Duration) {
+    thread::Builder::new()
+        .name("db-catch-up".to_string())
+        .spawn(move || loop {
+            thread::sleep(interval);
+            if let Err(err) = aptos_db.try_catch_up_with_primary() {
+                warn!("Failed to catch up with the primary DB: {:?}", err);
+            }
+        })
+        .expect("Failed to spawn the DB catch up thread.");
+}
@@ -32,13 +32,14 @@ use aptos_storage_service_server::{
 };
 use aptos_storage_service_types::StorageServiceMessage;
 use aptos_time_service::TimeService;
-use aptos_types::waypoint::Waypoint;
+use aptos_types::{account_config::new_block_event_key, waypoint::Waypoint};
 use aptos_vm::AptosVM;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
 /// Creates the event subscription service and two reconfiguration
-/// notification listeners (for mempool and consensus, respectively).
+/// notification listeners (for mempool and consensus, respectively),
+/// as well as a new block event listener for the streaming APIs.
 pub fn create_event_subscription_service(
     node_config: &NodeConfig,
     db_rw: &DbReaderWriter,
@@ -54,6 +55,7 @@ pub fn create_event_subscription_service(
         ReconfigNotificationListener<DbBackedOnChainConfig>,
         EventNotificationListener,
     )>, // (reconfig_events, jwk_updated_events) for JWK consensus
+    Option<EventNotificationListener>, // new_block_events for the streaming APIs
 ) {
     // Create the event subscription service
     let mut event_subscription_service =
@@ -99,12 +101,24 @@ pub fn create_event_subscription_service(
         None
     };
 
+    // Create a new block event subscription for the streaming APIs (if they're enabled)
+    let api_commit_subscription = if node_config.api.enabled && node_config.api.streaming_enabled {
+        Some(
+            event_subscription_service
+                .subscribe_to_events(vec![new_block_event_key()], vec![])
+                .expect("The streaming APIs must subscribe to new block events"),
+        )
+    } else {
+        None
+    };
+
     (
         event_subscription_service,
         mempool_reconfig_subscription,
         consensus_reconfig_subscription,
         dkg_subscriptions,
         jwk_consensus_subscriptions,
+        api_commit_subscription,
     )
 }
 
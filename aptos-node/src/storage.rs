@@ -126,6 +126,9 @@ fn create_rocksdb_checkpoint_and_change_working_dir(
         &source_dir,
         &checkpoint_dir,
         node_config.storage.rocksdb_configs.enable_storage_sharding,
+        // In-memory DBs don't outlive the process, so only on-disk ones can be checkpointed here.
+        /*in_memory=*/
+        false,
     )
     .expect("AptosDB checkpoint creation failed.");
 
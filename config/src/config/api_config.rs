@@ -49,8 +49,18 @@ pub struct ApiConfig {
     /// Enables transaction simulation
     #[serde(default = "default_enabled")]
     pub transaction_simulation_enabled: bool,
+    /// Enables the mempool introspection APIs, which expose the pending transactions in mempool
+    #[serde(default = "default_disabled")]
+    pub mempool_introspection_enabled: bool,
+    /// Enables the streaming APIs, which push committed transactions and events to clients
+    #[serde(default = "default_disabled")]
+    pub streaming_enabled: bool,
+    /// Maximum number of concurrent clients of the streaming APIs
+    pub max_streaming_connections: usize,
     /// Maximum number of transactions that can be sent with the Batch submit API
     pub max_submit_transaction_batch_size: usize,
+    /// Maximum number of transactions that can be simulated with the Simulate sequence API
+    pub max_simulation_sequence_size: usize,
     /// Maximum page size for transaction paginated APIs
     pub max_transactions_page_size: u16,
     /// Maximum page size for event paginated APIs
@@ -63,6 +73,16 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be executed with the Batch view API
+    pub max_view_function_batch_size: usize,
+    /// Maximum gas unit limit for all the view functions of a batch
+    ///
+    /// View functions of a batch that exceeds the limit are not executed.
+    pub max_gas_view_function_batch: u64,
+    /// Maximum time spent executing the view functions of a batch in milliseconds
+    ///
+    /// View functions of a batch that exceeds the limit are not executed.
+    pub view_function_batch_timeout_ms: u64,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -87,10 +107,15 @@ const DEFAULT_ADDRESS: &str = "127.0.0.1";
 const DEFAULT_PORT: u16 = 8080;
 const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 8 * 1024 * 1024; // 8 MB
 pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
+const DEFAULT_MAX_SIMULATION_SEQUENCE_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+const DEFAULT_MAX_STREAMING_CONNECTIONS: usize = 100;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
+const DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_VIEW_BATCH_GAS: u64 = 10 * DEFAULT_MAX_VIEW_GAS;
+const DEFAULT_VIEW_FUNCTION_BATCH_TIMEOUT_MS: u64 = 5_000;
 
 fn default_enabled() -> bool {
     true
@@ -116,12 +141,19 @@ impl Default for ApiConfig {
             encode_submission_enabled: default_enabled(),
             transaction_submission_enabled: default_enabled(),
             transaction_simulation_enabled: default_enabled(),
+            mempool_introspection_enabled: default_disabled(),
+            streaming_enabled: default_disabled(),
+            max_streaming_connections: DEFAULT_MAX_STREAMING_CONNECTIONS,
             max_submit_transaction_batch_size: DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE,
+            max_simulation_sequence_size: DEFAULT_MAX_SIMULATION_SEQUENCE_SIZE,
             max_transactions_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_events_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_batch_size: DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE,
+            max_gas_view_function_batch: DEFAULT_MAX_VIEW_BATCH_GAS,
+            view_function_batch_timeout_ms: DEFAULT_VIEW_FUNCTION_BATCH_TIMEOUT_MS,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
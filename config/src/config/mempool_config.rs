@@ -4,7 +4,8 @@
 
 use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
-    node_config_loader::NodeType, Error, NodeConfig, MAX_APPLICATION_MESSAGE_SIZE,
+    node_config_loader::NodeType, transaction_filter_type::Filter, Error, NodeConfig,
+    MAX_APPLICATION_MESSAGE_SIZE,
 };
 use aptos_global_constants::DEFAULT_BUCKETS;
 use aptos_types::chain_id::ChainId;
@@ -53,6 +54,21 @@ pub struct MempoolConfig {
     pub broadcast_buckets: Vec<u64>,
     pub eager_expire_threshold_ms: Option<u64>,
     pub eager_expire_time_ms: u64,
+    /// Filter applied to transactions on admission (before VM validation). Uses the same rules
+    /// as the consensus transaction filter, but block matchers never match.
+    pub transaction_filter: Filter,
+    /// The policy used to order transactions that are ready to be pulled into blocks
+    pub ordering_policy: MempoolOrderingPolicyType,
+    /// Enables replace-by-fee if set: a transaction with the same sender and sequence number as
+    /// a pending transaction replaces it, even with a different payload, expiration or max gas
+    /// amount, if its gas unit price is at least this many percent higher.
+    pub replace_by_fee_min_gas_price_bump_pct: Option<u64>,
+    /// Enables persisting the pending transactions to a snapshot in the data directory, so that
+    /// they are restored (and revalidated) when the node restarts
+    pub persistence_enabled: bool,
+    /// The interval to persist the mempool snapshot. The snapshot is also persisted when the
+    /// mempool runtime shuts down.
+    pub persistence_interval_secs: u64,
 }
 
 impl Default for MempoolConfig {
@@ -77,17 +93,51 @@ impl Default for MempoolConfig {
             broadcast_buckets: DEFAULT_BUCKETS.to_vec(),
             eager_expire_threshold_ms: Some(10_000),
             eager_expire_time_ms: 3_000,
+            transaction_filter: Filter::empty(),
+            ordering_policy: MempoolOrderingPolicyType::default(),
+            replace_by_fee_min_gas_price_bump_pct: None,
+            persistence_enabled: false,
+            persistence_interval_secs: 60,
         }
     }
 }
 
+/// The policy used to order transactions in the mempool priority index, i.e., the order in
+/// which transactions are pulled into blocks. Ties are broken by expiration time and insertion.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MempoolOrderingPolicyType {
+    /// Orders transactions by gas unit price
+    #[default]
+    GasPrice,
+    /// Orders transactions by gas unit price per byte of the signed transaction, which favors
+    /// small transactions over large ones with the same gas unit price
+    GasPricePerByte,
+    /// Orders transactions by the tip paid above `min_gas_unit_price`. The tip of transactions
+    /// with a fee payer (i.e., sponsored transactions) is scaled by `fee_payer_tip_pct`.
+    FeePayerAwareTip {
+        min_gas_unit_price: u64,
+        fee_payer_tip_pct: u64,
+    },
+}
+
 impl ConfigSanitizer for MempoolConfig {
     fn sanitize(
-        _node_config: &NodeConfig,
+        node_config: &NodeConfig,
         _node_type: NodeType,
         _chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
-        Ok(()) // TODO: add reasonable verifications
+        let sanitizer_name = Self::get_sanitizer_name();
+        let mempool_config = &node_config.mempool;
+
+        // Verify that the snapshot can be persisted periodically
+        if mempool_config.persistence_enabled && mempool_config.persistence_interval_secs == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "persistence_interval_secs must be greater than 0!".into(),
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -143,6 +193,27 @@ impl ConfigOptimizer for MempoolConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sanitize_persistence_interval() {
+        // Create a node config with persistence enabled and a zero interval
+        let mut node_config = NodeConfig {
+            mempool: MempoolConfig {
+                persistence_enabled: true,
+                persistence_interval_secs: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = MempoolConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Disable persistence and verify that the interval is no longer checked
+        node_config.mempool.persistence_enabled = false;
+        MempoolConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+    }
+
     #[test]
     fn test_optimize_vfn_configs() {
         // Create the default VFN config
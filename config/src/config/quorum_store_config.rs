@@ -41,6 +41,49 @@ impl Default for QuorumStoreBackPressureConfig {
     }
 }
 
+/// Bounds and thresholds of the controller that adapts the batch size, the batch creation
+/// interval and the number of batches per pull to the observed proof latency, commit pipeline
+/// latency and proof backlog. When disabled, the static limits of `QuorumStoreConfig` are used.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuorumStoreAutoTuningConfig {
+    pub enabled: bool,
+    pub adjustment_interval_ms: u64,
+    // Limits are decreased when the average time to form a proof of our batches is above this
+    pub target_proof_latency_ms: u64,
+    // Limits are decreased when the latest committed block is older than this
+    pub pipeline_latency_limit_ms: u64,
+    pub min_batch_txns: usize,
+    pub max_batch_txns: usize,
+    pub min_batch_interval_ms: usize,
+    pub max_batch_interval_ms: usize,
+    pub min_num_batches: usize,
+    pub max_num_batches: usize,
+    // Multiplicative decrease, applied to the batch size and the number of batches
+    pub decrease_fraction: f64,
+    // Additive increase, as a fraction of each bound range
+    pub increase_fraction: f64,
+}
+
+impl Default for QuorumStoreAutoTuningConfig {
+    fn default() -> QuorumStoreAutoTuningConfig {
+        QuorumStoreAutoTuningConfig {
+            enabled: false,
+            adjustment_interval_ms: 1000,
+            target_proof_latency_ms: 1000,
+            pipeline_latency_limit_ms: 2500,
+            min_batch_txns: 50,
+            max_batch_txns: 250,
+            min_batch_interval_ms: 50,
+            max_batch_interval_ms: 250,
+            min_num_batches: 2,
+            max_num_batches: DEFAULT_MAX_NUM_BATCHES,
+            decrease_fraction: 0.5,
+            increase_fraction: 0.1,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct QuorumStoreConfig {
@@ -69,6 +112,7 @@ pub struct QuorumStoreConfig {
     pub db_quota: usize,
     pub batch_quota: usize,
     pub back_pressure: QuorumStoreBackPressureConfig,
+    pub auto_tuning: QuorumStoreAutoTuningConfig,
     pub num_workers_for_remote_batches: usize,
     pub batch_buckets: Vec<u64>,
 }
@@ -104,6 +148,7 @@ impl Default for QuorumStoreConfig {
             db_quota: 300_000_000,
             batch_quota: 300_000,
             back_pressure: QuorumStoreBackPressureConfig::default(),
+            auto_tuning: QuorumStoreAutoTuningConfig::default(),
             // number of batch coordinators to handle QS batch messages, should be >= 1
             num_workers_for_remote_batches: 10,
             batch_buckets: DEFAULT_BUCKETS.to_vec(),
@@ -136,6 +181,13 @@ impl QuorumStoreConfig {
                 dynamic_max_txn_per_s: 200,
                 ..Default::default()
             },
+            auto_tuning: QuorumStoreAutoTuningConfig {
+                min_batch_txns: 30,
+                max_batch_txns: 300,
+                min_num_batches: 1,
+                max_num_batches: 5,
+                ..Default::default()
+            },
             ..Default::default()
         }
     }
@@ -213,6 +265,73 @@ impl QuorumStoreConfig {
         }
         Ok(())
     }
+
+    fn sanitize_auto_tuning_limits(
+        sanitizer_name: &str,
+        config: &QuorumStoreConfig,
+    ) -> Result<(), Error> {
+        let auto_tuning = &config.auto_tuning;
+        if !auto_tuning.enabled {
+            return Ok(());
+        }
+        let min_max_pairs = [
+            (
+                auto_tuning.min_batch_txns,
+                auto_tuning.max_batch_txns,
+                "batch_txns",
+            ),
+            (
+                auto_tuning.min_batch_interval_ms,
+                auto_tuning.max_batch_interval_ms,
+                "batch_interval_ms",
+            ),
+            (
+                auto_tuning.min_num_batches,
+                auto_tuning.max_num_batches,
+                "num_batches",
+            ),
+            // the batch generator pulls at least every batch_generation_max_interval_ms
+            (
+                auto_tuning.max_batch_interval_ms,
+                config.batch_generation_max_interval_ms,
+                "max_batch_interval_ms",
+            ),
+            // tuned batches must still be accepted by the receivers
+            (
+                auto_tuning.max_batch_txns,
+                config.receiver_max_batch_txns,
+                "max_batch_txns",
+            ),
+            (
+                auto_tuning.max_num_batches,
+                config.receiver_max_num_batches,
+                "max_num_batches",
+            ),
+        ];
+        for (min, max, label) in &min_max_pairs {
+            if *min == 0 || *min > *max {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_owned(),
+                    format!(
+                        "Failed auto tuning {}: invalid bounds {}..={}",
+                        label, *min, *max
+                    ),
+                ));
+            }
+        }
+        for (fraction, label) in [
+            (auto_tuning.decrease_fraction, "decrease_fraction"),
+            (auto_tuning.increase_fraction, "increase_fraction"),
+        ] {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_owned(),
+                    format!("Failed auto tuning {}: {} not in (0, 1]", label, fraction),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ConfigSanitizer for QuorumStoreConfig {
@@ -232,6 +351,9 @@ impl ConfigSanitizer for QuorumStoreConfig {
         // Sanitize the batch total limits
         Self::sanitize_batch_total_limits(&sanitizer_name, &node_config.consensus.quorum_store)?;
 
+        // Sanitize the auto tuning bounds
+        Self::sanitize_auto_tuning_limits(&sanitizer_name, &node_config.consensus.quorum_store)?;
+
         Ok(())
     }
 }
@@ -429,4 +551,51 @@ mod test {
             QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_auto_tuning_limits() {
+        // Invalid bounds are ignored while auto tuning is disabled
+        let mut node_config = NodeConfig {
+            consensus: ConsensusConfig {
+                quorum_store: QuorumStoreConfig {
+                    auto_tuning: QuorumStoreAutoTuningConfig {
+                        min_batch_txns: 200,
+                        max_batch_txns: 100,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+
+        // Sanitize the config and verify that it fails
+        node_config.consensus.quorum_store.auto_tuning.enabled = true;
+        let error =
+            QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Bounds above the receiver limits are rejected too
+        node_config.consensus.quorum_store.auto_tuning = QuorumStoreAutoTuningConfig {
+            enabled: true,
+            max_batch_txns: 1000,
+            ..Default::default()
+        };
+        let error =
+            QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // The defaults are valid, including the ones for DAG
+        node_config
+            .consensus
+            .quorum_store
+            .auto_tuning
+            .max_batch_txns = 250;
+        QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+        node_config.consensus.quorum_store = QuorumStoreConfig::default_for_dag();
+        node_config.consensus.quorum_store.auto_tuning.enabled = true;
+        QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+    }
 }
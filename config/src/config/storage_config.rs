@@ -127,6 +127,12 @@ pub struct RocksdbConfigs {
     pub index_db_config: RocksdbConfig,
     // Note: Not ready for production use yet.
     pub enable_storage_sharding: bool,
+    /// Maintains an index of the transactions touching each account (as a signer, event key
+    /// owner or resource owner) in the internal indexer DB. Requires `enable_indexer`.
+    pub enable_account_transactions_index: bool,
+    /// Keeps the DBs in memory instead of in RocksDB, so nothing is written to disk and the data
+    /// is lost when the DBs are closed. For tests and benchmarks only.
+    pub enable_in_memory_backend: bool,
 }
 
 impl Default for RocksdbConfigs {
@@ -140,6 +146,8 @@ impl Default for RocksdbConfigs {
                 ..Default::default()
             },
             enable_storage_sharding: false,
+            enable_account_transactions_index: false,
+            enable_in_memory_backend: false,
         }
     }
 }
@@ -175,6 +183,28 @@ pub struct StorageConfig {
     /// If not specificed, will use `dir` as default.
     /// Only allowed when sharding is enabled.
     pub db_path_overrides: Option<DbPathConfig>,
+    /// If set, the node runs as a read replica: it opens the primary's DB (located by `dir` and
+    /// `db_path_overrides`) as a RocksDB secondary instance and only serves the API.
+    pub read_replica: Option<ReadReplicaConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadReplicaConfig {
+    /// Directory for the secondary instance's own info logs, must be different from any of the
+    /// primary's DB paths.
+    pub secondary_dir: PathBuf,
+    /// How often to catch up with the primary.
+    pub catch_up_interval_ms: u64,
+}
+
+impl Default for ReadReplicaConfig {
+    fn default() -> Self {
+        Self {
+            secondary_dir: PathBuf::from("/opt/aptos/data/secondary_db"),
+            catch_up_interval_ms: 500,
+        }
+    }
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -322,6 +352,7 @@ impl Default for StorageConfig {
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
             db_path_overrides: None,
+            read_replica: None,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -472,12 +503,22 @@ impl ShardedDbPaths {
 impl ConfigSanitizer for StorageConfig {
     fn sanitize(
         node_config: &NodeConfig,
-        _node_type: NodeType,
-        _chain_id: Option<ChainId>,
+        node_type: NodeType,
+        chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
         let config = &node_config.storage;
 
+        // Verify that the in-memory backend is not used in mainnet
+        if let Some(chain_id) = chain_id {
+            if chain_id.is_mainnet() && config.rocksdb_configs.enable_in_memory_backend {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "The in-memory backend is not supported on mainnet nodes!".to_string(),
+                ));
+            }
+        }
+
         let ledger_prune_window = config
             .storage_pruner_config
             .ledger_pruner_config
@@ -517,6 +558,34 @@ impl ConfigSanitizer for StorageConfig {
             ));
         }
 
+        if let Some(read_replica) = config.read_replica.as_ref() {
+            if node_type.is_validator() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Validators can't run as read replicas.".to_string(),
+                ));
+            }
+            if node_config.indexer_table_info.enabled {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "indexer_table_info can't be enabled on read replicas, it writes to the primary's db directory.".to_string(),
+                ));
+            }
+            if read_replica.catch_up_interval_ms == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "read_replica.catch_up_interval_ms must be positive.".to_string(),
+                ));
+            }
+            if read_replica.secondary_dir.starts_with(config.dir()) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "read_replica.secondary_dir must not be inside the primary's db directory."
+                        .to_string(),
+                ));
+            }
+        }
+
         if let Some(db_path_overrides) = config.db_path_overrides.as_ref() {
             if !config.rocksdb_configs.enable_storage_sharding {
                 return Err(Error::ConfigSanitizerFailed(
@@ -574,7 +643,63 @@ impl ConfigSanitizer for StorageConfig {
 
 #[cfg(test)]
 mod test {
-    use crate::config::{PrunerConfig, ShardPathConfig, ShardedDbPathConfig};
+    use crate::config::{
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+        PrunerConfig, ReadReplicaConfig, RocksdbConfigs, ShardPathConfig, ShardedDbPathConfig,
+        StorageConfig,
+    };
+    use aptos_types::chain_id::ChainId;
+
+    #[test]
+    pub fn test_sanitize_in_memory_backend_on_mainnet() {
+        let node_config = NodeConfig {
+            storage: StorageConfig {
+                rocksdb_configs: RocksdbConfigs {
+                    enable_in_memory_backend: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let error =
+            StorageConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        StorageConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+            .unwrap();
+    }
+
+    #[test]
+    pub fn test_sanitize_read_replica() {
+        let mut node_config = NodeConfig {
+            storage: StorageConfig {
+                read_replica: Some(ReadReplicaConfig::default()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+
+        node_config.indexer_table_info.enabled = true;
+        let error =
+            StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        node_config.indexer_table_info.enabled = false;
+
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        node_config.storage.read_replica = Some(ReadReplicaConfig {
+            secondary_dir: node_config.storage.dir().join("secondary"),
+            ..Default::default()
+        });
+        let error =
+            StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 
     #[test]
     pub fn test_default_prune_window() {
@@ -4,10 +4,60 @@
 use aptos_crypto::HashValue;
 use aptos_types::{
     account_address::AccountAddress,
-    transaction::{SignedTransaction, TransactionPayload},
+    transaction::{
+        authenticator::{AccountAuthenticator, AnySignature},
+        SignedTransaction, TransactionPayload,
+    },
 };
 use serde::{Deserialize, Serialize};
 
+/// The kind of authenticator used by one of the signers of a transaction.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticatorKind {
+    Ed25519,
+    MultiEd25519,
+    Secp256k1Ecdsa,
+    WebAuthn,
+    Keyless,
+    MultiKey,
+}
+
+impl AuthenticatorKind {
+    fn matches_account_authenticator(&self, authenticator: &AccountAuthenticator) -> bool {
+        match authenticator {
+            AccountAuthenticator::Ed25519 { .. } => *self == AuthenticatorKind::Ed25519,
+            AccountAuthenticator::MultiEd25519 { .. } => *self == AuthenticatorKind::MultiEd25519,
+            AccountAuthenticator::SingleKey { authenticator } => {
+                self.matches_any_signature(authenticator.signature())
+            },
+            AccountAuthenticator::MultiKey { authenticator } => {
+                *self == AuthenticatorKind::MultiKey
+                    || authenticator
+                        .signatures()
+                        .into_iter()
+                        .any(|(_, signature)| self.matches_any_signature(signature))
+            },
+        }
+    }
+
+    fn matches_any_signature(&self, signature: &AnySignature) -> bool {
+        match signature {
+            AnySignature::Ed25519 { .. } => *self == AuthenticatorKind::Ed25519,
+            AnySignature::Secp256k1Ecdsa { .. } => *self == AuthenticatorKind::Secp256k1Ecdsa,
+            AnySignature::WebAuthn { .. } => *self == AuthenticatorKind::WebAuthn,
+            AnySignature::Keyless { .. } => *self == AuthenticatorKind::Keyless,
+        }
+    }
+}
+
+/// A predicate over a transaction and, when available, the block it is included in.
+///
+/// `And`, `Or` and `Not` allow composing the other matchers, e.g. to deny keyless transactions
+/// that call into a specific module while leaving all other traffic untouched. Block matchers
+/// (`BlockId` and the block timestamp matchers) are undecided when a transaction is evaluated
+/// outside of a block, e.g. on mempool admission, and so is any composition whose outcome depends
+/// on them (`Not` of a block matcher included). Rules only apply to transactions their matcher
+/// definitely matches.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Matcher {
     All,
@@ -18,15 +68,68 @@ pub enum Matcher {
     Sender(AccountAddress),
     ModuleAddress(AccountAddress),
     EntryFunction(AccountAddress, String, String),
+    /// Matches any transaction with a script payload
+    AnyScript,
+    /// Matches script payloads whose code has the given sha3-256 hash
+    ScriptHash(HashValue),
+    /// Matches multisig payloads executed on behalf of the given multisig account
+    MultisigAddress(AccountAddress),
+    /// Matches any transaction that has a fee payer (i.e., sponsored transactions)
+    AnyFeePayer,
+    /// Matches transactions whose gas is paid by the given fee payer
+    FeePayer(AccountAddress),
+    /// Matches transactions that include the given account as a secondary signer
+    SecondarySigner(AccountAddress),
+    /// Matches transactions where any signer (sender, secondary signers or fee payer) uses the
+    /// given authenticator kind
+    AuthenticatorKind(AuthenticatorKind),
+    GasUnitPriceGreaterThan(u64),
+    GasUnitPriceLessThan(u64),
+    MaxGasAmountGreaterThan(u64),
+    /// Matches if all of the inner matchers match (an empty list always matches)
+    And(Vec<Matcher>),
+    /// Matches if any of the inner matchers match (an empty list never matches)
+    Or(Vec<Matcher>),
+    /// Matches if the inner matcher does not match
+    Not(Box<Matcher>),
 }
 
 impl Matcher {
-    fn matches(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
-        match self {
+    /// Returns whether the matcher matches, or None if that depends on a block the transaction is
+    /// not part of.
+    fn matches(&self, block: Option<(HashValue, u64)>, txn: &SignedTransaction) -> Option<bool> {
+        let matches = match self {
+            Matcher::BlockId(id) => return block.map(|(block_id, _)| block_id == *id),
+            Matcher::BlockTimeStampGreaterThan(ts) => {
+                return block.map(|(_, timestamp)| timestamp > *ts)
+            },
+            Matcher::BlockTimeStampLessThan(ts) => {
+                return block.map(|(_, timestamp)| timestamp < *ts)
+            },
+            Matcher::And(matchers) => {
+                let mut result = Some(true);
+                for matcher in matchers {
+                    match matcher.matches(block, txn) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                return result;
+            },
+            Matcher::Or(matchers) => {
+                let mut result = Some(false);
+                for matcher in matchers {
+                    match matcher.matches(block, txn) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => (),
+                    }
+                }
+                return result;
+            },
+            Matcher::Not(matcher) => return matcher.matches(block, txn).map(|matches| !matches),
             Matcher::All => true,
-            Matcher::BlockId(id) => block_id == *id,
-            Matcher::BlockTimeStampGreaterThan(ts) => timestamp > *ts,
-            Matcher::BlockTimeStampLessThan(ts) => timestamp < *ts,
             Matcher::TransactionId(id) => txn.clone().committed_hash() == *id,
             Matcher::Sender(sender) => txn.sender() == *sender,
             Matcher::ModuleAddress(address) => match txn.payload() {
@@ -43,7 +146,35 @@ impl Matcher {
                 },
                 _ => false,
             },
-        }
+            Matcher::AnyScript => matches!(txn.payload(), TransactionPayload::Script(_)),
+            Matcher::ScriptHash(hash) => match txn.payload() {
+                TransactionPayload::Script(script) => {
+                    HashValue::sha3_256_of(script.code()) == *hash
+                },
+                _ => false,
+            },
+            Matcher::MultisigAddress(address) => match txn.payload() {
+                TransactionPayload::Multisig(multisig) => multisig.multisig_address == *address,
+                _ => false,
+            },
+            Matcher::AnyFeePayer => txn.authenticator_ref().fee_payer_address().is_some(),
+            Matcher::FeePayer(address) => {
+                txn.authenticator_ref().fee_payer_address() == Some(*address)
+            },
+            Matcher::SecondarySigner(address) => txn
+                .authenticator_ref()
+                .secondary_signer_addresses()
+                .contains(address),
+            Matcher::AuthenticatorKind(kind) => txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|authenticator| kind.matches_account_authenticator(authenticator)),
+            Matcher::GasUnitPriceGreaterThan(price) => txn.gas_unit_price() > *price,
+            Matcher::GasUnitPriceLessThan(price) => txn.gas_unit_price() < *price,
+            Matcher::MaxGasAmountGreaterThan(amount) => txn.max_gas_amount() > *amount,
+        };
+        Some(matches)
     }
 }
 
@@ -69,17 +200,17 @@ enum EvalResult {
 }
 
 impl Rule {
-    fn eval(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> EvalResult {
+    fn eval(&self, block: Option<(HashValue, u64)>, txn: &SignedTransaction) -> EvalResult {
         match self {
             Rule::Allow(matcher) => {
-                if matcher.matches(block_id, timestamp, txn) {
+                if matcher.matches(block, txn) == Some(true) {
                     EvalResult::Allow
                 } else {
                     EvalResult::NoMatch
                 }
             },
             Rule::Deny(matcher) => {
-                if matcher.matches(block_id, timestamp, txn) {
+                if matcher.matches(block, txn) == Some(true) {
                     EvalResult::Deny
                 } else {
                     EvalResult::NoMatch
@@ -197,15 +328,52 @@ impl Filter {
         self
     }
 
+    pub fn add_allow(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Allow(matcher));
+        self
+    }
+
+    pub fn add_deny(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Deny(matcher));
+        self
+    }
+
+    pub fn add_deny_script(mut self) -> Self {
+        self.rules.push(Rule::Deny(Matcher::AnyScript));
+        self
+    }
+
+    pub fn add_deny_fee_payer(mut self, fee_payer: AccountAddress) -> Self {
+        self.rules.push(Rule::Deny(Matcher::FeePayer(fee_payer)));
+        self
+    }
+
+    pub fn add_deny_authenticator_kind(mut self, kind: AuthenticatorKind) -> Self {
+        self.rules
+            .push(Rule::Deny(Matcher::AuthenticatorKind(kind)));
+        self
+    }
+
     pub fn rules(&self) -> &[Rule] {
         &self.rules
     }
 
+    /// Evaluates the rules against a transaction included in the given block.
     pub fn allows(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
+        self.evaluate(Some((block_id, timestamp)), txn)
+    }
+
+    /// Evaluates the rules against a transaction that is not (yet) part of a block, e.g. on
+    /// mempool admission. Rules whose outcome depends on block matchers never apply in this case.
+    pub fn allows_transaction(&self, txn: &SignedTransaction) -> bool {
+        self.evaluate(None, txn)
+    }
+
+    fn evaluate(&self, block: Option<(HashValue, u64)>, txn: &SignedTransaction) -> bool {
         for rule in &self.rules {
             // Rules are evaluated in the order and the first rule that matches is used. If no rule
             // matches, the transaction is allowed.
-            match rule.eval(block_id, timestamp, txn) {
+            match rule.eval(block, txn) {
                 EvalResult::Allow => return true,
                 EvalResult::Deny => return false,
                 EvalResult::NoMatch => continue,
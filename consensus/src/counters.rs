@@ -389,6 +389,36 @@ pub static CONSENSUS_PARTICIPATION_STATUS: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Share of the total voting power of each proposer, to compare with its election rate.
+pub static PROPOSER_STAKE_SHARE: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "aptos_consensus_proposer_stake_share",
+        "Share of the total voting power of each proposer",
+        &["peer_id"]
+    )
+    .unwrap()
+});
+
+/// Fraction of the rounds each proposer was elected for, since the start of the epoch.
+pub static PROPOSER_ELECTION_RATE: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "aptos_consensus_proposer_election_rate",
+        "Fraction of the rounds each proposer was elected for in the current epoch",
+        &["peer_id"]
+    )
+    .unwrap()
+});
+
+/// Whether each proposer is currently excluded from the stake weighted proposer election.
+pub static PROPOSER_EXCLUDED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_consensus_proposer_excluded",
+        "1 if the proposer is excluded because of recent failed rounds, 0 otherwise",
+        &["peer_id"]
+    )
+    .unwrap()
+});
+
 /// Voting power of the validator
 pub static VALIDATOR_VOTING_POWER: Lazy<Gauge> = Lazy::new(|| {
     register_gauge!(
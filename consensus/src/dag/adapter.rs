@@ -312,6 +312,7 @@ impl StorageAdapter {
                 &new_block_event.previous_block_votes_bitvec().clone().into(),
             )?,
             Self::indices_to_validators(validators, new_block_event.failed_proposer_indices())?,
+            new_block_event.proposed_time(),
         ))
     }
 
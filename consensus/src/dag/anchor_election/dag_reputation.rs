@@ -0,0 +1,255 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reputation based anchor election scored on DAG signals.
+//!
+//! Validators must elect the same anchor for a round, so every signal has to be computed from
+//! data all of them have, including after a restart, when the history is recovered from the
+//! on-chain `NewBlockEvent`s. Those only carry the anchors: their author, round, timestamp,
+//! strong links and failed authors. Hence the scored signals are failed anchor rounds, strong
+//! link coverage and anchor latency.
+//!
+//! Two signals can't be scored deterministically:
+//! - Node certification latency: when a node gathers its certificate is observed locally and
+//!   differs between validators, and the committed history has no record of it. Anchor latency,
+//!   derived from the committed anchor timestamps, stands in for it.
+//! - Fetch requests served: each validator only sees the requests it sent itself. They are
+//!   reported by the `aptos_consensus_dag_fetch_requests_served` metric only.
+
+use crate::{
+    dag::{
+        anchor_election::{AnchorElection, CommitHistory},
+        observability::counters::{ANCHOR_REPUTATION_LATENCY, ANCHOR_REPUTATION_WEIGHT},
+        storage::CommitEvent,
+        NodeId,
+    },
+    liveness::{leader_reputation::VotingPowerRatio, proposer_election::choose_index},
+};
+use aptos_collections::BoundedVecDeque;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_infallible::Mutex;
+use aptos_types::on_chain_config::DagReputationConfig;
+use std::collections::HashMap;
+
+/// What the commit events in the window tell about a validator.
+#[derive(Clone, Debug, Default)]
+struct AnchorSignals {
+    /// Anchors of the validator that were committed.
+    committed_anchors: u64,
+    /// Anchor rounds of the validator that were skipped.
+    failed_anchors: u64,
+    /// Committed anchors that strongly link the validator's node of the previous round.
+    strong_links: u64,
+    /// Sum and count of the latencies of the validator's committed anchors: the time since the
+    /// previous committed anchor, per anchor round in between.
+    latency_sum_usecs: u64,
+    latency_count: u64,
+}
+
+impl AnchorSignals {
+    fn average_latency_usecs(&self) -> Option<u64> {
+        (self.latency_count > 0).then(|| self.latency_sum_usecs / self.latency_count)
+    }
+}
+
+struct ReputationState {
+    window: BoundedVecDeque<CommitEvent>,
+    weights: Vec<u128>,
+}
+
+/// Anchor election scoring validators on what the committed DAG shows about them: how often
+/// their anchor rounds failed, how often committed anchors strongly link their nodes, and how
+/// long their anchors took compared to the others'.
+///
+/// All the signals come from commit events, which are identical on every validator and are
+/// recovered from the on-chain commit history after a restart, so all validators agree on the
+/// anchors.
+pub struct DagReputationAnchorElection {
+    epoch: u64,
+    validators: Vec<Author>,
+    author_to_index: HashMap<Author, usize>,
+    voting_powers: Vec<u64>,
+    config: DagReputationConfig,
+    state: Mutex<ReputationState>,
+}
+
+impl DagReputationAnchorElection {
+    pub fn new(
+        epoch: u64,
+        validators: Vec<Author>,
+        voting_powers: Vec<u64>,
+        config: DagReputationConfig,
+    ) -> Self {
+        assert_eq!(validators.len(), voting_powers.len());
+        let window_size = std::cmp::max(
+            1,
+            validators.len() * config.window_num_validators_multiplier,
+        );
+        let author_to_index = validators
+            .iter()
+            .enumerate()
+            .map(|(index, author)| (*author, index))
+            .collect();
+        let election = Self {
+            epoch,
+            author_to_index,
+            voting_powers,
+            config,
+            state: Mutex::new(ReputationState {
+                window: BoundedVecDeque::new(window_size),
+                weights: vec![],
+            }),
+            validators,
+        };
+        let mut state = election.state.lock();
+        state.weights = election.compute_weights(&state.window);
+        drop(state);
+        election
+    }
+
+    fn compute_signals(&self, window: &BoundedVecDeque<CommitEvent>) -> Vec<AnchorSignals> {
+        let mut signals = vec![AnchorSignals::default(); self.validators.len()];
+        let mut previous: Option<&CommitEvent> = None;
+        for event in window.iter() {
+            if let Some(index) = self.author_to_index.get(event.author()) {
+                let author_signals = &mut signals[*index];
+                author_signals.committed_anchors += 1;
+                if let Some(previous) =
+                    previous.filter(|previous| previous.epoch() == event.epoch())
+                {
+                    let anchor_rounds =
+                        std::cmp::max(1, event.round().saturating_sub(previous.round()) / 2);
+                    author_signals.latency_sum_usecs +=
+                        event.timestamp().saturating_sub(previous.timestamp()) / anchor_rounds;
+                    author_signals.latency_count += 1;
+                }
+            }
+            for author in event.failed_authors() {
+                if let Some(index) = self.author_to_index.get(author) {
+                    signals[*index].failed_anchors += 1;
+                }
+            }
+            for author in event.parents() {
+                if let Some(index) = self.author_to_index.get(author) {
+                    signals[*index].strong_links += 1;
+                }
+            }
+            previous = Some(event);
+        }
+        signals
+    }
+
+    fn compute_weights(&self, window: &BoundedVecDeque<CommitEvent>) -> Vec<u128> {
+        let signals = self.compute_signals(window);
+        let num_events = window.iter().count() as u64;
+
+        let mut latencies: Vec<_> = signals
+            .iter()
+            .filter_map(AnchorSignals::average_latency_usecs)
+            .collect();
+        latencies.sort_unstable();
+        let median_latency = latencies.get(latencies.len() / 2).copied();
+
+        signals
+            .iter()
+            .zip(self.validators.iter().zip(self.voting_powers.iter()))
+            .map(|(signals, (author, voting_power))| {
+                let weight = self.weight(signals, num_events, median_latency);
+                ANCHOR_REPUTATION_WEIGHT
+                    .with_label_values(&[&author.to_hex()])
+                    .set(weight as i64);
+                if let Some(latency) = signals.average_latency_usecs() {
+                    ANCHOR_REPUTATION_LATENCY
+                        .with_label_values(&[&author.to_hex()])
+                        .set(latency as i64);
+                }
+                if self.config.weight_by_voting_power {
+                    weight as u128 * *voting_power as u128
+                } else {
+                    weight as u128
+                }
+            })
+            .collect()
+    }
+
+    fn weight(&self, signals: &AnchorSignals, num_events: u64, median_latency: Option<u64>) -> u64 {
+        let config = &self.config;
+        let elected = signals.committed_anchors + signals.failed_anchors;
+        if elected > 0
+            && signals.failed_anchors * 100 > elected * config.failure_threshold_percent as u64
+        {
+            return config.failed_weight;
+        }
+        if signals.committed_anchors == 0 && signals.strong_links == 0 {
+            return config.inactive_weight;
+        }
+
+        let mut weight = config.active_weight;
+        let coverage_percent = signals.strong_links * 100 / std::cmp::max(1, num_events);
+        if coverage_percent < config.min_strong_link_coverage_percent as u64 {
+            weight = weight * std::cmp::max(1, coverage_percent)
+                / config.min_strong_link_coverage_percent as u64;
+        }
+        if let (Some(latency), Some(median)) = (signals.average_latency_usecs(), median_latency) {
+            if latency * 100 > median * config.slow_latency_threshold_percent as u64 {
+                weight = weight * config.slow_weight_percent as u64 / 100;
+            }
+        }
+        std::cmp::max(weight, config.failed_weight)
+    }
+}
+
+impl AnchorElection for DagReputationAnchorElection {
+    fn get_anchor(&self, round: Round) -> Author {
+        let weights = self.state.lock().weights.clone();
+        let state = [
+            self.epoch.to_le_bytes().to_vec(),
+            round.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        self.validators[choose_index(weights, state)]
+    }
+
+    fn update_reputation(&self, commit_event: CommitEvent) {
+        let mut state = self.state.lock();
+        // Committed blocks get a timestamp strictly larger than their parent's, which is what
+        // the on-chain history recovered after a restart has, so do the same here.
+        let commit_event = match state.window.iter().last() {
+            Some(last) if commit_event.timestamp() <= last.timestamp() => CommitEvent::new(
+                NodeId::new(
+                    commit_event.epoch(),
+                    commit_event.round(),
+                    *commit_event.author(),
+                ),
+                commit_event.parents().to_vec(),
+                commit_event.failed_authors().to_vec(),
+                last.timestamp() + 1,
+            ),
+            _ => commit_event,
+        };
+        state.window.push_back(commit_event);
+        state.weights = self.compute_weights(&state.window);
+    }
+}
+
+impl CommitHistory for DagReputationAnchorElection {
+    fn get_voting_power_participation_ratio(&self, _round: Round) -> VotingPowerRatio {
+        let state = self.state.lock();
+        // Not enough history to tell yet.
+        if !state.window.is_full() {
+            return 1.0;
+        }
+        let signals = self.compute_signals(&state.window);
+        let total_voting_power: u128 = self.voting_powers.iter().map(|vp| *vp as u128).sum();
+        let participating_voting_power: u128 = signals
+            .iter()
+            .zip(self.voting_powers.iter())
+            .filter(|(signals, _)| signals.committed_anchors > 0 || signals.strong_links > 0)
+            .map(|(_, vp)| *vp as u128)
+            .sum();
+        if total_voting_power == 0 {
+            return 1.0;
+        }
+        participating_voting_power as f64 / total_voting_power as f64
+    }
+}
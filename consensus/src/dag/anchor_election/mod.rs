@@ -14,8 +14,10 @@ pub trait CommitHistory: Send + Sync {
     fn get_voting_power_participation_ratio(&self, round: Round) -> VotingPowerRatio;
 }
 
+mod dag_reputation;
 mod leader_reputation_adapter;
 mod round_robin;
 
+pub use dag_reputation::DagReputationAnchorElection;
 pub use leader_reputation_adapter::{LeaderReputationAdapter, MetadataBackendAdapter};
 pub use round_robin::RoundRobinAnchorElection;
@@ -2,7 +2,9 @@
 
 use super::{
     adapter::{OrderedNotifierAdapter, TLedgerInfoProvider},
-    anchor_election::{AnchorElection, CommitHistory, RoundRobinAnchorElection},
+    anchor_election::{
+        AnchorElection, CommitHistory, DagReputationAnchorElection, RoundRobinAnchorElection,
+    },
     dag_driver::DagDriver,
     dag_fetcher::{DagFetcher, DagFetcherService, FetchRequestHandler},
     dag_handler::NetworkHandler,
@@ -478,6 +480,27 @@ impl DagBootstrapper {
                     Some(commit_events),
                 )
             },
+            AnchorElectionMode::DagReputation(config) => {
+                let window_size =
+                    config.window_num_validators_multiplier * self.epoch_state.verifier.len();
+                let commit_events = self
+                    .storage
+                    .get_latest_k_committed_events(window_size as u64)
+                    .expect("Failed to read commit events from storage");
+                let voting_powers: Vec<u64> = self
+                    .epoch_state
+                    .verifier
+                    .get_ordered_account_addresses_iter()
+                    .map(|p| self.epoch_state.verifier.get_voting_power(&p).unwrap())
+                    .collect();
+                let election = Arc::new(DagReputationAnchorElection::new(
+                    self.epoch_state.epoch,
+                    self.epoch_state.verifier.get_ordered_account_addresses(),
+                    voting_powers,
+                    *config,
+                ));
+                (election.clone(), election, Some(commit_events))
+            },
         }
     }
 
@@ -710,7 +733,7 @@ impl DagBootstrapper {
     }
 }
 
-pub(super) fn bootstrap_dag_for_test(
+pub(crate) fn bootstrap_dag_for_test(
     self_peer: Author,
     signer: ValidatorSigner,
     epoch_state: Arc<EpochState>,
@@ -722,6 +745,7 @@ pub(super) fn bootstrap_dag_for_test(
     payload_manager: Arc<PayloadManager>,
     payload_client: Arc<dyn PayloadClient>,
     execution_client: Arc<dyn TExecutionClient>,
+    onchain_config: DagConsensusConfigV1,
 ) -> (
     JoinHandle<SyncOutcome>,
     JoinHandle<()>,
@@ -734,7 +758,7 @@ pub(super) fn bootstrap_dag_for_test(
     let bootstraper = DagBootstrapper::new(
         self_peer,
         DagConsensusConfig::default(),
-        DagConsensusConfigV1::default(),
+        onchain_config,
         signer.into(),
         epoch_state.clone(),
         storage.clone(),
@@ -5,7 +5,10 @@ use super::{dag_store::DagStore, errors::DagFetchError, DAGRpcResult};
 use crate::dag::{
     dag_network::{RpcResultWithResponder, TDAGNetworkSender},
     errors::FetchRequestHandleError,
-    observability::logging::{LogEvent, LogSchema},
+    observability::{
+        counters::FETCH_REQUESTS_SERVED,
+        logging::{LogEvent, LogSchema},
+    },
     types::{CertifiedNode, FetchResponse, Node, NodeMetadata, RemoteFetchRequest},
     RpcHandler, RpcWithFallback,
 };
@@ -331,6 +334,9 @@ impl TDagFetcher for DagFetcher {
                         response.verify(&remote_request, &self.epoch_state.verifier)
                     }) {
                         Ok(fetch_response) => {
+                            FETCH_REQUESTS_SERVED
+                                .with_label_values(&[&responder.to_hex()])
+                                .inc();
                             let certified_nodes = fetch_response.certified_nodes();
                             // TODO: support chunk response or fallback to state sync
                             {
@@ -24,9 +24,13 @@ mod tests;
 mod types;
 
 pub use adapter::{ProofNotifier, StorageAdapter};
+#[cfg(test)]
+pub(crate) use bootstrap::bootstrap_dag_for_test;
 pub use bootstrap::DagBootstrapper;
 pub use commit_signer::DagCommitSigner;
 pub use dag_network::{RpcHandler, RpcWithFallback, TDAGNetworkSender};
 #[cfg(test)]
+pub(crate) use tests::dag_test::MockStorage as MockDagStorage;
+#[cfg(test)]
 pub use types::Extensions;
 pub use types::{CertifiedNode, DAGMessage, DAGNetworkMessage, DAGRpcResult, Node, NodeId, Vote};
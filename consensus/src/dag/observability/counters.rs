@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    register_histogram, register_histogram_vec, register_int_gauge, Histogram, HistogramVec,
-    IntGauge,
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -67,3 +67,34 @@ pub static NUM_ROUNDS_PER_BLOCK: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Selection weight of each validator under the DAG reputation anchor election.
+pub static ANCHOR_REPUTATION_WEIGHT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_consensus_dag_anchor_reputation_weight",
+        "Selection weight of each validator under the DAG reputation anchor election",
+        &["author"]
+    )
+    .unwrap()
+});
+
+/// Anchor latency of each validator in the reputation window, in microseconds.
+pub static ANCHOR_REPUTATION_LATENCY: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_consensus_dag_anchor_reputation_latency_usecs",
+        "Average anchor latency of each validator in the DAG reputation window",
+        &["author"]
+    )
+    .unwrap()
+});
+
+/// Counts the fetch requests each peer served successfully. This is a local observation, so it
+/// is only reported and doesn't feed into anchor election, which must agree across validators.
+pub static FETCH_REQUESTS_SERVED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_dag_fetch_requests_served",
+        "Counts the fetch requests served successfully by each peer",
+        &["author"]
+    )
+    .unwrap()
+});
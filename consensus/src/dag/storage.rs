@@ -13,14 +13,21 @@ pub struct CommitEvent {
     node_id: NodeId,
     parents: Vec<Author>,
     failed_authors: Vec<Author>,
+    timestamp: u64,
 }
 
 impl CommitEvent {
-    pub fn new(node_id: NodeId, parents: Vec<Author>, failed_authors: Vec<Author>) -> Self {
+    pub fn new(
+        node_id: NodeId,
+        parents: Vec<Author>,
+        failed_authors: Vec<Author>,
+        timestamp: u64,
+    ) -> Self {
         CommitEvent {
             node_id,
             parents,
             failed_authors,
+            timestamp,
         }
     }
 
@@ -43,6 +50,10 @@ impl CommitEvent {
     pub fn failed_authors(&self) -> &[Author] {
         &self.failed_authors
     }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
 }
 
 pub trait DAGStorage: Send + Sync {
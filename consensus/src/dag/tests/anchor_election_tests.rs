@@ -0,0 +1,163 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    anchor_election::{AnchorElection, CommitHistory, DagReputationAnchorElection},
+    storage::CommitEvent,
+    NodeId,
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_types::{
+    on_chain_config::DagReputationConfig, validator_verifier::random_validator_verifier,
+};
+use std::collections::HashMap;
+
+const NUM_VALIDATORS: usize = 4;
+
+fn new_election() -> (DagReputationAnchorElection, Vec<Author>) {
+    let (_, verifier) = random_validator_verifier(NUM_VALIDATORS, None, false);
+    let validators = verifier.get_ordered_account_addresses();
+    (
+        DagReputationAnchorElection::new(
+            1,
+            validators.clone(),
+            vec![1; NUM_VALIDATORS],
+            DagReputationConfig::default(),
+        ),
+        validators,
+    )
+}
+
+fn anchor_counts(election: &DagReputationAnchorElection) -> HashMap<Author, usize> {
+    let mut counts = HashMap::new();
+    for round in (1_000..3_000).step_by(2) {
+        *counts.entry(election.get_anchor(round)).or_default() += 1;
+    }
+    counts
+}
+
+/// Commits an anchor every other round, with `anchor_of` picking the author, and optionally the
+/// validators that failed the anchor rounds in between.
+fn commit_events(
+    validators: &[Author],
+    num_events: usize,
+    anchor_of: impl Fn(usize) -> (usize, Vec<usize>),
+    latency_of: impl Fn(usize) -> u64,
+) -> Vec<CommitEvent> {
+    let mut round: Round = 0;
+    let mut timestamp = 1_000_000;
+    (0..num_events)
+        .map(|i| {
+            let (anchor, failed) = anchor_of(i);
+            round += 2 * (failed.len() as u64 + 1);
+            timestamp += latency_of(anchor) * (failed.len() as u64 + 1);
+            CommitEvent::new(
+                NodeId::new(1, round, validators[anchor]),
+                validators.to_vec(),
+                failed.into_iter().map(|index| validators[index]).collect(),
+                timestamp,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_dag_reputation_without_history() {
+    let (election, validators) = new_election();
+    let counts = anchor_counts(&election);
+    for validator in &validators {
+        assert!(counts[validator] > 100);
+    }
+    assert_eq!(election.get_voting_power_participation_ratio(1), 1.0);
+}
+
+#[test]
+fn test_dag_reputation_failed_anchors() {
+    let (election, validators) = new_election();
+    // Every anchor round of validator 0 fails.
+    for event in commit_events(
+        &validators,
+        40,
+        |i| (1 + i % 3, if i % 3 == 0 { vec![0] } else { vec![] }),
+        |_| 100_000,
+    ) {
+        election.update_reputation(event);
+    }
+    let counts = anchor_counts(&election);
+    assert!(counts.get(&validators[0]).copied().unwrap_or(0) < 10);
+    for validator in &validators[1..] {
+        assert!(counts[validator] > 200);
+    }
+}
+
+#[test]
+fn test_dag_reputation_slow_anchors() {
+    let (election, validators) = new_election();
+    // Validator 3's anchors take 5 times longer than the others'.
+    for event in commit_events(
+        &validators,
+        40,
+        |i| (i % NUM_VALIDATORS, vec![]),
+        |anchor| if anchor == 3 { 500_000 } else { 100_000 },
+    ) {
+        election.update_reputation(event);
+    }
+    let counts = anchor_counts(&election);
+    assert!(counts.get(&validators[3]).copied().unwrap_or(0) < 100);
+    for validator in &validators[..3] {
+        assert!(counts[validator] > 250);
+    }
+}
+
+#[test]
+fn test_dag_reputation_missing_strong_links() {
+    let (election, validators) = new_election();
+    // Validator 2 is never strongly linked by the anchors and never elected.
+    let linked: Vec<_> = [0, 1, 3].iter().map(|index| validators[*index]).collect();
+    for (i, round) in (2..80).step_by(2).enumerate() {
+        election.update_reputation(CommitEvent::new(
+            NodeId::new(1, round, linked[i % 3]),
+            linked.clone(),
+            vec![],
+            1_000_000 + round * 100_000,
+        ));
+    }
+    let counts = anchor_counts(&election);
+    assert!(counts.get(&validators[2]).copied().unwrap_or(0) < 10);
+}
+
+#[test]
+fn test_dag_reputation_recovered_timestamps() {
+    // Live events may carry the raw anchor timestamp, while recovered ones carry the block
+    // timestamp, which is strictly increasing. Both must lead to the same anchors.
+    let (live, validators) = new_election();
+    let recovered = DagReputationAnchorElection::new(
+        1,
+        validators.clone(),
+        vec![1; NUM_VALIDATORS],
+        DagReputationConfig::default(),
+    );
+    let mut last_block_timestamp = 0;
+    for (i, round) in (2..100).step_by(2).enumerate() {
+        let anchor = validators[i % NUM_VALIDATORS];
+        // Anchors within the same eight rounds share their timestamp.
+        let anchor_timestamp = 1_000_000 + (round - round % 8) * 100_000;
+        let block_timestamp = std::cmp::max(anchor_timestamp, last_block_timestamp + 1);
+        last_block_timestamp = block_timestamp;
+        live.update_reputation(CommitEvent::new(
+            NodeId::new(1, round, anchor),
+            validators.clone(),
+            vec![],
+            anchor_timestamp,
+        ));
+        recovered.update_reputation(CommitEvent::new(
+            NodeId::new(1, round, anchor),
+            validators.clone(),
+            vec![],
+            block_timestamp,
+        ));
+    }
+    for round in 100..300 {
+        assert_eq!(live.get_anchor(round), recovered.get_anchor(round));
+    }
+}
@@ -28,6 +28,7 @@ use aptos_time_service::TimeService;
 use aptos_types::{
     epoch_state::EpochState,
     ledger_info::generate_ledger_info_with_sig,
+    on_chain_config::DagConsensusConfigV1,
     validator_signer::ValidatorSigner,
     validator_verifier::{random_validator_verifier, ValidatorVerifier},
 };
@@ -94,6 +95,7 @@ impl DagBootstrapUnit {
                 payload_manager,
                 payload_client,
                 execution_client,
+                DagConsensusConfigV1::default(),
             );
 
         (
@@ -23,6 +23,7 @@ use crate::{
         rotating_proposer_election::{choose_leader, RotatingProposer},
         round_proposer_election::RoundProposer,
         round_state::{ExponentialTimeInterval, RoundState},
+        stake_weighted_proposer_election::StakeWeightedProposer,
     },
     logging::{LogEvent, LogSchema},
     metrics_safety_rules::MetricsSafetyRules,
@@ -364,6 +365,35 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                         + PROPOSER_ELECTION_CACHING_WINDOW_ADDITION,
                 ))
             },
+            ProposerElectionType::StakeWeighted(stake_weighted_config) => {
+                let seek_len = onchain_config.leader_reputation_exclude_round() as usize
+                    + onchain_config.max_failed_authors_to_store()
+                    + PROPOSER_ROUND_BEHIND_STORAGE_BUFFER;
+                // there is at most one block event per round
+                let backend = Arc::new(AptosDBBackend::new(
+                    stake_weighted_config.exclusion_window_rounds as usize,
+                    seek_len,
+                    self.storage.aptos_db(),
+                ));
+                let voting_powers: Vec<_> = proposers
+                    .iter()
+                    .map(|p| epoch_state.verifier.get_voting_power(p).unwrap())
+                    .collect();
+                let proposer_election = Box::new(StakeWeightedProposer::new(
+                    epoch_state.epoch,
+                    proposers,
+                    voting_powers,
+                    backend,
+                    *stake_weighted_config,
+                    onchain_config.leader_reputation_exclude_round(),
+                ));
+                Arc::new(CachedProposerElection::new(
+                    epoch_state.epoch,
+                    proposer_election,
+                    onchain_config.max_failed_authors_to_store()
+                        + PROPOSER_ELECTION_CACHING_WINDOW_ADDITION,
+                ))
+            },
             ProposerElectionType::RoundProposer(round_proposers) => {
                 // Hardcoded to the first proposer
                 let default_proposer = proposers.first().unwrap();
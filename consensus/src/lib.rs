@@ -36,6 +36,8 @@ pub mod quorum_store;
 mod rand;
 mod recovery_manager;
 mod round_manager;
+#[cfg(test)]
+mod simulator;
 mod state_computer;
 #[cfg(test)]
 mod state_computer_tests;
@@ -58,7 +60,7 @@ pub mod network_interface;
 mod payload_manager;
 mod qc_aggregator;
 mod transaction_deduper;
-mod transaction_filter;
+pub mod transaction_filter;
 mod transaction_shuffler;
 mod txn_hash_and_authenticator_deduper;
 
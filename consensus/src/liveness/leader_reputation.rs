@@ -13,7 +13,7 @@ use crate::{
     },
     liveness::proposer_election::{choose_index, ProposerElection},
 };
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use aptos_bitvec::BitVec;
 use aptos_consensus_types::common::{Author, Round};
 use aptos_crypto::HashValue;
@@ -21,7 +21,9 @@ use aptos_infallible::{Mutex, MutexGuard};
 use aptos_logger::prelude::*;
 use aptos_storage_interface::DbReader;
 use aptos_types::{
-    account_config::NewBlockEvent, epoch_change::EpochChangeProof, epoch_state::EpochState,
+    account_config::NewBlockEvent, block_metadata_ext::BlockMetadataExt,
+    epoch_change::EpochChangeProof, epoch_state::EpochState, randomness::Randomness,
+    transaction::Version,
 };
 use std::{
     cmp::max,
@@ -41,6 +43,18 @@ pub trait MetadataBackend: Send + Sync {
         target_epoch: u64,
         target_round: Round,
     ) -> (Vec<NewBlockEvent>, HashValue);
+
+    /// Same as `get_block_metadata`, along with the on-chain randomness of the latest block in
+    /// the window, if randomness was enabled for it. Backends without on-chain randomness never
+    /// return any.
+    fn get_block_metadata_and_randomness(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue, Option<Vec<u8>>) {
+        let (events, root_hash) = self.get_block_metadata(target_epoch, target_round);
+        (events, root_hash, None)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -107,7 +121,7 @@ impl AptosDBBackend {
         target_round: Round,
         events: &Vec<VersionedNewBlockEvent>,
         hit_end: bool,
-    ) -> (Vec<NewBlockEvent>, HashValue) {
+    ) -> (Vec<NewBlockEvent>, HashValue, Option<Version>) {
         // Do not warn when round==0, because check will always be unsure of whether we have
         // all events from the previous epoch. If there is an actual issue, next round will log it.
         if target_round != 0 {
@@ -152,17 +166,17 @@ impl AptosDBBackend {
                 );
                 HashValue::zero()
             });
-        (result, root_hash)
+        let latest_version = (!result.is_empty()).then_some(max_version);
+        (result, root_hash, latest_version)
     }
-}
 
-impl MetadataBackend for AptosDBBackend {
+    /// Returns the window, its root hash and the version of its latest block.
     // assume the target_round only increases
-    fn get_block_metadata(
+    fn get_versioned_block_metadata(
         &self,
         target_epoch: u64,
         target_round: Round,
-    ) -> (Vec<NewBlockEvent>, HashValue) {
+    ) -> (Vec<NewBlockEvent>, HashValue, Option<Version>) {
         let locked = self.db_result.lock();
         let events = &locked.0;
         let version = locked.1;
@@ -184,13 +198,67 @@ impl MetadataBackend for AptosDBBackend {
                     warn!(
                         error = ?e, "[leader reputation] Fail to refresh window",
                     );
-                    (vec![], HashValue::zero())
+                    (vec![], HashValue::zero(), None)
                 },
             }
         } else {
             self.get_from_db_result(target_epoch, target_round, events, hit_end)
         }
     }
+
+    /// Returns the randomness of the block whose metadata transaction is at the given version.
+    fn get_block_randomness(&self, version: Version) -> Result<Option<Vec<u8>>> {
+        let transaction = self
+            .aptos_db
+            .get_transaction_iterator(version, 1)?
+            .next()
+            .ok_or_else(|| anyhow!("No transaction at version {}", version))??;
+        Ok(match transaction.try_as_block_metadata_ext() {
+            Some(BlockMetadataExt::V1(block_metadata)) => block_metadata
+                .randomness
+                .as_ref()
+                .map(Randomness::randomness_cloned),
+            _ => None,
+        })
+    }
+}
+
+impl MetadataBackend for AptosDBBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let (events, root_hash, _) = self.get_versioned_block_metadata(target_epoch, target_round);
+        (events, root_hash)
+    }
+
+    /// The randomness is read from the metadata transaction of the latest block in the window,
+    /// i.e. the transaction that emitted its `NewBlockEvent`. If it can't be read (e.g. it was
+    /// pruned), the window is treated as unavailable, as when its events can't be read, so that
+    /// no node falls back to a different seed.
+    fn get_block_metadata_and_randomness(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue, Option<Vec<u8>>) {
+        let (events, root_hash, latest_version) =
+            self.get_versioned_block_metadata(target_epoch, target_round);
+        let Some(latest_version) = latest_version else {
+            return (events, root_hash, None);
+        };
+        match self.get_block_randomness(latest_version) {
+            Ok(randomness) => (events, root_hash, randomness),
+            Err(e) => {
+                warn!(
+                    error = ?e,
+                    "[leader election] Fail to read the block metadata at version {}",
+                    latest_version,
+                );
+                (vec![], HashValue::zero(), None)
+            },
+        }
+    }
 }
 
 /// Interface to calculate weights for proposers based on history.
@@ -16,13 +16,15 @@ use aptos_consensus_types::common::{Author, Round};
 use aptos_crypto::{bls12381, HashValue};
 use aptos_infallible::Mutex;
 use aptos_keygen::KeyGen;
-use aptos_storage_interface::DbReader;
+use aptos_storage_interface::{AptosDbError, DbReader};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{new_block_event_key, NewBlockEvent},
+    block_metadata_ext::BlockMetadataExt,
     contract_event::{ContractEvent, EventWithVersion},
     epoch_state::EpochState,
-    transaction::Version,
+    randomness::{RandMetadata, Randomness},
+    transaction::{Transaction, Version},
     validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
 };
 use claims::assert_err;
@@ -395,6 +397,9 @@ struct MockDbReader {
     epoch: Mutex<u64>,
     round: Mutex<u64>,
     to_add_event_after_call: Mutex<Option<(u64, Round)>>,
+    // randomness of the blocks whose metadata transaction is at the given version
+    randomness: Mutex<HashMap<Version, Vec<u8>>>,
+    pruned_before: Mutex<Version>,
 
     fetched: Mutex<usize>,
 }
@@ -409,6 +414,8 @@ impl MockDbReader {
             epoch: Mutex::new(0),
             round: Mutex::new(0),
             to_add_event_after_call: Mutex::new(None),
+            randomness: Mutex::new(HashMap::new()),
+            pruned_before: Mutex::new(0),
             fetched: Mutex::new(0),
         }
     }
@@ -460,6 +467,20 @@ impl MockDbReader {
         (*epoch, *round)
     }
 
+    pub fn add_event_with_randomness(
+        &self,
+        expected_epoch: u64,
+        expected_round: Round,
+        randomness: Vec<u8>,
+    ) {
+        self.add_event(expected_epoch, expected_round);
+        self.randomness.lock().insert(*self.idx.lock(), randomness);
+    }
+
+    pub fn prune_before(&self, version: Version) {
+        *self.pruned_before.lock() = version;
+    }
+
     pub fn new_epoch(&self) {
         *self.epoch.lock() += 1;
         *self.round.lock() = 0;
@@ -517,6 +538,41 @@ impl DbReader for MockDbReader {
     ) -> aptos_storage_interface::Result<HashValue> {
         Ok(HashValue::zero())
     }
+
+    fn get_transaction_iterator(
+        &self,
+        start_version: Version,
+        limit: u64,
+    ) -> aptos_storage_interface::Result<
+        Box<dyn Iterator<Item = aptos_storage_interface::Result<Transaction>> + '_>,
+    > {
+        if start_version < *self.pruned_before.lock() {
+            return Err(AptosDbError::Other(format!(
+                "Transaction at version {} is pruned",
+                start_version
+            )));
+        }
+        let randomness = self.randomness.lock().clone();
+        Ok(Box::new((start_version..start_version + limit).map(
+            move |version| {
+                Ok(Transaction::from(BlockMetadataExt::new_v1(
+                    HashValue::zero(),
+                    0,
+                    0,
+                    AccountAddress::random(),
+                    vec![],
+                    vec![],
+                    0,
+                    randomness.get(&version).map(|randomness| {
+                        Randomness::new(
+                            RandMetadata::new(0, 0, HashValue::zero(), 0),
+                            randomness.clone(),
+                        )
+                    }),
+                )))
+            },
+        )))
+    }
 }
 
 #[test]
@@ -602,6 +658,50 @@ fn backend_wrapper_test() {
     assert_history(14, vec![13, 12, 11], false);
 }
 
+#[test]
+fn backend_randomness_test() {
+    let aptos_db = Arc::new(MockDbReader::new());
+    let backend = AptosDBBackend::new(3, 3, aptos_db.clone());
+
+    aptos_db.add_event(0, 1);
+    aptos_db.add_event_with_randomness(0, 2, vec![2; 32]);
+    aptos_db.add_event_with_randomness(0, 3, vec![3; 32]);
+
+    let get = |round| {
+        let (history, root_hash, randomness) = backend.get_block_metadata_and_randomness(0, round);
+        let history: Vec<Round> = history.iter().map(|e| e.round()).collect();
+        (history, root_hash, randomness)
+    };
+
+    // the randomness of the latest block in the window, read along with it
+    assert_eq!(
+        get(3),
+        (vec![3, 2, 1], HashValue::zero(), Some(vec![3; 32]))
+    );
+    assert_eq!(get(2), (vec![2, 1], HashValue::zero(), Some(vec![2; 32])));
+    // none for a block without randomness
+    assert_eq!(get(1), (vec![1], HashValue::zero(), None));
+    // the same with the cache filled by a later round first
+    assert_eq!(
+        get(3),
+        (vec![3, 2, 1], HashValue::zero(), Some(vec![3; 32]))
+    );
+
+    // once the latest block of the window is pruned, the window is unavailable, rather than
+    // seeded from the root hash
+    aptos_db.prune_before(3);
+    assert_eq!(get(2), (vec![], HashValue::zero(), None));
+    assert_eq!(
+        get(3),
+        (vec![3, 2, 1], HashValue::zero(), Some(vec![3; 32]))
+    );
+    assert_eq!(
+        backend.get_block_metadata(0, 2).0.len(),
+        2,
+        "Only the randomness can't be read"
+    );
+}
+
 #[test]
 fn backend_test_cross_epoch() {
     let aptos_db = Arc::new(MockDbReader::new());
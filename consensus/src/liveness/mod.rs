@@ -9,6 +9,7 @@ pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
 pub(crate) mod round_proposer_election;
 pub(crate) mod round_state;
+pub(crate) mod stake_weighted_proposer_election;
 pub(crate) mod unequivocal_proposer_election;
 
 #[cfg(test)]
@@ -22,4 +23,6 @@ mod round_proposer_test;
 #[cfg(test)]
 mod round_state_test;
 #[cfg(test)]
+mod stake_weighted_proposer_election_test;
+#[cfg(test)]
 mod unequivocal_proposer_election_test;
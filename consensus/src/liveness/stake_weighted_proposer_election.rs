@@ -0,0 +1,145 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{PROPOSER_ELECTION_RATE, PROPOSER_EXCLUDED, PROPOSER_STAKE_SHARE},
+    liveness::{
+        leader_reputation::MetadataBackend,
+        proposer_election::{choose_index, ProposerElection},
+    },
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_infallible::Mutex;
+use aptos_types::{account_config::NewBlockEvent, on_chain_config::StakeWeightedConfig};
+use std::sync::Arc;
+
+/// Stake weighted random proposer election, which excludes validators that failed too many of
+/// their recent rounds until those failures leave the exclusion window.
+///
+/// Both the failed rounds and the seed come from committed history, `exclude_round` rounds
+/// behind, so that all validators agree on them: the seed is the on-chain randomness of the
+/// latest committed block, or the accumulator root hash if randomness was disabled for it. Both
+/// are read along with the window, so whether there is randomness only depends on that block.
+///
+/// Election statistics are counted every time a round is computed, so this is meant to be wrapped
+/// in a `CachedProposerElection`.
+pub struct StakeWeightedProposer {
+    epoch: u64,
+    proposers: Vec<Author>,
+    voting_powers: Vec<u64>,
+    backend: Arc<dyn MetadataBackend>,
+    config: StakeWeightedConfig,
+    exclude_round: u64,
+    // number of times each proposer was elected, and in total
+    elections: Mutex<(Vec<u64>, u64)>,
+}
+
+impl StakeWeightedProposer {
+    pub fn new(
+        epoch: u64,
+        proposers: Vec<Author>,
+        voting_powers: Vec<u64>,
+        backend: Arc<dyn MetadataBackend>,
+        config: StakeWeightedConfig,
+        exclude_round: u64,
+    ) -> Self {
+        assert_eq!(proposers.len(), voting_powers.len());
+        let total_voting_power: u128 = voting_powers.iter().map(|vp| *vp as u128).sum();
+        for (proposer, voting_power) in proposers.iter().zip(voting_powers.iter()) {
+            PROPOSER_STAKE_SHARE
+                .with_label_values(&[&proposer.to_hex()])
+                .set(*voting_power as f64 / std::cmp::max(1, total_voting_power) as f64);
+        }
+        let num_proposers = proposers.len();
+        Self {
+            epoch,
+            proposers,
+            voting_powers,
+            backend,
+            config,
+            exclude_round,
+            elections: Mutex::new((vec![0; num_proposers], 0)),
+        }
+    }
+
+    /// Returns which proposers are excluded, given the committed history up to target_round.
+    fn get_excluded(&self, history: &[NewBlockEvent], target_round: Round) -> Vec<bool> {
+        let window_start = target_round.saturating_sub(self.config.exclusion_window_rounds);
+        let mut failures = vec![0u32; self.proposers.len()];
+        for event in history
+            .iter()
+            .filter(|event| event.epoch() == self.epoch && event.round() > window_start)
+        {
+            for index in event.failed_proposer_indices() {
+                if let Some(count) = failures.get_mut(*index as usize) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let mut candidates: Vec<_> = (0..self.proposers.len())
+            .filter(|index| failures[*index] >= self.config.exclusion_failure_threshold)
+            .collect();
+        // most failures first, ties broken by index so that everyone agrees
+        candidates.sort_by_key(|index| (std::cmp::Reverse(failures[*index]), *index));
+
+        let total_voting_power: u128 = self.voting_powers.iter().map(|vp| *vp as u128).sum();
+        let max_excluded_voting_power =
+            total_voting_power * self.config.max_excluded_voting_power_percent as u128 / 100;
+        let mut excluded_voting_power = 0;
+        let mut excluded = vec![false; self.proposers.len()];
+        for index in candidates {
+            let voting_power = self.voting_powers[index] as u128;
+            // never exclude everyone
+            if excluded_voting_power + voting_power <= max_excluded_voting_power
+                && excluded_voting_power + voting_power < total_voting_power
+            {
+                excluded_voting_power += voting_power;
+                excluded[index] = true;
+            }
+        }
+        excluded
+    }
+
+    fn update_metrics(&self, chosen_index: usize, excluded: &[bool]) {
+        let mut elections = self.elections.lock();
+        elections.0[chosen_index] += 1;
+        elections.1 += 1;
+        for (index, proposer) in self.proposers.iter().enumerate() {
+            PROPOSER_ELECTION_RATE
+                .with_label_values(&[&proposer.to_hex()])
+                .set(elections.0[index] as f64 / elections.1 as f64);
+            PROPOSER_EXCLUDED
+                .with_label_values(&[&proposer.to_hex()])
+                .set(excluded[index] as i64);
+        }
+    }
+}
+
+impl ProposerElection for StakeWeightedProposer {
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        let target_round = round.saturating_sub(self.exclude_round);
+        let (history, root_hash, randomness) = self
+            .backend
+            .get_block_metadata_and_randomness(self.epoch, target_round);
+        let excluded = self.get_excluded(&history, target_round);
+        let seed = randomness.unwrap_or_else(|| root_hash.to_vec());
+
+        let weights: Vec<u128> = self
+            .voting_powers
+            .iter()
+            .zip(excluded.iter())
+            .map(|(vp, excluded)| if *excluded { 0 } else { *vp as u128 })
+            .collect();
+        let state = [
+            seed,
+            self.epoch.to_le_bytes().to_vec(),
+            round.to_le_bytes().to_vec(),
+        ]
+        .concat();
+
+        let chosen_index = choose_index(weights, state);
+        self.update_metrics(chosen_index, &excluded);
+        self.proposers[chosen_index]
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    leader_reputation::MetadataBackend, proposer_election::ProposerElection,
+    stake_weighted_proposer_election::StakeWeightedProposer,
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_types::{
+    account_address::AccountAddress, account_config::NewBlockEvent,
+    on_chain_config::StakeWeightedConfig,
+};
+use std::{collections::HashMap, sync::Arc};
+
+const EPOCH: u64 = 1;
+
+struct MockBackend {
+    // ordered from the latest, like the on-chain history
+    events: Mutex<Vec<NewBlockEvent>>,
+    seed: Mutex<Option<Vec<u8>>>,
+}
+
+impl MockBackend {
+    fn new() -> Self {
+        Self {
+            events: Mutex::new(vec![]),
+            seed: Mutex::new(None),
+        }
+    }
+
+    fn add_block(&self, round: Round, proposer: Author, failed_proposers: Vec<u64>) {
+        self.events.lock().insert(
+            0,
+            NewBlockEvent::new(
+                AccountAddress::random(),
+                EPOCH,
+                round,
+                round,
+                vec![],
+                proposer,
+                failed_proposers,
+                round * 1000,
+            ),
+        );
+    }
+}
+
+impl MetadataBackend for MockBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let events = self
+            .events
+            .lock()
+            .iter()
+            .filter(|e| (e.epoch(), e.round()) <= (target_epoch, target_round))
+            .cloned()
+            .collect();
+        (events, HashValue::zero())
+    }
+
+    fn get_block_metadata_and_randomness(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue, Option<Vec<u8>>) {
+        let (events, root_hash) = self.get_block_metadata(target_epoch, target_round);
+        (events, root_hash, self.seed.lock().clone())
+    }
+}
+
+fn new_election(
+    voting_powers: Vec<u64>,
+    config: StakeWeightedConfig,
+) -> (StakeWeightedProposer, Arc<MockBackend>, Vec<Author>) {
+    let proposers: Vec<_> = (0..voting_powers.len()).map(|_| Author::random()).collect();
+    let backend = Arc::new(MockBackend::new());
+    let election = StakeWeightedProposer::new(
+        EPOCH,
+        proposers.clone(),
+        voting_powers,
+        backend.clone(),
+        config,
+        0,
+    );
+    (election, backend, proposers)
+}
+
+fn election_counts(
+    election: &StakeWeightedProposer,
+    rounds: impl Iterator<Item = Round>,
+) -> HashMap<Author, usize> {
+    let mut counts = HashMap::new();
+    for round in rounds {
+        *counts
+            .entry(election.get_valid_proposer(round))
+            .or_default() += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_stake_weighted_distribution() {
+    let (election, _, proposers) = new_election(vec![1, 2, 3, 4], StakeWeightedConfig::default());
+    let num_rounds = 10_000;
+    let counts = election_counts(&election, 1..=num_rounds);
+    for (index, proposer) in proposers.iter().enumerate() {
+        let expected = num_rounds as f64 * (index + 1) as f64 / 10.0;
+        let actual = counts[proposer] as f64;
+        assert!(
+            (actual - expected).abs() < expected * 0.1,
+            "proposer {} elected {} times, expected about {}",
+            index,
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_stake_weighted_exclusion_window() {
+    let config = StakeWeightedConfig {
+        exclusion_window_rounds: 20,
+        exclusion_failure_threshold: 2,
+        max_excluded_voting_power_percent: 30,
+    };
+    let (election, backend, proposers) = new_election(vec![1; 4], config);
+    // proposer 0 fails rounds 2 and 4
+    backend.add_block(1, proposers[1], vec![]);
+    backend.add_block(3, proposers[2], vec![0]);
+    backend.add_block(5, proposers[3], vec![0]);
+
+    // excluded while both failures are within the window
+    let counts = election_counts(&election, 6..=22);
+    assert!(!counts.contains_key(&proposers[0]));
+
+    // and back once the first one left it
+    let counts = election_counts(&election, 23..1_000);
+    assert!(counts[&proposers[0]] > 100);
+}
+
+#[test]
+fn test_stake_weighted_exclusion_cap() {
+    let config = StakeWeightedConfig {
+        exclusion_window_rounds: 100,
+        exclusion_failure_threshold: 1,
+        max_excluded_voting_power_percent: 30,
+    };
+    let (election, backend, proposers) = new_election(vec![1; 4], config);
+    // proposer 2 fails twice, proposers 0 and 1 once: only one of them fits in the cap, the one
+    // with the most failures
+    backend.add_block(2, proposers[3], vec![2]);
+    backend.add_block(6, proposers[3], vec![0, 1, 2]);
+
+    let counts = election_counts(&election, 7..=100);
+    assert!(!counts.contains_key(&proposers[2]));
+    for index in [0, 1, 3] {
+        assert!(counts[&proposers[index]] > 10);
+    }
+}
+
+#[test]
+fn test_stake_weighted_never_excludes_everyone() {
+    let config = StakeWeightedConfig {
+        exclusion_window_rounds: 100,
+        exclusion_failure_threshold: 1,
+        max_excluded_voting_power_percent: 100,
+    };
+    let (election, backend, proposers) = new_election(vec![1; 3], config);
+    backend.add_block(4, proposers[0], vec![0, 1, 2]);
+
+    let counts = election_counts(&election, 5..100);
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[&proposers[2]], 95);
+}
+
+#[test]
+fn test_stake_weighted_randomness_seed() {
+    let (election, backend, _) = new_election(vec![1; 10], StakeWeightedConfig::default());
+    let without_seed: Vec<_> = (1..100)
+        .map(|round| election.get_valid_proposer(round))
+        .collect();
+    *backend.seed.lock() = Some(vec![42; 32]);
+    let with_seed: Vec<_> = (1..100)
+        .map(|round| election.get_valid_proposer(round))
+        .collect();
+    assert_ne!(without_seed, with_seed);
+    // deterministic for a given seed
+    assert_eq!(
+        with_seed,
+        (1..100)
+            .map(|round| election.get_valid_proposer(round))
+            .collect::<Vec<_>>()
+    );
+}
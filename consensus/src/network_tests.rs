@@ -86,6 +86,9 @@ pub struct NetworkPlayground {
     author_to_twin_ids: Arc<RwLock<AuthorToTwinIds>>,
     /// Information about connections
     peers_and_metadata: Arc<PeersAndMetadata>,
+    /// Queue rpc requests like direct-send messages instead of forwarding them
+    /// immediately, so that a driver can schedule their delivery.
+    queue_rpcs: bool,
 }
 
 impl NetworkPlayground {
@@ -101,9 +104,18 @@ impl NetworkPlayground {
             executor,
             author_to_twin_ids: Arc::new(RwLock::new(AuthorToTwinIds::default())),
             peers_and_metadata: PeersAndMetadata::new(&[NetworkId::Validator]),
+            queue_rpcs: false,
         }
     }
 
+    /// Queue the rpc requests of nodes added from now on with the other outbound
+    /// messages, instead of forwarding them immediately. The driver then has to
+    /// deliver them through `try_next_outbound` and `push_notification`.
+    pub fn with_queued_rpcs(mut self) -> Self {
+        self.queue_rpcs = true;
+        self
+    }
+
     pub fn handle(&self) -> Handle {
         self.executor.clone()
     }
@@ -135,6 +147,7 @@ impl NetworkPlayground {
             >,
         >,
         author_to_twin_ids: Arc<RwLock<AuthorToTwinIds>>,
+        queue_rpcs: bool,
     ) {
         while let Some(net_req) = network_reqs_rx.next().await {
             match net_req {
@@ -148,7 +161,7 @@ impl NetworkPlayground {
                 // but because the rpc call blocks and depends on the message
                 // delivery, we'd have to spawn the sending behaviour on a
                 // separate task, which is inconvenient.
-                PeerManagerRequest::SendRpc(dst, outbound_req) => {
+                PeerManagerRequest::SendRpc(dst, outbound_req) if !queue_rpcs => {
                     let dst_twin_ids = author_to_twin_ids.read().get_twin_ids(dst);
 
                     let dst_twin_id = match dst_twin_ids.iter().find(|dst_twin_id| {
@@ -205,6 +218,7 @@ impl NetworkPlayground {
             self.outbound_msgs_tx.clone(),
             self.node_consensus_txs.clone(),
             self.author_to_twin_ids.clone(),
+            self.queue_rpcs,
         );
         let fut2 = conn_mgr_reqs_rx.map(Ok).forward(::futures::sink::drain());
         self.executor.spawn(futures::future::join(fut1, fut2));
@@ -244,6 +258,26 @@ impl NetworkPlayground {
         msg_copy
     }
 
+    /// Return the next queued outbound message and its sender, if there is one,
+    /// without delivering it.
+    pub fn try_next_outbound(&mut self) -> Option<(TwinId, PeerManagerRequest)> {
+        self.outbound_msgs_rx.try_next().ok().flatten()
+    }
+
+    /// Push a network notification from `src_twin_id` into the inbound queue of
+    /// `dst_twin_id`. Notifications for nodes that have shut down are dropped.
+    pub fn push_notification(
+        &self,
+        src_twin_id: TwinId,
+        dst_twin_id: TwinId,
+        protocol_id: ProtocolId,
+        msg_notif: PeerManagerNotification,
+    ) {
+        if let Some(node_consensus_tx) = self.node_consensus_txs.lock().get(&dst_twin_id) {
+            let _ = node_consensus_tx.push((src_twin_id.author, protocol_id), msg_notif);
+        }
+    }
+
     /// Wait for exactly `num_messages` to be enqueued and delivered. Return a
     /// copy of all messages for verification.
     /// While all the sent messages are delivered (except those configured to be dropped),
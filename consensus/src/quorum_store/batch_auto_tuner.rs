@@ -0,0 +1,258 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::{batch_generator::BackPressure, counters};
+use aptos_config::config::{QuorumStoreAutoTuningConfig, QuorumStoreConfig};
+use aptos_logger::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Limits the batch generator creates batches with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct BatchLimits {
+    pub max_batch_txns: usize,
+    pub min_non_empty_interval_ms: usize,
+    pub max_num_batches: usize,
+}
+
+/// The signal that triggered an adjustment, in order of precedence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum AdjustmentReason {
+    ProofBacklog,
+    PipelineLatency,
+    ProofTimeouts,
+    ProofLatency,
+    Healthy,
+}
+
+impl AdjustmentReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentReason::ProofBacklog => "proof_backlog",
+            AdjustmentReason::PipelineLatency => "pipeline_latency",
+            AdjustmentReason::ProofTimeouts => "proof_timeouts",
+            AdjustmentReason::ProofLatency => "proof_latency",
+            AdjustmentReason::Healthy => "healthy",
+        }
+    }
+
+    fn is_decrease(&self) -> bool {
+        *self != AdjustmentReason::Healthy
+    }
+}
+
+/// Adapts the batch limits with additive increase / multiplicative decrease, like the dynamic
+/// pull rate: limits shrink while the proof backlog is over the back pressure threshold, the
+/// commit pipeline lags, proofs of our batches time out or take longer than the target to form,
+/// and grow back while proofs form in time. Limits stay within the configured bounds, and are
+/// the static ones from `QuorumStoreConfig` when auto tuning is disabled.
+pub(crate) struct BatchAutoTuner {
+    config: QuorumStoreAutoTuningConfig,
+    limits: BatchLimits,
+    // observed since the last adjustment
+    proof_latency_sum: Duration,
+    num_proofs: u64,
+    num_proof_timeouts: u64,
+    last_adjustment: Instant,
+}
+
+impl BatchAutoTuner {
+    pub(crate) fn new(config: &QuorumStoreConfig) -> Self {
+        let auto_tuning = config.auto_tuning;
+        let mut limits = BatchLimits {
+            max_batch_txns: config.sender_max_batch_txns,
+            min_non_empty_interval_ms: config.batch_generation_min_non_empty_interval_ms,
+            max_num_batches: config.sender_max_num_batches,
+        };
+        if auto_tuning.enabled {
+            limits = BatchLimits {
+                max_batch_txns: limits
+                    .max_batch_txns
+                    .clamp(auto_tuning.min_batch_txns, auto_tuning.max_batch_txns),
+                min_non_empty_interval_ms: limits.min_non_empty_interval_ms.clamp(
+                    auto_tuning.min_batch_interval_ms,
+                    auto_tuning.max_batch_interval_ms,
+                ),
+                max_num_batches: limits
+                    .max_num_batches
+                    .clamp(auto_tuning.min_num_batches, auto_tuning.max_num_batches),
+            };
+        }
+        let tuner = Self {
+            config: auto_tuning,
+            limits,
+            proof_latency_sum: Duration::ZERO,
+            num_proofs: 0,
+            num_proof_timeouts: 0,
+            last_adjustment: Instant::now(),
+        };
+        tuner.update_limit_counters();
+        tuner
+    }
+
+    pub(crate) fn limits(&self) -> &BatchLimits {
+        &self.limits
+    }
+
+    pub(crate) fn observe_proof_latency(&mut self, latency: Duration) {
+        self.proof_latency_sum += latency;
+        self.num_proofs += 1;
+    }
+
+    pub(crate) fn observe_proof_timeouts(&mut self, num_timeouts: usize) {
+        self.num_proof_timeouts += num_timeouts as u64;
+    }
+
+    /// Adjusts the limits if auto tuning is enabled and the adjustment interval has passed.
+    pub(crate) fn maybe_adjust(
+        &mut self,
+        now: Instant,
+        back_pressure: BackPressure,
+        pipeline_latency: Duration,
+    ) -> Option<AdjustmentReason> {
+        if !self.config.enabled
+            || now.duration_since(self.last_adjustment)
+                < Duration::from_millis(self.config.adjustment_interval_ms)
+        {
+            return None;
+        }
+        self.last_adjustment = now;
+        self.adjust(back_pressure, pipeline_latency)
+    }
+
+    /// Adjusts the limits based on the signals observed since the last adjustment, and returns
+    /// why if they changed.
+    pub(crate) fn adjust(
+        &mut self,
+        back_pressure: BackPressure,
+        pipeline_latency: Duration,
+    ) -> Option<AdjustmentReason> {
+        let avg_proof_latency =
+            (self.num_proofs > 0).then(|| self.proof_latency_sum / self.num_proofs as u32);
+        let num_proof_timeouts = self.num_proof_timeouts;
+        self.proof_latency_sum = Duration::ZERO;
+        self.num_proofs = 0;
+        self.num_proof_timeouts = 0;
+
+        for (signal, value) in [
+            (
+                "proof_latency_ms",
+                avg_proof_latency.unwrap_or_default().as_millis() as f64,
+            ),
+            ("proof_timeouts", num_proof_timeouts as f64),
+            ("pipeline_latency_ms", pipeline_latency.as_millis() as f64),
+            ("backlog_txn_exceeded", back_pressure.txn_count as u8 as f64),
+            (
+                "backlog_proof_exceeded",
+                back_pressure.proof_count as u8 as f64,
+            ),
+        ] {
+            counters::QS_AUTO_TUNING_SIGNALS
+                .with_label_values(&[signal])
+                .set(value);
+        }
+
+        let reason = if back_pressure.txn_count || back_pressure.proof_count {
+            AdjustmentReason::ProofBacklog
+        } else if pipeline_latency > Duration::from_millis(self.config.pipeline_latency_limit_ms) {
+            AdjustmentReason::PipelineLatency
+        } else if num_proof_timeouts > 0 {
+            AdjustmentReason::ProofTimeouts
+        } else {
+            // nothing to learn from an idle period
+            let avg_proof_latency = avg_proof_latency?;
+            if avg_proof_latency > Duration::from_millis(self.config.target_proof_latency_ms) {
+                AdjustmentReason::ProofLatency
+            } else {
+                AdjustmentReason::Healthy
+            }
+        };
+
+        let previous = self.limits;
+        if reason.is_decrease() {
+            self.decrease();
+        } else {
+            self.increase();
+        }
+        if self.limits == previous {
+            return None;
+        }
+
+        let direction = if reason.is_decrease() {
+            "decrease"
+        } else {
+            "increase"
+        };
+        counters::QS_AUTO_TUNING_ADJUSTMENTS
+            .with_label_values(&[direction, reason.as_str()])
+            .inc();
+        self.update_limit_counters();
+        info!(
+            "QS: auto tuning {} batch limits due to {}: {:?} -> {:?}, proof latency {:?}, proof timeouts {}, pipeline latency {:?}, back pressure txn {} proof {}",
+            direction,
+            reason.as_str(),
+            previous,
+            self.limits,
+            avg_proof_latency,
+            num_proof_timeouts,
+            pipeline_latency,
+            back_pressure.txn_count,
+            back_pressure.proof_count,
+        );
+        Some(reason)
+    }
+
+    fn decrease(&mut self) {
+        let config = &self.config;
+        let limits = &mut self.limits;
+        limits.max_batch_txns = std::cmp::max(
+            (limits.max_batch_txns as f64 * config.decrease_fraction) as usize,
+            config.min_batch_txns,
+        );
+        limits.max_num_batches = std::cmp::max(
+            (limits.max_num_batches as f64 * config.decrease_fraction) as usize,
+            config.min_num_batches,
+        );
+        limits.min_non_empty_interval_ms = std::cmp::min(
+            (limits.min_non_empty_interval_ms as f64 / config.decrease_fraction) as usize,
+            config.max_batch_interval_ms,
+        );
+    }
+
+    fn increase(&mut self) {
+        let config = &self.config;
+        let step = |min: usize, max: usize| {
+            std::cmp::max(((max - min) as f64 * config.increase_fraction) as usize, 1)
+        };
+        let limits = &mut self.limits;
+        limits.max_batch_txns = std::cmp::min(
+            limits.max_batch_txns + step(config.min_batch_txns, config.max_batch_txns),
+            config.max_batch_txns,
+        );
+        limits.max_num_batches = std::cmp::min(
+            limits.max_num_batches + step(config.min_num_batches, config.max_num_batches),
+            config.max_num_batches,
+        );
+        limits.min_non_empty_interval_ms = std::cmp::max(
+            limits.min_non_empty_interval_ms.saturating_sub(step(
+                config.min_batch_interval_ms,
+                config.max_batch_interval_ms,
+            )),
+            config.min_batch_interval_ms,
+        );
+    }
+
+    fn update_limit_counters(&self) {
+        for (limit, value) in [
+            ("max_batch_txns", self.limits.max_batch_txns),
+            (
+                "min_non_empty_interval_ms",
+                self.limits.min_non_empty_interval_ms,
+            ),
+            ("max_num_batches", self.limits.max_num_batches),
+        ] {
+            counters::QS_AUTO_TUNED_LIMITS
+                .with_label_values(&[limit])
+                .set(value as i64);
+        }
+    }
+}
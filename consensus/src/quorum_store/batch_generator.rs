@@ -4,6 +4,7 @@ use crate::{
     monitor,
     network::{NetworkSender, QuorumStoreSender},
     quorum_store::{
+        batch_auto_tuner::BatchAutoTuner,
         batch_store::BatchWriter,
         counters,
         quorum_store_db::QuorumStoreStorage,
@@ -31,6 +32,7 @@ use tokio::time::Interval;
 pub enum BatchGeneratorCommand {
     CommitNotification(u64, Vec<BatchInfo>),
     ProofExpiration(Vec<BatchId>),
+    ProofLatencies(Vec<Duration>),
     Shutdown(tokio::sync::oneshot::Sender<()>),
 }
 
@@ -55,6 +57,7 @@ pub struct BatchGenerator {
     last_end_batch_time: Instant,
     // quorum store back pressure, get updated from proof manager
     back_pressure: BackPressure,
+    auto_tuner: BatchAutoTuner,
 }
 
 impl BatchGenerator {
@@ -83,6 +86,7 @@ impl BatchGenerator {
         db.save_batch_id(epoch, incremented_batch_id)
             .expect("Could not save to db");
 
+        let auto_tuner = BatchAutoTuner::new(&config);
         Self {
             epoch,
             my_peer_id,
@@ -100,6 +104,7 @@ impl BatchGenerator {
                 txn_count: false,
                 proof_count: false,
             },
+            auto_tuner,
         }
     }
 
@@ -157,7 +162,8 @@ impl BatchGenerator {
             if *total_batches_remaining == 0 {
                 return;
             }
-            let num_take_txns = std::cmp::min(self.config.sender_max_batch_txns, txns_remaining);
+            let num_take_txns =
+                std::cmp::min(self.auto_tuner.limits().max_batch_txns, txns_remaining);
             let mut batch_bytes_remaining = self.config.sender_max_batch_bytes as u64;
             let num_batch_txns = txns
                 .iter()
@@ -200,7 +206,7 @@ impl BatchGenerator {
             .cloned()
             .collect();
 
-        let mut max_batches_remaining = self.config.sender_max_num_batches as u64;
+        let mut max_batches_remaining = self.auto_tuner.limits().max_num_batches as u64;
         let mut batches = vec![];
         for bucket_start in &reverse_buckets_excluding_zero {
             if pulled_txns.is_empty() || max_batches_remaining == 0 {
@@ -287,6 +293,17 @@ impl BatchGenerator {
         self.txns_in_progress_sorted.len()
     }
 
+    /// Time since the latest committed block, zero until the first commit notification.
+    fn pipeline_latency(&self) -> Duration {
+        if self.latest_block_timestamp == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_micros(
+            (aptos_infallible::duration_since_epoch().as_micros() as u64)
+                .saturating_sub(self.latest_block_timestamp),
+        )
+    }
+
     pub(crate) async fn handle_scheduled_pull(&mut self, max_count: u64) -> Vec<Batch> {
         counters::BATCH_PULL_EXCLUDED_TXNS.observe(self.txns_in_progress_sorted.len() as f64);
         trace!(
@@ -395,12 +412,14 @@ impl BatchGenerator {
                     } else {
                         counters::QS_BACKPRESSURE_PROOF_COUNT.observe(0.0);
                     }
+                    let pipeline_latency = self.pipeline_latency();
+                    self.auto_tuner.maybe_adjust(tick_start, self.back_pressure, pipeline_latency);
                     let since_last_non_empty_pull_ms = std::cmp::min(
                         tick_start.duration_since(last_non_empty_pull).as_millis(),
                         self.config.batch_generation_max_interval_ms as u128
                     ) as usize;
                     if (!self.back_pressure.proof_count
-                        && since_last_non_empty_pull_ms >= self.config.batch_generation_min_non_empty_interval_ms)
+                        && since_last_non_empty_pull_ms >= self.auto_tuner.limits().min_non_empty_interval_ms)
                         || since_last_non_empty_pull_ms == self.config.batch_generation_max_interval_ms {
 
                         let dynamic_pull_max_txn = std::cmp::max(
@@ -464,6 +483,7 @@ impl BatchGenerator {
                             }
                         },
                         BatchGeneratorCommand::ProofExpiration(batch_ids) => {
+                            self.auto_tuner.observe_proof_timeouts(batch_ids.len());
                             for batch_id in batch_ids {
                                 counters::BATCH_IN_PROGRESS_TIMEOUT.inc();
                                 debug!(
@@ -473,7 +493,12 @@ impl BatchGenerator {
                                 // Not able to gather the proof, allow transactions to be polled again.
                                 self.remove_batch_in_progress(&batch_id);
                             }
-                        }
+                        },
+                        BatchGeneratorCommand::ProofLatencies(latencies) => {
+                            for latency in latencies {
+                                self.auto_tuner.observe_proof_latency(latency);
+                            }
+                        },
                         BatchGeneratorCommand::Shutdown(ack_tx) => {
                             ack_tx
                                 .send(())
@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, op_counters::DurationHistogram, register_avg_counter, register_histogram,
-    register_histogram_vec, register_int_counter, register_int_counter_vec, Histogram,
-    HistogramVec, IntCounter, IntCounterVec,
+    exponential_buckets, op_counters::DurationHistogram, register_avg_counter, register_gauge_vec,
+    register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge_vec, GaugeVec, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 use std::time::Duration;
@@ -581,6 +582,37 @@ pub static QS_BACKPRESSURE_DYNAMIC_MAX: Lazy<Histogram> = Lazy::new(|| {
     )
 });
 
+/// Number of batch limit adjustments made by the auto tuner, by direction and the signal that
+/// triggered them.
+pub static QS_AUTO_TUNING_ADJUSTMENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "quorum_store_auto_tuning_adjustments",
+        "Number of batch limit adjustments made by the auto tuner",
+        &["direction", "reason"]
+    )
+    .unwrap()
+});
+
+/// Current value of each batch limit controlled by the auto tuner.
+pub static QS_AUTO_TUNED_LIMITS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "quorum_store_auto_tuned_limits",
+        "Current value of each batch limit controlled by the auto tuner",
+        &["limit"]
+    )
+    .unwrap()
+});
+
+/// Signals the latest auto tuner adjustment was based on.
+pub static QS_AUTO_TUNING_SIGNALS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "quorum_store_auto_tuning_signals",
+        "Signals the latest auto tuner adjustment was based on",
+        &["signal"]
+    )
+    .unwrap()
+});
+
 /// Latencies
 
 /// Histogram of the time durations for batch creation.
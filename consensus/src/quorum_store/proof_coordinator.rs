@@ -146,6 +146,8 @@ pub(crate) struct ProofCoordinator {
     batch_reader: Arc<dyn BatchReader>,
     batch_generator_cmd_tx: tokio::sync::mpsc::Sender<BatchGeneratorCommand>,
     broadcast_proofs: bool,
+    // time to form the proofs completed since the last expiration tick, for the auto tuner
+    proof_latencies: Vec<Duration>,
 }
 
 //PoQS builder object - gather signed digest to form PoQS
@@ -167,6 +169,7 @@ impl ProofCoordinator {
             batch_reader,
             batch_generator_cmd_tx,
             broadcast_proofs,
+            proof_latencies: Vec::new(),
         }
     }
 
@@ -235,6 +238,7 @@ impl ProofCoordinator {
                         .remove(&digest)
                         .expect("Batch created without recording the time!");
                 counters::BATCH_TO_POS_DURATION.observe_duration(Duration::from_micros(duration));
+                self.proof_latencies.push(Duration::from_micros(duration));
                 return Ok(Some(proof));
             }
         } else if let Some(value) = self
@@ -283,6 +287,17 @@ impl ProofCoordinator {
                 Self::update_counters_on_expire(&state);
             }
         }
+        if !self.proof_latencies.is_empty()
+            && self
+                .batch_generator_cmd_tx
+                .send(BatchGeneratorCommand::ProofLatencies(std::mem::take(
+                    &mut self.proof_latencies,
+                )))
+                .await
+                .is_err()
+        {
+            warn!("Failed to send proof latencies to batch generator");
+        }
         if self
             .batch_generator_cmd_tx
             .send(BatchGeneratorCommand::ProofExpiration(batch_ids))
@@ -59,6 +59,17 @@ impl QuorumStoreDB {
 
         Self { db }
     }
+
+    /// Opens an existing QuorumStoreDB without taking the lock, so it can be inspected while the
+    /// node is running. Writes will fail.
+    pub(crate) fn open_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(QUORUM_STORE_DB_NAME);
+        let db = DB::open_cf_readonly(&Options::default(), path, QUORUM_STORE_DB_NAME, vec![
+            BATCH_CF_NAME,
+            BATCH_ID_CF_NAME,
+        ])?;
+        Ok(Self { db })
+    }
 }
 
 impl QuorumStoreStorage for QuorumStoreDB {
@@ -0,0 +1,180 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::{
+    batch_auto_tuner::{AdjustmentReason, BatchAutoTuner, BatchLimits},
+    batch_generator::BackPressure,
+};
+use aptos_config::config::{QuorumStoreAutoTuningConfig, QuorumStoreConfig};
+use std::time::{Duration, Instant};
+
+const NO_BACK_PRESSURE: BackPressure = BackPressure {
+    txn_count: false,
+    proof_count: false,
+};
+
+fn auto_tuning_config() -> QuorumStoreConfig {
+    QuorumStoreConfig {
+        sender_max_batch_txns: 200,
+        batch_generation_min_non_empty_interval_ms: 100,
+        sender_max_num_batches: 10,
+        auto_tuning: QuorumStoreAutoTuningConfig {
+            enabled: true,
+            target_proof_latency_ms: 1000,
+            pipeline_latency_limit_ms: 2000,
+            min_batch_txns: 50,
+            max_batch_txns: 250,
+            min_batch_interval_ms: 50,
+            max_batch_interval_ms: 250,
+            min_num_batches: 2,
+            max_num_batches: 20,
+            decrease_fraction: 0.5,
+            increase_fraction: 0.1,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn observe_proofs(tuner: &mut BatchAutoTuner, latency_ms: u64) {
+    for _ in 0..10 {
+        tuner.observe_proof_latency(Duration::from_millis(latency_ms));
+    }
+}
+
+#[test]
+fn test_auto_tuning_disabled() {
+    let config = QuorumStoreConfig {
+        auto_tuning: QuorumStoreAutoTuningConfig {
+            enabled: false,
+            ..auto_tuning_config().auto_tuning
+        },
+        sender_max_batch_txns: 1000,
+        ..auto_tuning_config()
+    };
+    let mut tuner = BatchAutoTuner::new(&config);
+    // the static limits, even out of the auto tuning bounds
+    assert_eq!(tuner.limits().max_batch_txns, 1000);
+
+    observe_proofs(&mut tuner, 5000);
+    let later = Instant::now() + Duration::from_secs(10);
+    assert_eq!(
+        tuner.maybe_adjust(later, NO_BACK_PRESSURE, Duration::ZERO),
+        None
+    );
+    assert_eq!(tuner.limits().max_batch_txns, 1000);
+}
+
+#[test]
+fn test_auto_tuning_initial_limits() {
+    let config = QuorumStoreConfig {
+        sender_max_batch_txns: 1000,
+        batch_generation_min_non_empty_interval_ms: 10,
+        ..auto_tuning_config()
+    };
+    let tuner = BatchAutoTuner::new(&config);
+    assert_eq!(*tuner.limits(), BatchLimits {
+        max_batch_txns: 250,
+        min_non_empty_interval_ms: 50,
+        max_num_batches: 10,
+    });
+}
+
+#[test]
+fn test_auto_tuning_adjustment_interval() {
+    let mut tuner = BatchAutoTuner::new(&auto_tuning_config());
+    observe_proofs(&mut tuner, 5000);
+    assert_eq!(
+        tuner.maybe_adjust(Instant::now(), NO_BACK_PRESSURE, Duration::ZERO),
+        None
+    );
+    let later = Instant::now() + Duration::from_secs(1);
+    assert_eq!(
+        tuner.maybe_adjust(later, NO_BACK_PRESSURE, Duration::ZERO),
+        Some(AdjustmentReason::ProofLatency)
+    );
+}
+
+#[test]
+fn test_auto_tuning_proof_latency() {
+    let mut tuner = BatchAutoTuner::new(&auto_tuning_config());
+
+    // slow proofs shrink the limits down to the bounds
+    observe_proofs(&mut tuner, 1500);
+    assert_eq!(
+        tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO),
+        Some(AdjustmentReason::ProofLatency)
+    );
+    assert_eq!(*tuner.limits(), BatchLimits {
+        max_batch_txns: 100,
+        min_non_empty_interval_ms: 200,
+        max_num_batches: 5,
+    });
+    for _ in 0..5 {
+        observe_proofs(&mut tuner, 1500);
+        tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO);
+    }
+    assert_eq!(*tuner.limits(), BatchLimits {
+        max_batch_txns: 50,
+        min_non_empty_interval_ms: 250,
+        max_num_batches: 2,
+    });
+    observe_proofs(&mut tuner, 1500);
+    assert_eq!(tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO), None);
+
+    // proofs forming in time grow them back, one step at a time
+    observe_proofs(&mut tuner, 500);
+    assert_eq!(
+        tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO),
+        Some(AdjustmentReason::Healthy)
+    );
+    assert_eq!(*tuner.limits(), BatchLimits {
+        max_batch_txns: 70,
+        min_non_empty_interval_ms: 230,
+        max_num_batches: 3,
+    });
+    for _ in 0..20 {
+        observe_proofs(&mut tuner, 500);
+        tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO);
+    }
+    assert_eq!(*tuner.limits(), BatchLimits {
+        max_batch_txns: 250,
+        min_non_empty_interval_ms: 50,
+        max_num_batches: 20,
+    });
+
+    // nothing observed, nothing to adjust
+    assert_eq!(tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO), None);
+}
+
+#[test]
+fn test_auto_tuning_signal_precedence() {
+    let mut tuner = BatchAutoTuner::new(&auto_tuning_config());
+
+    observe_proofs(&mut tuner, 500);
+    tuner.observe_proof_timeouts(1);
+    assert_eq!(
+        tuner.adjust(NO_BACK_PRESSURE, Duration::ZERO),
+        Some(AdjustmentReason::ProofTimeouts)
+    );
+
+    observe_proofs(&mut tuner, 500);
+    tuner.observe_proof_timeouts(1);
+    assert_eq!(
+        tuner.adjust(NO_BACK_PRESSURE, Duration::from_secs(3)),
+        Some(AdjustmentReason::PipelineLatency)
+    );
+
+    // the proof backlog shrinks the limits even without any proof formed
+    let mut tuner = BatchAutoTuner::new(&auto_tuning_config());
+    assert_eq!(
+        tuner.adjust(
+            BackPressure {
+                txn_count: false,
+                proof_count: true,
+            },
+            Duration::from_secs(3),
+        ),
+        Some(AdjustmentReason::ProofBacklog)
+    );
+}
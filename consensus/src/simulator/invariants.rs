@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus_types::{common::Round, pipelined_block::PipelinedBlock};
+use aptos_crypto::HashValue;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+/// What the invariant checks need to know about a committed block.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CommittedBlock {
+    pub epoch: u64,
+    pub round: Round,
+    pub id: HashValue,
+    pub parent_id: HashValue,
+}
+
+impl From<&PipelinedBlock> for CommittedBlock {
+    fn from(block: &PipelinedBlock) -> Self {
+        Self {
+            epoch: block.epoch(),
+            round: block.round(),
+            id: block.id(),
+            parent_id: block.parent_id(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "violation", rename_all = "snake_case")]
+pub enum Violation {
+    /// Two nodes committed different blocks at the same epoch and round.
+    ConflictingCommit {
+        node: usize,
+        block: CommittedBlock,
+        other_node: usize,
+        other_block: CommittedBlock,
+    },
+    /// `block` extends a committed block older than `skipped`, which is committed as well, so
+    /// the two are on different branches.
+    Fork {
+        node: usize,
+        block: CommittedBlock,
+        other_node: usize,
+        skipped: CommittedBlock,
+    },
+    /// A node committed a block that is not after its previous commit.
+    NonMonotonicCommit {
+        node: usize,
+        block: CommittedBlock,
+        previous: (u64, Round),
+    },
+    /// A node committed fewer blocks than expected in the liveness window.
+    NoProgress {
+        node: usize,
+        after_ms: u64,
+        commits: usize,
+        expected: usize,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::ConflictingCommit {
+                node,
+                block,
+                other_node,
+                other_block,
+            } => write!(
+                f,
+                "Node {} committed {} at epoch {} round {}, node {} committed {}.",
+                node, block.id, block.epoch, block.round, other_node, other_block.id
+            ),
+            Self::Fork {
+                node,
+                block,
+                other_node,
+                skipped,
+            } => write!(
+                f,
+                "Node {} committed {} (epoch {} round {}) which does not extend {} (round {}) \
+                 committed by node {}.",
+                node, block.id, block.epoch, block.round, skipped.id, skipped.round, other_node
+            ),
+            Self::NonMonotonicCommit {
+                node,
+                block,
+                previous,
+            } => write!(
+                f,
+                "Node {} committed epoch {} round {} after epoch {} round {}.",
+                node, block.epoch, block.round, previous.0, previous.1
+            ),
+            Self::NoProgress {
+                node,
+                after_ms,
+                commits,
+                expected,
+            } => write!(
+                f,
+                "Node {} committed {} blocks after {}ms, expected at least {}.",
+                node, commits, after_ms, expected
+            ),
+        }
+    }
+}
+
+/// Checks that all nodes commit a single chain, as far as the commits observed allow to tell.
+///
+/// Within an epoch every committed block extends the previous committed one, so a block whose
+/// parent is committed at round `p` while another block is committed at a round in `(p, r)` is
+/// a fork. Parents nobody was seen committing (e.g. because all nodes state synced past them)
+/// can't be checked.
+#[derive(Default)]
+pub struct SafetyChecker {
+    /// The committed blocks and the first node seen committing them.
+    committed: BTreeMap<(u64, Round), (CommittedBlock, usize)>,
+    keys_by_id: HashMap<HashValue, (u64, Round)>,
+    last_committed: HashMap<usize, (u64, Round)>,
+}
+
+impl SafetyChecker {
+    pub fn record(&mut self, node: usize, block: &CommittedBlock) -> Vec<Violation> {
+        let mut violations = vec![];
+        let key = (block.epoch, block.round);
+
+        if let Some(previous) = self.last_committed.insert(node, key) {
+            if previous >= key {
+                violations.push(Violation::NonMonotonicCommit {
+                    node,
+                    block: block.clone(),
+                    previous,
+                });
+            }
+        }
+
+        if let Some((other_block, other_node)) = self.committed.get(&key) {
+            if other_block.id != block.id {
+                violations.push(Violation::ConflictingCommit {
+                    node,
+                    block: block.clone(),
+                    other_node: *other_node,
+                    other_block: other_block.clone(),
+                });
+            }
+            return violations;
+        }
+
+        // Does the new block skip a committed one?
+        if let Some(parent_key) = self
+            .keys_by_id
+            .get(&block.parent_id)
+            .filter(|parent_key| parent_key.0 == key.0)
+        {
+            if let Some((skipped, other_node)) = self
+                .committed
+                .range((parent_key.0, parent_key.1 + 1)..key)
+                .map(|(_, entry)| entry)
+                .next()
+            {
+                violations.push(Violation::Fork {
+                    node,
+                    block: block.clone(),
+                    other_node: *other_node,
+                    skipped: skipped.clone(),
+                });
+            }
+        }
+        // Does a later committed block skip the new one?
+        for (later, later_node) in self
+            .committed
+            .range((key.0, key.1 + 1)..(key.0 + 1, 0))
+            .map(|(_, entry)| entry)
+        {
+            if let Some(parent_key) = self.keys_by_id.get(&later.parent_id) {
+                if parent_key.0 == key.0 && *parent_key < key {
+                    violations.push(Violation::Fork {
+                        node: *later_node,
+                        block: later.clone(),
+                        other_node: node,
+                        skipped: block.clone(),
+                    });
+                }
+            }
+        }
+
+        self.keys_by_id.insert(block.id, key);
+        self.committed.insert(key, (block.clone(), node));
+        violations
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A deterministic multi-validator simulator.
+//!
+//! Validators (and their twins) run `RoundManager` or the DAG on a simulated clock, connected
+//! through the `NetworkPlayground`. Every message they send goes through `SimNetwork`, which
+//! decides from the scenario's seed whether and when it is delivered, and applies the scripted
+//! partitions, link rules and crashes. A `Scenario` is described in YAML (see `scenarios/`); a
+//! run produces a `Trace` that can be saved, replayed, and is checked for safety and liveness.
+
+mod invariants;
+mod network;
+mod node;
+mod runner;
+mod scenario;
+mod simulator_test;
+mod trace;
+
+pub use invariants::{CommittedBlock, SafetyChecker, Violation};
+pub use runner::{SimulationReport, Simulator};
+pub use scenario::{Action, Scenario};
+pub use trace::{Trace, TraceEvent};
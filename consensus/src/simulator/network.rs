@@ -0,0 +1,278 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_interface::ConsensusMsg,
+    network_tests::{NetworkPlayground, TwinId},
+    simulator::{
+        scenario::{Action, LinkConfig, LinkRule},
+        trace::{DropReason, Outcome, RecordedDecisions, Trace, TraceEvent},
+    },
+};
+use aptos_crypto::HashValue;
+use aptos_network::{
+    peer_manager::{PeerManagerNotification, PeerManagerRequest},
+    protocols::{
+        direct_send::Message,
+        network::SerializedRequest,
+        rpc::{InboundRpcRequest, OutboundRpcRequest},
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Duration,
+};
+
+enum Request {
+    DirectSend(Message),
+    Rpc(OutboundRpcRequest),
+}
+
+impl Request {
+    fn data(&self) -> &[u8] {
+        match self {
+            Request::DirectSend(msg) => msg.data(),
+            Request::Rpc(req) => req.data(),
+        }
+    }
+
+    /// The name of the consensus message, for traces and link rules.
+    fn kind(&self) -> String {
+        let msg = match self {
+            Request::DirectSend(msg) => msg.to_message::<ConsensusMsg>(),
+            Request::Rpc(req) => req.to_message::<ConsensusMsg>(),
+        };
+        msg.map_or_else(|_| "Unknown".to_string(), |msg| msg.name().to_string())
+    }
+}
+
+struct Envelope {
+    from: TwinId,
+    to: TwinId,
+    kind: String,
+    digest: HashValue,
+    request: Request,
+}
+
+/// Decides the fate of every message sent between simulated nodes and delivers them at their
+/// virtual delivery time.
+///
+/// Outbound messages are collected in batches while the nodes run, and each batch is sorted
+/// before any random decision is taken, so that the decisions only depend on the seed and on
+/// what was sent, not on the order the nodes' tasks happened to run in.
+pub struct SimNetwork {
+    rng: StdRng,
+    default_link: LinkConfig,
+    /// Group of each node in the current partition, if any.
+    partition: Option<HashMap<usize, usize>>,
+    rules: Vec<LinkRule>,
+    crashed: HashSet<usize>,
+    /// Messages by delivery time and send order.
+    in_flight: BTreeMap<(Duration, u64), Envelope>,
+    next_seq: u64,
+    replay: Option<RecordedDecisions>,
+}
+
+impl SimNetwork {
+    pub fn new(seed: u64, default_link: LinkConfig, replay: Option<RecordedDecisions>) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            default_link,
+            partition: None,
+            rules: vec![],
+            crashed: HashSet::new(),
+            in_flight: BTreeMap::new(),
+            next_seq: 0,
+            replay,
+        }
+    }
+
+    /// Applies the actions that change the network. Others are ignored.
+    pub fn apply(&mut self, action: &Action) {
+        match action {
+            Action::Partition { groups } => {
+                self.partition = Some(
+                    groups
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(group, nodes)| nodes.iter().map(move |node| (*node, group)))
+                        .collect(),
+                );
+            },
+            Action::Heal => self.partition = None,
+            Action::Link(rule) => self.rules.push(rule.clone()),
+            Action::ClearLinks => self.rules.clear(),
+            Action::Crash { nodes } => self.crashed.extend(nodes),
+            Action::EpochChange { .. } => {},
+        }
+    }
+
+    /// Drops the messages in flight, e.g. because the nodes they were sent to are gone.
+    pub fn clear_in_flight(&mut self) {
+        self.in_flight.clear();
+    }
+
+    fn is_partitioned(&self, from: usize, to: usize) -> bool {
+        self.partition.as_ref().map_or(false, |partition| {
+            partition.get(&from).unwrap_or(&usize::MAX) != partition.get(&to).unwrap_or(&usize::MAX)
+        })
+    }
+
+    fn link(&self, from: usize, to: usize, kind: &str) -> &LinkConfig {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(from, to, kind))
+            .map_or(&self.default_link, |rule| &rule.link)
+    }
+
+    fn decide(&mut self, envelope: &Envelope, now: Duration, trace: &mut Trace) -> Outcome {
+        let (from, to) = (envelope.from.id, envelope.to.id);
+        let recorded = self
+            .replay
+            .as_mut()
+            .map(|replay| replay.take(from, to, envelope.digest));
+
+        if self.crashed.contains(&to) {
+            return Outcome::Drop {
+                reason: DropReason::Crashed,
+            };
+        }
+        if self.is_partitioned(from, to) {
+            return Outcome::Drop {
+                reason: DropReason::Partitioned,
+            };
+        }
+        match recorded {
+            Some(Some(outcome)) => return outcome,
+            Some(None) => trace.push(TraceEvent::Divergence {
+                time_ms: now.as_millis() as u64,
+                from,
+                to,
+                kind: envelope.kind.clone(),
+                digest: envelope.digest,
+            }),
+            None => {},
+        }
+
+        let link = self.link(from, to, &envelope.kind).clone();
+        if link.drop_rate > 0.0 && self.rng.gen_bool(link.drop_rate) {
+            Outcome::Drop {
+                reason: DropReason::Lost,
+            }
+        } else {
+            Outcome::Deliver {
+                delay_ms: self.rng.gen_range(link.delay_ms.0, link.delay_ms.1 + 1),
+            }
+        }
+    }
+
+    /// Takes a batch of outbound messages sent at `now` and schedules or drops them.
+    pub fn send(
+        &mut self,
+        now: Duration,
+        playground: &NetworkPlayground,
+        outbound: Vec<(TwinId, PeerManagerRequest)>,
+        trace: &mut Trace,
+    ) {
+        let mut envelopes = vec![];
+        for (from, net_req) in outbound {
+            let (dst, request) = match net_req {
+                PeerManagerRequest::SendDirectSend(dst, msg) => (dst, Request::DirectSend(msg)),
+                PeerManagerRequest::SendRpc(dst, req) => (dst, Request::Rpc(req)),
+            };
+            let digest = HashValue::sha3_256_of(request.data());
+            let kind = request.kind();
+            let mut dst_twin_ids = playground.get_twin_ids(dst);
+            dst_twin_ids.sort_by_key(|twin_id| twin_id.id);
+            match request {
+                // Every twin receives its copy of a direct-send message.
+                Request::DirectSend(msg) => {
+                    for to in dst_twin_ids {
+                        envelopes.push(Envelope {
+                            from,
+                            to,
+                            kind: kind.clone(),
+                            digest,
+                            request: Request::DirectSend(msg.clone()),
+                        });
+                    }
+                },
+                // An rpc has a single response channel, it goes to the first twin that can be
+                // reached.
+                Request::Rpc(req) => {
+                    let to = dst_twin_ids
+                        .iter()
+                        .find(|to| {
+                            !self.crashed.contains(&to.id) && !self.is_partitioned(from.id, to.id)
+                        })
+                        .unwrap_or(&dst_twin_ids[0]);
+                    envelopes.push(Envelope {
+                        from,
+                        to: *to,
+                        kind,
+                        digest,
+                        request: Request::Rpc(req),
+                    });
+                },
+            }
+        }
+        envelopes.sort_by(|a, b| {
+            (a.from.id, a.to.id, &a.kind, a.digest).cmp(&(b.from.id, b.to.id, &b.kind, b.digest))
+        });
+
+        for envelope in envelopes {
+            let outcome = self.decide(&envelope, now, trace);
+            trace.push(TraceEvent::Message {
+                time_ms: now.as_millis() as u64,
+                from: envelope.from.id,
+                to: envelope.to.id,
+                kind: envelope.kind.clone(),
+                digest: envelope.digest,
+                outcome,
+            });
+            // Dropping an rpc request drops its response channel, which fails the rpc.
+            if let Outcome::Deliver { delay_ms } = outcome {
+                let deliver_at = now + Duration::from_millis(delay_ms);
+                self.in_flight.insert((deliver_at, self.next_seq), envelope);
+                self.next_seq += 1;
+            }
+        }
+    }
+
+    pub fn next_delivery_time(&self) -> Option<Duration> {
+        self.in_flight
+            .keys()
+            .next()
+            .map(|(deliver_at, _)| *deliver_at)
+    }
+
+    /// Delivers the messages due by `now` to the nodes that are still running.
+    pub fn deliver_due(&mut self, now: Duration, playground: &NetworkPlayground) {
+        while let Some(entry) = self.in_flight.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let envelope = entry.remove();
+            if self.crashed.contains(&envelope.to.id) {
+                continue;
+            }
+            let (protocol_id, msg_notif) = match envelope.request {
+                Request::DirectSend(msg) => (
+                    msg.protocol_id,
+                    PeerManagerNotification::RecvMessage(envelope.from.author, msg),
+                ),
+                Request::Rpc(req) => (
+                    req.protocol_id,
+                    PeerManagerNotification::RecvRpc(envelope.from.author, InboundRpcRequest {
+                        protocol_id: req.protocol_id,
+                        data: req.data,
+                        res_tx: req.res_tx,
+                    }),
+                ),
+            };
+            playground.push_notification(envelope.from, envelope.to, protocol_id, msg_notif);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters,
+    dag::{bootstrap_dag_for_test, MockDagStorage},
+    epoch_manager::EpochManager,
+    network::{IncomingDAGRequest, NetworkSender, NetworkTask, RpcResponder},
+    network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
+    network_tests::{NetworkPlayground, TwinId},
+    payload_manager::PayloadManager,
+    pipeline::{buffer_manager::OrderedBlocks, execution_client::DummyExecutionClient},
+    quorum_store::quorum_store_db::MockQuorumStoreDB,
+    rand::rand_gen::storage::in_memory::InMemRandDb,
+    simulator::invariants::CommittedBlock,
+    test_utils::{mock_execution_client::MockExecutionClient, MockPayloadManager, MockStorage},
+    util::{mock_time_service::SimulatedTimeService, time_service::TimeService as _},
+};
+use aptos_bounded_executor::BoundedExecutor;
+use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
+use aptos_config::{config::NodeConfig, network_id::NetworkId};
+use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_mempool::mocks::MockSharedMempool;
+use aptos_network::{
+    application::interface::{NetworkClient, NetworkServiceEvents},
+    peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::network::{self, Event, NetworkEvents, NewNetworkEvents, NewNetworkSender},
+};
+use aptos_time_service::{MockTimeService, TimeService, TimeServiceTrait};
+use aptos_types::{
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{
+        DagConsensusConfigV1, InMemoryOnChainConfig, OnChainConfig, OnChainConfigPayload,
+        OnChainConsensusConfig, ValidatorSet,
+    },
+    transaction::SignedTransaction,
+    validator_signer::ValidatorSigner,
+};
+use aptos_validator_transaction_pool::VTxnPoolState;
+use futures::{channel::mpsc, stream::select, StreamExt};
+use maplit::hashmap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::runtime::{Builder, Runtime};
+
+/// Large enough that the inbound queues never drop messages when the simulator delivers a burst
+/// of them at the same virtual time.
+const CHANNEL_SIZE: usize = 1_024;
+
+/// The virtual clock shared by all nodes of a simulation. Consensus timers run on the
+/// `SimulatedTimeService`, the DAG and the other components using `aptos_time_service` run on
+/// the `MockTimeService`; both are moved forward together by the simulator.
+#[derive(Clone)]
+pub struct SimClock {
+    pub consensus: Arc<SimulatedTimeService>,
+    pub aptos: MockTimeService,
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        Self {
+            consensus: Arc::new(SimulatedTimeService::new()),
+            aptos: MockTimeService::new(),
+        }
+    }
+
+    pub fn now(&self) -> Duration {
+        self.consensus.get_current_timestamp()
+    }
+
+    /// The earliest consensus timer that is still pending.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.consensus.next_deadline()
+    }
+
+    /// Moves both clocks to `now` and fires the timers that are due.
+    pub fn advance_to(&self, now: Duration) {
+        let aptos_now = self.aptos.now_unix_time();
+        if now > aptos_now {
+            self.aptos.advance(now - aptos_now);
+        }
+        self.consensus.advance_to(now);
+    }
+}
+
+/// A batch of blocks committed (or ordered, for the DAG) by a node, with its proof.
+pub struct NodeCommit {
+    pub blocks: Vec<CommittedBlock>,
+    pub ledger_info: LedgerInfoWithSignatures,
+}
+
+/// The parked workers of a node's runtime, and how many times one woke up.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+struct WorkerActivity {
+    parked: usize,
+    wake_ups: u64,
+}
+
+/// Whether all the tasks of the nodes are waiting, for a message or a timer.
+///
+/// A node's runtime is idle when all its workers are parked, nothing is queued and no blocking
+/// task runs. Nodes wake each other up directly (e.g. with rpc responses), so all of them are
+/// checked between two snapshots of their workers' activity, and any wake up in between means
+/// they are not idle. Timers on the real clock are not waited for, the nodes must only sleep on
+/// the `SimClock`.
+pub fn all_idle<'a>(nodes: impl Iterator<Item = &'a SimNode> + Clone) -> bool {
+    let activity: Vec<_> = nodes.clone().map(|node| *node.activity.lock()).collect();
+    nodes.clone().zip(&activity).all(|(node, activity)| {
+        let Some(runtime) = &node.runtime else {
+            return true;
+        };
+        let metrics = runtime.metrics();
+        let num_workers = metrics.num_workers();
+        activity.parked == num_workers
+            && metrics.injection_queue_depth() == 0
+            && (0..num_workers).all(|worker| metrics.worker_local_queue_depth(worker) == 0)
+            && metrics.blocking_queue_depth() == 0
+            // The workers run on blocking threads too.
+            && metrics.num_blocking_threads() <= metrics.num_idle_blocking_threads() + num_workers
+    }) && nodes
+        .map(|node| *node.activity.lock())
+        .eq(activity.into_iter())
+}
+
+/// A simulated validator node. Dropping it shuts the node down, which is how crashes are
+/// simulated.
+pub struct SimNode {
+    pub twin_id: TwinId,
+    commits: mpsc::UnboundedReceiver<NodeCommit>,
+    runtime: Option<Runtime>,
+    activity: Arc<Mutex<WorkerActivity>>,
+    _shared_mempool: Option<MockSharedMempool>,
+    _state_sync: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
+}
+
+struct NodeNetwork {
+    client: ConsensusNetworkClient<NetworkClient<ConsensusMsg>>,
+    events: NetworkEvents<ConsensusMsg>,
+}
+
+fn register_node(playground: &mut NetworkPlayground, twin_id: TwinId) -> NodeNetwork {
+    let (network_reqs_tx, network_reqs_rx) =
+        aptos_channel::new(QueueStyle::FIFO, CHANNEL_SIZE, None);
+    let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+    let (consensus_tx, consensus_rx) = aptos_channel::new(QueueStyle::FIFO, CHANNEL_SIZE, None);
+    let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = aptos_channels::new_test(8);
+    let (_, conn_notifs_channel) = conn_notifs_channel::new();
+    let network_sender = network::NetworkSender::new(
+        PeerManagerRequestSender::new(network_reqs_tx),
+        ConnectionRequestSender::new(connection_reqs_tx),
+    );
+    let network_client = NetworkClient::new(
+        DIRECT_SEND.into(),
+        RPC.into(),
+        hashmap! {NetworkId::Validator => network_sender},
+        playground.peer_protocols(),
+    );
+    playground.add_node(twin_id, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
+
+    NodeNetwork {
+        client: ConsensusNetworkClient::new(network_client),
+        events: NetworkEvents::new(consensus_rx, conn_notifs_channel, None),
+    }
+}
+
+/// Like `aptos_runtimes::spawn_named_runtime`, but keeping track of the parked workers.
+fn spawn_runtime(twin_id: TwinId) -> (Runtime, Arc<Mutex<WorkerActivity>>) {
+    let activity = Arc::new(Mutex::new(WorkerActivity::default()));
+    let on_park = Arc::clone(&activity);
+    let on_unpark = Arc::clone(&activity);
+    let runtime = Builder::new_multi_thread()
+        .thread_name(format!("sim-{}", twin_id.id))
+        .disable_lifo_slot()
+        .on_thread_park(move || on_park.lock().parked += 1)
+        .on_thread_unpark(move || {
+            let mut activity = on_unpark.lock();
+            activity.parked -= 1;
+            activity.wake_ups += 1;
+        })
+        .enable_all()
+        .build()
+        .expect("Failed to spawn the node runtime.");
+    (runtime, activity)
+}
+
+impl SimNode {
+    /// Starts a node running `RoundManager` through the `EpochManager`, as in the twins tests,
+    /// but on the simulated clock and in the given epoch.
+    pub fn start_round_manager(
+        playground: &mut NetworkPlayground,
+        twin_id: TwinId,
+        config: NodeConfig,
+        consensus_config: OnChainConsensusConfig,
+        storage: Arc<MockStorage>,
+        epoch: u64,
+        clock: &SimClock,
+    ) -> Self {
+        let (runtime, activity) = spawn_runtime(twin_id);
+        let _entered_runtime = runtime.enter();
+
+        let network = register_node(playground, twin_id);
+        let network_service_events =
+            NetworkServiceEvents::new(hashmap! {NetworkId::Validator => network.events});
+
+        let (state_sync_client, state_sync) = mpsc::unbounded();
+        let (ordered_blocks_tx, mut ordered_blocks_events) = mpsc::unbounded::<OrderedBlocks>();
+        let shared_mempool = MockSharedMempool::new();
+        let (quorum_store_to_mempool_sender, _) = mpsc::channel(1_024);
+        let execution_client = Arc::new(MockExecutionClient::new(
+            state_sync_client,
+            ordered_blocks_tx,
+            Arc::clone(&storage),
+        ));
+
+        let (reconfig_sender, reconfig_events) = aptos_channel::new(QueueStyle::LIFO, 1, None);
+        let reconfig_listener = ReconfigNotificationListener {
+            notification_receiver: reconfig_events,
+        };
+        let mut configs = HashMap::new();
+        configs.insert(
+            ValidatorSet::CONFIG_ID,
+            bcs::to_bytes(storage.get_validator_set()).unwrap(),
+        );
+        configs.insert(
+            OnChainConsensusConfig::CONFIG_ID,
+            // Requires double serialization, check deserialize_into_config for more details
+            bcs::to_bytes(&bcs::to_bytes(&consensus_config).unwrap()).unwrap(),
+        );
+        let payload = OnChainConfigPayload::new(epoch, InMemoryOnChainConfig::new(configs));
+        reconfig_sender
+            .push((), ReconfigNotification {
+                version: storage.get_ledger_info().version(),
+                on_chain_configs: payload,
+            })
+            .unwrap();
+
+        let (timeout_sender, timeout_receiver) =
+            aptos_channels::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
+        let (self_sender, self_receiver) =
+            aptos_channels::new_unbounded(&counters::PENDING_SELF_MESSAGES);
+        let bounded_executor = BoundedExecutor::new(2, runtime.handle().clone());
+
+        let epoch_mgr = EpochManager::new(
+            &config,
+            clock.consensus.clone(),
+            self_sender,
+            network.client,
+            timeout_sender,
+            quorum_store_to_mempool_sender,
+            execution_client.clone(),
+            storage,
+            Arc::new(MockQuorumStoreDB::new()),
+            reconfig_listener,
+            bounded_executor,
+            TimeService::from_mock(clock.aptos.clone()),
+            VTxnPoolState::default(),
+            Arc::new(InMemRandDb::new()),
+        );
+        let (network_task, network_receiver) =
+            NetworkTask::new(network_service_events, self_receiver);
+        runtime.spawn(network_task.start());
+        runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
+
+        let (commit_tx, commits) = mpsc::unbounded();
+        runtime.spawn(async move {
+            while let Some(ordered_blocks) = ordered_blocks_events.next().await {
+                let blocks = ordered_blocks
+                    .ordered_blocks
+                    .iter()
+                    .map(CommittedBlock::from)
+                    .collect();
+                let ledger_info = ordered_blocks.ordered_proof.clone();
+                execution_client
+                    .commit_to_storage(ordered_blocks)
+                    .await
+                    .unwrap();
+                if commit_tx
+                    .unbounded_send(NodeCommit {
+                        blocks,
+                        ledger_info,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            twin_id,
+            commits,
+            runtime: Some(runtime),
+            activity,
+            _shared_mempool: Some(shared_mempool),
+            _state_sync: state_sync,
+        }
+    }
+
+    /// Starts a node running the DAG protocol, rooted at `root`, as in the DAG integration tests
+    /// but on the simulated clock.
+    pub fn start_dag(
+        playground: &mut NetworkPlayground,
+        twin_id: TwinId,
+        signer: ValidatorSigner,
+        epoch_state: Arc<EpochState>,
+        root: LedgerInfoWithSignatures,
+        onchain_config: DagConsensusConfigV1,
+        clock: &SimClock,
+    ) -> Self {
+        let (runtime, activity) = spawn_runtime(twin_id);
+        let _entered_runtime = runtime.enter();
+
+        let network = register_node(playground, twin_id);
+        let (self_sender, self_receiver) = aptos_channels::new_unbounded_test();
+        let network_sender = Arc::new(NetworkSender::new(
+            twin_id.author,
+            network.client,
+            self_sender,
+            epoch_state.verifier.clone(),
+        ));
+        let mut network_events = select(network.events, self_receiver);
+
+        let (_, _state_sync) = mpsc::unbounded();
+        let (_dh_handle, _df_handle, dag_rpc_tx, mut ordered_nodes_rx) = bootstrap_dag_for_test(
+            twin_id.author,
+            signer,
+            epoch_state.clone(),
+            Arc::new(MockDagStorage::new_with_ledger_info(root, epoch_state)),
+            network_sender.clone(),
+            network_sender.clone(),
+            network_sender,
+            TimeService::from_mock(clock.aptos.clone()),
+            Arc::new(PayloadManager::DirectMempool),
+            Arc::new(MockPayloadManager::new(None)),
+            Arc::new(DummyExecutionClient),
+            onchain_config,
+        );
+
+        runtime.spawn(async move {
+            while let Some(event) = network_events.next().await {
+                match event {
+                    Event::RpcRequest(
+                        sender,
+                        ConsensusMsg::DAGMessage(msg),
+                        protocol,
+                        response_sender,
+                    ) => {
+                        let _ = dag_rpc_tx.push(sender, IncomingDAGRequest {
+                            req: msg,
+                            sender,
+                            responder: RpcResponder {
+                                protocol,
+                                response_sender,
+                            },
+                        });
+                    },
+                    event => warn!("[simulator] Unexpected DAG network event: {:?}", event),
+                }
+            }
+        });
+
+        let (commit_tx, commits) = mpsc::unbounded();
+        runtime.spawn(async move {
+            while let Some(ordered) = ordered_nodes_rx.next().await {
+                let OrderedBlocks {
+                    ordered_blocks,
+                    ordered_proof,
+                    callback,
+                } = ordered;
+                let blocks = ordered_blocks.iter().map(CommittedBlock::from).collect();
+                let ordered_blocks: Vec<_> = ordered_blocks.into_iter().map(Arc::new).collect();
+                callback(&ordered_blocks, ordered_proof.clone());
+                if commit_tx
+                    .unbounded_send(NodeCommit {
+                        blocks,
+                        ledger_info: ordered_proof,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            twin_id,
+            commits,
+            runtime: Some(runtime),
+            activity,
+            _shared_mempool: None,
+            _state_sync,
+        }
+    }
+
+    /// Returns the next commit of the node, if one is ready.
+    pub fn try_next_commit(&mut self) -> Option<NodeCommit> {
+        self.commits.try_next().ok().flatten()
+    }
+}
+
+impl Drop for SimNode {
+    fn drop(&mut self) {
+        // The simulator drops nodes from within its own runtime, where blocking on the shutdown
+        // is not allowed.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
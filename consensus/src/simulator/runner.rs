@@ -0,0 +1,517 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::{NetworkPlayground, TwinId},
+    simulator::{
+        invariants::{SafetyChecker, Violation},
+        network::SimNetwork,
+        node::{self, SimClock, SimNode},
+        scenario::{Action, Protocol, Scenario, ScheduledAction},
+        trace::{Trace, TraceEvent},
+    },
+    test_utils::{MockSharedStorage, MockStorage},
+};
+use anyhow::{bail, Result};
+use aptos_config::{
+    config::{NodeConfig, WaypointConfig},
+    generator::{self, ValidatorSwarm},
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_consensus_types::common::Author;
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use aptos_network::{
+    protocols::wire::handshake::v1::ProtocolIdSet, transport::ConnectionMetadata, ProtocolId,
+};
+use aptos_types::{
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{generate_ledger_info_with_sig, LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::{ConsensusConfigV1, OnChainConsensusConfig, ValidatorSet},
+    validator_info::ValidatorInfo,
+    validator_signer::ValidatorSigner,
+    waypoint::Waypoint,
+};
+use std::{iter::FromIterator, sync::Arc, time::Duration};
+use tokio::runtime::{Builder, Handle};
+
+/// How often the nodes are checked while they are busy. It only affects how fast a simulation
+/// runs, not its outcome.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Runs a `Scenario` and checks its invariants.
+///
+/// Virtual time only moves when the nodes are idle, i.e. when all their tasks wait for a message
+/// or a timer and everything they sent was collected. It then jumps to the next message
+/// delivery, timer or scripted action, so which batch a message is sent in doesn't depend on
+/// how fast the host runs the nodes. All the network's random decisions derive from the
+/// scenario's seed, and the resulting trace can be replayed: the replayed run takes its network
+/// decisions from the trace and records a divergence for every message the trace has no
+/// decision for.
+pub struct Simulator {
+    scenario: Scenario,
+    replay: Option<Trace>,
+}
+
+pub struct SimulationReport {
+    pub trace: Trace,
+    pub violations: Vec<Violation>,
+}
+
+impl SimulationReport {
+    pub fn check(&self) -> Result<()> {
+        if !self.violations.is_empty() {
+            bail!(
+                "Invariants violated:\n{}",
+                self.violations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    pub fn num_divergences(&self) -> usize {
+        self.trace
+            .events
+            .iter()
+            .filter(|event| matches!(event, TraceEvent::Divergence { .. }))
+            .count()
+    }
+}
+
+impl Simulator {
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            replay: None,
+        }
+    }
+
+    /// Takes the network decisions from `trace` instead of the seed.
+    pub fn replaying(mut self, trace: Trace) -> Self {
+        self.replay = Some(trace);
+        self
+    }
+
+    pub fn run(self) -> SimulationReport {
+        // The simulator's own tasks, which forward the nodes' messages, only run when it waits,
+        // so that it can tell when they are done.
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let mut run = Run::new(self.scenario, self.replay, runtime.handle().clone());
+        runtime.block_on(run.run());
+        SimulationReport {
+            trace: run.trace,
+            violations: run.violations,
+        }
+    }
+}
+
+fn author_from_config(config: &NodeConfig) -> Author {
+    config.validator_network.as_ref().unwrap().peer_id()
+}
+
+struct Run {
+    scenario: Scenario,
+    handle: Handle,
+    /// Validator configs, sorted by author.
+    configs: Vec<NodeConfig>,
+    signers: Vec<ValidatorSigner>,
+    validator_set: ValidatorSet,
+    clock: SimClock,
+    playground: NetworkPlayground,
+    network: SimNetwork,
+    /// Indexed by node, `None` once crashed.
+    nodes: Vec<Option<SimNode>>,
+    crashed: Vec<bool>,
+    epoch: u64,
+    protocol: Protocol,
+    /// The epoch ending ledger info the current epoch started from.
+    root: LedgerInfo,
+    highest_commit: Option<LedgerInfoWithSignatures>,
+    safety: SafetyChecker,
+    /// Virtual times of the blocks committed by each node.
+    commit_times: Vec<Vec<Duration>>,
+    trace: Trace,
+    violations: Vec<Violation>,
+}
+
+impl Run {
+    fn new(scenario: Scenario, replay: Option<Trace>, handle: Handle) -> Self {
+        let ValidatorSwarm { nodes: mut configs } =
+            generator::validator_swarm_for_testing(scenario.validators);
+        configs.sort_by_key(author_from_config);
+        let signers: Vec<_> = configs
+            .iter()
+            .map(|config| {
+                let test_config = config.consensus.safety_rules.test.as_ref().unwrap();
+                ValidatorSigner::new(
+                    test_config.author,
+                    test_config.consensus_key.as_ref().unwrap().private_key(),
+                )
+            })
+            .collect();
+        let validator_set = ValidatorSet::new(
+            signers
+                .iter()
+                .enumerate()
+                .map(|(index, signer)| {
+                    ValidatorInfo::new_with_test_network_keys(
+                        signer.author(),
+                        signer.public_key(),
+                        1,
+                        index as u64,
+                    )
+                })
+                .collect(),
+        );
+        let network = SimNetwork::new(
+            scenario.seed,
+            scenario.network.clone(),
+            replay.as_ref().map(Trace::decisions),
+        );
+        let num_nodes = scenario.num_nodes();
+
+        Self {
+            protocol: scenario.protocol,
+            playground: NetworkPlayground::new(handle.clone()),
+            handle,
+            configs,
+            signers,
+            root: LedgerInfo::mock_genesis(Some(validator_set.clone())),
+            validator_set,
+            clock: SimClock::new(),
+            network,
+            nodes: (0..num_nodes).map(|_| None).collect(),
+            crashed: vec![false; num_nodes],
+            epoch: 1,
+            highest_commit: None,
+            safety: SafetyChecker::default(),
+            commit_times: vec![vec![]; num_nodes],
+            trace: Trace::default(),
+            violations: vec![],
+            scenario,
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.clock.now().as_millis() as u64
+    }
+
+    async fn run(&mut self) {
+        self.trace.push(TraceEvent::Start {
+            scenario: self.scenario.name.clone(),
+            seed: self.scenario.seed,
+        });
+        self.start_nodes();
+
+        let actions = self.scenario.sorted_actions();
+        let mut next_action = 0;
+        let end = Duration::from_millis(self.scenario.duration_ms);
+        loop {
+            self.settle().await;
+            let now = self.clock.now();
+            while let Some(scheduled) = actions.get(next_action) {
+                if Duration::from_millis(scheduled.at_ms) > now {
+                    break;
+                }
+                self.apply(scheduled);
+                next_action += 1;
+            }
+            if now >= end {
+                break;
+            }
+
+            let next = [
+                self.network.next_delivery_time(),
+                self.clock.next_deadline(),
+                actions
+                    .get(next_action)
+                    .map(|scheduled| Duration::from_millis(scheduled.at_ms)),
+            ]
+            .into_iter()
+            .flatten()
+            .fold(
+                now + Duration::from_millis(self.scenario.tick_ms),
+                Duration::min,
+            )
+            .min(end);
+            self.clock.advance_to(next);
+            self.network.deliver_due(self.clock.now(), &self.playground);
+        }
+        self.check_liveness();
+        info!(
+            "[simulator] Scenario {} finished at {}ms with {} violations.",
+            self.scenario.name,
+            self.now_ms(),
+            self.violations.len()
+        );
+    }
+
+    /// Waits until the nodes are idle, then hands what they sent to the network.
+    async fn settle(&mut self) {
+        let mut outbound = vec![];
+        loop {
+            // Lets the playground forward what the nodes sent.
+            tokio::task::yield_now().await;
+            // Checked before collecting, so that whatever was sent before the nodes went idle is
+            // collected and only an empty collection ends the wait.
+            let idle = self.is_idle();
+            let mut progress = self.collect_commits();
+            while let Some(msg) = self.playground.try_next_outbound() {
+                outbound.push(msg);
+                progress = true;
+            }
+            if idle && !progress {
+                break;
+            }
+            if !idle {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+        self.network.send(
+            self.clock.now(),
+            &self.playground,
+            outbound,
+            &mut self.trace,
+        );
+    }
+
+    /// Whether the nodes are idle and none of the simulator's tasks is ready to run.
+    fn is_idle(&self) -> bool {
+        let metrics = Handle::current().metrics();
+        node::all_idle(self.nodes.iter().flatten())
+            && metrics.injection_queue_depth() == 0
+            && metrics.worker_local_queue_depth(0) == 0
+    }
+
+    fn collect_commits(&mut self) -> bool {
+        let now = self.clock.now();
+        let mut progress = false;
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            let Some(node) = node else {
+                continue;
+            };
+            while let Some(commit) = node.try_next_commit() {
+                progress = true;
+                for block in commit.blocks {
+                    for violation in self.safety.record(index, &block) {
+                        error!("[simulator] {}", violation);
+                        self.trace.push(TraceEvent::Violation {
+                            time_ms: now.as_millis() as u64,
+                            violation: violation.clone(),
+                        });
+                        self.violations.push(violation);
+                    }
+                    self.trace.push(TraceEvent::Commit {
+                        time_ms: now.as_millis() as u64,
+                        node: index,
+                        block,
+                    });
+                    self.commit_times[index].push(now);
+                }
+                let commit_info = commit.ledger_info.ledger_info().commit_info();
+                if self.highest_commit.as_ref().map_or(true, |highest| {
+                    let highest = highest.ledger_info().commit_info();
+                    (highest.epoch(), highest.round()) < (commit_info.epoch(), commit_info.round())
+                }) {
+                    self.highest_commit = Some(commit.ledger_info);
+                }
+            }
+        }
+        progress
+    }
+
+    fn apply(&mut self, scheduled: &ScheduledAction) {
+        info!("[simulator] {}ms: {:?}", scheduled.at_ms, scheduled.action);
+        self.trace.push(TraceEvent::Action {
+            time_ms: self.now_ms(),
+            action: scheduled.action.clone(),
+        });
+        self.network.apply(&scheduled.action);
+        match &scheduled.action {
+            Action::Crash { nodes } => {
+                for node in nodes {
+                    self.crashed[*node] = true;
+                    self.nodes[*node] = None;
+                }
+            },
+            Action::EpochChange { protocol } => {
+                self.change_epoch(*protocol);
+            },
+            Action::Partition { .. } | Action::Heal | Action::Link(_) | Action::ClearLinks => {},
+        }
+    }
+
+    fn twin_id(&self, node: usize) -> TwinId {
+        TwinId {
+            id: node,
+            author: self.signers[self.scenario.validator_of(node)].author(),
+        }
+    }
+
+    /// Starts every node that is not crashed in the current epoch, on a fresh playground.
+    fn start_nodes(&mut self) {
+        self.playground = NetworkPlayground::new(self.handle.clone()).with_queued_rpcs();
+        let peers_and_metadata = self.playground.peer_protocols();
+        for signer in &self.signers {
+            let mut conn_meta = ConnectionMetadata::mock(signer.author());
+            conn_meta.application_protocols = ProtocolIdSet::from_iter([
+                ProtocolId::ConsensusDirectSendJson,
+                ProtocolId::ConsensusDirectSendBcs,
+                ProtocolId::ConsensusRpcBcs,
+            ]);
+            peers_and_metadata
+                .insert_connection_metadata(
+                    PeerNetworkId::new(NetworkId::Validator, signer.author()),
+                    conn_meta,
+                )
+                .unwrap();
+        }
+
+        for node in 0..self.scenario.num_nodes() {
+            if !self.crashed[node] {
+                self.nodes[node] = Some(self.start_node(node));
+            }
+        }
+    }
+
+    fn start_node(&mut self, node: usize) -> SimNode {
+        let twin_id = self.twin_id(node);
+        let validator = self.scenario.validator_of(node);
+        match self.protocol {
+            Protocol::RoundManager => {
+                let mut config = self.configs[validator].clone();
+                let waypoint = Waypoint::new_epoch_boundary(&self.root)
+                    .expect("Unable to produce waypoint with the provided LedgerInfo");
+                config
+                    .consensus
+                    .safety_rules
+                    .test
+                    .as_mut()
+                    .unwrap()
+                    .waypoint = Some(waypoint);
+                config.base.waypoint = WaypointConfig::FromConfig(waypoint);
+                config.consensus.round_initial_timeout_ms = self.scenario.round_timeout_ms;
+
+                let storage = Arc::new(MockStorage::new_with_ledger_info(
+                    Arc::new(MockSharedStorage::new(self.validator_set.clone())),
+                    self.root.clone(),
+                ));
+                let authors: Vec<_> = self.signers.iter().map(ValidatorSigner::author).collect();
+                let consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+                    proposer_election_type: self
+                        .scenario
+                        .proposer_election
+                        .to_proposer_election_type(&authors),
+                    ..ConsensusConfigV1::default()
+                });
+                SimNode::start_round_manager(
+                    &mut self.playground,
+                    twin_id,
+                    config,
+                    consensus_config,
+                    storage,
+                    self.epoch,
+                    &self.clock,
+                )
+            },
+            Protocol::Dag => SimNode::start_dag(
+                &mut self.playground,
+                twin_id,
+                self.signers[validator].clone(),
+                Arc::new(EpochState {
+                    epoch: self.epoch,
+                    verifier: (&self.validator_set).into(),
+                }),
+                generate_ledger_info_with_sig(&self.signers, self.root.clone()),
+                self.scenario.dag_config(),
+                &self.clock,
+            ),
+        }
+    }
+
+    /// Restarts the nodes in the next epoch, rooted at the highest commit of the current one.
+    fn change_epoch(&mut self, protocol: Option<Protocol>) {
+        self.collect_commits();
+        for node in self.nodes.iter_mut() {
+            node.take();
+        }
+        self.network.clear_in_flight();
+
+        let commit_info = self.highest_commit.as_ref().map_or_else(
+            || self.root.commit_info().clone(),
+            |highest| highest.ledger_info().commit_info().clone(),
+        );
+        self.epoch += 1;
+        self.root = LedgerInfo::new(
+            BlockInfo::new(
+                commit_info.epoch(),
+                commit_info.round(),
+                commit_info.id(),
+                commit_info.executed_state_id(),
+                commit_info.version(),
+                commit_info.timestamp_usecs(),
+                Some(EpochState {
+                    epoch: self.epoch,
+                    verifier: (&self.validator_set).into(),
+                }),
+            ),
+            HashValue::zero(),
+        );
+        self.highest_commit = None;
+        if let Some(protocol) = protocol {
+            self.protocol = protocol;
+        }
+        info!(
+            "[simulator] Starting epoch {} with {:?} from round {}.",
+            self.epoch,
+            self.protocol,
+            commit_info.round()
+        );
+        self.start_nodes();
+    }
+
+    fn check_liveness(&mut self) {
+        let Some(liveness) = self.scenario.expect.liveness.clone() else {
+            return;
+        };
+        let after_ms = liveness.after_ms.unwrap_or_else(|| {
+            self.scenario
+                .actions
+                .iter()
+                .map(|scheduled| scheduled.at_ms)
+                .max()
+                .unwrap_or(0)
+        });
+        let nodes = liveness.nodes.unwrap_or_else(|| {
+            (0..self.scenario.num_nodes())
+                .filter(|node| !self.crashed[*node])
+                .collect()
+        });
+        let now_ms = self.now_ms();
+        for node in nodes {
+            let commits = self.commit_times[node]
+                .iter()
+                .filter(|time| **time >= Duration::from_millis(after_ms))
+                .count();
+            if commits < liveness.min_commits {
+                let violation = Violation::NoProgress {
+                    node,
+                    after_ms,
+                    commits,
+                    expected: liveness.min_commits,
+                };
+                error!("[simulator] {}", violation);
+                self.trace.push(TraceEvent::Violation {
+                    time_ms: now_ms,
+                    violation: violation.clone(),
+                });
+                self.violations.push(violation);
+            }
+        }
+    }
+}
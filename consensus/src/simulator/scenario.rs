@@ -0,0 +1,344 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Context, Result};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_types::on_chain_config::{
+    AnchorElectionMode, DagConsensusConfigV1, ProposerElectionType,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// A simulation scenario, usually loaded from a YAML file (see `scenarios/`).
+///
+/// Nodes are referred to by index: `0..validators` are the validators, and the twin of the i-th
+/// entry of `twins` is node `validators + i`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    /// Seeds every random decision of the simulated network.
+    pub seed: u64,
+    /// Number of validators, each with voting power 1.
+    pub validators: usize,
+    /// Validators that get a twin: a second node with the same identity and keys, which is how
+    /// equivocation is simulated.
+    #[serde(default)]
+    pub twins: Vec<usize>,
+    #[serde(default)]
+    pub protocol: Protocol,
+    /// Only used by `RoundManager`, the DAG elects its anchors itself.
+    #[serde(default)]
+    pub proposer_election: ProposerElection,
+    /// Only used by the DAG, defaults to the on-chain default.
+    #[serde(default)]
+    pub dag_anchor_election: Option<AnchorElectionMode>,
+    #[serde(default = "default_round_timeout_ms")]
+    pub round_timeout_ms: u64,
+    /// Virtual time the simulation runs for.
+    pub duration_ms: u64,
+    /// Largest step virtual time takes when no message or timer is due, so that components
+    /// sleeping on the clock still wake up.
+    #[serde(default = "default_tick_ms")]
+    pub tick_ms: u64,
+    /// Behavior of the links that no `link` action applies to.
+    #[serde(default)]
+    pub network: LinkConfig,
+    #[serde(default)]
+    pub actions: Vec<ScheduledAction>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+fn default_round_timeout_ms() -> u64 {
+    1_000
+}
+
+fn default_tick_ms() -> u64 {
+    100
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    #[default]
+    RoundManager,
+    Dag,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProposerElection {
+    Rotating {
+        contiguous_rounds: u32,
+    },
+    Fixed {
+        contiguous_rounds: u32,
+    },
+    /// Maps rounds to the index of their proposer, the other rounds fall back to the default
+    /// of `RoundProposer`.
+    RoundProposer {
+        rounds: BTreeMap<Round, usize>,
+    },
+}
+
+impl Default for ProposerElection {
+    fn default() -> Self {
+        Self::Rotating {
+            contiguous_rounds: 1,
+        }
+    }
+}
+
+impl ProposerElection {
+    /// `authors` are the validator addresses, indexed like the scenario's nodes.
+    pub fn to_proposer_election_type(&self, authors: &[Author]) -> ProposerElectionType {
+        match self {
+            Self::Rotating { contiguous_rounds } => {
+                ProposerElectionType::RotatingProposer(*contiguous_rounds)
+            },
+            Self::Fixed { contiguous_rounds } => {
+                ProposerElectionType::FixedProposer(*contiguous_rounds)
+            },
+            Self::RoundProposer { rounds } => ProposerElectionType::RoundProposer(
+                rounds
+                    .iter()
+                    .map(|(round, index)| (*round, authors[*index]))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Delay and loss of a link. Delays are drawn uniformly from `delay_ms`, inclusive.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LinkConfig {
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: (u64, u64),
+    #[serde(default)]
+    pub drop_rate: f64,
+}
+
+fn default_delay_ms() -> (u64, u64) {
+    (10, 50)
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            delay_ms: default_delay_ms(),
+            drop_rate: 0.0,
+        }
+    }
+}
+
+impl LinkConfig {
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            self.delay_ms.0 <= self.delay_ms.1,
+            "Delay range {:?} is empty.",
+            self.delay_ms
+        );
+        ensure!(
+            (0.0..=1.0).contains(&self.drop_rate),
+            "Drop rate {} is not within [0, 1].",
+            self.drop_rate
+        );
+        Ok(())
+    }
+}
+
+/// Overrides the link behavior for messages from `from` to `to`. An empty `messages` matches
+/// every message, otherwise only the listed kinds (e.g. `VoteMsg`, `DAGMessage`) match. The last
+/// matching rule wins.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LinkRule {
+    pub from: Vec<usize>,
+    pub to: Vec<usize>,
+    #[serde(default)]
+    pub messages: Vec<String>,
+    #[serde(flatten)]
+    pub link: LinkConfig,
+}
+
+impl LinkRule {
+    pub fn matches(&self, from: usize, to: usize, kind: &str) -> bool {
+        self.from.contains(&from)
+            && self.to.contains(&to)
+            && (self.messages.is_empty() || self.messages.iter().any(|m| m == kind))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScheduledAction {
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Cuts every link between nodes of different groups, replacing the previous partition.
+    /// Nodes not listed form one more group.
+    Partition {
+        groups: Vec<Vec<usize>>,
+    },
+    /// Removes the partition.
+    Heal,
+    Link(LinkRule),
+    /// Removes all link rules.
+    ClearLinks,
+    /// Shuts the nodes down for the rest of the simulation.
+    Crash {
+        nodes: Vec<usize>,
+    },
+    /// Shuts all nodes down and restarts the live ones in the next epoch, rooted at the highest
+    /// ledger info committed so far, optionally switching protocol. The mocks don't execute
+    /// reconfiguration transactions, so this replaces the on-chain reconfiguration.
+    EpochChange {
+        #[serde(default)]
+        protocol: Option<Protocol>,
+    },
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Safety is always checked, liveness only if this is set.
+    #[serde(default)]
+    pub liveness: Option<Liveness>,
+}
+
+/// Each of `nodes` must commit at least `min_commits` blocks after `after_ms`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Liveness {
+    /// Defaults to the time of the last scheduled action.
+    #[serde(default)]
+    pub after_ms: Option<u64>,
+    #[serde(default = "default_min_commits")]
+    pub min_commits: usize,
+    /// Defaults to all the nodes that are not crashed.
+    #[serde(default)]
+    pub nodes: Option<Vec<usize>>,
+}
+
+fn default_min_commits() -> usize {
+    1
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let scenario: Self = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse scenario {}", path.display()))?;
+        scenario
+            .validate()
+            .with_context(|| format!("Invalid scenario {}", path.display()))?;
+        Ok(scenario)
+    }
+
+    pub fn dag_config(&self) -> DagConsensusConfigV1 {
+        let default = DagConsensusConfigV1::default();
+        DagConsensusConfigV1 {
+            anchor_election_mode: self
+                .dag_anchor_election
+                .clone()
+                .unwrap_or(default.anchor_election_mode),
+            ..default
+        }
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.validators + self.twins.len()
+    }
+
+    /// The validator whose identity node `node` runs with.
+    pub fn validator_of(&self, node: usize) -> usize {
+        if node < self.validators {
+            node
+        } else {
+            self.twins[node - self.validators]
+        }
+    }
+
+    /// The actions sorted by time, in file order for actions at the same time.
+    pub fn sorted_actions(&self) -> Vec<ScheduledAction> {
+        let mut actions = self.actions.clone();
+        actions.sort_by_key(|action| action.at_ms);
+        actions
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.validators > 0, "At least one validator is required.");
+        ensure!(self.duration_ms > 0, "duration_ms must be positive.");
+        ensure!(self.tick_ms > 0, "tick_ms must be positive.");
+        let mut twinned = HashSet::new();
+        for validator in &self.twins {
+            ensure!(
+                *validator < self.validators,
+                "Twin of unknown validator {}.",
+                validator
+            );
+            ensure!(
+                twinned.insert(*validator),
+                "Validator {} is twinned more than once.",
+                validator
+            );
+        }
+        if let ProposerElection::RoundProposer { rounds } = &self.proposer_election {
+            for index in rounds.values() {
+                ensure!(*index < self.validators, "Unknown proposer {}.", index);
+            }
+        }
+        self.network.validate()?;
+
+        let check_nodes = |nodes: &[usize]| -> Result<()> {
+            for node in nodes {
+                ensure!(*node < self.num_nodes(), "Unknown node {}.", node);
+            }
+            Ok(())
+        };
+        for scheduled in &self.actions {
+            ensure!(
+                scheduled.at_ms <= self.duration_ms,
+                "Action at {}ms is after the end of the simulation.",
+                scheduled.at_ms
+            );
+            match &scheduled.action {
+                Action::Partition { groups } => {
+                    let mut seen = HashSet::new();
+                    for group in groups {
+                        check_nodes(group)?;
+                        for node in group {
+                            ensure!(
+                                seen.insert(*node),
+                                "Node {} is in more than one group.",
+                                node
+                            );
+                        }
+                    }
+                },
+                Action::Link(rule) => {
+                    check_nodes(&rule.from)?;
+                    check_nodes(&rule.to)?;
+                    rule.link.validate()?;
+                },
+                Action::Crash { nodes } => check_nodes(nodes)?,
+                Action::Heal | Action::ClearLinks | Action::EpochChange { .. } => {},
+            }
+        }
+        if let Some(liveness) = &self.expect.liveness {
+            if let Some(nodes) = &liveness.nodes {
+                check_nodes(nodes)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::simulator::{
+    Action, CommittedBlock, SafetyChecker, Scenario, SimulationReport, Simulator, Trace,
+    TraceEvent, Violation,
+};
+use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
+use aptos_types::on_chain_config::AnchorElectionMode;
+use std::path::PathBuf;
+
+fn scenario(name: &str) -> Scenario {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/simulator/scenarios")
+        .join(format!("{}.yaml", name));
+    Scenario::load(&path).unwrap()
+}
+
+fn run_and_check(name: &str) {
+    let report = Simulator::new(scenario(name)).run();
+    report.check().unwrap();
+    assert!(!report.trace.commits().is_empty());
+}
+
+#[test]
+fn happy_path_test() {
+    run_and_check("happy_path");
+}
+
+#[test]
+fn partition_heal_test() {
+    run_and_check("partition_heal");
+}
+
+#[test]
+fn twins_equivocation_test() {
+    run_and_check("twins_equivocation");
+}
+
+#[test]
+fn leader_crash_test() {
+    run_and_check("leader_crash");
+}
+
+#[test]
+fn epoch_change_test() {
+    run_and_check("epoch_change");
+}
+
+#[test]
+fn dag_lossy_test() {
+    run_and_check("dag_lossy");
+}
+
+#[test]
+/// With slow validators, the DAG reputation anchor election commits more than round robin
+/// once it has seen them fail.
+fn dag_slow_validators_test() {
+    let scenario = scenario("dag_slow_validators");
+    let reputation = Simulator::new(scenario.clone()).run();
+    reputation.check().unwrap();
+
+    let round_robin = Simulator::new(Scenario {
+        dag_anchor_election: Some(AnchorElectionMode::RoundRobin),
+        expect: Default::default(),
+        ..scenario
+    })
+    .run();
+    round_robin.check().unwrap();
+
+    // Committed blocks of a fast validator after the reputation window filled up.
+    let commits_after = |report: &SimulationReport, after_ms: u64| {
+        report
+            .trace
+            .events
+            .iter()
+            .filter(|event| match event {
+                TraceEvent::Commit { time_ms, node, .. } => *node == 2 && *time_ms >= after_ms,
+                _ => false,
+            })
+            .count()
+    };
+    assert!(commits_after(&reputation, 5_000) > commits_after(&round_robin, 5_000));
+}
+
+#[test]
+/// A saved trace replays to the same commits.
+fn replay_test() {
+    let scenario = scenario("leader_crash");
+    let report = Simulator::new(scenario.clone()).run();
+    report.check().unwrap();
+
+    let path = TempPath::new();
+    report.trace.save(path.path()).unwrap();
+    let trace = Trace::load(path.path()).unwrap();
+    assert_eq!(trace, report.trace);
+
+    let replayed = Simulator::new(scenario).replaying(trace).run();
+    replayed.check().unwrap();
+    assert_eq!(replayed.num_divergences(), 0);
+    assert_eq!(replayed.trace.commits(), report.trace.commits());
+}
+
+#[test]
+fn scenario_parse_test() {
+    let scenario: Scenario = serde_yaml::from_str(
+        r#"
+name: test
+seed: 7
+validators: 4
+twins: [1]
+duration_ms: 1000
+actions:
+  - at_ms: 500
+    action: link
+    from: [0]
+    to: [4]
+    messages: [VoteMsg]
+    drop_rate: 1.0
+  - at_ms: 100
+    action: crash
+    nodes: [2]
+"#,
+    )
+    .unwrap();
+    scenario.validate().unwrap();
+    assert_eq!(scenario.num_nodes(), 5);
+    assert_eq!(scenario.validator_of(4), 1);
+
+    let actions = scenario.sorted_actions();
+    assert_eq!(actions[0].action, Action::Crash { nodes: vec![2] });
+    let Action::Link(rule) = &actions[1].action else {
+        panic!("Expected a link rule");
+    };
+    assert!(rule.matches(0, 4, "VoteMsg"));
+    assert!(!rule.matches(0, 4, "ProposalMsg"));
+    assert_eq!(rule.link.drop_rate, 1.0);
+    assert_eq!(rule.link.delay_ms, (10, 50));
+}
+
+#[test]
+fn scenario_validate_test() {
+    let mut scenario: Scenario = serde_yaml::from_str(
+        "{ name: test, seed: 0, validators: 4, twins: [4], duration_ms: 1000 }",
+    )
+    .unwrap();
+    assert!(scenario.validate().is_err());
+
+    scenario.twins = vec![0];
+    scenario.actions = serde_yaml::from_str("[{ at_ms: 10, action: crash, nodes: [5] }]").unwrap();
+    assert!(scenario.validate().is_err());
+
+    assert!(serde_yaml::from_str::<Scenario>(
+        "{ name: test, seed: 0, validators: 4, duration_ms: 1000, unknown: 1 }"
+    )
+    .is_err());
+}
+
+fn block(epoch: u64, round: u64, id: u8, parent_id: u8) -> CommittedBlock {
+    CommittedBlock {
+        epoch,
+        round,
+        id: HashValue::new([id; HashValue::LENGTH]),
+        parent_id: HashValue::new([parent_id; HashValue::LENGTH]),
+    }
+}
+
+#[test]
+fn safety_checker_test() {
+    let mut checker = SafetyChecker::default();
+    assert!(checker.record(0, &block(1, 1, 1, 0)).is_empty());
+    assert!(checker.record(1, &block(1, 1, 1, 0)).is_empty());
+    assert!(checker.record(0, &block(1, 2, 2, 1)).is_empty());
+
+    // Different block at a committed round.
+    assert!(matches!(
+        checker.record(1, &block(1, 2, 3, 1)).as_slice(),
+        [Violation::ConflictingCommit { other_node: 0, .. }]
+    ));
+    // Going backwards.
+    assert!(matches!(
+        checker.record(0, &block(1, 1, 1, 0)).as_slice(),
+        [Violation::NonMonotonicCommit { .. }]
+    ));
+    // Round 4 extends round 1 while round 2 is committed.
+    assert!(matches!(
+        checker.record(2, &block(1, 4, 4, 1)).as_slice(),
+        [Violation::Fork { skipped, .. }] if skipped.round == 2
+    ));
+    // Round 3 is skipped by round 5, committed before.
+    let mut checker = SafetyChecker::default();
+    assert!(checker.record(0, &block(1, 1, 1, 0)).is_empty());
+    assert!(checker.record(0, &block(1, 5, 5, 1)).is_empty());
+    assert!(matches!(
+        checker.record(1, &block(1, 3, 3, 1)).as_slice(),
+        [Violation::Fork { node: 0, skipped, .. }] if skipped.round == 3
+    ));
+    // Parents from a previous epoch are not checked.
+    assert!(checker.record(0, &block(2, 1, 6, 5)).is_empty());
+}
@@ -0,0 +1,163 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::simulator::{
+    invariants::{CommittedBlock, Violation},
+    scenario::Action,
+};
+use anyhow::{Context, Result};
+use aptos_crypto::HashValue;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Everything that happened in a simulation, in order. Times are in virtual milliseconds.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Trace {
+    pub events: Vec<TraceEvent>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    Start {
+        scenario: String,
+        seed: u64,
+    },
+    Action {
+        time_ms: u64,
+        action: Action,
+    },
+    /// A message sent from one node to another, and what the network decided to do with it.
+    /// Messages are identified by the digest of their serialized bytes.
+    Message {
+        time_ms: u64,
+        from: usize,
+        to: usize,
+        kind: String,
+        digest: HashValue,
+        outcome: Outcome,
+    },
+    Commit {
+        time_ms: u64,
+        node: usize,
+        block: CommittedBlock,
+    },
+    Violation {
+        time_ms: u64,
+        violation: Violation,
+    },
+    /// The replayed run sent a message the recorded trace has no decision for.
+    Divergence {
+        time_ms: u64,
+        from: usize,
+        to: usize,
+        kind: String,
+        digest: HashValue,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Outcome {
+    Deliver { delay_ms: u64 },
+    Drop { reason: DropReason },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    Partitioned,
+    Lost,
+    Crashed,
+}
+
+impl Trace {
+    pub fn push(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    /// Writes the trace as JSON lines, one event per line.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(
+            File::create(path)
+                .with_context(|| format!("Failed to create trace {}", path.display()))?,
+        );
+        for event in &self.events {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read trace {}", path.display()))?;
+        let events = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<TraceEvent>)
+            .collect::<Result<_, _>>()
+            .with_context(|| format!("Failed to parse trace {}", path.display()))?;
+        Ok(Self { events })
+    }
+
+    /// The blocks committed by each node, in commit order.
+    pub fn commits(&self) -> BTreeMap<usize, Vec<CommittedBlock>> {
+        let mut commits: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for event in &self.events {
+            if let TraceEvent::Commit { node, block, .. } = event {
+                commits.entry(*node).or_default().push(block.clone());
+            }
+        }
+        commits
+    }
+
+    pub fn violations(&self) -> Vec<&Violation> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Violation { violation, .. } => Some(violation),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The network decisions of the trace, for replaying it.
+    pub fn decisions(&self) -> RecordedDecisions {
+        let mut decisions: HashMap<_, VecDeque<_>> = HashMap::new();
+        for event in &self.events {
+            if let TraceEvent::Message {
+                from,
+                to,
+                digest,
+                outcome,
+                ..
+            } = event
+            {
+                decisions
+                    .entry((*from, *to, *digest))
+                    .or_default()
+                    .push_back(*outcome);
+            }
+        }
+        RecordedDecisions(decisions)
+    }
+}
+
+/// Recorded outcomes by sender, receiver and message digest. Identical messages sent more than
+/// once on the same link get their outcomes in the recorded order.
+pub struct RecordedDecisions(HashMap<(usize, usize, HashValue), VecDeque<Outcome>>);
+
+impl RecordedDecisions {
+    pub fn take(&mut self, from: usize, to: usize, digest: HashValue) -> Option<Outcome> {
+        self.0
+            .get_mut(&(from, to, digest))
+            .and_then(|outcomes| outcomes.pop_front())
+    }
+}
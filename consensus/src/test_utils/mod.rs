@@ -40,7 +40,7 @@ pub use mock_payload_manager::MockPayloadManager;
 pub use mock_state_computer::EmptyStateComputer;
 #[cfg(test)]
 pub use mock_state_computer::RandomComputeResultStateComputer;
-pub use mock_storage::{EmptyStorage, MockStorage};
+pub use mock_storage::{EmptyStorage, MockSharedStorage, MockStorage};
 use move_core_types::account_address::AccountAddress;
 
 pub const TEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -4,14 +4,33 @@
 use aptos_config::config::transaction_filter_type::Filter;
 use aptos_crypto::HashValue;
 use aptos_types::transaction::SignedTransaction;
+use arc_swap::ArcSwap;
+use std::sync::Arc;
 
+/// Filters the transactions of a block before execution. The underlying [`Filter`] can be
+/// replaced at runtime (e.g., through the admin service). Clones share the same filter.
+#[derive(Clone)]
 pub struct TransactionFilter {
-    filter: Filter,
+    filter: Arc<ArcSwap<Filter>>,
 }
 
 impl TransactionFilter {
     pub(crate) fn new(filter: Filter) -> Self {
-        Self { filter }
+        Self {
+            filter: Arc::new(ArcSwap::from_pointee(filter)),
+        }
+    }
+
+    /// Returns the currently active filter
+    pub fn current(&self) -> Arc<Filter> {
+        self.filter.load_full()
+    }
+
+    /// Replaces the active filter and returns the previous one. The new filter takes effect
+    /// for the next block that is filtered; blocks that are being filtered concurrently keep
+    /// using the filter they started with.
+    pub fn update(&self, filter: Filter) -> Arc<Filter> {
+        self.filter.swap(Arc::new(filter))
     }
 
     pub fn filter(
@@ -20,12 +39,15 @@ impl TransactionFilter {
         timestamp: u64,
         txns: Vec<SignedTransaction>,
     ) -> Vec<SignedTransaction> {
+        // Load the filter once so the whole block is evaluated against the same rules
+        let filter = self.filter.load();
+
         // Special case for no filter to avoid unnecessary iteration through all transactions in the default case
-        if self.filter.is_empty() {
+        if filter.is_empty() {
             return txns;
         }
         txns.into_iter()
-            .filter(|txn| self.filter.allows(block_id, timestamp, txn))
+            .filter(|txn| filter.allows(block_id, timestamp, txn))
             .collect()
     }
 }
@@ -33,12 +55,15 @@ impl TransactionFilter {
 #[cfg(test)]
 mod test {
     use crate::transaction_filter::TransactionFilter;
-    use aptos_config::config::transaction_filter_type::Filter;
+    use aptos_config::config::transaction_filter_type::{AuthenticatorKind, Filter, Matcher};
     use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
     use aptos_types::{
         chain_id::ChainId,
         move_utils::MemberId,
-        transaction::{EntryFunction, RawTransaction, SignedTransaction, TransactionPayload},
+        transaction::{
+            authenticator::AccountAuthenticator, EntryFunction, Multisig, RawTransaction, Script,
+            SignedTransaction, TransactionPayload,
+        },
     };
     use move_core_types::account_address::AccountAddress;
 
@@ -68,6 +93,55 @@ mod test {
         )
     }
 
+    fn create_signed_transaction_with_payload(
+        payload: TransactionPayload,
+        gas_unit_price: u64,
+    ) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = private_key.public_key();
+        let raw_transaction = RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            payload,
+            0,
+            gas_unit_price,
+            0,
+            ChainId::new(10),
+        );
+
+        SignedTransaction::new(
+            raw_transaction.clone(),
+            public_key,
+            private_key.sign(&raw_transaction).unwrap(),
+        )
+    }
+
+    fn create_fee_payer_transaction(fee_payer_address: AccountAddress) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = private_key.public_key();
+        let payload = TransactionPayload::Script(Script::new(vec![], vec![], vec![]));
+        let raw_transaction = RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            payload,
+            0,
+            0,
+            0,
+            ChainId::new(10),
+        );
+        let signature = private_key.sign(&raw_transaction).unwrap();
+        let authenticator = AccountAuthenticator::ed25519(public_key, signature);
+
+        SignedTransaction::new_fee_payer(
+            raw_transaction,
+            authenticator.clone(),
+            vec![],
+            vec![],
+            fee_payer_address,
+            authenticator,
+        )
+    }
+
     fn get_transactions() -> Vec<SignedTransaction> {
         vec![
             create_signed_transaction(str::parse("0x1::test::add").unwrap()),
@@ -287,4 +361,237 @@ mod test {
         let filtered_txns = allow_list_filter.filter(block_id, 0, txns.clone());
         assert_eq!(filtered_txns, txns[4..].to_vec());
     }
+
+    #[test]
+    fn test_script_filter() {
+        let mut txns = get_transactions();
+        let script = Script::new(vec![1, 2, 3], vec![], vec![]);
+        txns.push(create_signed_transaction_with_payload(
+            TransactionPayload::Script(script.clone()),
+            0,
+        ));
+        txns.push(create_signed_transaction_with_payload(
+            TransactionPayload::Script(Script::new(vec![4, 5, 6], vec![], vec![])),
+            0,
+        ));
+        let block_id = HashValue::random();
+
+        let script_filter = TransactionFilter::new(Filter::empty().add_deny_script());
+        let filtered_txns = script_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..7].to_vec());
+
+        let script_hash_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::ScriptHash(HashValue::sha3_256_of(script.code()))),
+        );
+        let filtered_txns = script_hash_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, [&txns[0..7], &txns[8..]].concat());
+    }
+
+    #[test]
+    fn test_multisig_filter() {
+        let mut txns = get_transactions();
+        let multisig_address = AccountAddress::random();
+        txns.push(create_signed_transaction_with_payload(
+            TransactionPayload::Multisig(Multisig {
+                multisig_address,
+                transaction_payload: None,
+            }),
+            0,
+        ));
+        let block_id = HashValue::random();
+
+        let multisig_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::MultisigAddress(multisig_address)),
+        );
+        let filtered_txns = multisig_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..7].to_vec());
+    }
+
+    #[test]
+    fn test_fee_payer_filter() {
+        let mut txns = get_transactions();
+        let fee_payer_address = AccountAddress::random();
+        txns.push(create_fee_payer_transaction(fee_payer_address));
+        txns.push(create_fee_payer_transaction(AccountAddress::random()));
+        let block_id = HashValue::random();
+
+        let fee_payer_filter =
+            TransactionFilter::new(Filter::empty().add_deny_fee_payer(fee_payer_address));
+        let filtered_txns = fee_payer_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, [&txns[0..7], &txns[8..]].concat());
+
+        let any_fee_payer_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::AnyFeePayer));
+        let filtered_txns = any_fee_payer_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..7].to_vec());
+    }
+
+    #[test]
+    fn test_authenticator_kind_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+
+        let ed25519_filter = TransactionFilter::new(
+            Filter::empty().add_deny_authenticator_kind(AuthenticatorKind::Ed25519),
+        );
+        let filtered_txns = ed25519_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![]);
+
+        let keyless_filter = TransactionFilter::new(
+            Filter::empty().add_deny_authenticator_kind(AuthenticatorKind::Keyless),
+        );
+        let filtered_txns = keyless_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+    }
+
+    #[test]
+    fn test_gas_filter() {
+        let txns: Vec<_> = (0..5)
+            .map(|gas_unit_price| {
+                create_signed_transaction_with_payload(
+                    TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+                    gas_unit_price * 100,
+                )
+            })
+            .collect();
+        let block_id = HashValue::random();
+
+        // Only allows transactions with a gas unit price in (100, 400)
+        let gas_filter = TransactionFilter::new(
+            Filter::empty()
+                .add_deny(Matcher::GasUnitPriceLessThan(101))
+                .add_deny(Matcher::GasUnitPriceGreaterThan(399)),
+        );
+        let filtered_txns = gas_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[2..4].to_vec());
+
+        let max_gas_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::MaxGasAmountGreaterThan(0)));
+        let filtered_txns = max_gas_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+    }
+
+    #[test]
+    fn test_composite_matchers() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+
+        // Denies transactions calling into 0x1 modules, except for test::check
+        let filter = TransactionFilter::new(Filter::empty().add_deny(Matcher::And(vec![
+            Matcher::ModuleAddress(get_module_address(&txns[0])),
+            Matcher::Not(Box::new(Matcher::EntryFunction(
+                get_module_address(&txns[1]),
+                get_module_name(&txns[1]),
+                get_function_name(&txns[1]),
+            ))),
+        ])));
+        let filtered_txns = filter.filter(block_id, 0, txns.clone());
+        assert_eq!(
+            filtered_txns,
+            txns[1..2]
+                .iter()
+                .chain(&txns[4..])
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        // Allows only transactions calling into 0x2 or 0x3 modules
+        let filter = TransactionFilter::new(
+            Filter::empty()
+                .add_allow(Matcher::Or(vec![
+                    Matcher::ModuleAddress(get_module_address(&txns[4])),
+                    Matcher::ModuleAddress(get_module_address(&txns[5])),
+                ]))
+                .add_deny_all(),
+        );
+        let filtered_txns = filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[4..6].to_vec());
+    }
+
+    #[test]
+    fn test_composite_matchers_from_yaml() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let filter = serde_yaml::from_str::<Filter>(r#"
+            rules:
+                - Deny:
+                    And:
+                        - AuthenticatorKind: Ed25519
+                        - Or:
+                            - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000002"
+                            - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000003"
+                - Deny:
+                    Not:
+                        GasUnitPriceLessThan: 1
+              "#).unwrap();
+
+        let filter = TransactionFilter::new(filter);
+        let filtered_txns = filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, [&txns[0..4], &txns[6..]].concat());
+    }
+
+    #[test]
+    fn test_allows_transaction_without_block() {
+        let txns = get_transactions();
+
+        // Block matchers never match outside of a block
+        let filter = Filter::empty()
+            .add_deny_block_id(HashValue::random())
+            .add_deny(Matcher::BlockTimeStampLessThan(u64::MAX))
+            .add_deny_sender(txns[0].sender());
+        assert!(!filter.allows_transaction(&txns[0]));
+        for txn in &txns[1..] {
+            assert!(filter.allows_transaction(txn));
+        }
+
+        // Neither do rules that depend on them, negated or composed
+        let filter = Filter::empty()
+            .add_deny(Matcher::Not(Box::new(
+                Matcher::BlockId(HashValue::random()),
+            )))
+            .add_deny(Matcher::Or(vec![
+                Matcher::BlockTimeStampGreaterThan(0),
+                Matcher::Sender(txns[1].sender()),
+            ]))
+            .add_deny(Matcher::And(vec![
+                Matcher::Not(Box::new(Matcher::BlockTimeStampLessThan(0))),
+                Matcher::All,
+            ]));
+        assert!(!filter.allows_transaction(&txns[1]));
+        for txn in [&txns[0..1], &txns[2..]].concat() {
+            assert!(filter.allows_transaction(&txn));
+        }
+        // but still apply when the outcome does not depend on the block
+        let filter = Filter::empty().add_deny(Matcher::And(vec![
+            Matcher::BlockId(HashValue::random()),
+            Matcher::Not(Box::new(Matcher::All)),
+        ]));
+        assert!(filter.allows_transaction(&txns[0]));
+        let filter = Filter::empty().add_deny(Matcher::Or(vec![
+            Matcher::Not(Box::new(Matcher::BlockId(HashValue::random()))),
+            Matcher::All,
+        ]));
+        assert!(!filter.allows_transaction(&txns[0]));
+    }
+
+    #[test]
+    fn test_update_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let txn_filter = TransactionFilter::new(Filter::empty());
+        let shared_txn_filter = txn_filter.clone();
+
+        let previous_filter = txn_filter.update(Filter::empty().add_deny_sender(txns[0].sender()));
+        assert!(previous_filter.is_empty());
+        assert_eq!(
+            *shared_txn_filter.current(),
+            Filter::empty().add_deny_sender(txns[0].sender())
+        );
+        let filtered_txns = shared_txn_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[1..].to_vec());
+
+        txn_filter.update(Filter::empty());
+        let filtered_txns = shared_txn_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+    }
 }
@@ -0,0 +1,612 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensusdb::{ConsensusDB, CONSENSUS_DB_NAME},
+    quorum_store::{
+        quorum_store_db::{QuorumStoreDB, QuorumStoreStorage},
+        types::PersistedValue,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_config::config::{
+    RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Author, Payload, Round},
+    proof_of_store::{BatchInfo, ProofOfStore},
+    quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate,
+    vote::Vote,
+};
+use aptos_crypto::HashValue;
+use aptos_db::AptosDB;
+use aptos_storage_interface::DbReader;
+use aptos_types::{block_info::BlockInfo, ledger_info::LedgerInfoWithSignatures};
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+#[derive(Parser)]
+#[clap(about = "Inspect and repair the consensus db and the quorum store db.")]
+pub enum Cmd {
+    /// Shows the committed root, the highest certificates and the number of orphans.
+    Summary(Opt),
+    /// Lists the blocks by (epoch, round) with their parents and their position relative to the
+    /// committed root.
+    ListBlocks(Opt),
+    /// Lists the blocks and QCs not descending from the committed root, the stale quorum store
+    /// batches and the batches referenced by live blocks but missing from the quorum store db.
+    FindOrphans(Opt),
+    /// Exports everything to a JSON file.
+    Export(ExportCmd),
+    /// Deletes the blocks and QCs not descending from the committed root, same as what the node
+    /// does when recovering successfully on start up.
+    PruneToCommittedRoot(PruneCmd),
+}
+
+impl Cmd {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Cmd::Summary(opt) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&opt.inspect()?.summary())?
+                );
+            },
+            Cmd::ListBlocks(opt) => {
+                for block in opt.inspect()?.block_summaries() {
+                    println!(
+                        "epoch {} round {} {} parent {} {:?}{}",
+                        block.epoch,
+                        block.round,
+                        block.id,
+                        block.parent_id,
+                        block.status,
+                        if block.has_qc { "" } else { " (no QC)" },
+                    );
+                }
+            },
+            Cmd::FindOrphans(opt) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&opt.inspect()?.orphans())?
+                );
+            },
+            Cmd::Export(cmd) => cmd.run()?,
+            Cmd::PruneToCommittedRoot(cmd) => cmd.run()?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(
+        long,
+        value_parser,
+        help = "The node's storage dir, containing the consensus db and the quorum store db."
+    )]
+    db_dir: PathBuf,
+
+    #[clap(
+        long,
+        value_parser,
+        help = "The AptosDB dir, where the committed root is read from. [Defaults to --db-dir]"
+    )]
+    aptos_db_dir: Option<PathBuf>,
+
+    #[clap(long)]
+    enable_storage_sharding: bool,
+}
+
+impl Opt {
+    fn latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
+        let aptos_db = AptosDB::open(
+            StorageDirPaths::from_path(self.aptos_db_dir.as_ref().unwrap_or(&self.db_dir)),
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs {
+                enable_storage_sharding: self.enable_storage_sharding,
+                ..Default::default()
+            },
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        Ok(aptos_db.get_latest_ledger_info()?)
+    }
+
+    /// Reads both dbs without locking them, this works while the node is running.
+    fn inspect(&self) -> Result<Inspection> {
+        let consensus_db = ConsensusDB::open_readonly(&self.db_dir)?;
+        let quorum_store_db = QuorumStoreDB::open_readonly(&self.db_dir)?;
+        Inspection::new(
+            self.latest_ledger_info()?,
+            &consensus_db,
+            quorum_store_db.get_all_batches()?,
+        )
+    }
+}
+
+#[derive(Parser)]
+pub struct ExportCmd {
+    #[clap(flatten)]
+    opt: Opt,
+
+    #[clap(long, value_parser)]
+    output_file: PathBuf,
+}
+
+impl ExportCmd {
+    fn run(self) -> Result<()> {
+        let inspection = self.opt.inspect()?;
+        let export = Export {
+            summary: inspection.summary(),
+            last_vote: inspection.last_vote.as_ref(),
+            highest_2chain_timeout_certificate: inspection
+                .highest_2chain_timeout_certificate
+                .as_ref(),
+            block_summaries: inspection.block_summaries(),
+            blocks: &inspection.blocks,
+            quorum_certs: &inspection.quorum_certs,
+            batches: inspection.batch_summaries(),
+        };
+        serde_json::to_writer_pretty(File::create(&self.output_file)?, &export)?;
+        println!("Exported to {:?}.", self.output_file);
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+pub struct PruneCmd {
+    #[clap(flatten)]
+    opt: Opt,
+
+    #[clap(
+        long,
+        value_parser,
+        help = "A checkpoint of the consensus db is made here before anything is deleted."
+    )]
+    backup_dir: PathBuf,
+
+    #[clap(
+        long,
+        help = "Delete everything if the committed root can't be recovered from the consensus db, \
+        in which case the node needs to sync the blocks from its peers on start up."
+    )]
+    allow_wipe: bool,
+
+    #[clap(long, help = "Actually delete, otherwise only the plan is printed.")]
+    execute: bool,
+}
+
+impl PruneCmd {
+    fn run(self) -> Result<()> {
+        prune_to_committed_root(
+            &self.opt.db_dir,
+            self.opt.latest_ledger_info()?,
+            &self.backup_dir,
+            self.allow_wipe,
+            self.execute,
+        )?;
+        Ok(())
+    }
+}
+
+/// Prints the prune plan for the consensus db under `db_dir`, and applies it if `execute` is set,
+/// after checkpointing the consensus db to `backup_dir`.
+pub(crate) fn prune_to_committed_root(
+    db_dir: &Path,
+    ledger_info: LedgerInfoWithSignatures,
+    backup_dir: &Path,
+    allow_wipe: bool,
+    execute: bool,
+) -> Result<PrunePlan> {
+    // Not read only, this fails if the node is running.
+    let consensus_db = ConsensusDB::open(db_dir).map_err(|err| {
+        anyhow!(
+            "Failed to open the consensus db, make sure it exists and the node is stopped: {}",
+            err
+        )
+    })?;
+    let plan = Inspection::new(ledger_info, &consensus_db, HashMap::new())?.prune_plan();
+    println!("{}", serde_json::to_string_pretty(&plan)?);
+    ensure!(
+        plan.root_recoverable || allow_wipe,
+        "The committed root can't be recovered from the consensus db, pass --allow-wipe to \
+        delete all blocks and QCs."
+    );
+    if !execute {
+        println!("Dry run, pass --execute to apply.");
+        return Ok(plan);
+    }
+
+    ensure!(
+        !backup_dir.join(CONSENSUS_DB_NAME).exists(),
+        "A backup already exists in {:?}.",
+        backup_dir
+    );
+    fs::create_dir_all(backup_dir)?;
+    consensus_db.create_checkpoint(backup_dir)?;
+    println!("Backed up the consensus db to {:?}.", backup_dir);
+
+    if !plan.blocks_to_delete.is_empty() {
+        consensus_db.delete_blocks_and_quorum_certificates(plan.blocks_to_delete.clone())?;
+    }
+    if plan.delete_last_vote {
+        consensus_db.delete_last_vote_msg()?;
+    }
+    if plan.delete_highest_2chain_timeout_certificate {
+        consensus_db.delete_highest_2chain_timeout_certificate()?;
+    }
+    println!("Pruned.");
+    Ok(plan)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockStatus {
+    /// The block committed by the latest ledger info in AptosDB.
+    Root,
+    /// Descends from the root, kept on recovery.
+    Descendant,
+    /// On the path from the root to genesis, already committed.
+    Ancestor,
+    /// Neither, can never be committed.
+    Orphaned,
+}
+
+#[derive(Serialize)]
+pub struct BlockSummary {
+    pub id: HashValue,
+    pub parent_id: HashValue,
+    pub epoch: u64,
+    pub round: Round,
+    pub timestamp_usecs: u64,
+    pub author: Option<Author>,
+    pub num_batches: usize,
+    pub has_qc: bool,
+    pub status: BlockStatus,
+}
+
+#[derive(Serialize)]
+pub struct BatchSummary {
+    pub info: BatchInfo,
+    pub has_payload: bool,
+    pub stale: bool,
+}
+
+#[derive(Serialize)]
+pub struct Summary {
+    pub committed_root: BlockInfo,
+    pub root_recoverable: bool,
+    pub highest_quorum_cert: Option<BlockInfo>,
+    pub highest_ordered_cert: Option<BlockInfo>,
+    pub last_vote: Option<String>,
+    pub highest_2chain_timeout_certificate: Option<String>,
+    pub num_blocks: usize,
+    pub num_quorum_certs: usize,
+    pub num_batches: usize,
+    pub num_orphaned_blocks: usize,
+    pub num_stale_batches: usize,
+    pub num_missing_batches: usize,
+}
+
+#[derive(Serialize)]
+pub struct Orphans {
+    pub blocks: Vec<HashValue>,
+    pub quorum_certs: Vec<HashValue>,
+    pub stale_batches: Vec<HashValue>,
+    pub missing_batches: Vec<HashValue>,
+}
+
+#[derive(Serialize)]
+pub struct PrunePlan {
+    pub root_recoverable: bool,
+    /// Ids of the blocks (and the QCs certifying them) to delete.
+    pub blocks_to_delete: Vec<HashValue>,
+    pub delete_last_vote: bool,
+    pub delete_highest_2chain_timeout_certificate: bool,
+}
+
+#[derive(Serialize)]
+struct Export<'a> {
+    summary: Summary,
+    last_vote: Option<&'a Vote>,
+    highest_2chain_timeout_certificate: Option<&'a TwoChainTimeoutCertificate>,
+    block_summaries: Vec<BlockSummary>,
+    blocks: &'a [Block],
+    quorum_certs: &'a [QuorumCert],
+    batches: Vec<BatchSummary>,
+}
+
+/// The content of the consensus db and the quorum store db, relative to the committed root in
+/// AptosDB.
+pub struct Inspection {
+    ledger_info: LedgerInfoWithSignatures,
+    /// Same as the root found on recovery: a virtual genesis block if the ledger info ends an
+    /// epoch, otherwise the block it commits.
+    root_id: HashValue,
+    root_epoch: u64,
+    root_timestamp_usecs: u64,
+    last_vote: Option<Vote>,
+    highest_2chain_timeout_certificate: Option<TwoChainTimeoutCertificate>,
+    /// Sorted by (epoch, round), so that parents come before children.
+    blocks: Vec<Block>,
+    quorum_certs: Vec<QuorumCert>,
+    batches: HashMap<HashValue, PersistedValue>,
+}
+
+impl Inspection {
+    pub fn new(
+        ledger_info: LedgerInfoWithSignatures,
+        consensus_db: &ConsensusDB,
+        batches: HashMap<HashValue, PersistedValue>,
+    ) -> Result<Self> {
+        let (last_vote, highest_2chain_timeout_certificate, mut blocks, mut quorum_certs) =
+            consensus_db.get_data()?;
+        blocks.sort_by_key(|block| (block.epoch(), block.round()));
+        quorum_certs.sort_by_key(|qc| (qc.certified_block().epoch(), qc.certified_block().round()));
+
+        let (root_id, root_epoch, root_timestamp_usecs) = if ledger_info.ledger_info().ends_epoch()
+        {
+            let genesis = Block::make_genesis_block_from_ledger_info(ledger_info.ledger_info());
+            (genesis.id(), genesis.epoch(), genesis.timestamp_usecs())
+        } else {
+            let commit_info = ledger_info.commit_info();
+            (
+                ledger_info.ledger_info().consensus_block_id(),
+                commit_info.epoch(),
+                commit_info.timestamp_usecs(),
+            )
+        };
+
+        Ok(Self {
+            ledger_info,
+            root_id,
+            root_epoch,
+            root_timestamp_usecs,
+            last_vote: last_vote.map(|bytes| bcs::from_bytes(&bytes)).transpose()?,
+            highest_2chain_timeout_certificate: highest_2chain_timeout_certificate
+                .map(|bytes| bcs::from_bytes(&bytes))
+                .transpose()?,
+            blocks,
+            quorum_certs,
+            batches,
+        })
+    }
+
+    /// Whether recovery on start up can find the root, its QC and its ordered cert, see
+    /// `LedgerRecoveryData::find_root`.
+    pub fn root_recoverable(&self) -> bool {
+        if self.ledger_info.ledger_info().ends_epoch() {
+            return true;
+        }
+        let root_id = self.root_id;
+        self.blocks.iter().any(|block| block.id() == root_id)
+            && self
+                .quorum_certs
+                .iter()
+                .any(|qc| qc.certified_block().id() == root_id)
+            && self
+                .quorum_certs
+                .iter()
+                .any(|qc| qc.commit_info().id() == root_id)
+    }
+
+    pub fn block_statuses(&self) -> HashMap<HashValue, BlockStatus> {
+        let root_id = self.root_id;
+        let mut statuses = HashMap::new();
+        statuses.insert(root_id, BlockStatus::Root);
+
+        let mut descendants = HashSet::from([root_id]);
+        for block in &self.blocks {
+            if block.id() != root_id && descendants.contains(&block.parent_id()) {
+                descendants.insert(block.id());
+                statuses.insert(block.id(), BlockStatus::Descendant);
+            }
+        }
+
+        let parents: HashMap<_, _> = self
+            .blocks
+            .iter()
+            .map(|block| (block.id(), block.parent_id()))
+            .collect();
+        let mut ancestor = parents.get(&root_id).copied();
+        while let Some(id) = ancestor {
+            if !parents.contains_key(&id) || statuses.contains_key(&id) {
+                break;
+            }
+            statuses.insert(id, BlockStatus::Ancestor);
+            ancestor = parents.get(&id).copied();
+        }
+
+        for block in &self.blocks {
+            statuses.entry(block.id()).or_insert(BlockStatus::Orphaned);
+        }
+        statuses
+    }
+
+    pub fn block_summaries(&self) -> Vec<BlockSummary> {
+        let statuses = self.block_statuses();
+        let certified: HashSet<_> = self
+            .quorum_certs
+            .iter()
+            .map(|qc| qc.certified_block().id())
+            .collect();
+        self.blocks
+            .iter()
+            .map(|block| BlockSummary {
+                id: block.id(),
+                parent_id: block.parent_id(),
+                epoch: block.epoch(),
+                round: block.round(),
+                timestamp_usecs: block.timestamp_usecs(),
+                author: block.author(),
+                num_batches: payload_proofs(block).len(),
+                has_qc: certified.contains(&block.id()),
+                status: statuses[&block.id()],
+            })
+            .collect()
+    }
+
+    pub fn highest_quorum_cert(&self) -> Option<&QuorumCert> {
+        self.quorum_certs
+            .iter()
+            .max_by_key(|qc| (qc.certified_block().epoch(), qc.certified_block().round()))
+    }
+
+    /// The QC with the highest commit info, i.e. the one ordering the highest block.
+    pub fn highest_ordered_cert(&self) -> Option<&QuorumCert> {
+        self.quorum_certs
+            .iter()
+            .filter(|qc| qc.commit_info().id() != BlockInfo::empty().id())
+            .max_by_key(|qc| (qc.commit_info().epoch(), qc.commit_info().round()))
+    }
+
+    /// Batches from past epochs, or expired as of the root block.
+    fn is_stale(&self, batch: &BatchInfo) -> bool {
+        batch.epoch() < self.root_epoch || batch.expiration() <= self.root_timestamp_usecs
+    }
+
+    pub fn batch_summaries(&self) -> Vec<BatchSummary> {
+        let mut summaries: Vec<_> = self
+            .batches
+            .values()
+            .map(|batch| BatchSummary {
+                info: batch.batch_info().clone(),
+                has_payload: batch.payload().is_some(),
+                stale: self.is_stale(batch.batch_info()),
+            })
+            .collect();
+        summaries.sort_by_key(|summary| (summary.info.epoch(), summary.info.expiration()));
+        summaries
+    }
+
+    pub fn orphans(&self) -> Orphans {
+        let statuses = self.block_statuses();
+        let is_live = |id: &HashValue| {
+            matches!(
+                statuses.get(id),
+                Some(BlockStatus::Root) | Some(BlockStatus::Descendant)
+            )
+        };
+
+        let blocks = self
+            .blocks
+            .iter()
+            .map(Block::id)
+            .filter(|id| statuses[id] == BlockStatus::Orphaned)
+            .collect();
+        let quorum_certs = self
+            .quorum_certs
+            .iter()
+            .map(|qc| qc.certified_block().id())
+            .filter(|id| !statuses.contains_key(id))
+            .collect();
+        let mut stale_batches: Vec<_> = self
+            .batches
+            .iter()
+            .filter(|(_, batch)| self.is_stale(batch.batch_info()))
+            .map(|(digest, _)| *digest)
+            .collect();
+        stale_batches.sort();
+        let missing_batches = self
+            .blocks
+            .iter()
+            .filter(|block| block.id() != self.root_id && is_live(&block.id()))
+            .flat_map(payload_proofs)
+            .map(|proof| *proof.digest())
+            .filter(|digest| !self.batches.contains_key(digest))
+            .collect();
+
+        Orphans {
+            blocks,
+            quorum_certs,
+            stale_batches,
+            missing_batches,
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        let orphans = self.orphans();
+        Summary {
+            committed_root: self.ledger_info.commit_info().clone(),
+            root_recoverable: self.root_recoverable(),
+            highest_quorum_cert: self
+                .highest_quorum_cert()
+                .map(|qc| qc.certified_block().clone()),
+            highest_ordered_cert: self
+                .highest_ordered_cert()
+                .map(|qc| qc.commit_info().clone()),
+            last_vote: self.last_vote.as_ref().map(ToString::to_string),
+            highest_2chain_timeout_certificate: self
+                .highest_2chain_timeout_certificate
+                .as_ref()
+                .map(ToString::to_string),
+            num_blocks: self.blocks.len(),
+            num_quorum_certs: self.quorum_certs.len(),
+            num_batches: self.batches.len(),
+            num_orphaned_blocks: orphans.blocks.len(),
+            num_stale_batches: orphans.stale_batches.len(),
+            num_missing_batches: orphans.missing_batches.len(),
+        }
+    }
+
+    /// Mirrors the pruning done on a successful recovery: only the root and its descendants
+    /// (and the QCs certifying them) are kept, and the last vote and timeout certificate are only
+    /// kept if they are from the root's epoch. If the root can't be recovered, everything goes.
+    pub fn prune_plan(&self) -> PrunePlan {
+        let root_recoverable = self.root_recoverable();
+        let statuses = self.block_statuses();
+        let keep = |id: &HashValue| {
+            root_recoverable
+                && matches!(
+                    statuses.get(id),
+                    Some(BlockStatus::Root) | Some(BlockStatus::Descendant)
+                )
+        };
+
+        let mut blocks_to_delete: Vec<_> = self
+            .blocks
+            .iter()
+            .map(Block::id)
+            .chain(self.quorum_certs.iter().map(|qc| qc.certified_block().id()))
+            .filter(|id| !keep(id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        blocks_to_delete.sort();
+
+        let epoch = self.root_epoch;
+        PrunePlan {
+            root_recoverable,
+            blocks_to_delete,
+            delete_last_vote: self
+                .last_vote
+                .as_ref()
+                .map_or(false, |vote| vote.epoch() != epoch),
+            delete_highest_2chain_timeout_certificate: self
+                .highest_2chain_timeout_certificate
+                .as_ref()
+                .map_or(false, |tc| tc.epoch() != epoch),
+        }
+    }
+}
+
+fn payload_proofs(block: &Block) -> &[ProofOfStore] {
+    match block.payload() {
+        Some(Payload::InQuorumStore(proof_with_data)) => &proof_with_data.proofs,
+        Some(Payload::InQuorumStoreWithLimit(proof_with_data)) => {
+            &proof_with_data.proof_with_data.proofs
+        },
+        Some(Payload::DirectMempool(_)) | None => &[],
+    }
+}
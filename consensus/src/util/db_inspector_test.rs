@@ -0,0 +1,276 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    consensusdb::{ConsensusDB, CONSENSUS_DB_NAME},
+    util::db_inspector::{prune_to_committed_root, BlockStatus, Inspection},
+};
+use aptos_consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, placeholder_certificate_for_block},
+        Block,
+    },
+    common::Payload,
+    quorum_cert::QuorumCert,
+    vote_data::VoteData,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
+use std::{collections::HashMap, path::Path};
+
+/// Builds genesis <- a1 <- a2, with the QCs of genesis and a1, the QC of a2 which orders a1, and
+/// a ledger info committing a1, which doesn't end the epoch.
+fn chain_committing_a1(
+    signer: &ValidatorSigner,
+) -> (
+    Vec<Block>,
+    Vec<QuorumCert>,
+    QuorumCert,
+    LedgerInfoWithSignatures,
+) {
+    let genesis = Block::make_genesis_block_from_ledger_info(&LedgerInfo::mock_genesis(None));
+    let genesis_qc = certificate_for_genesis();
+    let a1 = Block::new_proposal(
+        Payload::DirectMempool(vec![]),
+        1,
+        1,
+        genesis_qc.clone(),
+        signer,
+        vec![],
+    )
+    .unwrap();
+    let a1_qc = placeholder_certificate_for_block(&[signer.clone()], a1.id(), 1, genesis.id(), 0);
+    let a2 = Block::new_proposal(
+        Payload::DirectMempool(vec![]),
+        2,
+        2,
+        a1_qc.clone(),
+        signer,
+        vec![],
+    )
+    .unwrap();
+
+    let a1_info = a1_qc.certified_block().clone();
+    let vote_data = VoteData::new(
+        BlockInfo::new(
+            a1_info.epoch(),
+            a2.round(),
+            a2.id(),
+            a1_info.executed_state_id(),
+            a1_info.version(),
+            a2.timestamp_usecs(),
+            None,
+        ),
+        a1_info.clone(),
+    );
+    let a2_qc = QuorumCert::new(
+        vote_data.clone(),
+        LedgerInfoWithSignatures::new(
+            LedgerInfo::new(a1_info.clone(), vote_data.hash()),
+            AggregateSignature::empty(),
+        ),
+    );
+    let ledger_info = LedgerInfoWithSignatures::new(
+        LedgerInfo::new(a1_info, HashValue::zero()),
+        AggregateSignature::empty(),
+    );
+    (
+        vec![genesis, a1, a2],
+        vec![genesis_qc, a1_qc],
+        a2_qc,
+        ledger_info,
+    )
+}
+
+fn num_blocks(db_dir: &Path) -> usize {
+    ConsensusDB::open(db_dir)
+        .unwrap()
+        .get_data()
+        .unwrap()
+        .2
+        .len()
+}
+
+#[test]
+fn test_find_orphans_and_prune_plan() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+    let signer = ValidatorSigner::random(None);
+
+    // The ledger info ends the epoch, so the root is the virtual genesis of the next epoch.
+    let ledger_info = LedgerInfo::mock_genesis(None);
+    let genesis = Block::make_genesis_block_from_ledger_info(&ledger_info);
+    let genesis_qc = certificate_for_genesis();
+    let a1 = Block::new_proposal(
+        Payload::DirectMempool(vec![]),
+        1,
+        1,
+        genesis_qc.clone(),
+        &signer,
+        vec![],
+    )
+    .unwrap();
+    let a1_qc = placeholder_certificate_for_block(&[signer.clone()], a1.id(), 1, genesis.id(), 0);
+    let a2 = Block::new_proposal(
+        Payload::DirectMempool(vec![]),
+        2,
+        2,
+        a1_qc.clone(),
+        &signer,
+        vec![],
+    )
+    .unwrap();
+    // A block whose parent is unknown.
+    let orphan = Block::new_proposal(
+        Payload::DirectMempool(vec![]),
+        6,
+        6,
+        placeholder_certificate_for_block(
+            &[signer.clone()],
+            HashValue::random(),
+            5,
+            HashValue::random(),
+            4,
+        ),
+        &signer,
+        vec![],
+    )
+    .unwrap();
+    db.save_blocks_and_quorum_certificates(
+        vec![genesis.clone(), a1.clone(), a2.clone(), orphan.clone()],
+        vec![genesis_qc, a1_qc],
+    )
+    .unwrap();
+
+    let inspection = Inspection::new(
+        LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty()),
+        &db,
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let statuses = inspection.block_statuses();
+    assert_eq!(statuses[&genesis.id()], BlockStatus::Root);
+    assert_eq!(statuses[&a1.id()], BlockStatus::Descendant);
+    assert_eq!(statuses[&a2.id()], BlockStatus::Descendant);
+    assert_eq!(statuses[&orphan.id()], BlockStatus::Orphaned);
+    assert_eq!(
+        inspection
+            .highest_quorum_cert()
+            .unwrap()
+            .certified_block()
+            .id(),
+        a1.id()
+    );
+    assert_eq!(inspection.orphans().blocks, vec![orphan.id()]);
+
+    let plan = inspection.prune_plan();
+    assert!(plan.root_recoverable);
+    assert_eq!(plan.blocks_to_delete, vec![orphan.id()]);
+    assert!(!plan.delete_last_vote);
+}
+
+#[test]
+fn test_prune_plan_non_epoch_ending_root() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+    let signer = ValidatorSigner::random(None);
+    let (blocks, qcs, a2_qc, ledger_info) = chain_committing_a1(&signer);
+    let (genesis, a1, a2) = (blocks[0].id(), blocks[1].id(), blocks[2].id());
+    db.save_blocks_and_quorum_certificates(blocks, qcs).unwrap();
+
+    // Without a QC ordering a1, recovery can't find the root and everything goes.
+    let inspection = Inspection::new(ledger_info.clone(), &db, HashMap::new()).unwrap();
+    assert!(!inspection.root_recoverable());
+    let plan = inspection.prune_plan();
+    assert!(!plan.root_recoverable);
+    let mut all_blocks = vec![genesis, a1, a2];
+    all_blocks.sort();
+    assert_eq!(plan.blocks_to_delete, all_blocks);
+
+    // With it, only the already committed ancestors go.
+    db.save_blocks_and_quorum_certificates(vec![], vec![a2_qc])
+        .unwrap();
+    let inspection = Inspection::new(ledger_info, &db, HashMap::new()).unwrap();
+    let statuses = inspection.block_statuses();
+    assert_eq!(statuses[&genesis], BlockStatus::Ancestor);
+    assert_eq!(statuses[&a1], BlockStatus::Root);
+    assert_eq!(statuses[&a2], BlockStatus::Descendant);
+    assert_eq!(
+        inspection
+            .highest_ordered_cert()
+            .unwrap()
+            .commit_info()
+            .id(),
+        a1
+    );
+    assert!(inspection.orphans().blocks.is_empty());
+    let plan = inspection.prune_plan();
+    assert!(plan.root_recoverable);
+    assert_eq!(plan.blocks_to_delete, vec![genesis]);
+}
+
+#[test]
+fn test_prune_to_committed_root() {
+    let tmp_dir = TempPath::new();
+    let signer = ValidatorSigner::random(None);
+    let (blocks, qcs, a2_qc, ledger_info) = chain_committing_a1(&signer);
+    let genesis = blocks[0].id();
+    ConsensusDB::new(&tmp_dir)
+        .save_blocks_and_quorum_certificates(blocks, qcs)
+        .unwrap();
+
+    // Opening doesn't create a missing db.
+    let missing_dir = TempPath::new();
+    let backup_dir = TempPath::new();
+    assert!(prune_to_committed_root(
+        missing_dir.path(),
+        ledger_info.clone(),
+        backup_dir.path(),
+        true,
+        true
+    )
+    .is_err());
+    assert!(!missing_dir.path().exists());
+
+    // The root isn't recoverable, so nothing is deleted without --allow-wipe.
+    assert!(prune_to_committed_root(
+        tmp_dir.path(),
+        ledger_info.clone(),
+        backup_dir.path(),
+        false,
+        true
+    )
+    .is_err());
+    assert_eq!(num_blocks(tmp_dir.path()), 3);
+
+    ConsensusDB::open(tmp_dir.path())
+        .unwrap()
+        .save_blocks_and_quorum_certificates(vec![], vec![a2_qc])
+        .unwrap();
+
+    // A dry run changes nothing.
+    let plan = prune_to_committed_root(
+        tmp_dir.path(),
+        ledger_info.clone(),
+        backup_dir.path(),
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(plan.blocks_to_delete, vec![genesis]);
+    assert_eq!(num_blocks(tmp_dir.path()), 3);
+    assert!(!backup_dir.path().exists());
+
+    // Executing backs the db up first.
+    prune_to_committed_root(tmp_dir.path(), ledger_info, backup_dir.path(), false, true).unwrap();
+    assert_eq!(num_blocks(tmp_dir.path()), 2);
+    assert!(backup_dir.path().join(CONSENSUS_DB_NAME).exists());
+    assert_eq!(num_blocks(backup_dir.path()), 3);
+}
@@ -127,6 +127,40 @@ impl SimulatedTimeService {
             futures::executor::block_on(t.run());
         }
     }
+
+    /// Moves time forward to `now` (it never goes back) and runs the pending tasks that are due
+    /// by then, in deadline order. Used by the consensus simulator, which owns the clock and jumps
+    /// it to the next timer once the nodes are idle.
+    #[cfg(test)]
+    pub fn advance_to(&self, now: Duration) {
+        let mut due: Vec<_> = {
+            let mut inner = self.inner.lock();
+            inner.now = inner.now.max(now).min(inner.max);
+            inner.time_limit = inner.time_limit.max(inner.now);
+            let now = inner.now;
+            let (due, pending): (Vec<_>, Vec<_>) = inner
+                .pending
+                .drain(..)
+                .partition(|(deadline, _)| *deadline <= now);
+            inner.pending = pending;
+            due
+        };
+        due.sort_by_key(|(deadline, _)| *deadline);
+        for (_, mut t) in due {
+            futures::executor::block_on(t.run());
+        }
+    }
+
+    /// The earliest deadline of the pending tasks, if any.
+    #[cfg(test)]
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.inner
+            .lock()
+            .pending
+            .iter()
+            .map(|(deadline, _)| *deadline)
+            .min()
+    }
 }
 
 impl Clone for SimulatedTimeService {
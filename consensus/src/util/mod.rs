@@ -7,6 +7,10 @@ use aptos_types::{
     validator_txn::ValidatorTransaction,
 };
 
+#[cfg(feature = "db-inspector")]
+pub mod db_inspector;
+#[cfg(all(test, feature = "db-inspector"))]
+mod db_inspector_test;
 pub mod db_tool;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock_time_service;
@@ -5,11 +5,14 @@ use crate::server::utils::reply_with_status;
 use aptos_config::config::{AuthenticationConfig, NodeConfig};
 use aptos_consensus::{
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
+    transaction_filter::TransactionFilter,
 };
 use aptos_infallible::RwLock;
 use aptos_logger::info;
+use aptos_mempool::MempoolTransactionFilter;
 use aptos_storage_interface::DbReaderWriter;
 use hyper::{
+    server::conn::AddrStream,
     service::{make_service_fn, service_fn},
     Body, Request, Response, Server, StatusCode,
 };
@@ -24,8 +27,10 @@ use tokio::runtime::Runtime;
 mod consensus;
 #[cfg(target_os = "linux")]
 pub mod profiling;
+mod pruner;
 #[cfg(target_os = "linux")]
 mod thread_dump;
+mod transaction_filter;
 mod utils;
 
 #[derive(Default)]
@@ -35,6 +40,9 @@ pub struct Context {
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+    transaction_filter: RwLock<Option<TransactionFilter>>,
+    mempool_transaction_filter: RwLock<Option<MempoolTransactionFilter>>,
+    transaction_filter_audit_log: Arc<transaction_filter::FilterAuditLog>,
 }
 
 impl Context {
@@ -50,6 +58,86 @@ impl Context {
         *self.consensus_db.write() = Some(consensus_db);
         *self.quorum_store_db.write() = Some(quorum_store_db);
     }
+
+    fn set_transaction_filter(&self, transaction_filter: TransactionFilter) {
+        *self.transaction_filter.write() = Some(transaction_filter);
+    }
+
+    fn set_mempool_transaction_filter(&self, transaction_filter: MempoolTransactionFilter) {
+        *self.mempool_transaction_filter.write() = Some(transaction_filter);
+    }
+
+    /// Returns the transaction filter of the component the given endpoint path belongs to.
+    fn reloadable_filter(&self, path: &str) -> Option<transaction_filter::ReloadableFilter> {
+        if path.starts_with("/debug/mempool/") {
+            self.mempool_transaction_filter
+                .read()
+                .clone()
+                .map(transaction_filter::ReloadableFilter::Mempool)
+        } else {
+            self.transaction_filter
+                .read()
+                .clone()
+                .map(transaction_filter::ReloadableFilter::Consensus)
+        }
+    }
+
+    /// Authenticates the request against the configured authentication configs. Returns None if
+    /// the request doesn't match any of them.
+    fn authenticate(&self, req: &Request<Body>) -> Option<Authentication> {
+        if self.authentication_configs.is_empty() {
+            return Some(Authentication::Disabled);
+        }
+
+        for authentication_config in &self.authentication_configs {
+            match authentication_config {
+                AuthenticationConfig::PasscodeSha256(passcode_sha256) => {
+                    let query = req.uri().query().unwrap_or("");
+                    let query_pairs: HashMap<_, _> =
+                        url::form_urlencoded::parse(query.as_bytes()).collect();
+                    let passcode: Option<String> =
+                        query_pairs.get("passcode").map(|p| p.to_string());
+                    if let Some(passcode) = passcode {
+                        if sha256::digest(passcode) == *passcode_sha256 {
+                            // Operators are told apart by their passcodes, so identify them by
+                            // a prefix of the passcode hash, which is safe to log.
+                            let prefix_len = std::cmp::min(8, passcode_sha256.len());
+                            return Some(Authentication::Operator(format!(
+                                "passcode_sha256:{}",
+                                &passcode_sha256[..prefix_len]
+                            )));
+                        }
+                    }
+                },
+            }
+        }
+        None
+    }
+}
+
+/// How a request was authenticated.
+enum Authentication {
+    /// No authentication is configured, so every request is accepted anonymously.
+    Disabled,
+    /// The request carried the credential of the given operator.
+    Operator(String),
+}
+
+impl Authentication {
+    /// Returns the operator changes made by this request are attributed to, or the response to
+    /// reply with if the request isn't allowed to change the node's state.
+    fn operator(self, req: &Request<Body>) -> Result<String, Response<Body>> {
+        match self {
+            Authentication::Operator(operator) => Ok(operator),
+            Authentication::Disabled => Err(reply_with_status(
+                StatusCode::FORBIDDEN,
+                format!(
+                    "{} endpoint requires authentication configs to be set up.",
+                    req.uri().path()
+                ),
+            )),
+        }
+    }
 }
 
 pub struct AdminService {
@@ -108,14 +196,24 @@ impl AdminService {
             .set_consensus_dbs(consensus_db, quorum_store_db)
     }
 
+    pub fn set_transaction_filter(&self, transaction_filter: TransactionFilter) {
+        self.context.set_transaction_filter(transaction_filter)
+    }
+
+    pub fn set_mempool_transaction_filter(&self, transaction_filter: MempoolTransactionFilter) {
+        self.context
+            .set_mempool_transaction_filter(transaction_filter)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
-            let make_service = make_service_fn(move |_conn| {
+            let make_service = make_service_fn(move |conn: &AddrStream| {
                 let context = context.clone();
+                let remote_address = conn.remote_addr();
                 async move {
                     Ok::<_, Infallible>(service_fn(move |req| {
-                        Self::serve_requests(context.clone(), req, enabled)
+                        Self::serve_requests(context.clone(), req, remote_address, enabled)
                     }))
                 }
             });
@@ -129,6 +227,7 @@ impl AdminService {
     async fn serve_requests(
         context: Arc<Context>,
         req: Request<Body>,
+        remote_address: SocketAddr,
         enabled: bool,
     ) -> hyper::Result<Response<Body>> {
         if !enabled {
@@ -138,35 +237,16 @@ impl AdminService {
             ));
         }
 
-        let mut authenticated = false;
-        if context.authentication_configs.is_empty() {
-            authenticated = true;
-        } else {
-            for authentication_config in &context.authentication_configs {
-                match authentication_config {
-                    AuthenticationConfig::PasscodeSha256(passcode_sha256) => {
-                        let query = req.uri().query().unwrap_or("");
-                        let query_pairs: HashMap<_, _> =
-                            url::form_urlencoded::parse(query.as_bytes()).collect();
-                        let passcode: Option<String> =
-                            query_pairs.get("passcode").map(|p| p.to_string());
-                        if let Some(passcode) = passcode {
-                            if sha256::digest(passcode) == *passcode_sha256 {
-                                authenticated = true;
-                            }
-                        }
-                    },
-                }
-            }
+        let authentication = match context.authenticate(&req) {
+            Some(authentication) => authentication,
+            None => {
+                return Ok(reply_with_status(
+                    StatusCode::NETWORK_AUTHENTICATION_REQUIRED,
+                    format!("{} endpoint requires authentication.", req.uri().path()),
+                ))
+            },
         };
 
-        if !authenticated {
-            return Ok(reply_with_status(
-                StatusCode::NETWORK_AUTHENTICATION_REQUIRED,
-                format!("{} endpoint requires authentication.", req.uri().path()),
-            ));
-        }
-
         match (req.method().clone(), req.uri().path()) {
             #[cfg(target_os = "linux")]
             (hyper::Method::GET, "/profilez") => profiling::handle_cpu_profiling_request(req).await,
@@ -211,6 +291,105 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/consensus/transaction_filter")
+            | (hyper::Method::GET, "/debug/mempool/transaction_filter") => {
+                if let Some(transaction_filter) = context.reloadable_filter(req.uri().path()) {
+                    transaction_filter::handle_get_transaction_filter_request(
+                        req,
+                        transaction_filter,
+                    )
+                    .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Transaction filter is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::PUT, "/debug/consensus/transaction_filter")
+            | (hyper::Method::PUT, "/debug/mempool/transaction_filter") => {
+                let operator = match authentication.operator(&req) {
+                    Ok(operator) => operator,
+                    Err(response) => return Ok(response),
+                };
+                if let Some(transaction_filter) = context.reloadable_filter(req.uri().path()) {
+                    transaction_filter::handle_update_transaction_filter_request(
+                        req,
+                        operator,
+                        remote_address,
+                        transaction_filter,
+                        context.transaction_filter_audit_log.clone(),
+                    )
+                    .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Transaction filter is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/consensus/transaction_filter/dry_run") => {
+                let transaction_filter = context.transaction_filter.read().clone();
+                let consensus_db = context.consensus_db.read().clone();
+                let quorum_store_db = context.quorum_store_db.read().clone();
+                match (transaction_filter, consensus_db, quorum_store_db) {
+                    (Some(transaction_filter), Some(consensus_db), Some(quorum_store_db)) => {
+                        transaction_filter::handle_dry_run_transaction_filter_request(
+                            req,
+                            transaction_filter,
+                            consensus_db,
+                            quorum_store_db,
+                        )
+                        .await
+                    },
+                    _ => Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Transaction filter, consensus db and/or quorum store db is not available.",
+                    )),
+                }
+            },
+            (hyper::Method::GET, "/debug/consensus/transaction_filter/audit_log") => {
+                transaction_filter::handle_transaction_filter_audit_log_request(
+                    req,
+                    context.transaction_filter_audit_log.clone(),
+                )
+                .await
+            },
+            (hyper::Method::GET, "/debug/storage/pruners") => {
+                let aptos_db = context.aptos_db.read().clone();
+                if let Some(aptos_db) = aptos_db {
+                    pruner::handle_get_pruner_statuses_request(req, aptos_db).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Aptos db is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/storage/pruners/pause")
+            | (hyper::Method::POST, "/debug/storage/pruners/resume") => {
+                let operator = match authentication.operator(&req) {
+                    Ok(operator) => operator,
+                    Err(response) => return Ok(response),
+                };
+                let paused = req.uri().path().ends_with("/pause");
+                let aptos_db = context.aptos_db.read().clone();
+                if let Some(aptos_db) = aptos_db {
+                    pruner::handle_set_pruner_paused_request(
+                        req,
+                        operator,
+                        remote_address,
+                        aptos_db,
+                        paused,
+                    )
+                    .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Aptos db is not available.",
+                    ))
+                }
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
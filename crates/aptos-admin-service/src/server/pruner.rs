@@ -0,0 +1,61 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{reply_with, reply_with_status, spawn_blocking};
+use aptos_logger::info;
+use aptos_storage_interface::DbReaderWriter;
+use hyper::{Body, Request, Response, StatusCode};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+pub async fn handle_get_pruner_statuses_request(
+    _req: Request<Body>,
+    aptos_db: Arc<DbReaderWriter>,
+) -> hyper::Result<Response<Body>> {
+    let result = spawn_blocking(move || {
+        let statuses = aptos_db.reader.get_pruner_statuses()?;
+        Ok(serde_yaml::to_string(&statuses)?)
+    })
+    .await;
+
+    match result {
+        Ok(result) => Ok(reply_with(vec![], result)),
+        Err(e) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Pauses or resumes the pruner given by the `pruner` query parameter, or all enabled pruners if
+/// it's not provided, on behalf of the operator the request was authenticated as. Only lasts
+/// until the node restarts.
+pub async fn handle_set_pruner_paused_request(
+    req: Request<Body>,
+    operator: String,
+    remote_address: SocketAddr,
+    aptos_db: Arc<DbReaderWriter>,
+    paused: bool,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+    let pruner = query_pairs.get("pruner").map(|pruner| pruner.to_string());
+
+    info!(
+        operator = operator,
+        remote_address = remote_address,
+        pruner = pruner,
+        paused = paused,
+        "Setting pruner paused."
+    );
+
+    match spawn_blocking(move || {
+        Ok(aptos_db
+            .writer
+            .set_pruner_paused(pruner.as_deref(), paused)?)
+    })
+    .await
+    {
+        Ok(()) => Ok(reply_with_status(StatusCode::OK, "OK")),
+        Err(e) => Ok(reply_with_status(StatusCode::BAD_REQUEST, e.to_string())),
+    }
+}
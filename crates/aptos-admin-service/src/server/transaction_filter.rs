@@ -0,0 +1,286 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{reply_with, reply_with_status, spawn_blocking};
+use anyhow::Error;
+use aptos_config::config::transaction_filter_type::Filter;
+use aptos_consensus::{
+    persistent_liveness_storage::PersistentLivenessStorage,
+    quorum_store::quorum_store_db::QuorumStoreStorage, transaction_filter::TransactionFilter,
+    util::db_tool::extract_txns_from_block,
+};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::{info, Key, Schema, Value, Visitor};
+use aptos_mempool::MempoolTransactionFilter;
+use hyper::{Body, Request, Response, StatusCode};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+};
+
+/// The maximum number of filter changes kept in the in-memory audit log.
+const MAX_AUDIT_LOG_ENTRIES: usize = 100;
+
+/// The component a transaction filter applies to.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterTarget {
+    Consensus,
+    Mempool,
+}
+
+/// A transaction filter that can be replaced at runtime.
+#[derive(Clone)]
+pub enum ReloadableFilter {
+    Consensus(TransactionFilter),
+    Mempool(MempoolTransactionFilter),
+}
+
+impl ReloadableFilter {
+    fn target(&self) -> FilterTarget {
+        match self {
+            ReloadableFilter::Consensus(_) => FilterTarget::Consensus,
+            ReloadableFilter::Mempool(_) => FilterTarget::Mempool,
+        }
+    }
+
+    fn current(&self) -> Arc<Filter> {
+        match self {
+            ReloadableFilter::Consensus(filter) => filter.current(),
+            ReloadableFilter::Mempool(filter) => filter.current(),
+        }
+    }
+
+    fn update(&self, new_filter: Filter) -> Arc<Filter> {
+        match self {
+            ReloadableFilter::Consensus(filter) => filter.update(new_filter),
+            ReloadableFilter::Mempool(filter) => filter.update(new_filter),
+        }
+    }
+}
+
+/// A single change of an active transaction filter.
+#[derive(Clone, Debug, Serialize)]
+pub struct FilterChange {
+    pub timestamp_usecs: u64,
+    pub target: FilterTarget,
+    pub operator: String,
+    pub remote_address: SocketAddr,
+    pub previous_filter: Filter,
+    pub new_filter: Filter,
+}
+
+impl Schema for FilterChange {
+    fn visit(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_pair(
+            Key::new("transaction_filter_change"),
+            Value::from_serde(self),
+        )
+    }
+}
+
+/// Keeps track of the most recent changes made to the transaction filters. Every change is also
+/// emitted to the structured log, so the complete history is available in the node logs.
+#[derive(Default)]
+pub struct FilterAuditLog {
+    changes: Mutex<VecDeque<FilterChange>>,
+}
+
+impl FilterAuditLog {
+    fn record(&self, change: FilterChange) {
+        info!(change, "Transaction filter updated.");
+
+        let mut changes = self.changes.lock();
+        if changes.len() >= MAX_AUDIT_LOG_ENTRIES {
+            changes.pop_front();
+        }
+        changes.push_back(change);
+    }
+
+    fn changes(&self) -> Vec<FilterChange> {
+        self.changes.lock().iter().cloned().collect()
+    }
+}
+
+pub async fn handle_get_transaction_filter_request(
+    _req: Request<Body>,
+    transaction_filter: ReloadableFilter,
+) -> hyper::Result<Response<Body>> {
+    match serde_yaml::to_string(transaction_filter.current().as_ref()) {
+        Ok(result) => Ok(reply_with(vec![], result)),
+        Err(e) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Replaces the transaction filter with the one in the request body, on behalf of the operator
+/// the request was authenticated as.
+pub async fn handle_update_transaction_filter_request(
+    req: Request<Body>,
+    operator: String,
+    remote_address: SocketAddr,
+    transaction_filter: ReloadableFilter,
+    audit_log: Arc<FilterAuditLog>,
+) -> hyper::Result<Response<Body>> {
+    let new_filter = match parse_filter(req).await {
+        Ok(Some(filter)) => filter,
+        Ok(None) => {
+            return Ok(reply_with_status(
+                StatusCode::BAD_REQUEST,
+                "The request body must contain the new transaction filter.",
+            ))
+        },
+        Err(e) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, e.to_string())),
+    };
+
+    let previous_filter = transaction_filter.update(new_filter.clone());
+    audit_log.record(FilterChange {
+        timestamp_usecs: aptos_infallible::duration_since_epoch().as_micros() as u64,
+        target: transaction_filter.target(),
+        operator,
+        remote_address,
+        previous_filter: previous_filter.as_ref().clone(),
+        new_filter,
+    });
+
+    match serde_yaml::to_string(previous_filter.as_ref()) {
+        Ok(result) => Ok(reply_with(vec![], result)),
+        Err(e) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+pub async fn handle_dry_run_transaction_filter_request(
+    req: Request<Body>,
+    transaction_filter: TransactionFilter,
+    consensus_db: Arc<dyn PersistentLivenessStorage>,
+    quorum_store_db: Arc<dyn QuorumStoreStorage>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let block_id: Option<HashValue> = match query_pairs.get("block_id") {
+        Some(val) => match val.parse() {
+            Ok(val) => Some(val),
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => None,
+    };
+
+    // Dry-run the filter in the request body, or the active filter if none is given
+    let filter = match parse_filter(req).await {
+        Ok(Some(filter)) => Arc::new(filter),
+        Ok(None) => transaction_filter.current(),
+        Err(e) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, e.to_string())),
+    };
+
+    info!("Dry-running transaction filter.");
+
+    match spawn_blocking(move || {
+        dry_run_filter(
+            filter.as_ref(),
+            consensus_db.as_ref(),
+            quorum_store_db.as_ref(),
+            block_id,
+        )
+    })
+    .await
+    {
+        Ok(result) => {
+            info!("Finished dry-running transaction filter.");
+            Ok(reply_with(vec![], result))
+        },
+        Err(e) => {
+            info!("Failed to dry-run transaction filter: {e:?}");
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string(),
+            ))
+        },
+    }
+}
+
+pub async fn handle_transaction_filter_audit_log_request(
+    _req: Request<Body>,
+    audit_log: Arc<FilterAuditLog>,
+) -> hyper::Result<Response<Body>> {
+    match serde_yaml::to_string(&audit_log.changes()) {
+        Ok(result) => Ok(reply_with(vec![], result)),
+        Err(e) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Parses a YAML encoded filter from the request body. Returns None if the body is empty.
+async fn parse_filter(req: Request<Body>) -> anyhow::Result<Option<Filter>> {
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(Error::msg)?;
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    Ok(Some(serde_yaml::from_slice(&body)?))
+}
+
+fn dry_run_filter(
+    filter: &Filter,
+    consensus_db: &dyn PersistentLivenessStorage,
+    quorum_store_db: &dyn QuorumStoreStorage,
+    block_id: Option<HashValue>,
+) -> anyhow::Result<String> {
+    let mut body = String::new();
+
+    let all_batches = quorum_store_db.get_all_batches()?;
+
+    let (_, _, blocks, _) = consensus_db.consensus_db().get_data()?;
+
+    for block in blocks {
+        let id = block.id();
+        if block_id.is_none() || id == block_id.unwrap() {
+            match extract_txns_from_block(&block, &all_batches) {
+                Ok(txns) => {
+                    let denied_txns: Vec<_> = txns
+                        .iter()
+                        .filter(|txn| !filter.allows(id, block.timestamp_usecs(), txn))
+                        .collect();
+                    body.push_str(&format!(
+                        "Block ({id:?}): {} txns, {} denied\n",
+                        txns.len(),
+                        denied_txns.len()
+                    ));
+                    for txn in denied_txns {
+                        body.push_str(&format!(
+                            "  Denied txn ({:?}), sender: {}, sequence number: {}\n",
+                            (*txn).clone().committed_hash(),
+                            txn.sender(),
+                            txn.sequence_number(),
+                        ));
+                    }
+                },
+                Err(e) => {
+                    body.push_str(&format!("Block ({id:?}): not available: {e:?}\n"));
+                },
+            };
+            body.push('\n');
+        }
+    }
+
+    if body.is_empty() {
+        if let Some(block_id) = block_id {
+            body.push_str(&format!("Done, block ({block_id:?}) is not found."));
+        } else {
+            body.push_str("Done, no block is found.");
+        }
+    }
+
+    Ok(body)
+}
@@ -9,6 +9,9 @@ pub enum Cmd {
     #[clap(subcommand)]
     AptosDb(aptos_db_tool::DBTool),
 
+    #[clap(subcommand)]
+    ConsensusDb(aptos_consensus::util::db_inspector::Cmd),
+
     Decode(aptos_move_debugger::bcs_txn_decoder::Command),
 
     DumpPendingTxns(aptos_consensus::util::db_tool::Command),
@@ -21,6 +24,7 @@ impl Cmd {
     pub async fn run(self) -> Result<()> {
         match self {
             Cmd::AptosDb(cmd) => cmd.run().await,
+            Cmd::ConsensusDb(cmd) => cmd.run().await,
             Cmd::Decode(cmd) => cmd.run().await,
             Cmd::DumpPendingTxns(cmd) => cmd.run().await,
             Cmd::Move(cmd) => cmd.run().await,
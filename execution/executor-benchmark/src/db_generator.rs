@@ -19,6 +19,11 @@ use aptos_storage_interface::DbReaderWriter;
 use aptos_vm::AptosVM;
 use std::{fs, path::Path};
 
+/// Creates a DB with genesis and `num_accounts` funded accounts at `db_dir`. With
+/// `enable_in_memory_backend`, the DBs must be retained with
+/// [`InMemoryBackend::retain_dbs`](aptos_schemadb::backend::InMemoryBackend::retain_dbs), as the
+/// genesis DB is closed before the accounts are created.
+#[allow(clippy::too_many_arguments)]
 pub fn create_db_with_accounts<V>(
     num_accounts: usize,
     init_account_balance: u64,
@@ -27,6 +32,7 @@ pub fn create_db_with_accounts<V>(
     storage_pruner_config: PrunerConfig,
     verify_sequence_numbers: bool,
     enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
     pipeline_config: PipelineConfig,
 ) where
     V: TransactionBlockExecutor + 'static,
@@ -39,7 +45,7 @@ pub fn create_db_with_accounts<V>(
     // create if not exists
     fs::create_dir_all(db_dir.as_ref()).unwrap();
 
-    bootstrap_with_genesis(&db_dir, enable_storage_sharding);
+    bootstrap_with_genesis(&db_dir, enable_storage_sharding, enable_in_memory_backend);
 
     println!(
         "Finished empty DB creation, DB dir: {}. Creating accounts now...",
@@ -55,16 +61,22 @@ pub fn create_db_with_accounts<V>(
         storage_pruner_config,
         verify_sequence_numbers,
         enable_storage_sharding,
+        enable_in_memory_backend,
         pipeline_config,
     );
 }
 
-fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, enable_storage_sharding: bool) {
+fn bootstrap_with_genesis(
+    db_dir: impl AsRef<Path>,
+    enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
+) {
     let (config, _genesis_key) = aptos_genesis::test_utils::test_config();
 
     let mut rocksdb_configs = RocksdbConfigs::default();
     rocksdb_configs.state_merkle_db_config.max_open_files = -1;
     rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    rocksdb_configs.enable_in_memory_backend = enable_in_memory_backend;
     let (_db, db_rw) = DbReaderWriter::wrap(
         AptosDB::open(
             StorageDirPaths::from_path(db_dir),
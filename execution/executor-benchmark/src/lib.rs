@@ -79,6 +79,7 @@ fn create_checkpoint(
     source_dir: impl AsRef<Path>,
     checkpoint_dir: impl AsRef<Path>,
     enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
 ) {
     // Create rocksdb checkpoint.
     if checkpoint_dir.as_ref().exists() {
@@ -86,11 +87,17 @@ fn create_checkpoint(
     }
     std::fs::create_dir_all(checkpoint_dir.as_ref()).unwrap();
 
-    AptosDB::create_checkpoint(source_dir, checkpoint_dir, enable_storage_sharding)
-        .expect("db checkpoint creation fails.");
+    AptosDB::create_checkpoint(
+        source_dir,
+        checkpoint_dir,
+        enable_storage_sharding,
+        enable_in_memory_backend,
+    )
+    .expect("db checkpoint creation fails.");
 }
 
-/// Runs the benchmark with given parameters.
+/// Runs the benchmark with given parameters. With `enable_in_memory_backend`, the DB at
+/// `source_dir` must be an in-memory one created by the same process, and still alive.
 #[allow(clippy::too_many_arguments)]
 pub fn run_benchmark<V>(
     block_size: usize,
@@ -107,6 +114,7 @@ pub fn run_benchmark<V>(
     verify_sequence_numbers: bool,
     pruner_config: PrunerConfig,
     enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
     pipeline_config: PipelineConfig,
 ) where
     V: TransactionBlockExecutor + 'static,
@@ -115,12 +123,14 @@ pub fn run_benchmark<V>(
         source_dir.as_ref(),
         checkpoint_dir.as_ref(),
         enable_storage_sharding,
+        enable_in_memory_backend,
     );
 
     let (mut config, genesis_key) = aptos_genesis::test_utils::test_config();
     config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
     config.storage.storage_pruner_config = pruner_config;
     config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    config.storage.rocksdb_configs.enable_in_memory_backend = enable_in_memory_backend;
 
     let (db, executor) = init_db_and_executor::<V>(&config);
     let mut root_account = TransactionGenerator::read_root_account(genesis_key, &db);
@@ -295,6 +305,7 @@ pub fn add_accounts<V>(
     pruner_config: PrunerConfig,
     verify_sequence_numbers: bool,
     enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
     pipeline_config: PipelineConfig,
 ) where
     V: TransactionBlockExecutor + 'static,
@@ -304,6 +315,7 @@ pub fn add_accounts<V>(
         source_dir.as_ref(),
         checkpoint_dir.as_ref(),
         enable_storage_sharding,
+        enable_in_memory_backend,
     );
     add_accounts_impl::<V>(
         num_new_accounts,
@@ -314,6 +326,7 @@ pub fn add_accounts<V>(
         pruner_config,
         verify_sequence_numbers,
         enable_storage_sharding,
+        enable_in_memory_backend,
         pipeline_config,
     );
 }
@@ -327,6 +340,7 @@ fn add_accounts_impl<V>(
     pruner_config: PrunerConfig,
     verify_sequence_numbers: bool,
     enable_storage_sharding: bool,
+    enable_in_memory_backend: bool,
     pipeline_config: PipelineConfig,
 ) where
     V: TransactionBlockExecutor + 'static,
@@ -335,6 +349,7 @@ fn add_accounts_impl<V>(
     config.storage.dir = output_dir.as_ref().to_path_buf();
     config.storage.storage_pruner_config = pruner_config;
     config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    config.storage.rocksdb_configs.enable_in_memory_backend = enable_in_memory_backend;
     let (db, executor) = init_db_and_executor::<V>(&config);
 
     let start_version = db.reader.get_latest_version().unwrap();
@@ -696,6 +711,7 @@ mod tests {
     use crate::{native_executor::NativeExecutor, pipeline::PipelineConfig};
     use aptos_config::config::NO_OP_STORAGE_PRUNER_CONFIG;
     use aptos_executor::block_executor::TransactionBlockExecutor;
+    use aptos_schemadb::backend::InMemoryBackend;
     use aptos_temppath::TempPath;
     use aptos_transaction_generator_lib::{args::TransactionTypeArg, WorkflowProgress};
     use aptos_vm::AptosVM;
@@ -703,6 +719,7 @@ mod tests {
     fn test_generic_benchmark<E>(
         transaction_type: Option<TransactionTypeArg>,
         verify_sequence_numbers: bool,
+        enable_in_memory_backend: bool,
     ) where
         E: TransactionBlockExecutor + 'static,
     {
@@ -722,6 +739,7 @@ mod tests {
             NO_OP_STORAGE_PRUNER_CONFIG, /* prune_window */
             verify_sequence_numbers,
             false,
+            enable_in_memory_backend,
             PipelineConfig::default(),
         );
 
@@ -743,13 +761,21 @@ mod tests {
             verify_sequence_numbers,
             NO_OP_STORAGE_PRUNER_CONFIG,
             false,
+            enable_in_memory_backend,
             PipelineConfig::default(),
         );
     }
 
     #[test]
     fn test_benchmark_default() {
-        test_generic_benchmark::<AptosVM>(None, true);
+        test_generic_benchmark::<AptosVM>(None, true, false);
+    }
+
+    #[test]
+    fn test_benchmark_in_memory() {
+        // The genesis DB is dropped before the accounts are created
+        let _retained = InMemoryBackend::retain_dbs();
+        test_generic_benchmark::<AptosVM>(None, true, true);
     }
 
     #[test]
@@ -761,12 +787,13 @@ mod tests {
         test_generic_benchmark::<AptosVM>(
             Some(TransactionTypeArg::ResourceGroupsGlobalWriteTag1KB),
             true,
+            false,
         );
     }
 
     #[test]
     fn test_native_benchmark() {
         // correct execution not yet implemented, so cannot be checked for validity
-        test_generic_benchmark::<NativeExecutor>(None, false);
+        test_generic_benchmark::<NativeExecutor>(None, false, false);
     }
 }
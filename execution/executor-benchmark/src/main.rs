@@ -22,6 +22,7 @@ use aptos_experimental_runtimes::thread_manager::{ThreadConfigStrategy, ThreadMa
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_profiler::{ProfilerConfig, ProfilerHandler};
 use aptos_push_metrics::MetricsPusher;
+use aptos_schemadb::backend::InMemoryBackend;
 use aptos_transaction_generator_lib::{args::TransactionTypeArg, WorkflowProgress};
 use aptos_vm::AptosVM;
 use clap::{ArgGroup, Parser, Subcommand};
@@ -41,6 +42,8 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 pub static START_TIME: Lazy<IntGauge> =
     Lazy::new(|| register_int_gauge!("node_process_start_time", "Start time").unwrap());
 
+const DEFAULT_INIT_ACCOUNT_BALANCE: u64 = 10000000000;
+
 #[derive(Debug, Parser)]
 struct PrunerOpt {
     #[clap(long)]
@@ -244,6 +247,12 @@ struct Opt {
     #[clap(long)]
     enable_storage_sharding: bool,
 
+    /// Keeps the DBs in memory instead of in RocksDB. Only supported by `run-executor`, which then
+    /// creates the accounts DB at `data-dir` first, as in-memory DBs don't outlive the process.
+    /// `data-dir` must not exist.
+    #[clap(long)]
+    enable_in_memory_backend: bool,
+
     #[clap(flatten)]
     pipeline_opt: PipelineOpt,
 
@@ -283,7 +292,7 @@ enum Command {
         #[clap(long, default_value_t = 1000000)]
         num_accounts: usize,
 
-        #[clap(long, default_value_t = 10000000000)]
+        #[clap(long, default_value_t = DEFAULT_INIT_ACCOUNT_BALANCE)]
         init_account_balance: u64,
     },
     RunExecutor {
@@ -347,6 +356,10 @@ where
             num_accounts,
             init_account_balance,
         } => {
+            assert!(
+                !opt.enable_in_memory_backend,
+                "An in-memory DB doesn't outlive the process, use run-executor directly."
+            );
             aptos_executor_benchmark::db_generator::create_db_with_accounts::<E>(
                 num_accounts,
                 init_account_balance,
@@ -355,6 +368,7 @@ where
                 opt.pruner_opt.pruner_config(),
                 opt.verify_sequence_numbers,
                 opt.enable_storage_sharding,
+                false,
                 opt.pipeline_opt.pipeline_config(),
             );
         },
@@ -390,6 +404,20 @@ where
                 }
             }
 
+            if opt.enable_in_memory_backend {
+                aptos_executor_benchmark::db_generator::create_db_with_accounts::<E>(
+                    main_signer_accounts + additional_dst_pool_accounts,
+                    DEFAULT_INIT_ACCOUNT_BALANCE,
+                    opt.block_size,
+                    &data_dir,
+                    opt.pruner_opt.pruner_config(),
+                    opt.verify_sequence_numbers,
+                    opt.enable_storage_sharding,
+                    true,
+                    opt.pipeline_opt.pipeline_config(),
+                );
+            }
+
             aptos_executor_benchmark::run_benchmark::<E>(
                 opt.block_size,
                 blocks,
@@ -405,6 +433,7 @@ where
                 opt.verify_sequence_numbers,
                 opt.pruner_opt.pruner_config(),
                 opt.enable_storage_sharding,
+                opt.enable_in_memory_backend,
                 opt.pipeline_opt.pipeline_config(),
             );
         },
@@ -414,6 +443,10 @@ where
             num_new_accounts,
             init_account_balance,
         } => {
+            assert!(
+                !opt.enable_in_memory_backend,
+                "An in-memory DB doesn't outlive the process, use run-executor directly."
+            );
             aptos_executor_benchmark::add_accounts::<E>(
                 num_new_accounts,
                 init_account_balance,
@@ -423,6 +456,7 @@ where
                 opt.pruner_opt.pruner_config(),
                 opt.verify_sequence_numbers,
                 opt.enable_storage_sharding,
+                false,
                 opt.pipeline_opt.pipeline_config(),
             );
         },
@@ -495,6 +529,11 @@ fn main() {
     let config = ProfilerConfig::new_with_defaults();
     let handler = ProfilerHandler::new(config);
 
+    // The benchmark closes and opens the DBs again, e.g. after the genesis.
+    let _retained_in_memory_dbs = opt
+        .enable_in_memory_backend
+        .then(InMemoryBackend::retain_dbs);
+
     let cpu_profiling = opt.profiler_opt.cpu_profiling;
     let memory_profiling = opt.profiler_opt.memory_profiling;
 
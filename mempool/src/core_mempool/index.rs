@@ -3,7 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 /// This module provides various indexes used by Mempool.
-use crate::core_mempool::transaction::{MempoolTransaction, SequenceInfo, TimelineState};
+use crate::core_mempool::{
+    ordering_policy::MempoolOrderingPolicy,
+    transaction::{MempoolTransaction, SequenceInfo, TimelineState},
+};
 use crate::{
     counters,
     logging::{LogEntry, LogSchema},
@@ -18,6 +21,7 @@ use std::{
     collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap},
     iter::Rev,
     ops::Bound,
+    sync::Arc,
     time::Duration,
 };
 
@@ -25,20 +29,23 @@ pub type AccountTransactions = BTreeMap<u64, MempoolTransaction>;
 
 /// PriorityIndex represents the main Priority Queue in Mempool.
 /// It's used to form the transaction block for Consensus.
-/// Transactions are ordered by gas price. Second level ordering is done by expiration time.
+/// Transactions are ordered by the score assigned by the `MempoolOrderingPolicy` (gas price by
+/// default). Second level ordering is done by expiration time.
 ///
 /// We don't store the full content of transactions in the index.
 /// Instead we use `OrderedQueueKey` - logical reference to the transaction in the main store.
 pub struct PriorityIndex {
     data: BTreeSet<OrderedQueueKey>,
+    ordering_policy: Arc<dyn MempoolOrderingPolicy>,
 }
 
 pub type PriorityQueueIter<'a> = Rev<Iter<'a, OrderedQueueKey>>;
 
 impl PriorityIndex {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(ordering_policy: Arc<dyn MempoolOrderingPolicy>) -> Self {
         Self {
             data: BTreeSet::new(),
+            ordering_policy,
         }
     }
 
@@ -54,9 +61,14 @@ impl PriorityIndex {
         self.data.contains(&self.make_key(txn))
     }
 
+    /// Returns the score the transaction is ordered by, as assigned by the ordering policy
+    pub(crate) fn priority_score(&self, txn: &MempoolTransaction) -> u64 {
+        self.ordering_policy.priority_score(txn)
+    }
+
     fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
-            gas_ranking_score: txn.ranking_score,
+            gas_ranking_score: self.priority_score(txn),
             expiration_time: txn.expiration_time,
             address: txn.get_sender(),
             sequence_number: txn.sequence_info,
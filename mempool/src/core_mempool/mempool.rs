@@ -7,12 +7,15 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
+        ordering_policy::create_ordering_policy,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
-    shared_mempool::types::MultiBucketTimelineIndexIds,
+    shared_mempool::types::{
+        MempoolStats, MultiBucketTimelineIndexIds, PendingAccountInfo, PendingTransactionInfo,
+    },
 };
 use aptos_config::config::NodeConfig;
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
@@ -40,7 +43,10 @@ pub struct Mempool {
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
         Mempool {
-            transactions: TransactionStore::new(&config.mempool),
+            transactions: TransactionStore::new(
+                &config.mempool,
+                create_ordering_policy(&config.mempool.ordering_policy),
+            ),
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
@@ -451,6 +457,26 @@ impl Mempool {
         self.transactions.gen_snapshot()
     }
 
+    pub(crate) fn get_pending_transactions(&self, limit: usize) -> Vec<PendingTransactionInfo> {
+        self.transactions.get_pending_transactions(limit)
+    }
+
+    pub(crate) fn get_pending_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> PendingAccountInfo {
+        self.transactions.get_pending_account_transactions(address)
+    }
+
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        self.transactions.get_stats()
+    }
+
+    /// Returns an iterator over all transactions in mempool, in no particular order.
+    pub(crate) fn iter_transactions(&self) -> impl Iterator<Item = &MempoolTransaction> {
+        self.transactions.iter_transactions()
+    }
+
     #[cfg(test)]
     pub fn get_parking_lot_size(&self) -> usize {
         self.transactions.get_parking_lot_size()
@@ -4,6 +4,7 @@
 
 mod index;
 mod mempool;
+mod ordering_policy;
 mod transaction;
 mod transaction_store;
 
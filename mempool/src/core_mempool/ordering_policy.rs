@@ -0,0 +1,76 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::core_mempool::transaction::MempoolTransaction;
+use aptos_config::config::MempoolOrderingPolicyType;
+use std::sync::Arc;
+
+/// Scale applied to the gas unit price before dividing by the transaction size, so that
+/// per-byte scores keep enough precision to differentiate transactions.
+const GAS_PRICE_PER_BYTE_SCALE: u128 = 1_000;
+
+/// Defines the primary order of transactions in the `PriorityIndex`, i.e., the order in which
+/// transactions are pulled into blocks. Transactions with higher scores are pulled first; ties
+/// are broken by expiration time and insertion order.
+pub trait MempoolOrderingPolicy: Send + Sync {
+    /// Returns the priority score of the given transaction. The score must only depend on the
+    /// contents of the transaction, as it is also used to locate the transaction on removal.
+    fn priority_score(&self, txn: &MempoolTransaction) -> u64;
+}
+
+/// Creates the ordering policy specified by the given config.
+pub fn create_ordering_policy(
+    policy_type: &MempoolOrderingPolicyType,
+) -> Arc<dyn MempoolOrderingPolicy> {
+    match *policy_type {
+        MempoolOrderingPolicyType::GasPrice => Arc::new(GasPriceOrdering),
+        MempoolOrderingPolicyType::GasPricePerByte => Arc::new(GasPricePerByteOrdering),
+        MempoolOrderingPolicyType::FeePayerAwareTip {
+            min_gas_unit_price,
+            fee_payer_tip_pct,
+        } => Arc::new(FeePayerAwareTipOrdering {
+            min_gas_unit_price,
+            fee_payer_tip_pct,
+        }),
+    }
+}
+
+/// Orders transactions by their ranking score (i.e., the gas unit price).
+pub struct GasPriceOrdering;
+
+impl MempoolOrderingPolicy for GasPriceOrdering {
+    fn priority_score(&self, txn: &MempoolTransaction) -> u64 {
+        txn.ranking_score
+    }
+}
+
+/// Orders transactions by their ranking score per byte of the signed transaction.
+pub struct GasPricePerByteOrdering;
+
+impl MempoolOrderingPolicy for GasPricePerByteOrdering {
+    fn priority_score(&self, txn: &MempoolTransaction) -> u64 {
+        let txn_bytes = (txn.txn.txn_bytes_len() as u128).max(1);
+        let score = (txn.ranking_score as u128) * GAS_PRICE_PER_BYTE_SCALE / txn_bytes;
+        u64::try_from(score).unwrap_or(u64::MAX)
+    }
+}
+
+/// Orders transactions by the tip paid above the minimum gas unit price. The tip of
+/// transactions with a fee payer is scaled by `fee_payer_tip_pct`, so that operators can
+/// prioritize (> 100) or deprioritize (< 100) sponsored transactions.
+pub struct FeePayerAwareTipOrdering {
+    min_gas_unit_price: u64,
+    fee_payer_tip_pct: u64,
+}
+
+impl MempoolOrderingPolicy for FeePayerAwareTipOrdering {
+    fn priority_score(&self, txn: &MempoolTransaction) -> u64 {
+        let tip = txn.ranking_score.saturating_sub(self.min_gas_unit_price);
+        if txn.txn.authenticator_ref().fee_payer_address().is_some() {
+            let scaled_tip = (tip as u128) * (self.fee_payer_tip_pct as u128) / 100;
+            u64::try_from(scaled_tip).unwrap_or(u64::MAX)
+        } else {
+            tip
+        }
+    }
+}
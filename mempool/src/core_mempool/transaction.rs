@@ -66,6 +66,14 @@ impl MempoolTransaction {
     pub(crate) fn get_estimated_bytes(&self) -> usize {
         self.txn.raw_txn_bytes_len() + TXN_FIXED_ESTIMATED_BYTES + TXN_INDEX_ESTIMATED_BYTES
     }
+
+    pub(crate) fn is_client_submitted(&self) -> bool {
+        self.insertion_info.submitted_by == SubmittedBy::Client
+    }
+
+    pub(crate) fn qualifies_for_broadcast(&self) -> bool {
+        self.timeline_state != TimelineState::NonQualified
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Hash, Serialize)]
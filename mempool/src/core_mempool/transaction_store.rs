@@ -9,13 +9,17 @@ use crate::{
             PriorityQueueIter, TTLIndex,
         },
         mempool::Mempool,
+        ordering_policy::MempoolOrderingPolicy,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         TxnPointer,
     },
     counters,
     counters::{BROADCAST_BATCHED_LABEL, BROADCAST_READY_LABEL, CONSENSUS_READY_LABEL},
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
-    shared_mempool::types::MultiBucketTimelineIndexIds,
+    shared_mempool::types::{
+        MempoolStats, MultiBucketTimelineIndexIds, PendingAccountInfo, PendingTransactionInfo,
+        SequenceNumberGap,
+    },
 };
 use aptos_config::config::MempoolConfig;
 use aptos_crypto::HashValue;
@@ -30,6 +34,7 @@ use std::{
     collections::HashMap,
     mem::size_of,
     ops::Bound,
+    sync::Arc,
     time::{Duration, SystemTime},
 };
 
@@ -79,10 +84,17 @@ pub struct TransactionStore {
     // eager expiration
     eager_expire_threshold: Option<Duration>,
     eager_expire_time: Duration,
+
+    // replace-by-fee: minimum gas unit price bump (in percent) required to replace a pending
+    // transaction with a different one, or None if disabled
+    replace_by_fee_min_gas_price_bump_pct: Option<u64>,
 }
 
 impl TransactionStore {
-    pub(crate) fn new(config: &MempoolConfig) -> Self {
+    pub(crate) fn new(
+        config: &MempoolConfig,
+        ordering_policy: Arc<dyn MempoolOrderingPolicy>,
+    ) -> Self {
         Self {
             // main DS
             transactions: HashMap::new(),
@@ -93,7 +105,7 @@ impl TransactionStore {
             expiration_time_index: TTLIndex::new(Box::new(|t: &MempoolTransaction| {
                 Duration::from_secs(t.txn.expiration_timestamp_secs())
             })),
-            priority_index: PriorityIndex::new(),
+            priority_index: PriorityIndex::new(ordering_policy),
             timeline_index: MultiBucketTimelineIndex::new(config.broadcast_buckets.clone())
                 .unwrap(),
             parking_lot_index: ParkingLotIndex::new(),
@@ -111,6 +123,9 @@ impl TransactionStore {
             // eager expiration
             eager_expire_threshold: config.eager_expire_threshold_ms.map(Duration::from_millis),
             eager_expire_time: Duration::from_millis(config.eager_expire_time_ms),
+
+            // replace-by-fee
+            replace_by_fee_min_gas_price_bump_pct: config.replace_by_fee_min_gas_price_bump_pct,
         }
     }
 
@@ -197,15 +212,44 @@ impl TransactionStore {
         let txn_seq_num = txn.sequence_info.transaction_sequence_number;
         let acc_seq_num = txn.sequence_info.account_sequence_number;
         let mut gas_upgraded = false;
+        let mut replaced_by_fee = false;
 
         // If the transaction is already in Mempool, we only allow the user to
         // increase the gas unit price to speed up a transaction, but not the max gas.
+        // If replace-by-fee is enabled, the transaction can be replaced by one with any payload,
+        // expiration or max gas, as long as the gas unit price is sufficiently higher.
         //
         // Transactions with all the same inputs (but possibly signed differently) are idempotent
         // since the raw transaction is the same
-        if let Some(txns) = self.transactions.get_mut(&address) {
-            if let Some(current_version) = txns.get_mut(&txn_seq_num) {
-                if current_version.txn.payload() != txn.txn.payload() {
+        let replace_by_fee_min_gas_price_bump_pct = self.replace_by_fee_min_gas_price_bump_pct;
+        if let Some(txns) = self.transactions.get(&address) {
+            if let Some(current_version) = txns.get(&txn_seq_num) {
+                let is_different_transaction = current_version.txn.payload() != txn.txn.payload()
+                    || current_version.txn.expiration_timestamp_secs()
+                        != txn.txn.expiration_timestamp_secs()
+                    || current_version.txn.max_gas_amount() != txn.txn.max_gas_amount();
+                if let Some(min_bump_pct) =
+                    replace_by_fee_min_gas_price_bump_pct.filter(|_| is_different_transaction)
+                {
+                    if !is_sufficient_gas_price_bump(
+                        current_version.get_gas_price(),
+                        txn.get_gas_price(),
+                        min_bump_pct,
+                    ) {
+                        return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
+                            format!(
+                                "Transaction already in mempool with a different payload, expiration or max gas amount. \
+                                 Replacing it requires a gas unit price at least {}% higher than {}",
+                                min_bump_pct,
+                                current_version.get_gas_price(),
+                            ),
+                        );
+                    }
+                    // The replacement is tracked as gas upgraded, so it is pulled again even if
+                    // the old version was pulled.
+                    replaced_by_fee = true;
+                    gas_upgraded = true;
+                } else if current_version.txn.payload() != txn.txn.payload() {
                     return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
                         "Transaction already in mempool with a different payload".to_string(),
                     );
@@ -223,9 +267,6 @@ impl TransactionStore {
                     );
                 } else if current_version.get_gas_price() < txn.get_gas_price() {
                     // Update txn if gas unit price is a larger value than before
-                    if let Some(txn) = txns.remove(&txn_seq_num) {
-                        self.index_remove(&txn);
-                    };
                     gas_upgraded = true;
                 } else if current_version.get_gas_price() > txn.get_gas_price() {
                     return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
@@ -240,7 +281,32 @@ impl TransactionStore {
             }
         }
 
-        if self.check_is_full_after_eviction(&txn, acc_seq_num) {
+        if gas_upgraded {
+            // The new version takes the slot of the current one, so it only needs room for its
+            // extra bytes. Check before evicting the current version, so that it stays in place
+            // if the new one is rejected.
+            let current_bytes = self
+                .get_mempool_txn(&address, txn_seq_num)
+                .map_or(0, |current_version| current_version.get_estimated_bytes());
+            if self.size_bytes - current_bytes + txn.get_estimated_bytes() > self.capacity_bytes {
+                return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+                    "Mempool is full. Mempool size in bytes: {}, Capacity in bytes: {}",
+                    self.size_bytes, self.capacity_bytes,
+                ));
+            }
+
+            // Evict the current version from all indexes
+            if let Some(current_version) = self
+                .transactions
+                .get_mut(&address)
+                .and_then(|txns| txns.remove(&txn_seq_num))
+            {
+                self.index_remove(&current_version);
+            }
+            if replaced_by_fee {
+                counters::CORE_MEMPOOL_REPLACED_TXNS.inc();
+            }
+        } else if self.check_is_full_after_eviction(&txn, acc_seq_num) {
             return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
                 "Mempool is full. Mempool size: {}, Capacity: {}",
                 self.system_ttl_index.size(),
@@ -761,6 +827,91 @@ impl TransactionStore {
         self.priority_index.iter()
     }
 
+    pub(crate) fn iter_transactions(&self) -> impl Iterator<Item = &MempoolTransaction> {
+        self.transactions.values().flat_map(|txns| txns.values())
+    }
+
+    fn get_pending_transaction_info(&self, txn: &MempoolTransaction) -> PendingTransactionInfo {
+        PendingTransactionInfo {
+            txn: txn.txn.clone(),
+            priority_score: self.priority_index.priority_score(txn),
+            system_expiration_time: txn.expiration_time,
+            insertion_time: txn.insertion_info.insertion_time,
+            parked: self.parking_lot_index.contains(
+                &txn.get_sender(),
+                &txn.sequence_info.transaction_sequence_number,
+            ),
+        }
+    }
+
+    /// Returns up to `limit` pending transactions. Ready transactions are returned first, in the
+    /// order they are pulled into blocks, followed by parked transactions.
+    pub(crate) fn get_pending_transactions(&self, limit: usize) -> Vec<PendingTransactionInfo> {
+        let ready_txns = self.priority_index.iter().filter_map(|key| {
+            self.get_mempool_txn(
+                &key.address,
+                key.sequence_number.transaction_sequence_number,
+            )
+        });
+        let parked_txns = self.iter_transactions().filter(|txn| {
+            self.parking_lot_index.contains(
+                &txn.get_sender(),
+                &txn.sequence_info.transaction_sequence_number,
+            )
+        });
+        ready_txns
+            .chain(parked_txns)
+            .take(limit)
+            .map(|txn| self.get_pending_transaction_info(txn))
+            .collect()
+    }
+
+    /// Returns the pending transactions of the given account, and the gaps in their sequence
+    /// numbers that prevent them from being included in blocks.
+    pub(crate) fn get_pending_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> PendingAccountInfo {
+        let sequence_number = self.get_sequence_number(address).copied();
+        let mut transactions = vec![];
+        let mut sequence_number_gaps = vec![];
+        if let Some(txns) = self.transactions.get(address) {
+            let mut next_sequence_number = sequence_number;
+            for (txn_sequence_number, txn) in txns.iter() {
+                match next_sequence_number {
+                    Some(next) if next < *txn_sequence_number => {
+                        sequence_number_gaps.push(SequenceNumberGap {
+                            start: next,
+                            end: *txn_sequence_number,
+                        });
+                    },
+                    _ => {},
+                }
+                next_sequence_number = Some(txn_sequence_number + 1);
+                transactions.push(self.get_pending_transaction_info(txn));
+            }
+        }
+        PendingAccountInfo {
+            address: *address,
+            sequence_number,
+            transactions,
+            sequence_number_gaps,
+        }
+    }
+
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        MempoolStats {
+            num_transactions: self.system_ttl_index.size(),
+            num_ready_transactions: self.priority_index.size(),
+            num_parked_transactions: self.parking_lot_index.size(),
+            num_accounts: self.transactions.len(),
+            size_bytes: self.size_bytes,
+            capacity: self.capacity,
+            capacity_bytes: self.capacity_bytes,
+            capacity_per_user: self.capacity_per_user,
+        }
+    }
+
     pub(crate) fn gen_snapshot(&self) -> TxnsLog {
         let mut txns_log = TxnsLog::new();
         for (account, txns) in self.transactions.iter() {
@@ -791,3 +942,15 @@ impl TransactionStore {
         &self.transactions
     }
 }
+
+/// Returns true iff the new gas unit price is higher than the current one by at least
+/// `min_bump_pct` percent.
+fn is_sufficient_gas_price_bump(
+    current_gas_price: u64,
+    new_gas_price: u64,
+    min_bump_pct: u64,
+) -> bool {
+    new_gas_price > current_gas_price
+        && (new_gas_price as u128) * 100
+            >= (current_gas_price as u128) * (100 + min_bump_pct as u128)
+}
@@ -5,8 +5,9 @@
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_metrics_core::{
     exponential_buckets, histogram_opts, op_counters::DurationHistogram, register_histogram,
-    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge_vec,
-    Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec,
 };
 use aptos_short_hex_str::AsShortHexStr;
 use once_cell::sync::Lazy;
@@ -171,6 +172,43 @@ pub static CORE_MEMPOOL_IDEMPOTENT_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of pending txns replaced by a different txn (replace-by-fee)
+pub static CORE_MEMPOOL_REPLACED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_core_mempool_replaced_txns_count",
+        "Number of pending txns replaced by a different txn with a higher gas price"
+    )
+    .unwrap()
+});
+
+/// Gauge tracking the number of txns in the most recently persisted mempool snapshot
+pub static MEMPOOL_PERSISTED_SNAPSHOT_TXNS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_mempool_persisted_snapshot_txns",
+        "Number of txns in the most recently persisted mempool snapshot"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns restored from a persisted mempool snapshot, by status
+pub static MEMPOOL_RESTORED_SNAPSHOT_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_mempool_restored_snapshot_txns_count",
+        "Number of txns restored from a persisted mempool snapshot, by mempool status",
+        &["status"]
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns rejected by the mempool transaction filter
+pub static TRANSACTIONS_REJECTED_BY_FILTER: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_mempool_txns_rejected_by_filter_count",
+        "Number of txns rejected by the mempool transaction filter"
+    )
+    .unwrap()
+});
+
 pub fn core_mempool_txn_commit_latency(
     stage: &'static str,
     submitted_by: &'static str,
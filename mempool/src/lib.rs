@@ -38,7 +38,8 @@
 //! to current for account). This queue is ordered by gas price so that if a client is willing to
 //! pay more (than other clients) per unit of execution, then they can enter consensus earlier. Note
 //! that although global ordering is maintained by gas price, for a single account, transactions are
-//! ordered by sequence number.
+//! ordered by sequence number. The global ordering can be changed through the
+//! `MempoolOrderingPolicy` selected in `MempoolConfig` (e.g., gas price per byte).
 //!
 //! All transactions that are not ready to be included in the next block are part of separate
 //! `ParkingLotIndex`. They will be moved to the ordered queue once some event unblocks them. For
@@ -61,9 +62,11 @@ mod tests;
 pub use shared_mempool::{
     bootstrap, network,
     network::MempoolSyncMsg,
+    persistence::MempoolPersistenceHandle,
     types::{
-        MempoolClientRequest, MempoolClientSender, MempoolEventsReceiver, QuorumStoreRequest,
-        QuorumStoreResponse, SubmissionStatus,
+        MempoolClientRequest, MempoolClientSender, MempoolEventsReceiver, MempoolStats,
+        MempoolTransactionFilter, PendingAccountInfo, PendingTransactionInfo, QuorumStoreRequest,
+        QuorumStoreResponse, SequenceNumberGap, SubmissionStatus,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
@@ -156,6 +156,7 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    MempoolQuery,
     GetBlock,
     QuorumStore,
     StateSyncCommit,
@@ -173,6 +174,8 @@ pub enum LogEntry {
     DBError,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
+    PersistSnapshot,
+    RestoreSnapshot,
 }
 
 #[derive(Clone, Copy, Serialize)]
@@ -10,7 +10,7 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     network::MempoolSyncMsg,
     shared_mempool::{
-        tasks,
+        persistence, tasks,
         tasks::process_committed_transactions,
         types::{notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification},
     },
@@ -33,11 +33,12 @@ use aptos_network::{
 use aptos_types::on_chain_config::{OnChainConfigPayload, OnChainConfigProvider};
 use aptos_vm_validator::vm_validator::TransactionValidation;
 use futures::{
-    channel::mpsc,
+    channel::{mpsc, oneshot},
     stream::{select_all, FuturesUnordered},
     FutureExt, StreamExt,
 };
 use std::{
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
@@ -195,6 +196,33 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 ))
                 .await;
         },
+        MempoolClientRequest::GetPendingTransactions(limit, callback) => {
+            bounded_executor
+                .spawn(tasks::process_client_mempool_query(
+                    smp.clone(),
+                    move |mempool| mempool.get_pending_transactions(limit),
+                    callback,
+                ))
+                .await;
+        },
+        MempoolClientRequest::GetPendingAccountTransactions(address, callback) => {
+            bounded_executor
+                .spawn(tasks::process_client_mempool_query(
+                    smp.clone(),
+                    move |mempool| mempool.get_pending_account_transactions(&address),
+                    callback,
+                ))
+                .await;
+        },
+        MempoolClientRequest::GetMempoolStats(callback) => {
+            bounded_executor
+                .spawn(tasks::process_client_mempool_query(
+                    smp.clone(),
+                    |mempool| mempool.get_stats(),
+                    callback,
+                ))
+                .await;
+        },
     }
 }
 
@@ -407,3 +435,55 @@ pub(crate) async fn snapshot_job(mempool: Arc<Mutex<CoreMempool>>, snapshot_inte
         trace!(LogSchema::new(LogEntry::MempoolSnapshot).txns(snapshot));
     }
 }
+
+/// Restores the persisted mempool snapshot (if any), and then periodically persists a new one.
+/// When a shutdown is requested, a final snapshot is persisted before the request is acknowledged.
+pub(crate) async fn persistence_job<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    snapshot_path: PathBuf,
+    persistence_interval_secs: u64,
+    shutdown_receiver: oneshot::Receiver<oneshot::Sender<()>>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg> + 'static,
+    TransactionValidator: TransactionValidation + 'static,
+{
+    // Restore the snapshot before persisting anything, so it isn't overwritten
+    let mempool = smp.mempool.clone();
+    let path = snapshot_path.clone();
+    if let Err(error) =
+        tokio::task::spawn_blocking(move || persistence::restore_snapshot(&smp, &path)).await
+    {
+        let error = anyhow::Error::from(error);
+        error!(LogSchema::new(LogEntry::RestoreSnapshot).error(&error));
+    }
+
+    let mut interval =
+        IntervalStream::new(interval(Duration::from_secs(persistence_interval_secs))).fuse();
+    let mut shutdown_receiver = shutdown_receiver.fuse();
+    loop {
+        ::futures::select! {
+            _ = interval.select_next_some() => {
+                spawn_persist_snapshot(mempool.clone(), snapshot_path.clone()).await;
+            },
+            ack_sender = shutdown_receiver => {
+                // If the handle was dropped without requesting a shutdown, keep persisting
+                if let Ok(ack_sender) = ack_sender {
+                    spawn_persist_snapshot(mempool.clone(), snapshot_path.clone()).await;
+                    let _ = ack_sender.send(());
+                    return;
+                }
+            },
+        }
+    }
+}
+
+/// Persists a snapshot of core mempool without blocking the async runtime
+async fn spawn_persist_snapshot(mempool: Arc<Mutex<CoreMempool>>, snapshot_path: PathBuf) {
+    if let Err(error) =
+        tokio::task::spawn_blocking(move || persistence::persist_snapshot(&mempool, &snapshot_path))
+            .await
+    {
+        let error = anyhow::Error::from(error);
+        error!(LogSchema::new(LogEntry::PersistSnapshot).error(&error));
+    }
+}
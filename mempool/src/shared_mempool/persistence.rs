@@ -0,0 +1,187 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persists the transactions in core mempool to a snapshot on disk, so that pending transactions
+//! are not lost when the node restarts. When the node starts, the snapshot is restored through
+//! the regular submission path, i.e., all transactions are revalidated by the VM validator.
+
+use crate::{
+    core_mempool::{CoreMempool, TimelineState},
+    counters,
+    logging::{LogEntry, LogEvent, LogSchema},
+    network::MempoolSyncMsg,
+    shared_mempool::{tasks, types::SharedMempool},
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_network::application::interface::NetworkClientInterface;
+use aptos_types::transaction::SignedTransaction;
+use aptos_vm_validator::vm_validator::TransactionValidation;
+use futures::channel::oneshot;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+/// The name of the mempool snapshot file in the node's data directory
+pub const MEMPOOL_SNAPSHOT_FILE_NAME: &str = "mempool_snapshot.bcs";
+
+/// A transaction in a persisted mempool snapshot
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct PersistedTransaction {
+    pub txn: SignedTransaction,
+    /// Whether the transaction was submitted to this node by a client
+    pub client_submitted: bool,
+    /// Whether the transaction qualifies for broadcast (see `TimelineState::NonQualified`)
+    pub broadcast: bool,
+}
+
+/// A snapshot of all transactions in core mempool
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct MempoolSnapshot {
+    pub transactions: Vec<PersistedTransaction>,
+}
+
+impl MempoolSnapshot {
+    pub fn new(mempool: &CoreMempool) -> Self {
+        Self {
+            transactions: mempool
+                .iter_transactions()
+                .map(|txn| PersistedTransaction {
+                    txn: txn.txn.clone(),
+                    client_submitted: txn.is_client_submitted(),
+                    broadcast: txn.qualifies_for_broadcast(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the snapshot to the given path. The snapshot is written and synced to a temporary
+    /// file first and then renamed, so that a crash while persisting never corrupts the previous
+    /// snapshot.
+    pub fn persist(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = path.with_extension("tmp");
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(&bcs::to_bytes(self)?)?;
+        temp_file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Reads the snapshot from the given path. Returns None if there is no snapshot.
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(path)?;
+        Ok(Some(bcs::from_bytes(&bytes)?))
+    }
+}
+
+/// Persists a snapshot of all transactions in core mempool to the given path
+pub(crate) fn persist_snapshot(mempool: &Mutex<CoreMempool>, path: &Path) {
+    // Only hold the lock while collecting the transactions, not while writing to disk
+    let snapshot = MempoolSnapshot::new(&mempool.lock());
+    let num_txns = snapshot.transactions.len();
+    match snapshot.persist(path) {
+        Ok(()) => {
+            counters::MEMPOOL_PERSISTED_SNAPSHOT_TXNS.set(num_txns as i64);
+            debug!(
+                LogSchema::event_log(LogEntry::PersistSnapshot, LogEvent::Success)
+                    .num_txns(num_txns)
+            );
+        },
+        Err(error) => {
+            error!(LogSchema::new(LogEntry::PersistSnapshot).error(&error));
+        },
+    }
+}
+
+/// Restores the transactions of the snapshot at the given path (if any) into core mempool.
+/// Transactions are revalidated, so transactions that were committed or expired in the meantime
+/// are dropped.
+pub(crate) fn restore_snapshot<NetworkClient, TransactionValidator>(
+    smp: &SharedMempool<NetworkClient, TransactionValidator>,
+    path: &Path,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    let snapshot = match MempoolSnapshot::load(path) {
+        Ok(Some(snapshot)) => snapshot,
+        Ok(None) => return,
+        Err(error) => {
+            error!(LogSchema::new(LogEntry::RestoreSnapshot).error(&error));
+            return;
+        },
+    };
+    info!(
+        LogSchema::event_log(LogEntry::RestoreSnapshot, LogEvent::Start)
+            .num_txns(snapshot.transactions.len())
+    );
+
+    // Restore the transactions the same way they were originally inserted
+    let mut transactions_by_origin: HashMap<(bool, bool), Vec<SignedTransaction>> = HashMap::new();
+    for txn in snapshot.transactions {
+        transactions_by_origin
+            .entry((txn.client_submitted, txn.broadcast))
+            .or_default()
+            .push(txn.txn);
+    }
+
+    let batch_size = smp.config.shared_mempool_batch_size.max(1);
+    for ((client_submitted, broadcast), transactions) in transactions_by_origin {
+        let timeline_state = if broadcast {
+            TimelineState::NotReady
+        } else {
+            TimelineState::NonQualified
+        };
+        for batch in transactions.chunks(batch_size) {
+            let statuses = tasks::process_incoming_transactions(
+                smp,
+                batch.to_vec(),
+                timeline_state,
+                client_submitted,
+            );
+            for (_, (mempool_status, _)) in statuses {
+                counters::MEMPOOL_RESTORED_SNAPSHOT_TXNS
+                    .with_label_values(&[&mempool_status.code.to_string()])
+                    .inc();
+            }
+        }
+    }
+
+    info!(LogSchema::event_log(
+        LogEntry::RestoreSnapshot,
+        LogEvent::Success
+    ));
+}
+
+/// Handle to the mempool persistence task. Used to persist a final snapshot when the node shuts
+/// down, as the snapshot is otherwise only persisted periodically.
+pub struct MempoolPersistenceHandle {
+    shutdown_sender: Option<oneshot::Sender<oneshot::Sender<()>>>,
+}
+
+impl MempoolPersistenceHandle {
+    pub(crate) fn new(shutdown_sender: Option<oneshot::Sender<oneshot::Sender<()>>>) -> Self {
+        Self { shutdown_sender }
+    }
+
+    /// Stops the persistence task and waits until it has persisted a final snapshot. Does
+    /// nothing if persistence is disabled or the task was already stopped.
+    pub async fn shutdown(&mut self) {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let (ack_sender, ack_receiver) = oneshot::channel();
+            if shutdown_sender.send(ack_sender).is_ok() {
+                let _ = ack_receiver.await;
+            }
+        }
+    }
+}
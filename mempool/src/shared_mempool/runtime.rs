@@ -6,8 +6,12 @@ use crate::{
     core_mempool::CoreMempool,
     network::MempoolSyncMsg,
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
-        types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
+        coordinator::{coordinator, gc_coordinator, persistence_job, snapshot_job},
+        persistence::{MempoolPersistenceHandle, MEMPOOL_SNAPSHOT_FILE_NAME},
+        types::{
+            MempoolEventsReceiver, MempoolTransactionFilter, SharedMempool,
+            SharedMempoolNotification,
+        },
     },
     QuorumStoreRequest,
 };
@@ -23,7 +27,10 @@ use aptos_network::application::{
 use aptos_storage_interface::DbReader;
 use aptos_types::on_chain_config::OnChainConfigProvider;
 use aptos_vm_validator::vm_validator::{TransactionValidation, VMValidator};
-use futures::channel::mpsc::{Receiver, UnboundedSender};
+use futures::channel::{
+    mpsc::{Receiver, UnboundedSender},
+    oneshot,
+};
 use std::sync::Arc;
 use tokio::runtime::{Handle, Runtime};
 
@@ -32,6 +39,9 @@ use tokio::runtime::{Handle, Runtime};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - persistence_task (task that restores and periodically persists the mempool snapshot).
+/// Returns the transaction filter applied to incoming transactions, so it can be replaced at
+/// runtime, and the handle used to persist a final mempool snapshot on shutdown.
 pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     executor: &Handle,
     config: &NodeConfig,
@@ -46,7 +56,8 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     validator: Arc<RwLock<TransactionValidator>>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     peers_and_metadata: Arc<PeersAndMetadata>,
-) where
+) -> (MempoolTransactionFilter, MempoolPersistenceHandle)
+where
     TransactionValidator: TransactionValidation + 'static,
     ConfigProvider: OnChainConfigProvider,
 {
@@ -60,6 +71,20 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
             subscribers,
             config.base.role,
         );
+    let transaction_filter = smp.transaction_filter.clone();
+
+    let persistence_handle = if config.mempool.persistence_enabled {
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        executor.spawn(persistence_job(
+            smp.clone(),
+            config.base.data_dir.join(MEMPOOL_SNAPSHOT_FILE_NAME),
+            config.mempool.persistence_interval_secs,
+            shutdown_receiver,
+        ));
+        MempoolPersistenceHandle::new(Some(shutdown_sender))
+    } else {
+        MempoolPersistenceHandle::new(None)
+    };
 
     executor.spawn(coordinator(
         smp,
@@ -84,6 +109,8 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
             config.mempool.mempool_snapshot_interval_secs,
         ));
     }
+
+    (transaction_filter, persistence_handle)
 }
 
 pub fn bootstrap(
@@ -96,11 +123,11 @@ pub fn bootstrap(
     mempool_listener: MempoolNotificationListener,
     mempool_reconfig_events: ReconfigNotificationListener<DbBackedOnChainConfig>,
     peers_and_metadata: Arc<PeersAndMetadata>,
-) -> Runtime {
+) -> (Runtime, MempoolTransactionFilter, MempoolPersistenceHandle) {
     let runtime = aptos_runtimes::spawn_named_runtime("shared-mem".into(), None);
     let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
-    start_shared_mempool(
+    let (transaction_filter, persistence_handle) = start_shared_mempool(
         runtime.handle(),
         config,
         mempool,
@@ -115,5 +142,5 @@ pub fn bootstrap(
         vec![],
         peers_and_metadata,
     );
-    runtime
+    (runtime, transaction_filter, persistence_handle)
 }
@@ -16,7 +16,7 @@ use crate::{
     QuorumStoreRequest, QuorumStoreResponse, SubmissionStatus,
 };
 use anyhow::Result;
-use aptos_config::network_id::PeerNetworkId;
+use aptos_config::{config::transaction_filter_type::Filter, network_id::PeerNetworkId};
 use aptos_consensus_types::common::{RejectedTransactionSummary, TransactionSummary};
 use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
@@ -163,6 +163,27 @@ pub(crate) async fn process_client_get_transaction<NetworkClient, TransactionVal
     }
 }
 
+/// Processes a read-only mempool introspection request from a client such as the API service.
+pub(crate) async fn process_client_mempool_query<NetworkClient, TransactionValidator, Query, T>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    query: Query,
+    callback: oneshot::Sender<T>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+    Query: FnOnce(&CoreMempool) -> T,
+{
+    let result = query(&smp.mempool.lock());
+
+    if callback.send(result).is_err() {
+        warn!(LogSchema::event_log(
+            LogEntry::MempoolQuery,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionValidator>(
     smp: SharedMempool<NetworkClient, TransactionValidator>,
@@ -263,6 +284,13 @@ where
 {
     let mut statuses = vec![];
 
+    // Reject transactions that are denied by the configured transaction filter
+    let transactions = filter_transactions(
+        &smp.transaction_filter.current(),
+        transactions,
+        &mut statuses,
+    );
+
     let start_storage_read = Instant::now();
     let state_view = smp
         .db
@@ -329,6 +357,37 @@ where
     statuses
 }
 
+/// Removes the transactions denied by the given filter, recording a rejection status for each.
+fn filter_transactions(
+    filter: &Filter,
+    transactions: Vec<SignedTransaction>,
+    statuses: &mut Vec<SubmissionStatusBundle>,
+) -> Vec<SignedTransaction> {
+    // Special case for no filter to avoid unnecessary iteration through all transactions
+    if filter.is_empty() {
+        return transactions;
+    }
+
+    transactions
+        .into_iter()
+        .filter_map(|transaction| {
+            if filter.allows_transaction(&transaction) {
+                Some(transaction)
+            } else {
+                counters::TRANSACTIONS_REJECTED_BY_FILTER.inc();
+                statuses.push((
+                    transaction,
+                    (
+                        MempoolStatus::new(MempoolStatusCode::RejectedByFilter),
+                        None,
+                    ),
+                ));
+                None
+            }
+        })
+        .collect()
+}
+
 /// Perfoms VM validation on the transactions and inserts those that passes
 /// validation into the mempool.
 #[cfg(not(feature = "consensus-only-perf-test"))]
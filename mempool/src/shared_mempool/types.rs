@@ -9,7 +9,7 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_config::{
-    config::{MempoolConfig, RoleType},
+    config::{transaction_filter_type::Filter, MempoolConfig, RoleType},
     network_id::PeerNetworkId,
 };
 use aptos_consensus_types::common::{
@@ -22,9 +22,11 @@ use aptos_network::{
 };
 use aptos_storage_interface::DbReader;
 use aptos_types::{
-    mempool_status::MempoolStatus, transaction::SignedTransaction, vm_status::DiscardedVMStatus,
+    account_address::AccountAddress, mempool_status::MempoolStatus, transaction::SignedTransaction,
+    vm_status::DiscardedVMStatus,
 };
 use aptos_vm_validator::vm_validator::TransactionValidation;
+use arc_swap::ArcSwap;
 use futures::{
     channel::{mpsc, mpsc::UnboundedSender, oneshot},
     future::Future,
@@ -38,7 +40,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::Waker,
-    time::{Instant, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::runtime::Handle;
 
@@ -52,6 +54,7 @@ pub(crate) struct SharedMempool<NetworkClient, TransactionValidator> {
     pub validator: Arc<RwLock<TransactionValidator>>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     pub broadcast_within_validator_network: Arc<RwLock<bool>>,
+    pub transaction_filter: MempoolTransactionFilter,
 }
 
 impl<
@@ -69,6 +72,7 @@ impl<
         role: RoleType,
     ) -> Self {
         let network_interface = MempoolNetworkInterface::new(network_client, role, config.clone());
+        let transaction_filter = MempoolTransactionFilter::new(config.transaction_filter.clone());
         SharedMempool {
             mempool,
             config,
@@ -77,6 +81,7 @@ impl<
             validator,
             subscribers,
             broadcast_within_validator_network: Arc::new(RwLock::new(true)),
+            transaction_filter,
         }
     }
 
@@ -91,6 +96,32 @@ impl<
     }
 }
 
+/// Filters the transactions submitted to mempool. The underlying [`Filter`] can be replaced at
+/// runtime (e.g., through the admin service). Clones share the same filter.
+#[derive(Clone)]
+pub struct MempoolTransactionFilter {
+    filter: Arc<ArcSwap<Filter>>,
+}
+
+impl MempoolTransactionFilter {
+    pub(crate) fn new(filter: Filter) -> Self {
+        Self {
+            filter: Arc::new(ArcSwap::from_pointee(filter)),
+        }
+    }
+
+    /// Returns the currently active filter
+    pub fn current(&self) -> Arc<Filter> {
+        self.filter.load_full()
+    }
+
+    /// Replaces the active filter and returns the previous one. The new filter applies to the
+    /// transactions submitted after the update; transactions already in mempool are kept.
+    pub fn update(&self, filter: Filter) -> Arc<Filter> {
+        self.filter.swap(Arc::new(filter))
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SharedMempoolNotification {
     PeerStateChange,
@@ -232,6 +263,59 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Returns up to the given number of pending transactions
+    GetPendingTransactions(usize, oneshot::Sender<Vec<PendingTransactionInfo>>),
+    GetPendingAccountTransactions(AccountAddress, oneshot::Sender<PendingAccountInfo>),
+    GetMempoolStats(oneshot::Sender<MempoolStats>),
+}
+
+/// A transaction in mempool, as returned by mempool introspection requests. Ready transactions
+/// are pulled into blocks in the order of their `OrderedQueueKey`, i.e., by the highest
+/// `priority_score`, then by the earliest `system_expiration_time`, then by the highest sender
+/// address and the lowest sequence number.
+#[derive(Clone, Debug)]
+pub struct PendingTransactionInfo {
+    pub txn: SignedTransaction,
+    /// The score assigned by the configured ordering policy
+    pub priority_score: u64,
+    /// The time (since the Unix epoch) at which mempool evicts the transaction
+    pub system_expiration_time: Duration,
+    pub insertion_time: SystemTime,
+    /// Whether the transaction is parked, i.e., it can't be included in a block until the
+    /// transactions with lower sequence numbers of the same account are
+    pub parked: bool,
+}
+
+/// A range of sequence numbers `[start, end)` that are missing before a pending transaction
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SequenceNumberGap {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// The transactions of an account in mempool, as returned by mempool introspection requests
+#[derive(Clone, Debug)]
+pub struct PendingAccountInfo {
+    pub address: AccountAddress,
+    /// The account sequence number known to mempool, if any
+    pub sequence_number: Option<u64>,
+    /// The pending transactions, ordered by sequence number
+    pub transactions: Vec<PendingTransactionInfo>,
+    /// The sequence numbers missing before the pending transactions, which are why they're parked
+    pub sequence_number_gaps: Vec<SequenceNumberGap>,
+}
+
+/// Statistics of mempool, as returned by mempool introspection requests
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MempoolStats {
+    pub num_transactions: usize,
+    pub num_ready_transactions: usize,
+    pub num_parked_transactions: usize,
+    pub num_accounts: usize,
+    pub size_bytes: usize,
+    pub capacity: usize,
+    pub capacity_bytes: usize,
+    pub capacity_per_user: usize,
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -8,7 +8,7 @@ use crate::{
 };
 use anyhow::{format_err, Result};
 use aptos_compression::client::CompressionClient;
-use aptos_config::config::{NodeConfig, MAX_APPLICATION_MESSAGE_SIZE};
+use aptos_config::config::{MempoolOrderingPolicyType, NodeConfig, MAX_APPLICATION_MESSAGE_SIZE};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
 use aptos_types::{
@@ -36,6 +36,24 @@ pub(crate) fn setup_mempool_with_broadcast_buckets(
     (CoreMempool::new(&config), ConsensusMock::new())
 }
 
+pub(crate) fn setup_mempool_with_ordering_policy(
+    ordering_policy: MempoolOrderingPolicyType,
+) -> (CoreMempool, ConsensusMock) {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.broadcast_buckets = vec![0];
+    config.mempool.ordering_policy = ordering_policy;
+    (CoreMempool::new(&config), ConsensusMock::new())
+}
+
+pub(crate) fn setup_mempool_with_replace_by_fee(
+    min_gas_price_bump_pct: u64,
+) -> (CoreMempool, ConsensusMock) {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.broadcast_buckets = vec![0];
+    config.mempool.replace_by_fee_min_gas_price_bump_pct = Some(min_gas_price_bump_pct);
+    (CoreMempool::new(&config), ConsensusMock::new())
+}
+
 static ACCOUNTS: Lazy<Vec<AccountAddress>> = Lazy::new(|| {
     vec![
         AccountAddress::random(),
@@ -81,28 +99,72 @@ impl TestTransaction {
         self.make_signed_transaction_impl(100, u64::MAX)
     }
 
+    pub(crate) fn make_signed_transaction_with_script_code(
+        &self,
+        code: Vec<u8>,
+    ) -> SignedTransaction {
+        let raw_txn = self.make_raw_transaction(Script::new(code, vec![], vec![]), 100, u64::MAX);
+        let privkey = Self::make_private_key();
+        raw_txn
+            .sign(&privkey, privkey.public_key())
+            .expect("Failed to sign raw transaction.")
+            .into_inner()
+    }
+
+    pub(crate) fn make_fee_payer_signed_transaction(&self, fee_payer: usize) -> SignedTransaction {
+        let raw_txn = self.make_raw_transaction(Script::new(vec![], vec![], vec![]), 100, u64::MAX);
+        let privkey = Self::make_private_key();
+        raw_txn
+            .sign_fee_payer(
+                &privkey,
+                vec![],
+                vec![],
+                TestTransaction::get_address(fee_payer),
+                &privkey,
+            )
+            .expect("Failed to sign raw transaction.")
+            .into_inner()
+    }
+
     fn make_signed_transaction_impl(
         &self,
         max_gas_amount: u64,
         exp_timestamp_secs: u64,
     ) -> SignedTransaction {
-        let raw_txn = RawTransaction::new_script(
+        let raw_txn = self.make_raw_transaction(
+            Script::new(vec![], vec![], vec![]),
+            max_gas_amount,
+            exp_timestamp_secs,
+        );
+        let privkey = Self::make_private_key();
+        raw_txn
+            .sign(&privkey, privkey.public_key())
+            .expect("Failed to sign raw transaction.")
+            .into_inner()
+    }
+
+    fn make_raw_transaction(
+        &self,
+        script: Script,
+        max_gas_amount: u64,
+        exp_timestamp_secs: u64,
+    ) -> RawTransaction {
+        RawTransaction::new_script(
             TestTransaction::get_address(self.address),
             self.sequence_number,
-            Script::new(vec![], vec![], vec![]),
+            script,
             max_gas_amount,
             self.gas_price,
             exp_timestamp_secs,
             ChainId::test(),
-        );
+        )
+    }
+
+    fn make_private_key() -> Ed25519PrivateKey {
         let mut seed: [u8; 32] = [0u8; 32];
         seed[..4].copy_from_slice(&[1, 2, 3, 4]);
         let mut rng: StdRng = StdRng::from_seed(seed);
-        let privkey = Ed25519PrivateKey::generate(&mut rng);
-        raw_txn
-            .sign(&privkey, privkey.public_key())
-            .expect("Failed to sign raw transaction.")
-            .into_inner()
+        Ed25519PrivateKey::generate(&mut rng)
     }
 
     pub(crate) fn get_address(address: usize) -> AccountAddress {
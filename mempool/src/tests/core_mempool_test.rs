@@ -4,12 +4,14 @@
 
 use crate::{
     core_mempool::{CoreMempool, MempoolTransaction, SubmittedBy, TimelineState},
+    shared_mempool::types::SequenceNumberGap,
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, setup_mempool,
-        setup_mempool_with_broadcast_buckets, txn_bytes_len, TestTransaction,
+        setup_mempool_with_broadcast_buckets, setup_mempool_with_ordering_policy,
+        setup_mempool_with_replace_by_fee, txn_bytes_len, TestTransaction,
     },
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{MempoolOrderingPolicyType, NodeConfig};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
 use aptos_crypto::HashValue;
 use aptos_types::{
@@ -64,6 +66,72 @@ fn test_transaction_ordering_only_seqnos() {
     }
 }
 
+#[test]
+fn test_transaction_ordering_gas_price_per_byte() {
+    let small_txn = TestTransaction::new(0, 0, 10).make_signed_transaction();
+    let large_txn =
+        TestTransaction::new(1, 0, 12).make_signed_transaction_with_script_code(vec![0; 1024]);
+
+    // Gas price ordering prefers the larger transaction with the higher gas price
+    let (mut mempool, mut consensus) = setup_mempool();
+    add_signed_txn(&mut mempool, small_txn.clone()).unwrap();
+    add_signed_txn(&mut mempool, large_txn.clone()).unwrap();
+    assert_eq!(consensus.get_block(&mut mempool, 2, 10_000), vec![
+        large_txn.clone(),
+        small_txn.clone()
+    ]);
+
+    // Gas price per byte ordering prefers the smaller transaction
+    let (mut mempool, mut consensus) =
+        setup_mempool_with_ordering_policy(MempoolOrderingPolicyType::GasPricePerByte);
+    add_signed_txn(&mut mempool, small_txn.clone()).unwrap();
+    add_signed_txn(&mut mempool, large_txn.clone()).unwrap();
+    assert_eq!(consensus.get_block(&mut mempool, 2, 10_000), vec![
+        small_txn.clone(),
+        large_txn
+    ]);
+
+    // Introspection reports the score transactions are ordered by, not the gas unit price
+    let pending = mempool.get_pending_transactions(1);
+    assert_eq!(pending[0].txn, small_txn);
+    assert_eq!(
+        pending[0].priority_score,
+        small_txn.gas_unit_price() * 1_000 / small_txn.txn_bytes_len() as u64
+    );
+}
+
+#[test]
+fn test_transaction_ordering_fee_payer_aware_tip() {
+    let txn = TestTransaction::new(0, 0, 150).make_signed_transaction();
+    let sponsored_txn = TestTransaction::new(1, 0, 180).make_fee_payer_signed_transaction(2);
+
+    // Sponsored transactions only get half of their tip (80 / 2 = 40 < 50)
+    let (mut mempool, mut consensus) =
+        setup_mempool_with_ordering_policy(MempoolOrderingPolicyType::FeePayerAwareTip {
+            min_gas_unit_price: 100,
+            fee_payer_tip_pct: 50,
+        });
+    add_signed_txn(&mut mempool, txn.clone()).unwrap();
+    add_signed_txn(&mut mempool, sponsored_txn.clone()).unwrap();
+    assert_eq!(consensus.get_block(&mut mempool, 2, 10_000), vec![
+        txn.clone(),
+        sponsored_txn.clone()
+    ]);
+
+    // Without a discount, the sponsored transaction has the higher tip
+    let (mut mempool, mut consensus) =
+        setup_mempool_with_ordering_policy(MempoolOrderingPolicyType::FeePayerAwareTip {
+            min_gas_unit_price: 100,
+            fee_payer_tip_pct: 100,
+        });
+    add_signed_txn(&mut mempool, txn.clone()).unwrap();
+    add_signed_txn(&mut mempool, sponsored_txn.clone()).unwrap();
+    assert_eq!(consensus.get_block(&mut mempool, 2, 10_000), vec![
+        sponsored_txn,
+        txn
+    ]);
+}
+
 #[test]
 fn test_transaction_metrics() {
     let (mut mempool, _) = setup_mempool();
@@ -173,6 +241,154 @@ fn test_update_invalid_transaction_in_mempool() {
     assert_eq!(next_tnx[0].gas_unit_price(), 1);
 }
 
+#[test]
+fn test_replace_by_fee_transaction_in_mempool() {
+    let (mut mempool, mut consensus) = setup_mempool_with_replace_by_fee(10);
+    let txns = add_txns_to_mempool(&mut mempool, vec![
+        TestTransaction::new(0, 0, 10),
+        TestTransaction::new(1, 0, 5),
+    ]);
+
+    // Replace the first transaction with one that has a different payload and max gas amount
+    let replacement =
+        TestTransaction::new(0, 0, 11).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    assert!(add_signed_txn(&mut mempool, replacement.clone()).is_ok());
+    let replacement_with_max_gas =
+        TestTransaction::new(0, 0, 20).make_signed_transaction_with_max_gas_amount(200);
+    assert!(add_signed_txn(&mut mempool, replacement_with_max_gas.clone()).is_ok());
+
+    // The replaced versions are gone from all indexes
+    assert!(mempool
+        .get_by_hash(txns[0].clone().committed_hash())
+        .is_none());
+    assert!(mempool.get_by_hash(replacement.committed_hash()).is_none());
+    assert_eq!(mempool.get_transaction_store().get_parking_lot_size(), 0);
+
+    assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
+        replacement_with_max_gas
+    ]);
+    assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
+        txns[1].clone()
+    ]);
+}
+
+#[test]
+fn test_replace_by_fee_insufficient_gas_price_bump() {
+    let (mut mempool, mut consensus) = setup_mempool_with_replace_by_fee(10);
+    let txns = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 100)]);
+
+    // Less than 10% higher
+    let replacement =
+        TestTransaction::new(0, 0, 109).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    let status = mempool.add_txn(
+        replacement.clone(),
+        replacement.gas_unit_price(),
+        0,
+        TimelineState::NotReady,
+        false,
+    );
+    assert_eq!(status.code, MempoolStatusCode::InvalidUpdate);
+
+    // A lower gas price can't replace the transaction either
+    let replacement =
+        TestTransaction::new(0, 0, 50).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    assert!(add_signed_txn(&mut mempool, replacement).is_err());
+
+    assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
+        txns[0].clone()
+    ]);
+}
+
+#[test]
+fn test_replace_by_fee_disabled() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    let txns = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 1)]);
+
+    let replacement =
+        TestTransaction::new(0, 0, 100).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    assert!(add_signed_txn(&mut mempool, replacement).is_err());
+
+    assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
+        txns[0].clone()
+    ]);
+}
+
+#[test]
+fn test_replace_by_fee_include_gas_upgraded() {
+    let (mut pool, _) = setup_mempool_with_replace_by_fee(10);
+    let txns = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 1)]);
+    let pulled_txn = TransactionSummary::new(txns[0].sender(), txns[0].sequence_number());
+
+    let replacement =
+        TestTransaction::new(0, 0, 2).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    add_signed_txn(&mut pool, replacement.clone()).unwrap();
+
+    // The replacement is pulled again even though the original version was already pulled
+    let batch = pool.get_batch(10, 10240, true, true, btreemap! {
+        pulled_txn => TransactionInProgress::new(1)
+    });
+    assert_eq!(batch, vec![replacement]);
+    let batch = pool.get_batch(10, 10240, true, false, btreemap! {
+        pulled_txn => TransactionInProgress::new(1)
+    });
+    assert!(batch.is_empty());
+}
+
+#[test]
+fn test_replace_by_fee_rebroadcast() {
+    let (mut pool, _) = setup_mempool_with_replace_by_fee(10);
+    let txns = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 10)]);
+    let (batch, timeline_id) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(batch, txns);
+
+    // The replacement is queued for the next broadcast, after what peers already received
+    let replacement =
+        TestTransaction::new(0, 0, 20).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    add_signed_txn(&mut pool, replacement.clone()).unwrap();
+    let (batch, _) = pool.read_timeline(&timeline_id, 10);
+    assert_eq!(batch, vec![replacement]);
+}
+
+#[test]
+fn test_replace_by_fee_mempool_full() {
+    let txn = TestTransaction::new(0, 0, 10).make_signed_transaction();
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.replace_by_fee_min_gas_price_bump_pct = Some(10);
+    // Only room for the first version
+    config.mempool.capacity_bytes = MempoolTransaction::new(
+        txn.clone(),
+        Duration::from_secs(1),
+        1,
+        TimelineState::NotReady,
+        0,
+        SystemTime::now(),
+        false,
+    )
+    .get_estimated_bytes();
+    let mut pool = CoreMempool::new(&config);
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+
+    // A bigger replacement doesn't fit, and must leave the current version in place
+    let replacement =
+        TestTransaction::new(0, 0, 20).make_signed_transaction_with_script_code(vec![0; 1024]);
+    let status = pool.add_txn(
+        replacement.clone(),
+        replacement.gas_unit_price(),
+        0,
+        TimelineState::NotReady,
+        false,
+    );
+    assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+    assert!(pool.get_by_hash(txn.clone().committed_hash()).is_some());
+
+    // One of the same size takes the slot of the current version
+    let replacement =
+        TestTransaction::new(0, 0, 20).make_signed_transaction_with_max_gas_amount(200);
+    add_signed_txn(&mut pool, replacement.clone()).unwrap();
+    assert!(pool.get_by_hash(txn.committed_hash()).is_none());
+    assert!(pool.get_by_hash(replacement.committed_hash()).is_some());
+}
+
 #[test]
 fn test_commit_transaction() {
     let (mut pool, mut consensus) = setup_mempool();
@@ -959,3 +1175,53 @@ fn test_include_gas_upgraded() {
     });
     assert_eq!(batch.len(), 0);
 }
+
+#[test]
+fn test_mempool_introspection() {
+    let (mut pool, _) = setup_mempool();
+    // Account 0 has a gap at sequence number 1, so its transactions 2 and 3 are parked.
+    let transactions = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 2, 1),
+        TestTransaction::new(0, 3, 1),
+        TestTransaction::new(1, 0, 5),
+    ]);
+
+    let stats = pool.get_stats();
+    assert_eq!(stats.num_transactions, 4);
+    assert_eq!(stats.num_ready_transactions, 2);
+    assert_eq!(stats.num_parked_transactions, 2);
+    assert_eq!(stats.num_accounts, 2);
+    assert!(stats.size_bytes > 0);
+
+    // Ready transactions come first in priority order, followed by parked transactions.
+    let pending = pool.get_pending_transactions(10);
+    assert_eq!(
+        pending.iter().map(|txn| txn.parked).collect::<Vec<_>>(),
+        vec![false, false, true, true]
+    );
+    assert_eq!(pending[0].txn, transactions[3]);
+    assert_eq!(pending[1].txn, transactions[0]);
+    assert_eq!(pending[0].priority_score, 5);
+    assert_eq!(pending[1].priority_score, 1);
+    assert_eq!(pool.get_pending_transactions(1).len(), 1);
+
+    let account_info = pool.get_pending_account_transactions(&TestTransaction::get_address(0));
+    assert_eq!(account_info.sequence_number, Some(0));
+    assert_eq!(
+        account_info
+            .transactions
+            .iter()
+            .map(|txn| (txn.txn.sequence_number(), txn.parked))
+            .collect::<Vec<_>>(),
+        vec![(0, false), (2, true), (3, true)]
+    );
+    assert_eq!(account_info.sequence_number_gaps, vec![SequenceNumberGap {
+        start: 1,
+        end: 2
+    }]);
+
+    let account_info = pool.get_pending_account_transactions(&TestTransaction::get_address(2));
+    assert!(account_info.transactions.is_empty());
+    assert!(account_info.sequence_number_gaps.is_empty());
+}
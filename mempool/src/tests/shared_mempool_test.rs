@@ -3,14 +3,48 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    core_mempool::{CoreMempool, TimelineState},
     mocks::MockSharedMempool,
-    tests::common::{batch_add_signed_txn, TestTransaction},
+    network::MempoolSyncMsg,
+    shared_mempool::{
+        persistence::{
+            persist_snapshot, restore_snapshot, MempoolSnapshot, PersistedTransaction,
+            MEMPOOL_SNAPSHOT_FILE_NAME,
+        },
+        tasks,
+        types::SharedMempool,
+    },
+    tests::common::{add_txns_to_mempool, batch_add_signed_txn, setup_mempool, TestTransaction},
     QuorumStoreRequest,
 };
+use aptos_config::{
+    config::{
+        transaction_filter_type::{Filter, Matcher},
+        NodeConfig,
+    },
+    network_id::NetworkId,
+};
 use aptos_consensus_types::common::RejectedTransactionSummary;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, Uniform};
+use aptos_infallible::{Mutex, RwLock};
 use aptos_mempool_notifications::MempoolNotificationSender;
-use aptos_types::{transaction::Transaction, vm_status::DiscardedVMStatus};
+use aptos_network::{
+    application::{interface::NetworkClient, storage::PeersAndMetadata},
+    protocols::wire::handshake::v1::ProtocolId::MempoolDirectSend,
+};
+use aptos_storage_interface::mock::MockDbReaderWriter;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    mempool_status::MempoolStatusCode,
+    test_helpers::transaction_test_helpers::get_test_signed_txn,
+    transaction::{SignedTransaction, Transaction},
+    vm_status::DiscardedVMStatus,
+};
+use aptos_vm_validator::mocks::mock_vm_validator::{
+    MockVMValidator, SEQ_NUMBER_TOO_OLD_TEST_ADD, TXN_EXPIRATION_TIME_TEST_ADD,
+};
 use futures::{channel::oneshot, sink::SinkExt};
+use std::{collections::HashMap, sync::Arc};
 use tokio::time::timeout;
 
 #[tokio::test]
@@ -105,3 +139,213 @@ async fn test_mempool_notify_committed_txns() {
         );
     }
 }
+
+#[test]
+fn test_mempool_snapshot_persistence() {
+    let (mut pool, _) = setup_mempool();
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+    ]);
+    let client_txn = TestTransaction::new(1, 0, 2).make_signed_transaction();
+    pool.add_txn(
+        client_txn.clone(),
+        client_txn.gas_unit_price(),
+        0,
+        TimelineState::NonQualified,
+        true,
+    );
+    let pool = Mutex::new(pool);
+
+    let temp_dir = TempPath::new();
+    temp_dir.create_as_dir().unwrap();
+    let path = temp_dir.path().join(MEMPOOL_SNAPSHOT_FILE_NAME);
+    assert_eq!(MempoolSnapshot::load(&path).unwrap(), None);
+
+    // Persist the snapshot and verify all transactions are read back
+    persist_snapshot(&pool, &path);
+    let mut transactions = MempoolSnapshot::load(&path).unwrap().unwrap().transactions;
+    transactions.sort_by_key(|txn| {
+        (
+            txn.txn.sender() != txns[0].sender(),
+            txn.txn.sequence_number(),
+        )
+    });
+    assert_eq!(transactions, vec![
+        PersistedTransaction {
+            txn: txns[0].clone(),
+            client_submitted: false,
+            broadcast: true,
+        },
+        PersistedTransaction {
+            txn: txns[1].clone(),
+            client_submitted: false,
+            broadcast: true,
+        },
+        PersistedTransaction {
+            txn: client_txn,
+            client_submitted: true,
+            broadcast: false,
+        },
+    ]);
+
+    // Persisting again replaces the previous snapshot
+    pool.lock().commit_transaction(&txns[0].sender(), 1);
+    persist_snapshot(&pool, &path);
+    let snapshot = MempoolSnapshot::load(&path).unwrap().unwrap();
+    assert_eq!(snapshot.transactions.len(), 1);
+}
+
+#[test]
+fn test_restore_snapshot_revalidates_transactions() {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let valid_txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    // The mock validator rejects these senders as expired and as already committed
+    let expired_txn = get_test_signed_txn(
+        TXN_EXPIRATION_TIME_TEST_ADD,
+        0,
+        &private_key,
+        private_key.public_key(),
+        None,
+    );
+    let committed_txn = get_test_signed_txn(
+        SEQ_NUMBER_TOO_OLD_TEST_ADD,
+        0,
+        &private_key,
+        private_key.public_key(),
+        None,
+    );
+
+    let temp_dir = TempPath::new();
+    temp_dir.create_as_dir().unwrap();
+    let path = temp_dir.path().join(MEMPOOL_SNAPSHOT_FILE_NAME);
+    MempoolSnapshot {
+        transactions: [valid_txn.clone(), expired_txn, committed_txn]
+            .into_iter()
+            .map(|txn| PersistedTransaction {
+                txn,
+                client_submitted: false,
+                broadcast: true,
+            })
+            .collect(),
+    }
+    .persist(&path)
+    .unwrap();
+
+    // Only the transaction that is still valid is restored
+    let smp = new_shared_mempool(&NodeConfig::default());
+    restore_snapshot(&smp, &path);
+    let mempool = smp.mempool.lock();
+    let restored: Vec<_> = mempool
+        .iter_transactions()
+        .map(|txn| txn.txn.clone())
+        .collect();
+    assert_eq!(restored, vec![valid_txn.clone()]);
+    let (timeline, _) = mempool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].0, valid_txn);
+}
+
+#[test]
+fn test_process_incoming_transactions_with_filter() {
+    let denied_txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let allowed_txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+
+    // The negated block matcher is undecided outside of a block and must not deny everything
+    let mut config = NodeConfig::default();
+    config.mempool.transaction_filter = Filter::empty()
+        .add_deny(Matcher::Not(Box::new(
+            Matcher::BlockId(HashValue::random()),
+        )))
+        .add_deny_sender(denied_txn.sender());
+    let smp = new_shared_mempool(&config);
+
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        vec![denied_txn.clone(), allowed_txn.clone()],
+        TimelineState::NotReady,
+        true,
+    );
+    let codes: HashMap<_, _> = statuses
+        .into_iter()
+        .map(|(txn, (status, _))| (txn.sender(), status.code))
+        .collect();
+    assert_eq!(codes.len(), 2);
+    assert_eq!(
+        codes[&denied_txn.sender()],
+        MempoolStatusCode::RejectedByFilter
+    );
+    assert_eq!(codes[&allowed_txn.sender()], MempoolStatusCode::Accepted);
+
+    let pool = smp.mempool.lock();
+    assert!(pool.get_by_hash(denied_txn.committed_hash()).is_none());
+    assert!(pool.get_by_hash(allowed_txn.committed_hash()).is_some());
+}
+
+#[test]
+fn test_process_broadcast_with_conflicting_replacements() {
+    let mut config = NodeConfig::default();
+    config.mempool.replace_by_fee_min_gas_price_bump_pct = Some(10);
+    let smp = new_shared_mempool(&config);
+    let process_broadcast = |txn: &SignedTransaction| {
+        let statuses = tasks::process_incoming_transactions(
+            &smp,
+            vec![txn.clone()],
+            TimelineState::NotReady,
+            false,
+        );
+        statuses[0].1 .0.code
+    };
+
+    let original = TestTransaction::new(0, 0, 10).make_signed_transaction();
+    let replacement =
+        TestTransaction::new(0, 0, 20).make_signed_transaction_with_script_code(vec![1, 2, 3]);
+    let conflicting =
+        TestTransaction::new(0, 0, 21).make_signed_transaction_with_script_code(vec![4, 5, 6]);
+
+    // A replacement broadcast by a peer replaces the version received earlier
+    assert_eq!(process_broadcast(&original), MempoolStatusCode::Accepted);
+    assert_eq!(process_broadcast(&replacement), MempoolStatusCode::Accepted);
+
+    // Peers that still have the original version must not revert the replacement, and a
+    // conflicting replacement seen by other peers only wins with a sufficient gas price bump
+    assert_eq!(
+        process_broadcast(&original),
+        MempoolStatusCode::InvalidUpdate
+    );
+    assert_eq!(
+        process_broadcast(&conflicting),
+        MempoolStatusCode::InvalidUpdate
+    );
+
+    // Only the replacement is kept, and it's forwarded with the next broadcast
+    let pool = smp.mempool.lock();
+    assert!(pool.get_by_hash(original.committed_hash()).is_none());
+    assert!(pool.get_by_hash(conflicting.committed_hash()).is_none());
+    assert!(pool
+        .get_by_hash(replacement.clone().committed_hash())
+        .is_some());
+    let start_timeline_id = vec![0; config.mempool.broadcast_buckets.len()];
+    let (batch, _) = pool.read_timeline(&start_timeline_id.into(), 10);
+    assert_eq!(batch, vec![replacement]);
+}
+
+fn new_shared_mempool(
+    config: &NodeConfig,
+) -> SharedMempool<NetworkClient<MempoolSyncMsg>, MockVMValidator> {
+    let network_client = NetworkClient::new(
+        vec![MempoolDirectSend],
+        vec![],
+        HashMap::new(),
+        PeersAndMetadata::new(&[NetworkId::Validator]),
+    );
+    SharedMempool::new(
+        Arc::new(Mutex::new(CoreMempool::new(config))),
+        config.mempool.clone(),
+        network_client,
+        Arc::new(MockDbReaderWriter),
+        Arc::new(RwLock::new(MockVMValidator)),
+        vec![],
+        config.base.role,
+    )
+}
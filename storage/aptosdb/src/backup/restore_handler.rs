@@ -11,12 +11,14 @@ use crate::{
     AptosDB,
 };
 use aptos_crypto::HashValue;
-use aptos_storage_interface::{DbReader, Result};
+use aptos_storage_interface::{db_ensure as ensure, DbReader, Result};
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::definition::LeafCount,
-    state_store::{state_key::StateKey, state_value::StateValue},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
     transaction::{Transaction, TransactionInfo, Version},
     write_set::WriteSet,
 };
@@ -55,6 +57,20 @@ impl RestoreHandler {
         )
     }
 
+    pub fn get_state_snapshot_diff_restore(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<StateSnapshotDiffRestore> {
+        Ok(StateSnapshotDiffRestore {
+            state_store: Arc::clone(&self.state_store),
+            base_version,
+            version,
+            usage: self.ledger_db.metadata_db().get_usage(base_version)?,
+            hashed_updates: vec![],
+        })
+    }
+
     pub fn reset_state_store(&self) {
         self.state_store.reset();
     }
@@ -143,3 +159,49 @@ impl RestoreHandler {
         Ok(None)
     }
 }
+
+/// Applies the state updates between the state snapshots at `base_version` and `version` to the
+/// former chunk by chunk. The values are saved as chunks are added, only the key hashes, value
+/// hashes and keys of the updates are held until the result is merklized.
+pub struct StateSnapshotDiffRestore {
+    state_store: Arc<StateStore>,
+    base_version: Version,
+    version: Version,
+    usage: StateStorageUsage,
+    hashed_updates: Vec<(HashValue, Option<(HashValue, StateKey)>)>,
+}
+
+impl StateSnapshotDiffRestore {
+    /// Saves a chunk of updates, which must follow the previous ones in key hash order.
+    pub fn add_chunk(&mut self, updates: Vec<(StateKey, Option<StateValue>)>) -> Result<()> {
+        let hashed_updates = self.state_store.save_state_snapshot_diff_values(
+            self.base_version,
+            self.version,
+            updates,
+            &mut self.usage,
+        )?;
+        let mut prev_key_hash = self.hashed_updates.last().map(|(key_hash, _)| *key_hash);
+        for (key_hash, _) in &hashed_updates {
+            ensure!(
+                prev_key_hash.map_or(true, |prev| prev < *key_hash),
+                "State updates not ordered by key hash: {:?} follows {:?}.",
+                key_hash,
+                prev_key_hash,
+            );
+            prev_key_hash = Some(*key_hash);
+        }
+        self.hashed_updates.extend(hashed_updates);
+        Ok(())
+    }
+
+    /// Saves the state snapshot at `version` if its root hash is `expected_root_hash`.
+    pub fn finish(self, expected_root_hash: HashValue) -> Result<()> {
+        self.state_store.commit_state_snapshot_diff(
+            self.base_version,
+            self.version,
+            &self.hashed_updates,
+            self.usage,
+            expected_root_hash,
+        )
+    }
+}
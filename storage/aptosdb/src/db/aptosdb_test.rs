@@ -19,13 +19,14 @@ use aptos_config::config::{
     DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
 };
 use aptos_crypto::{hash::CryptoHash, HashValue};
-use aptos_storage_interface::{DbReader, ExecutedTrees, Order};
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader, ExecutedTrees, Order};
 use aptos_temppath::TempPath;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
     proof::SparseMerkleLeafNode,
     state_store::{
         state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        TStateView,
     },
     transaction::{
         ExecutionStatus, TransactionAuxiliaryData, TransactionAuxiliaryDataV1, TransactionInfo,
@@ -34,7 +35,10 @@ use aptos_types::{
     vm_status::StatusCode,
 };
 use proptest::prelude::*;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use test_helper::{test_save_blocks_impl, test_sync_transactions_impl};
 
 proptest! {
@@ -42,13 +46,28 @@ proptest! {
 
     #[test]
     fn test_save_blocks(input in arb_blocks_to_commit(), threshold in 10..20usize) {
-        test_save_blocks_impl(input, threshold);
+        test_save_blocks_impl(input, threshold, false /* in_memory */);
+    }
+
+    #[test]
+    fn test_save_blocks_in_memory(input in arb_blocks_to_commit(), threshold in 10..20usize) {
+        test_save_blocks_impl(input, threshold, true /* in_memory */);
     }
 
     #[test]
     fn test_sync_transactions(input in arb_blocks_to_commit(), threshold in 10..20usize) {
         test_sync_transactions_impl(input, threshold);
     }
+
+    #[test]
+    fn test_open_secondary(input in arb_blocks_to_commit()) {
+        test_open_secondary_impl(input, false /* enable_sharding */);
+    }
+
+    #[test]
+    fn test_open_secondary_sharded(input in arb_blocks_to_commit()) {
+        test_open_secondary_impl(input, true /* enable_sharding */);
+    }
 }
 
 #[test]
@@ -323,3 +342,92 @@ proptest! {
         test_state_merkle_pruning_impl(input);
     }
 }
+
+fn test_open_secondary_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+    enable_sharding: bool,
+) {
+    let tmp_dir = TempPath::new();
+    let secondary_dir = TempPath::new();
+    let db = if enable_sharding {
+        AptosDB::new_for_test_with_sharding(&tmp_dir, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD)
+    } else {
+        AptosDB::new_for_test(&tmp_dir)
+    };
+    let secondary_db = Arc::new(
+        AptosDB::open_secondary(
+            StorageDirPaths::from_path(&tmp_dir),
+            &secondary_dir,
+            RocksdbConfigs {
+                enable_storage_sharding: enable_sharding,
+                ..Default::default()
+            },
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+        .unwrap(),
+    );
+    let secondary_reader: Arc<dyn DbReader> = secondary_db.clone();
+    assert!(secondary_db
+        .get_latest_ledger_info_option()
+        .unwrap()
+        .is_none());
+
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut cur_ver: Version = 0;
+    let mut latest_values = HashMap::new();
+    for (txns_to_commit, ledger_info_with_sigs) in input {
+        test_helper::update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+        for txn_to_commit in &txns_to_commit {
+            latest_values.extend(
+                txn_to_commit
+                    .state_updates()
+                    .iter()
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+        }
+        db.save_transactions_for_test(
+            &txns_to_commit,
+            cur_ver,                /* first_version */
+            cur_ver.checked_sub(1), /* base_state_version */
+            Some(&ledger_info_with_sigs),
+            true, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+
+        // Not visible until caught up.
+        assert_ne!(
+            secondary_db.get_latest_ledger_info_option().unwrap(),
+            Some(ledger_info_with_sigs.clone())
+        );
+        secondary_db.try_catch_up_with_primary().unwrap();
+        assert_eq!(
+            secondary_db.get_latest_ledger_info().unwrap(),
+            ledger_info_with_sigs
+        );
+        test_helper::verify_committed_transactions(
+            &secondary_db,
+            &txns_to_commit,
+            cur_ver,
+            &ledger_info_with_sigs,
+            false, /* is_latest */
+        );
+
+        // The latest snapshot of the secondary can be behind the latest checkpoint, the state
+        // at the latest version is read from the state K/V DB.
+        let state_view = secondary_reader
+            .state_view_at_version(Some(ledger_info_with_sigs.ledger_info().version()))
+            .unwrap();
+        for (state_key, state_value) in &latest_values {
+            assert_eq!(&state_view.get_state_value(state_key).unwrap(), state_value);
+        }
+
+        cur_ver += txns_to_commit.len() as u64;
+    }
+}
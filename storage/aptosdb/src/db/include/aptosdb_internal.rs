@@ -96,6 +96,8 @@ impl AptosDB {
             myself.open_indexer(
                 db_paths.default_root_path(),
                 rocksdb_configs.index_db_config,
+                rocksdb_configs.enable_account_transactions_index,
+                rocksdb_configs.enable_in_memory_backend,
             )?;
         }
 
@@ -106,15 +108,25 @@ impl AptosDB {
         &mut self,
         db_root_path: impl AsRef<Path>,
         rocksdb_config: RocksdbConfig,
+        enable_account_transactions_index: bool,
+        in_memory: bool,
     ) -> Result<()> {
-        let indexer = Indexer::open(&db_root_path, rocksdb_config)?;
+        let indexer = Indexer::open(
+            &db_root_path,
+            rocksdb_config,
+            enable_account_transactions_index,
+            in_memory,
+        )?;
         let ledger_next_version = self.get_latest_version().map_or(0, |v| v + 1);
         info!(
             indexer_next_version = indexer.next_version(),
+            account_transactions_next_version = ?indexer.next_account_transactions_version(),
             ledger_next_version = ledger_next_version,
             "Opened AptosDB Indexer.",
         );
 
+        const BATCH_SIZE: Version = 10000;
+
         if indexer.next_version() < ledger_next_version {
             use aptos_storage_interface::state_view::DbStateViewAtVersion;
             let db : Arc<dyn DbReader> = self.state_store.clone();
@@ -123,7 +135,6 @@ impl AptosDB {
             let resolver = state_view.as_move_resolver();
             let annotator = MoveValueAnnotator::new(&resolver);
 
-            const BATCH_SIZE: Version = 10000;
             let mut next_version = indexer.next_version();
             while next_version < ledger_next_version {
                 info!(next_version = next_version, "AptosDB Indexer catching up. ",);
@@ -138,6 +149,47 @@ impl AptosDB {
                 next_version = end_version;
             }
         }
+
+        if let Some(account_transactions_next_version) = indexer.next_account_transactions_version()
+        {
+            // Transactions pruned from the ledger can't be indexed, so the index only covers
+            // the history available when it was enabled.
+            let mut next_version = std::cmp::max(
+                account_transactions_next_version,
+                self.ledger_pruner.get_min_readable_version(),
+            );
+            while next_version < ledger_next_version {
+                info!(
+                    next_version = next_version,
+                    "AptosDB account transactions index catching up.",
+                );
+                let end_version = std::cmp::min(ledger_next_version, next_version + BATCH_SIZE);
+                let num_versions = (end_version - next_version) as usize;
+                let txns = self
+                    .ledger_db
+                    .transaction_db()
+                    .get_transaction_iter(next_version, num_versions)?
+                    .collect::<Result<Vec<_>>>()?;
+                let events = self
+                    .ledger_db
+                    .event_db()
+                    .get_events_by_version_iter(next_version, num_versions)?
+                    .collect::<Result<Vec<_>>>()?;
+                let write_sets = self
+                    .ledger_db
+                    .write_set_db()
+                    .get_write_sets(next_version, end_version)?;
+                indexer.index_account_transactions(
+                    next_version,
+                    txns.iter()
+                        .zip(events.iter())
+                        .zip(write_sets.iter())
+                        .map(|((txn, events), write_set)| (txn, events.as_slice(), write_set)),
+                )?;
+
+                next_version = end_version;
+            }
+        }
         info!("AptosDB Indexer caught up.");
 
         self.indexer = Some(indexer);
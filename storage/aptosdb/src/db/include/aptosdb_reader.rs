@@ -102,6 +102,42 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_account_touched_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionsWithProof> {
+        gauged_api("get_account_touched_transactions", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            let indexer = match &self.indexer {
+                Some(indexer) => indexer,
+                None => bail!("Indexer not enabled."),
+            };
+
+            let min_readable_version = self.ledger_pruner.get_min_readable_version();
+            let txns_with_proofs = indexer
+                .get_account_transaction_versions(
+                    address,
+                    start_version,
+                    order,
+                    limit,
+                    min_readable_version,
+                    ledger_version,
+                )?
+                .into_iter()
+                .map(|version| {
+                    self.get_transaction_with_proof(version, ledger_version, include_events)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(AccountTransactionsWithProof::new(txns_with_proofs))
+        })
+    }
+
     /// This API is best-effort in that it CANNOT provide absence proof.
     fn get_transaction_by_hash(
         &self,
@@ -452,6 +488,40 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_first_state_value_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_first_state_value_version", || {
+            Ok(Some(
+                self.state_store.state_kv_pruner.get_min_readable_version(),
+            ))
+        })
+    }
+
+    fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<StateValue>)>> {
+        gauged_api("get_state_value_history", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            self.error_if_state_kv_pruned("StateValue", start_version)?;
+
+            // Values written before the min readable version can be gone unless they were still
+            // current at it, so the history before it is incomplete.
+            let min_readable_version = self.state_store.state_kv_pruner.get_min_readable_version();
+            self.state_store.get_state_value_history(
+                state_key,
+                start_version,
+                order,
+                limit,
+                min_readable_version,
+                ledger_version,
+            )
+        })
+    }
+
     /// Returns the proof of the given state key and version.
     fn get_state_proof_by_version_ext(
         &self,
@@ -799,6 +869,23 @@ impl DbReader for AptosDB {
         self.indexer.is_some()
     }
 
+    fn account_transactions_index_enabled(&self) -> bool {
+        self.indexer
+            .as_ref()
+            .map_or(false, |indexer| indexer.next_account_transactions_version().is_some())
+    }
+
+    fn get_pruner_statuses(&self) -> Result<Vec<PrunerStatus>> {
+        gauged_api("get_pruner_statuses", || {
+            Ok(vec![
+                self.ledger_pruner.get_status(),
+                self.state_store.state_kv_pruner.get_status(),
+                self.state_store.state_merkle_pruner.get_status(),
+                self.state_store.epoch_snapshot_pruner.get_status(),
+            ])
+        })
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         gauged_api("get_state_storage_usage", || {
             if let Some(v) = version {
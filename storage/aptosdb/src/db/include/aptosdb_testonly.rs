@@ -46,15 +46,23 @@ impl AptosDB {
         Self::new_without_pruner(db_root_path, false, BUFFERED_STATE_TARGET_ITEMS, 0, false)
     }
 
-    /// This opens db in non-readonly mode, without the pruner, and with the indexer
+    /// This opens db in non-readonly mode, without the pruner, and with the indexer, including
+    /// the account transactions index
     pub fn new_for_test_with_indexer<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        Self::new_without_pruner(
-            db_root_path,
+        let db_config = RocksdbConfigs {
+            enable_account_transactions_index: true,
+            ..Default::default()
+        };
+        Self::open(
+            StorageDirPaths::from_path(db_root_path),
             false,
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            db_config,
+            true, /* indexer */
             BUFFERED_STATE_TARGET_ITEMS,
             DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-            true, /* indexer */
         )
+        .expect("Unable to open AptosDB")
     }
 
     /// This opens db in non-readonly mode, without the pruner.
@@ -71,6 +79,35 @@ impl AptosDB {
         )
     }
 
+    /// This opens db in non-readonly mode, without the pruner, keeping all the data in memory.
+    pub fn new_for_test_in_memory<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        Self::new_for_test_in_memory_with_buffered_state_target_items(
+            db_root_path,
+            BUFFERED_STATE_TARGET_ITEMS,
+        )
+    }
+
+    /// This opens db in non-readonly mode, without the pruner, keeping all the data in memory.
+    pub fn new_for_test_in_memory_with_buffered_state_target_items<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        buffered_state_target_items: usize,
+    ) -> Self {
+        let db_config = RocksdbConfigs {
+            enable_in_memory_backend: true,
+            ..Default::default()
+        };
+        Self::open(
+            StorageDirPaths::from_path(db_root_path),
+            false,
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            db_config,
+            false, /* indexer */
+            buffered_state_target_items,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )
+        .expect("Unable to open AptosDB")
+    }
+
     /// This opens db in non-readonly mode, without the pruner.
     pub fn new_readonly_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
         Self::new_without_pruner(
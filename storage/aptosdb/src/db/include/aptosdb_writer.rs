@@ -197,6 +197,35 @@ impl DbWriter for AptosDB {
             Ok(())
         })
     }
+
+    fn set_pruner_paused(&self, pruner_name: Option<&str>, paused: bool) -> Result<()> {
+        gauged_api("set_pruner_paused", || {
+            let names = match pruner_name {
+                Some(name) => vec![name],
+                None => PRUNER_NAMES.to_vec(),
+            };
+            for name in names {
+                let enabled = match name {
+                    LEDGER_PRUNER_NAME => self.ledger_pruner.set_paused(paused),
+                    STATE_KV_PRUNER_NAME => self.state_store.state_kv_pruner.set_paused(paused),
+                    STATE_MERKLE_PRUNER_NAME => {
+                        self.state_store.state_merkle_pruner.set_paused(paused)
+                    },
+                    EPOCH_SNAPSHOT_PRUNER_NAME => {
+                        self.state_store.epoch_snapshot_pruner.set_paused(paused)
+                    },
+                    _ => bail!("Unknown pruner {}.", name),
+                };
+                // Disabled pruners are skipped unless asked for by name.
+                ensure!(
+                    enabled || pruner_name.is_none(),
+                    "Pruner {} is not enabled.",
+                    name
+                );
+            }
+            Ok(())
+        })
+    }
 }
 
 impl AptosDB {
@@ -617,6 +646,12 @@ impl AptosDB {
                 .start_timer();
             let write_sets: Vec<_> = txns_to_commit.iter().map(|txn| txn.write_set()).collect();
             indexer.index(self.state_store.clone(), first_version, &write_sets)?;
+            indexer.index_account_transactions(
+                first_version,
+                txns_to_commit
+                    .iter()
+                    .map(|txn| (txn.transaction(), txn.events(), txn.write_set())),
+            )?;
         }
 
         // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -15,7 +15,11 @@ use crate::{
         API_LATENCY_SECONDS, COMMITTED_TXNS, LATEST_TXN_VERSION, LEDGER_VERSION, NEXT_BLOCK_EPOCH,
         OTHER_TIMERS_SECONDS,
     },
-    pruner::{LedgerPrunerManager, PrunerManager, StateKvPrunerManager, StateMerklePrunerManager},
+    pruner::{
+        LedgerPrunerManager, PrunerManager, StateKvPrunerManager, StateMerklePrunerManager,
+        EPOCH_SNAPSHOT_PRUNER_NAME, LEDGER_PRUNER_NAME, PRUNER_NAMES, STATE_KV_PRUNER_NAME,
+        STATE_MERKLE_PRUNER_NAME,
+    },
     rocksdb_property_reporter::RocksdbPropertyReporter,
     schema::{
         block_info::BlockInfoSchema,
@@ -28,7 +32,8 @@ use crate::{
     utils::new_sharded_kv_schema_batch,
 };
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, StorageDirPaths, NO_OP_STORAGE_PRUNER_CONFIG,
+    PrunerConfig, RocksdbConfig, RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::HashValue;
 use aptos_db_indexer::Indexer;
@@ -39,8 +44,8 @@ use aptos_schemadb::{ReadOptions, SchemaBatch};
 use aptos_scratchpad::SparseMerkleTree;
 use aptos_storage_interface::{
     cached_state_view::ShardedStateCache, db_anyhow as anyhow, db_ensure as ensure,
-    db_other_bail as bail, state_delta::StateDelta, AptosDbError, DbReader, DbWriter,
-    ExecutedTrees, Order, Result, StateSnapshotReceiver, MAX_REQUEST_LIMIT,
+    db_other_bail as bail, pruner_status::PrunerStatus, state_delta::StateDelta, AptosDbError,
+    DbReader, DbWriter, ExecutedTrees, Order, Result, StateSnapshotReceiver, MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     account_address::AccountAddress,
@@ -156,23 +161,132 @@ impl AptosDB {
         )
     }
 
+    /// Opens the DB of another process (the primary) as a RocksDB secondary instance, which keeps
+    /// its own logs under `secondary_db_root_path`. It's read only, without pruners or indexer,
+    /// and only sees what the primary wrote up to the last `try_catch_up_with_primary`. Its latest
+    /// state checkpoint is the latest snapshot the primary persisted, which can be behind the
+    /// latest checkpoint, so the latest state is to be read at the latest version instead.
+    pub fn open_secondary(
+        db_paths: StorageDirPaths,
+        secondary_db_root_path: impl AsRef<Path>,
+        rocksdb_configs: RocksdbConfigs,
+        max_num_nodes_per_lru_cache_shard: usize,
+    ) -> Result<Self> {
+        let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs_internal(
+            &db_paths,
+            rocksdb_configs,
+            /*readonly=*/ true,
+            Some(secondary_db_root_path.as_ref()),
+            max_num_nodes_per_lru_cache_shard,
+        )?;
+
+        Ok(Self::new_with_dbs(
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            BUFFERED_STATE_TARGET_ITEMS,
+            /*hack_for_tests=*/ true,
+            /*empty_buffered_state_for_restore=*/ false,
+            rocksdb_configs.enable_storage_sharding,
+        ))
+    }
+
+    /// Makes what the primary committed so far visible, for a DB opened with `open_secondary`.
+    /// The ledger DB catches up last, so the state of every version it reports as committed is
+    /// there.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["try_catch_up_with_primary"])
+            .start_timer();
+
+        self.state_store
+            .state_db
+            .state_merkle_db
+            .try_catch_up_with_primary()?;
+        self.state_kv_db.try_catch_up_with_primary()?;
+        self.ledger_db.try_catch_up_with_primary()?;
+
+        self.ledger_pruner.reload_min_readable_version()?;
+        self.state_store
+            .state_kv_pruner
+            .reload_min_readable_version()?;
+        self.state_store
+            .state_merkle_pruner
+            .reload_min_readable_version()?;
+        self.state_store
+            .epoch_snapshot_pruner
+            .reload_min_readable_version()?;
+        self.state_store.reset_to_latest_snapshot()?;
+
+        if let Some(ledger_info) = self.ledger_db.metadata_db().get_latest_ledger_info_option() {
+            LEDGER_VERSION.set(ledger_info.ledger_info().version() as i64);
+        }
+        Ok(())
+    }
+
     pub fn open_dbs(
         db_paths: &StorageDirPaths,
         rocksdb_configs: RocksdbConfigs,
         readonly: bool,
         max_num_nodes_per_lru_cache_shard: usize,
     ) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
-        let ledger_db = LedgerDb::new(db_paths.ledger_db_root_path(), rocksdb_configs, readonly)?;
+        Self::open_dbs_internal(
+            db_paths,
+            rocksdb_configs,
+            readonly,
+            /*secondary_db_root_path=*/ None,
+            max_num_nodes_per_lru_cache_shard,
+        )
+    }
+
+    /// Opens the DBs of another process as RocksDB secondary instances, like `open_secondary`.
+    /// The state K/V and ledger DBs catch up again once the state merkle DB is open, so that
+    /// they have every version of the state snapshots it has.
+    pub fn open_dbs_as_secondary(
+        db_paths: &StorageDirPaths,
+        secondary_db_root_path: &Path,
+        rocksdb_configs: RocksdbConfigs,
+        max_num_nodes_per_lru_cache_shard: usize,
+    ) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
+        let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs_internal(
+            db_paths,
+            rocksdb_configs,
+            /*readonly=*/ true,
+            Some(secondary_db_root_path),
+            max_num_nodes_per_lru_cache_shard,
+        )?;
+        state_kv_db.try_catch_up_with_primary()?;
+        ledger_db.try_catch_up_with_primary()?;
+
+        Ok((ledger_db, state_merkle_db, state_kv_db))
+    }
+
+    fn open_dbs_internal(
+        db_paths: &StorageDirPaths,
+        rocksdb_configs: RocksdbConfigs,
+        readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        max_num_nodes_per_lru_cache_shard: usize,
+    ) -> Result<(LedgerDb, StateMerkleDb, StateKvDb)> {
+        let ledger_db = LedgerDb::new(
+            db_paths.ledger_db_root_path(),
+            rocksdb_configs,
+            readonly,
+            secondary_db_root_path,
+        )?;
         let state_kv_db = StateKvDb::new(
             db_paths,
             rocksdb_configs,
             readonly,
+            secondary_db_root_path,
             ledger_db.metadata_db_arc(),
         )?;
         let state_merkle_db = StateMerkleDb::new(
             db_paths,
             rocksdb_configs,
             readonly,
+            secondary_db_root_path,
             max_num_nodes_per_lru_cache_shard,
         )?;
 
@@ -184,21 +298,27 @@ impl AptosDB {
         BackupHandler::new(Arc::clone(&self.state_store), Arc::clone(&self.ledger_db))
     }
 
-    /// Creates new physical DB checkpoint in directory specified by `path`.
+    /// Creates new physical DB checkpoint in directory specified by `path`. With `in_memory`, the
+    /// DB must be an in-memory one that is alive, and the checkpoint lives as long as it does.
     pub fn create_checkpoint(
         db_path: impl AsRef<Path>,
         cp_path: impl AsRef<Path>,
         sharding: bool,
+        in_memory: bool,
     ) -> Result<()> {
         let start = Instant::now();
 
-        info!(sharding = sharding, "Creating checkpoint for AptosDB.");
+        info!(
+            sharding = sharding,
+            in_memory = in_memory,
+            "Creating checkpoint for AptosDB."
+        );
 
-        LedgerDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref(), sharding)?;
+        LedgerDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref(), sharding, in_memory)?;
         if sharding {
-            StateKvDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref())?;
+            StateKvDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref(), in_memory)?;
         }
-        StateMerkleDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref(), sharding)?;
+        StateMerkleDb::create_checkpoint(db_path.as_ref(), cp_path.as_ref(), sharding, in_memory)?;
 
         info!(
             db_path = db_path.as_ref(),
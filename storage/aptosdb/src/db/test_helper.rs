@@ -361,10 +361,17 @@ fn gen_snapshot_version(
 pub fn test_save_blocks_impl(
     input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
     snapshot_size_threshold: usize,
+    in_memory: bool,
 ) {
     let tmp_dir = TempPath::new();
-    let db =
-        AptosDB::new_for_test_with_buffered_state_target_items(&tmp_dir, snapshot_size_threshold);
+    let db = if in_memory {
+        AptosDB::new_for_test_in_memory_with_buffered_state_target_items(
+            &tmp_dir,
+            snapshot_size_threshold,
+        )
+    } else {
+        AptosDB::new_for_test_with_buffered_state_target_items(&tmp_dir, snapshot_size_threshold)
+    };
 
     let mut in_memory_state = db
         .state_store
@@ -25,6 +25,7 @@ impl Cmd {
             self.db_dir,
             self.output_dir,
             sharding_config.enable_storage_sharding,
+            /*in_memory=*/ false,
         )
     }
 }
@@ -30,6 +30,7 @@ impl DbDir {
                 ..Default::default()
             },
             false,
+            None,
             0,
         )
     }
@@ -42,6 +43,7 @@ impl DbDir {
                 ..Default::default()
             },
             true,
+            None,
         )
     }
 }
@@ -5,8 +5,10 @@ pub mod checkpoint;
 mod common;
 mod examine;
 pub mod ledger;
+pub mod pruner;
 pub mod state_tree;
 pub mod truncate;
+pub mod verify_state;
 
 use aptos_storage_interface::Result;
 use clap::Parser;
@@ -31,6 +33,11 @@ pub enum Cmd {
 
     #[clap(subcommand)]
     Examine(examine::Cmd),
+
+    #[clap(subcommand)]
+    Pruner(pruner::Cmd),
+
+    VerifyState(verify_state::Cmd),
 }
 
 impl Cmd {
@@ -41,6 +48,8 @@ impl Cmd {
             Cmd::Ledger(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
+            Cmd::Pruner(cmd) => cmd.run(),
+            Cmd::VerifyState(cmd) => cmd.run(),
         }
     }
 }
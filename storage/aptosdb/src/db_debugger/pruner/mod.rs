@@ -0,0 +1,23 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod prune;
+mod status;
+
+use aptos_storage_interface::Result;
+
+#[derive(clap::Subcommand)]
+#[clap(about = "Inspect and run pruners offline.")]
+pub enum Cmd {
+    Status(status::Cmd),
+    Prune(prune::Cmd),
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Self::Status(cmd) => cmd.run(),
+            Self::Prune(cmd) => cmd.run(),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::ShardingConfig,
+    ledger_db::LedgerDb,
+    pruner::{
+        DBPruner, LedgerPruner, StateKvPruner, StateMerklePruner, EPOCH_SNAPSHOT_PRUNER_NAME,
+        LEDGER_PRUNER_NAME, PRUNER_NAMES, STATE_KV_PRUNER_NAME, STATE_MERKLE_PRUNER_NAME,
+    },
+    schema::{
+        stale_node_index::StaleNodeIndexSchema,
+        stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+    },
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+    AptosDB,
+};
+use aptos_config::config::{NodeConfig, PrunerConfig, RocksdbConfigs, StorageDirPaths};
+use aptos_storage_interface::{db_ensure as ensure, db_other_bail as bail, AptosDbError, Result};
+use aptos_types::transaction::Version;
+use clap::{builder::PossibleValuesParser, Parser};
+use std::{fs, path::PathBuf, sync::Arc};
+
+type Dbs = (Arc<LedgerDb>, Arc<StateMerkleDb>, Arc<StateKvDb>);
+
+#[derive(Parser)]
+#[clap(about = "Prune data before the provided version, with the node stopped.")]
+#[clap(group(clap::ArgGroup::new("backup")
+        .required(true)
+        .args(&["backup_checkpoint_dir", "opt_out_backup_checkpoint"]),
+))]
+pub struct Cmd {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Config of the node owning the DB. Data within the prune windows it configures is kept.
+    #[clap(long, value_parser)]
+    node_config: PathBuf,
+
+    /// Data older than this version is pruned. It can't be after the latest epoch ending version,
+    /// so that the DB can still be bootstrapped and synced from, nor within the prune window of
+    /// the pruners run.
+    #[clap(long)]
+    target_version: Version,
+
+    /// Pruners to run, all of them if not provided.
+    #[clap(long, value_parser = PossibleValuesParser::new(PRUNER_NAMES))]
+    pruner: Vec<String>,
+
+    #[clap(long, default_value_t = 1000)]
+    batch_size: usize,
+
+    #[clap(long, value_parser, group = "backup")]
+    backup_checkpoint_dir: Option<PathBuf>,
+
+    #[clap(long, group = "backup")]
+    opt_out_backup_checkpoint: bool,
+
+    #[clap(flatten)]
+    sharding_config: ShardingConfig,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let pruner_config = NodeConfig::load_from_path(&self.node_config)
+            .map_err(|err| AptosDbError::Other(format!("Failed to load the node config: {err}")))?
+            .storage
+            .storage_pruner_config;
+        let pruner_names = if self.pruner.is_empty() {
+            PRUNER_NAMES.iter().map(|name| name.to_string()).collect()
+        } else {
+            self.pruner.clone()
+        };
+
+        // Check the target before creating the backup, and release the DBs for it.
+        {
+            let (ledger_db, _state_merkle_db, _state_kv_db) = self.open_dbs()?;
+            let latest_version = ledger_db.metadata_db().get_latest_version()?;
+            let latest_epoch_ending_version = ledger_db
+                .metadata_db()
+                .get_previous_epoch_ending(latest_version + 1)?
+                .map(|(_epoch, version)| version);
+            ensure!(
+                latest_epoch_ending_version.map_or(false, |v| self.target_version <= v),
+                "Target version {} is after the latest epoch ending version {:?}.",
+                self.target_version,
+                latest_epoch_ending_version,
+            );
+            for name in &pruner_names {
+                let (enable, prune_window) = Self::prune_window(&pruner_config, name)?;
+                ensure!(enable, "{} is disabled in the node config.", name);
+                ensure!(
+                    self.target_version <= latest_version.saturating_sub(prune_window),
+                    "Target version {} is within the prune window ({} versions) of {} before the \
+                    latest version {}.",
+                    self.target_version,
+                    prune_window,
+                    name,
+                    latest_version,
+                );
+            }
+        }
+
+        if !self.opt_out_backup_checkpoint {
+            let backup_checkpoint_dir = self.backup_checkpoint_dir.clone().unwrap();
+            ensure!(
+                !backup_checkpoint_dir.exists(),
+                "Backup dir already exists."
+            );
+            println!("Creating backup at: {:?}", &backup_checkpoint_dir);
+            fs::create_dir_all(&backup_checkpoint_dir)?;
+            AptosDB::create_checkpoint(
+                &self.db_dir,
+                backup_checkpoint_dir,
+                self.sharding_config.enable_storage_sharding,
+                /*in_memory=*/ false,
+            )?;
+            println!("Done!");
+        } else {
+            println!("Opted out backup creation!.");
+        }
+
+        let (ledger_db, state_merkle_db, state_kv_db) = self.open_dbs()?;
+        for name in pruner_names {
+            let pruner: Box<dyn DBPruner> = match name.as_str() {
+                LEDGER_PRUNER_NAME => Box::new(LedgerPruner::new(Arc::clone(&ledger_db))?),
+                STATE_KV_PRUNER_NAME => Box::new(StateKvPruner::new(Arc::clone(&state_kv_db))?),
+                STATE_MERKLE_PRUNER_NAME => Box::new(
+                    StateMerklePruner::<StaleNodeIndexSchema>::new(Arc::clone(&state_merkle_db))?,
+                ),
+                EPOCH_SNAPSHOT_PRUNER_NAME => {
+                    Box::new(StateMerklePruner::<StaleNodeIndexCrossEpochSchema>::new(
+                        Arc::clone(&state_merkle_db),
+                    )?)
+                },
+                _ => bail!("Unknown pruner {}.", name),
+            };
+
+            let progress = pruner.progress();
+            if progress >= self.target_version {
+                println!(
+                    "{} is already at version {}, skipping.",
+                    pruner.name(),
+                    progress
+                );
+                continue;
+            }
+            println!(
+                "Pruning {} from version {} to {}.",
+                pruner.name(),
+                progress,
+                self.target_version
+            );
+            pruner.set_target_version(self.target_version);
+            let progress = pruner.prune(self.batch_size)?;
+            println!("{} is now at version {}.", pruner.name(), progress);
+        }
+
+        println!("Done!");
+        Ok(())
+    }
+
+    /// Opens the DBs for writes, which fails while the node holds them.
+    fn open_dbs(&self) -> Result<Dbs> {
+        let rocksdb_config = RocksdbConfigs {
+            enable_storage_sharding: self.sharding_config.enable_storage_sharding,
+            ..Default::default()
+        };
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(&self.db_dir),
+            rocksdb_config,
+            /*readonly=*/ false,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )
+        .map_err(|err| {
+            AptosDbError::Other(format!(
+                "Failed to open the DB, make sure the node is stopped: {err}"
+            ))
+        })?;
+        Ok((
+            Arc::new(ledger_db),
+            Arc::new(state_merkle_db),
+            Arc::new(state_kv_db),
+        ))
+    }
+
+    /// Returns whether the pruner is enabled and its prune window in the node config.
+    fn prune_window(pruner_config: &PrunerConfig, name: &str) -> Result<(bool, Version)> {
+        Ok(match name {
+            // The state kv pruner follows the ledger pruner config.
+            LEDGER_PRUNER_NAME | STATE_KV_PRUNER_NAME => (
+                pruner_config.ledger_pruner_config.enable,
+                pruner_config.ledger_pruner_config.prune_window,
+            ),
+            STATE_MERKLE_PRUNER_NAME => (
+                pruner_config.state_merkle_pruner_config.enable,
+                pruner_config.state_merkle_pruner_config.prune_window,
+            ),
+            EPOCH_SNAPSHOT_PRUNER_NAME => (
+                pruner_config.epoch_snapshot_pruner_config.enable,
+                pruner_config.epoch_snapshot_pruner_config.prune_window,
+            ),
+            _ => bail!("Unknown pruner {}.", name),
+        })
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::ShardingConfig,
+    pruner::{
+        pruner_utils::{
+            get_ledger_pruner_progress, get_state_kv_pruner_progress,
+            get_state_merkle_pruner_progress,
+        },
+        EPOCH_SNAPSHOT_PRUNER_NAME, LEDGER_PRUNER_NAME, STATE_KV_PRUNER_NAME,
+        STATE_MERKLE_PRUNER_NAME,
+    },
+    schema::{
+        stale_node_index::StaleNodeIndexSchema,
+        stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+    },
+    AptosDB,
+};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_storage_interface::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(about = "Print the progress of each pruner, i.e. the min readable version of its data.")]
+pub struct Cmd {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    #[clap(flatten)]
+    sharding_config: ShardingConfig,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let rocksdb_config = RocksdbConfigs {
+            enable_storage_sharding: self.sharding_config.enable_storage_sharding,
+            ..Default::default()
+        };
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(&self.db_dir),
+            rocksdb_config,
+            /*readonly=*/ true,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+
+        let latest_version = ledger_db.metadata_db().get_latest_version()?;
+        println!("Latest version: {}", latest_version);
+        println!(
+            "Latest epoch ending version: {:?}",
+            ledger_db
+                .metadata_db()
+                .get_previous_epoch_ending(latest_version + 1)?
+                .map(|(_epoch, version)| version)
+        );
+
+        println!(
+            "{} progress: {}",
+            LEDGER_PRUNER_NAME,
+            get_ledger_pruner_progress(&ledger_db)?
+        );
+        println!(
+            "{} progress: {}",
+            STATE_KV_PRUNER_NAME,
+            get_state_kv_pruner_progress(&state_kv_db)?
+        );
+        println!(
+            "{} progress: {}",
+            STATE_MERKLE_PRUNER_NAME,
+            get_state_merkle_pruner_progress::<StaleNodeIndexSchema>(&state_merkle_db)?
+        );
+        println!(
+            "{} progress: {}",
+            EPOCH_SNAPSHOT_PRUNER_NAME,
+            get_state_merkle_pruner_progress::<StaleNodeIndexCrossEpochSchema>(&state_merkle_db)?
+        );
+
+        Ok(())
+    }
+}
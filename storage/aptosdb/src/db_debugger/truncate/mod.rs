@@ -66,6 +66,7 @@ impl Cmd {
                 &self.db_dir,
                 backup_checkpoint_dir,
                 self.sharding_config.enable_storage_sharding,
+                /*in_memory=*/ false,
             )?;
             println!("Done!");
         } else {
@@ -0,0 +1,550 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db::AptosDB,
+    db_debugger::common::DbDir,
+    schema::{jellyfish_merkle_node::JellyfishMerkleNodeSchema, state_value::StateValueSchema},
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+    utils::truncation_helper::get_current_version_in_state_merkle_db,
+};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_jellyfish_merkle::{
+    node_type::{Child, LeafNode, Node, NodeKey},
+    TreeReader,
+};
+use aptos_storage_interface::{db_ensure as ensure, AptosDbError, Result};
+use aptos_temppath::TempPath;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+#[derive(Parser)]
+#[clap(
+    about = "Verify the state tree at a version: recompute node hashes, check leaves against the \
+    state values and look for missing or dangling nodes."
+)]
+pub struct Cmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    /// Version of the state snapshot to verify, defaults to the latest one.
+    #[clap(long)]
+    version: Option<Version>,
+
+    /// File to keep the progress in. If it exists, verification resumes from where it stopped.
+    #[clap(long, value_parser)]
+    progress_file: Option<PathBuf>,
+
+    /// Stop after verifying this many leaves, so a large tree can be verified over several runs.
+    #[clap(long)]
+    max_leaves: Option<usize>,
+
+    /// Number of leaves between progress reports and progress file updates.
+    #[clap(long, default_value_t = 100_000)]
+    progress_interval: usize,
+}
+
+/// Progress of the verification of one state snapshot, persisted between runs.
+#[derive(Debug, Deserialize, Serialize)]
+struct Progress {
+    version: Version,
+    /// Nibbles of the position of the last verified leaf. Subtrees up to and including it are
+    /// skipped when resuming.
+    last_leaf_position: Option<Vec<u8>>,
+    num_leaves: usize,
+    num_issues: usize,
+    finished: bool,
+}
+
+impl Progress {
+    fn new(version: Version) -> Self {
+        Self {
+            version,
+            last_leaf_position: None,
+            num_leaves: 0,
+            num_issues: 0,
+            finished: false,
+        }
+    }
+
+    /// Whether the node at `position` is an ancestor of the last verified leaf, and so has been
+    /// checked against its parent by an earlier run.
+    fn is_ancestor_of_last_leaf(&self, position: &[u8]) -> bool {
+        self.last_leaf_position.as_ref().map_or(false, |last| {
+            last.len() > position.len() && last.starts_with(position)
+        })
+    }
+
+    fn is_verified(&self, position: &[u8]) -> bool {
+        match &self.last_leaf_position {
+            None => false,
+            Some(last) => {
+                position == last.as_slice()
+                    || (position < last.as_slice() && !last.starts_with(position))
+            },
+        }
+    }
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let progress = self.verify()?;
+        if progress.finished {
+            ensure!(
+                progress.num_issues == 0,
+                "Found {} issues in the state tree at version {}.",
+                progress.num_issues,
+                progress.version,
+            );
+            println!(
+                "State tree at version {} is consistent, {} leaves verified.",
+                progress.version, progress.num_leaves
+            );
+        } else {
+            println!(
+                "Stopped after {} leaves ({} issues so far), run again to resume.",
+                progress.num_leaves, progress.num_issues
+            );
+        }
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<Progress> {
+        // As secondary instances, so it can run against the DB of a live node, and sees what the
+        // node has written so far rather than only what it had flushed when it was opened.
+        let secondary_db_dir = TempPath::new();
+        secondary_db_dir.create_as_dir()?;
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs_as_secondary(
+            &StorageDirPaths::from_path(&self.db_dir),
+            secondary_db_dir.path(),
+            RocksdbConfigs {
+                enable_storage_sharding: self.db_dir.sharding_config.enable_storage_sharding,
+                ..Default::default()
+            },
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+
+        let progress = self.load_progress()?;
+        let version = match (self.version, &progress) {
+            (Some(version), Some(progress)) => {
+                ensure!(
+                    progress.version == version,
+                    "Progress file is for version {}, not {}.",
+                    progress.version,
+                    version,
+                );
+                version
+            },
+            (Some(version), None) => version,
+            (None, Some(progress)) => progress.version,
+            (None, None) => get_current_version_in_state_merkle_db(&state_merkle_db)?
+                .ok_or_else(|| AptosDbError::NotFound("No state snapshot found.".to_string()))?,
+        };
+        let mut progress = progress.unwrap_or_else(|| Progress::new(version));
+        if progress.finished {
+            println!("Verification of version {} already finished.", version);
+            return Ok(progress);
+        }
+        println!(
+            "Verifying the state tree at version {}, {} leaves verified so far.",
+            version, progress.num_leaves
+        );
+
+        let root_key = NodeKey::new_empty_path(version);
+        ensure!(
+            state_merkle_db
+                .get_node_option(&root_key, "verify_state")?
+                .is_some(),
+            "No state snapshot at version {}, it may have been pruned.",
+            version,
+        );
+        let state_checkpoint_hash = ledger_db
+            .transaction_info_db()
+            .get_transaction_info(version)?
+            .state_checkpoint_hash();
+
+        let resumed = progress.last_leaf_position.is_some();
+        let mut verifier = StateTreeVerifier {
+            cmd: self,
+            state_merkle_db: &state_merkle_db,
+            state_kv_db: &state_kv_db,
+            progress: &mut progress,
+            num_leaves_this_run: 0,
+            stopped: false,
+        };
+        let root_hash = verifier.verify_node(root_key, None)?;
+        if let (Some(root_hash), Some(state_checkpoint_hash)) = (root_hash, state_checkpoint_hash) {
+            // A resumed run has checked it already.
+            if !resumed && root_hash != state_checkpoint_hash {
+                verifier.report(format!(
+                    "Root hash {} doesn't match the state checkpoint hash {} in the ledger.",
+                    root_hash, state_checkpoint_hash,
+                ));
+            }
+        }
+        if !verifier.stopped {
+            verifier.check_dangling_nodes(version)?;
+            verifier.progress.finished = true;
+        }
+
+        self.save_progress(&progress)?;
+        Ok(progress)
+    }
+
+    fn load_progress(&self) -> Result<Option<Progress>> {
+        match &self.progress_file {
+            Some(path) if path.exists() => Ok(Some(bcs::from_bytes(&fs::read(path)?)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn save_progress(&self, progress: &Progress) -> Result<()> {
+        if let Some(path) = &self.progress_file {
+            fs::write(path, bcs::to_bytes(progress)?)?;
+        }
+        Ok(())
+    }
+}
+
+struct StateTreeVerifier<'a> {
+    cmd: &'a Cmd,
+    state_merkle_db: &'a StateMerkleDb,
+    state_kv_db: &'a StateKvDb,
+    progress: &'a mut Progress,
+    num_leaves_this_run: usize,
+    stopped: bool,
+}
+
+impl<'a> StateTreeVerifier<'a> {
+    fn report(&mut self, issue: String) {
+        println!("!!! {}", issue);
+        self.progress.num_issues += 1;
+    }
+
+    /// Verifies the subtree under `node_key` against the child entry of its parent, and returns
+    /// the hash of the node if it exists.
+    fn verify_node(
+        &mut self,
+        node_key: NodeKey,
+        expected: Option<&Child>,
+    ) -> Result<Option<HashValue>> {
+        let node = match self
+            .state_merkle_db
+            .get_node_option(&node_key, "verify_state")?
+        {
+            Some(node) => node,
+            None => {
+                self.report(format!("Missing node {:?}.", node_key));
+                return Ok(None);
+            },
+        };
+
+        // The hash of an internal node is computed from the hashes of its children, which are
+        // verified against the children themselves below.
+        let node_hash = node.hash();
+        let checked_before = self.progress.is_ancestor_of_last_leaf(&position(&node_key));
+        if let (Some(child), false) = (expected, checked_before) {
+            if node_hash != child.hash {
+                self.report(format!(
+                    "Node {:?} has hash {}, but its parent expects {}.",
+                    node_key, node_hash, child.hash,
+                ));
+            }
+            if node.node_type() != child.node_type {
+                self.report(format!(
+                    "Node {:?} is {:?}, but its parent expects {:?}.",
+                    node_key,
+                    node.node_type(),
+                    child.node_type,
+                ));
+            }
+        }
+
+        match node {
+            Node::Internal(internal_node) => {
+                for (nibble, child) in internal_node.children_sorted() {
+                    let child_key = node_key.gen_child_node_key(child.version, *nibble);
+                    if self.progress.is_verified(&position(&child_key)) {
+                        continue;
+                    }
+                    self.verify_node(child_key, Some(child))?;
+                    if self.stopped {
+                        break;
+                    }
+                }
+            },
+            Node::Leaf(leaf_node) => self.verify_leaf(&node_key, &leaf_node)?,
+            Node::Null => {
+                if !node_key.nibble_path().is_empty() {
+                    self.report(format!("Null node {:?} below the root.", node_key));
+                }
+            },
+        }
+
+        Ok(Some(node_hash))
+    }
+
+    fn verify_leaf(&mut self, node_key: &NodeKey, leaf_node: &LeafNode<StateKey>) -> Result<()> {
+        let (state_key, value_version) = leaf_node.value_index();
+        if CryptoHash::hash(state_key) != leaf_node.account_key() {
+            self.report(format!(
+                "Leaf {:?} has key hash {}, but its state key {:?} hashes to {}.",
+                node_key,
+                leaf_node.account_key(),
+                state_key,
+                CryptoHash::hash(state_key),
+            ));
+        }
+
+        match self
+            .state_kv_db
+            .db_shard(state_key.get_shard_id())
+            .get::<StateValueSchema>(&(state_key.clone(), *value_version))?
+        {
+            Some(Some(value)) => {
+                if CryptoHash::hash(&value) != leaf_node.value_hash() {
+                    self.report(format!(
+                        "Leaf {:?} has value hash {}, but the value of {:?} at version {} \
+                         hashes to {}.",
+                        node_key,
+                        leaf_node.value_hash(),
+                        state_key,
+                        value_version,
+                        CryptoHash::hash(&value),
+                    ));
+                }
+            },
+            Some(None) => self.report(format!(
+                "Leaf {:?} points to {:?} at version {}, which is a deletion.",
+                node_key, state_key, value_version,
+            )),
+            None => self.report(format!(
+                "Leaf {:?} points to {:?} at version {}, which has no value.",
+                node_key, state_key, value_version,
+            )),
+        }
+
+        self.progress.last_leaf_position = Some(position(node_key));
+        self.progress.num_leaves += 1;
+        self.num_leaves_this_run += 1;
+        if self.progress.num_leaves % self.cmd.progress_interval == 0 {
+            println!(
+                "Verified {} leaves, {} issues so far.",
+                self.progress.num_leaves, self.progress.num_issues
+            );
+            self.cmd.save_progress(self.progress)?;
+        }
+        if Some(self.num_leaves_this_run) == self.cmd.max_leaves {
+            self.stopped = true;
+        }
+        Ok(())
+    }
+
+    /// Checks that every node created at `version` is referenced by its parent in the tree.
+    fn check_dangling_nodes(&mut self, version: Version) -> Result<()> {
+        println!("Looking for dangling nodes at version {}.", version);
+        let state_merkle_db = self.state_merkle_db;
+        let dbs: Vec<_> = if state_merkle_db.sharding_enabled() {
+            std::iter::once(state_merkle_db.metadata_db())
+                .chain((0..state_merkle_db.num_shards()).map(|i| state_merkle_db.db_shard(i)))
+                .collect()
+        } else {
+            vec![state_merkle_db.metadata_db()]
+        };
+
+        for db in dbs {
+            let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+            iter.seek(&(version, 0))?;
+            for item in iter {
+                let (node_key, _) = item?;
+                if node_key.version() != version {
+                    break;
+                }
+                let nibble = match node_key.nibble_path().last() {
+                    Some(nibble) => nibble,
+                    None => continue,
+                };
+                let parent_key = node_key.gen_parent_node_key();
+                let referenced =
+                    match state_merkle_db.get_node_option(&parent_key, "verify_state")? {
+                        Some(Node::Internal(parent)) => parent
+                            .child(nibble)
+                            .map_or(false, |child| child.version == version),
+                        _ => false,
+                    };
+                if !referenced {
+                    self.report(format!("Dangling node {:?}.", node_key));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn position(node_key: &NodeKey) -> Vec<u8> {
+    node_key.nibble_path().nibbles().map(u8::from).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test_helper::{arb_blocks_to_commit, update_in_memory_state};
+    use aptos_schemadb::SchemaBatch;
+    use aptos_types::{
+        ledger_info::LedgerInfoWithSignatures, state_store::state_value::StateValue,
+        transaction::TransactionToCommit,
+    };
+    use proptest::prelude::*;
+
+    fn verify_state_cmd(
+        tmp_dir: &TempPath,
+        sharding: bool,
+        progress_file: Option<PathBuf>,
+        max_leaves: Option<usize>,
+    ) -> Cmd {
+        let db_dir = tmp_dir.path().to_str().unwrap();
+        let args = if sharding {
+            vec![
+                "verify-state",
+                "--db-dir",
+                db_dir,
+                "--enable-storage-sharding",
+            ]
+        } else {
+            vec!["verify-state", "--db-dir", db_dir]
+        };
+        Cmd::try_parse_from(args)
+            .map(|cmd| Cmd {
+                progress_file,
+                max_leaves,
+                ..cmd
+            })
+            .unwrap()
+    }
+
+    fn commit_blocks(db: &AptosDB, input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)]) {
+        let mut in_memory_state = db
+            .state_store
+            .buffered_state()
+            .lock()
+            .current_state()
+            .clone();
+        let mut version = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+            db.save_transactions_for_test(
+                txns_to_commit,
+                version,
+                version.checked_sub(1),
+                Some(ledger_info_with_sigs),
+                true,
+                in_memory_state.clone(),
+            )
+            .unwrap();
+            version += txns_to_commit.len() as u64;
+        }
+    }
+
+    fn test_verify_state_impl(
+        input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+        sharding: bool,
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = if sharding {
+            AptosDB::new_for_test_with_sharding(&tmp_dir, /*max_node_cache=*/ 0)
+        } else {
+            AptosDB::new_for_test(&tmp_dir)
+        };
+        commit_blocks(&db, &input);
+
+        // A full run finds no issues, while the DB is still open.
+        verify_state_cmd(&tmp_dir, sharding, None, None)
+            .run()
+            .unwrap();
+        drop(db);
+
+        // Runs limited to one leaf resume from where the previous one stopped.
+        let progress_path = TempPath::new();
+        let progress_file = Some(progress_path.path().to_path_buf());
+        let mut num_runs = 0;
+        let progress = loop {
+            let progress = verify_state_cmd(&tmp_dir, sharding, progress_file.clone(), Some(1))
+                .verify()
+                .unwrap();
+            num_runs += 1;
+            if progress.finished {
+                break progress;
+            }
+        };
+        assert_eq!(progress.num_issues, 0);
+        assert!(num_runs >= progress.num_leaves);
+
+        // Overwrite the value of the first leaf, which must be detected.
+        let (_, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(tmp_dir.path()),
+            RocksdbConfigs {
+                enable_storage_sharding: sharding,
+                ..Default::default()
+            },
+            /*readonly=*/ false,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )
+        .unwrap();
+        let mut node_key = NodeKey::new_empty_path(progress.version);
+        let leaf_node = loop {
+            match state_merkle_db
+                .get_node_option(&node_key, "test")
+                .unwrap()
+                .unwrap()
+            {
+                Node::Internal(internal_node) => {
+                    let (nibble, child) = internal_node.children_sorted().next().unwrap();
+                    node_key = node_key.gen_child_node_key(child.version, *nibble);
+                },
+                Node::Leaf(leaf_node) => break leaf_node,
+                Node::Null => unreachable!(),
+            }
+        };
+        let (state_key, value_version) = leaf_node.value_index();
+        let batch = SchemaBatch::new();
+        batch
+            .put::<StateValueSchema>(
+                &(state_key.clone(), *value_version),
+                &Some(StateValue::from(b"corrupted".to_vec())),
+            )
+            .unwrap();
+        state_kv_db
+            .db_shard(state_key.get_shard_id())
+            .write_schemas(batch)
+            .unwrap();
+
+        // Detected while the corrupting DB is still open as well.
+        let progress = verify_state_cmd(&tmp_dir, sharding, None, None)
+            .verify()
+            .unwrap();
+        assert_eq!(progress.num_issues, 1);
+        drop(state_merkle_db);
+        drop(state_kv_db);
+        assert!(verify_state_cmd(&tmp_dir, sharding, None, None)
+            .run()
+            .is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1))]
+
+        #[test]
+        fn test_verify_state(input in arb_blocks_to_commit()) {
+            test_verify_state_impl(input, /*sharding=*/ false);
+        }
+
+        #[test]
+        fn test_verify_state_sharded(input in arb_blocks_to_commit()) {
+            test_verify_state_impl(input, /*sharding=*/ true);
+        }
+    }
+}
@@ -14,8 +14,8 @@ use aptos_crypto::{
 };
 use aptos_infallible::Mutex;
 use aptos_storage_interface::{
-    cached_state_view::ShardedStateCache, state_delta::StateDelta, AptosDbError, DbReader,
-    DbWriter, ExecutedTrees, MAX_REQUEST_LIMIT,
+    cached_state_view::ShardedStateCache, pruner_status::PrunerStatus, state_delta::StateDelta,
+    AptosDbError, DbReader, DbWriter, ExecutedTrees, MAX_REQUEST_LIMIT,
 };
 use aptos_types::{
     access_path::AccessPath,
@@ -402,6 +402,10 @@ impl DbWriter for FakeAptosDB {
             latest_in_memory_state,
         )
     }
+
+    fn set_pruner_paused(&self, pruner_name: Option<&str>, paused: bool) -> Result<()> {
+        self.inner.set_pruner_paused(pruner_name, paused)
+    }
 }
 
 impl DbReader for FakeAptosDB {
@@ -679,6 +683,25 @@ impl DbReader for FakeAptosDB {
             .get_account_transactions(address, seq_num, limit, include_events, ledger_version)
     }
 
+    fn get_account_touched_transactions(
+        &self,
+        address: aptos_types::PeerId,
+        start_version: Version,
+        order: aptos_storage_interface::Order,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<aptos_types::transaction::AccountTransactionsWithProof> {
+        self.inner.get_account_touched_transactions(
+            address,
+            start_version,
+            order,
+            limit,
+            include_events,
+            ledger_version,
+        )
+    }
+
     fn get_state_proof_with_ledger_info(
         &self,
         known_version: u64,
@@ -731,6 +754,22 @@ impl DbReader for FakeAptosDB {
         }
     }
 
+    fn get_first_state_value_version(&self) -> Result<Option<Version>> {
+        self.inner.get_first_state_value_version()
+    }
+
+    fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        order: aptos_storage_interface::Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<StateValue>)>> {
+        self.inner
+            .get_state_value_history(state_key, start_version, order, limit, ledger_version)
+    }
+
     fn get_state_proof_by_version_ext(
         &self,
         state_key: &StateKey,
@@ -844,6 +883,14 @@ impl DbReader for FakeAptosDB {
         self.inner.indexer_enabled()
     }
 
+    fn account_transactions_index_enabled(&self) -> bool {
+        self.inner.account_transactions_index_enabled()
+    }
+
+    fn get_pruner_statuses(&self) -> Result<Vec<PrunerStatus>> {
+        self.inner.get_pruner_statuses()
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         self.inner.get_state_storage_usage(version)
     }
@@ -180,6 +180,11 @@ impl DbWriter for FastSyncStorageWrapper {
             sharded_state_cache,
         )
     }
+
+    fn set_pruner_paused(&self, pruner_name: Option<&str>, paused: bool) -> Result<()> {
+        self.get_aptos_db_write_ref()
+            .set_pruner_paused(pruner_name, paused)
+    }
 }
 
 impl DbReader for FastSyncStorageWrapper {
@@ -179,6 +179,14 @@ impl LedgerMetadataDb {
         Ok(li)
     }
 
+    /// Reloads the latest ledger info cached in memory from the DB, for a DB written by another
+    /// process.
+    pub(super) fn reload_latest_ledger_info(&self) -> Result<()> {
+        let ledger_info = get_latest_ledger_info_in_db_impl(&self.db)?;
+        self.latest_ledger_info.store(Arc::new(ledger_info));
+        Ok(())
+    }
+
     /// Stores the latest ledger info in memory.
     pub(crate) fn set_latest_ledger_info(&self, ledger_info_with_sigs: LedgerInfoWithSignatures) {
         self.latest_ledger_info
@@ -24,7 +24,7 @@ use crate::{
 };
 use aptos_config::config::{RocksdbConfig, RocksdbConfigs};
 use aptos_logger::prelude::info;
-use aptos_rocksdb_options::gen_rocksdb_options;
+use aptos_rocksdb_options::{gen_rocksdb_options, gen_secondary_rocksdb_options};
 use aptos_schemadb::{ColumnFamilyDescriptor, ColumnFamilyName, SchemaBatch, DB};
 use aptos_storage_interface::Result;
 use aptos_types::transaction::Version;
@@ -106,10 +106,13 @@ pub struct LedgerDb {
 }
 
 impl LedgerDb {
+    /// Opens the DBs as secondary instances keeping their own files under `secondary_db_root_path`
+    /// if it's provided, in which case `readonly` must be true.
     pub(crate) fn new<P: AsRef<Path>>(
         db_root_path: P,
         rocksdb_configs: RocksdbConfigs,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
         let ledger_metadata_db_path = Self::metadata_db_path(db_root_path.as_ref(), sharding);
@@ -122,6 +125,8 @@ impl LedgerDb {
             },
             &rocksdb_configs.ledger_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
         )?);
 
         info!(
@@ -157,6 +162,8 @@ impl LedgerDb {
             EVENT_DB_NAME,
             &rocksdb_configs.ledger_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
         )?);
         let event_db = EventDb::new(event_db_raw.clone(), EventStore::new(event_db_raw));
 
@@ -166,6 +173,8 @@ impl LedgerDb {
                 TRANSACTION_ACCUMULATOR_DB_NAME,
                 &rocksdb_configs.ledger_db_config,
                 readonly,
+                secondary_db_root_path,
+                rocksdb_configs.enable_in_memory_backend,
             )?));
 
         let transaction_auxiliary_data_db =
@@ -174,12 +183,16 @@ impl LedgerDb {
                 TRANSACTION_AUXILIARY_DATA_DB_NAME,
                 &rocksdb_configs.ledger_db_config,
                 readonly,
+                secondary_db_root_path,
+                rocksdb_configs.enable_in_memory_backend,
             )?));
         let transaction_db = TransactionDb::new(Arc::new(Self::open_rocksdb(
             ledger_db_folder.join(TRANSACTION_DB_NAME),
             TRANSACTION_DB_NAME,
             &rocksdb_configs.ledger_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
         )?));
 
         let transaction_info_db = TransactionInfoDb::new(Arc::new(Self::open_rocksdb(
@@ -187,6 +200,8 @@ impl LedgerDb {
             TRANSACTION_INFO_DB_NAME,
             &rocksdb_configs.ledger_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
         )?));
 
         let write_set_db = WriteSetDb::new(Arc::new(Self::open_rocksdb(
@@ -194,6 +209,8 @@ impl LedgerDb {
             WRITE_SET_DB_NAME,
             &rocksdb_configs.ledger_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
         )?));
 
         // TODO(grao): Handle data inconsistency.
@@ -213,12 +230,21 @@ impl LedgerDb {
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
         sharding: bool,
+        in_memory: bool,
     ) -> Result<()> {
         let rocksdb_configs = RocksdbConfigs {
             enable_storage_sharding: sharding,
+            enable_in_memory_backend: in_memory,
             ..Default::default()
         };
-        let ledger_db = Self::new(db_root_path, rocksdb_configs, /*readonly=*/ false)?;
+        let ledger_db = Self::new(
+            db_root_path,
+            rocksdb_configs,
+            // Opening an in-memory DB that doesn't exist would create an empty one.
+            /*readonly=*/
+            in_memory,
+            /*secondary_db_root_path=*/ None,
+        )?;
         let cp_ledger_db_folder = cp_root_path.as_ref().join(LEDGER_DB_FOLDER_NAME);
 
         info!(
@@ -275,6 +301,21 @@ impl LedgerDb {
         Ok(())
     }
 
+    /// Catches up with the primary, for DBs opened as secondary instances. The metadata DB goes
+    /// last, so that the data of the versions it says are committed is visible.
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.event_db_raw().try_catch_up_with_primary()?;
+        self.transaction_accumulator_db_raw()
+            .try_catch_up_with_primary()?;
+        self.transaction_auxiliary_data_db_raw()
+            .try_catch_up_with_primary()?;
+        self.transaction_db_raw().try_catch_up_with_primary()?;
+        self.transaction_info_db_raw().try_catch_up_with_primary()?;
+        self.write_set_db_raw().try_catch_up_with_primary()?;
+        self.ledger_metadata_db.db().try_catch_up_with_primary()?;
+        self.ledger_metadata_db.reload_latest_ledger_info()
+    }
+
     pub(crate) fn metadata_db(&self) -> &LedgerMetadataDb {
         &self.ledger_metadata_db
     }
@@ -339,8 +380,25 @@ impl LedgerDb {
         name: &str,
         db_config: &RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<DB> {
-        let db = if readonly {
+        let db = if in_memory {
+            DB::open_in_memory(
+                path.clone(),
+                name,
+                Self::get_column_families_by_name(name),
+                readonly,
+            )?
+        } else if let Some(secondary_db_root_path) = secondary_db_root_path {
+            DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(db_config),
+                path.clone(),
+                secondary_db_root_path.join(name),
+                name,
+                Self::get_column_families_by_name(name),
+            )?
+        } else if readonly {
             DB::open_cf_readonly(
                 &gen_rocksdb_options(db_config, true),
                 path.clone(),
@@ -5,13 +5,15 @@ use crate::{
     ledger_db::LedgerDb,
     metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS, PRUNER_WINDOW},
     pruner::{
-        ledger_pruner::LedgerPruner, pruner_manager::PrunerManager, pruner_utils,
+        ledger_pruner::{LedgerPruner, LEDGER_PRUNER_NAME},
+        pruner_manager::PrunerManager,
+        pruner_utils,
         pruner_worker::PrunerWorker,
     },
 };
 use aptos_config::config::LedgerPrunerConfig;
 use aptos_infallible::Mutex;
-use aptos_storage_interface::Result;
+use aptos_storage_interface::{pruner_status::PrunerStatus, Result};
 use aptos_types::transaction::{AtomicVersion, Version};
 use std::sync::{atomic::Ordering, Arc};
 
@@ -40,6 +42,12 @@ impl PrunerManager for LedgerPrunerManager {
         self.pruner_worker.is_some()
     }
 
+    fn is_pruner_running(&self) -> bool {
+        self.pruner_worker
+            .as_ref()
+            .map_or(false, |w| !w.is_paused())
+    }
+
     fn get_prune_window(&self) -> Version {
         self.prune_window
     }
@@ -50,7 +58,7 @@ impl PrunerManager for LedgerPrunerManager {
 
     fn get_min_viable_version(&self) -> Version {
         let min_version = self.get_min_readable_version();
-        if self.is_pruner_enabled() {
+        if self.is_pruner_running() {
             let adjusted_window = self
                 .prune_window
                 .saturating_sub(self.user_pruning_window_offset);
@@ -68,7 +76,7 @@ impl PrunerManager for LedgerPrunerManager {
         let min_readable_version = self.get_min_readable_version();
         // Only wake up the ledger pruner if there are `ledger_pruner_pruning_batch_size` pending
         // versions.
-        if self.is_pruner_enabled()
+        if self.is_pruner_running()
             && latest_version
                 >= min_readable_version + self.pruning_batch_size as u64 + self.prune_window
         {
@@ -93,6 +101,37 @@ impl PrunerManager for LedgerPrunerManager {
             .map_or(false, |w| w.is_pruning_pending())
     }
 
+    fn get_status(&self) -> PrunerStatus {
+        pruner_utils::get_pruner_status(
+            LEDGER_PRUNER_NAME,
+            self.pruner_worker.as_ref(),
+            self.prune_window,
+            self.get_min_readable_version(),
+        )
+    }
+
+    fn set_paused(&self, paused: bool) -> bool {
+        match &self.pruner_worker {
+            Some(worker) => {
+                worker.set_paused(paused);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn reload_min_readable_version(&self) -> Result<()> {
+        let min_readable_version = pruner_utils::get_ledger_pruner_progress(&self.ledger_db)?;
+        self.min_readable_version
+            .store(min_readable_version, Ordering::SeqCst);
+
+        PRUNER_VERSIONS
+            .with_label_values(&["ledger_pruner", "min_readable"])
+            .set(min_readable_version as i64);
+
+        Ok(())
+    }
+
     #[cfg(test)]
     fn set_worker_target_version(&self, target_version: Version) {
         self.pruner_worker
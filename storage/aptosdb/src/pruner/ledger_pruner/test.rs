@@ -44,6 +44,48 @@ proptest! {
         }
 }
 
+#[test]
+fn test_pruner_status_and_pause() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+
+    let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
+        enable: true,
+        prune_window: 10,
+        batch_size: 1,
+        user_pruning_window_offset: 0,
+    });
+    let status = pruner.get_status();
+    assert!(status.enabled);
+    assert!(!status.paused);
+    assert_eq!(status.prune_window, 10);
+    assert_eq!(status.backlog, 0);
+
+    assert!(pruner.set_paused(true));
+    assert!(pruner.get_status().paused);
+    // The min readable version is held while paused.
+    pruner.maybe_set_pruner_target_db_version(100);
+    assert_eq!(pruner.get_min_readable_version(), 0);
+    assert_eq!(pruner.get_min_viable_version(), 0);
+    assert!(pruner.set_paused(false));
+    assert!(!pruner.get_status().paused);
+    pruner.maybe_set_pruner_target_db_version(100);
+    assert_eq!(pruner.get_min_readable_version(), 90);
+
+    let disabled_pruner =
+        LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
+            enable: false,
+            prune_window: 10,
+            batch_size: 1,
+            user_pruning_window_offset: 0,
+        });
+    assert!(!disabled_pruner.set_paused(true));
+    let status = disabled_pruner.get_status();
+    assert!(!status.enabled);
+    assert!(!status.paused);
+    assert_eq!(status.progress, status.min_readable_version);
+}
+
 fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
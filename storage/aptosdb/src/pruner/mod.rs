@@ -6,12 +6,28 @@ mod db_pruner;
 mod db_sub_pruner;
 mod ledger_pruner;
 mod pruner_manager;
-mod pruner_utils;
+pub(crate) mod pruner_utils;
 mod pruner_worker;
 mod state_kv_pruner;
 mod state_merkle_pruner;
 
-pub(crate) use ledger_pruner::ledger_pruner_manager::LedgerPrunerManager;
+pub(crate) use db_pruner::DBPruner;
+pub(crate) use ledger_pruner::{
+    ledger_pruner_manager::LedgerPrunerManager, LedgerPruner, LEDGER_PRUNER_NAME,
+};
 pub(crate) use pruner_manager::PrunerManager;
-pub(crate) use state_kv_pruner::state_kv_pruner_manager::StateKvPrunerManager;
-pub(crate) use state_merkle_pruner::state_merkle_pruner_manager::StateMerklePrunerManager;
+pub(crate) use state_kv_pruner::{
+    state_kv_pruner_manager::StateKvPrunerManager, StateKvPruner, STATE_KV_PRUNER_NAME,
+};
+pub(crate) use state_merkle_pruner::{
+    generics::{EPOCH_SNAPSHOT_PRUNER_NAME, STATE_MERKLE_PRUNER_NAME},
+    state_merkle_pruner_manager::StateMerklePrunerManager,
+    StateMerklePruner,
+};
+
+pub(crate) const PRUNER_NAMES: [&str; 4] = [
+    LEDGER_PRUNER_NAME,
+    STATE_KV_PRUNER_NAME,
+    STATE_MERKLE_PRUNER_NAME,
+    EPOCH_SNAPSHOT_PRUNER_NAME,
+];
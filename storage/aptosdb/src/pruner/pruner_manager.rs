@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::pruner::db_pruner::DBPruner;
-use aptos_storage_interface::Result;
+use aptos_storage_interface::{pruner_status::PrunerStatus, Result};
 use aptos_types::transaction::Version;
 
 /// This module provides `Pruner` which manages a thread pruning old data in the background and is
@@ -19,6 +19,10 @@ pub trait PrunerManager: Sync {
 
     fn is_pruner_enabled(&self) -> bool;
 
+    /// Whether the pruner is enabled and not paused. The min readable version doesn't advance
+    /// while the pruner is paused, so the data kept meanwhile stays readable.
+    fn is_pruner_running(&self) -> bool;
+
     fn get_prune_window(&self) -> Version;
 
     fn get_min_viable_version(&self) -> Version {
@@ -36,6 +40,15 @@ pub trait PrunerManager: Sync {
 
     fn is_pruning_pending(&self) -> bool;
 
+    fn get_status(&self) -> PrunerStatus;
+
+    /// Pauses or resumes the pruner worker. Returns false if the pruner is not enabled.
+    fn set_paused(&self, paused: bool) -> bool;
+
+    /// Reloads the min readable version from the progress saved in the DB, for a DB pruned by
+    /// another process.
+    fn reload_min_readable_version(&self) -> Result<()>;
+
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
     /// an internal counter.
     #[cfg(test)]
@@ -5,7 +5,9 @@
 
 use crate::{
     ledger_db::LedgerDb,
-    pruner::state_merkle_pruner::generics::StaleNodeIndexSchemaTrait,
+    pruner::{
+        pruner_worker::PrunerWorker, state_merkle_pruner::generics::StaleNodeIndexSchemaTrait,
+    },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     state_kv_db::StateKvDb,
     state_merkle_db::StateMerkleDb,
@@ -14,6 +16,7 @@ use crate::{
 use anyhow::Result;
 use aptos_jellyfish_merkle::StaleNodeIndex;
 use aptos_schemadb::{schema::KeyCodec, DB};
+use aptos_storage_interface::pruner_status::PrunerStatus;
 use aptos_types::transaction::Version;
 
 pub(crate) fn get_ledger_pruner_progress(ledger_db: &LedgerDb) -> Result<Version> {
@@ -58,3 +61,33 @@ pub(crate) fn get_or_initialize_subpruner_progress(
         },
     )
 }
+
+/// `pruner_worker` is None iff the pruner is not enabled, in which case the progress can only be
+/// the min readable version.
+pub(crate) fn get_pruner_status(
+    name: &str,
+    pruner_worker: Option<&PrunerWorker>,
+    prune_window: Version,
+    min_readable_version: Version,
+) -> PrunerStatus {
+    let (paused, progress, target_version, versions_per_sec) = match pruner_worker {
+        Some(worker) => (
+            worker.is_paused(),
+            worker.progress(),
+            worker.target_version(),
+            worker.versions_per_sec(),
+        ),
+        None => (false, min_readable_version, min_readable_version, 0.0),
+    };
+    PrunerStatus {
+        name: name.to_string(),
+        enabled: pruner_worker.is_some(),
+        paused,
+        prune_window,
+        min_readable_version,
+        progress,
+        target_version,
+        backlog: target_version.saturating_sub(progress),
+        versions_per_sec,
+    }
+}
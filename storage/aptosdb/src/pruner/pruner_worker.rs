@@ -2,18 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::pruner::db_pruner::DBPruner;
+use aptos_infallible::Mutex;
 use aptos_logger::{
-    error,
+    error, info,
     prelude::{sample, SampleRate},
 };
 use aptos_types::transaction::Version;
 use std::{
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Condvar,
     },
     thread::{sleep, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 /// Maintains the pruner and periodically calls the db_pruner's prune method to prune the DB.
@@ -37,21 +38,36 @@ pub struct PrunerWorkerInner {
     /// Indicates whether the pruning loop should be running. Will only be set to true on pruner
     /// destruction.
     quit_worker: AtomicBool,
+    /// Set by an operator to stop pruning for a while.
+    paused: Mutex<bool>,
+    /// Wakes up the paused worker when it's resumed or destructed.
+    pause_cvar: Condvar,
+    rate: Mutex<PruningRate>,
 }
 
 impl PrunerWorkerInner {
     fn new(pruner: Arc<dyn DBPruner>, batch_size: usize) -> Arc<Self> {
+        let rate = Mutex::new(PruningRate::new(pruner.progress()));
         Arc::new(Self {
             pruning_time_interval_in_ms: if cfg!(test) { 100 } else { 1 },
             pruner,
             batch_size,
             quit_worker: AtomicBool::new(false),
+            paused: Mutex::new(false),
+            pause_cvar: Condvar::new(),
+            rate,
         })
     }
 
     // Loop that does the real pruning job.
     fn work(&self) {
         while !self.quit_worker.load(Ordering::SeqCst) {
+            self.rate.lock().update(self.pruner.progress());
+            if *self.paused.lock() {
+                self.wait_while_paused();
+                continue;
+            }
+
             let pruner_result = self.pruner.prune(self.batch_size);
             if pruner_result.is_err() {
                 sample!(
@@ -68,8 +84,23 @@ impl PrunerWorkerInner {
         }
     }
 
+    fn wait_while_paused(&self) {
+        let mut paused = self.paused.lock();
+        while *paused && !self.quit_worker.load(Ordering::SeqCst) {
+            paused = self.pause_cvar.wait(paused).expect("lock poisoned.");
+        }
+    }
+
+    fn set_paused(&self, paused: bool) {
+        *self.paused.lock() = paused;
+        self.pause_cvar.notify_all();
+    }
+
     fn stop_pruning(&self) {
+        // Set under the lock so that a worker about to wait can't miss the notification.
+        let _paused = self.paused.lock();
         self.quit_worker.store(true, Ordering::SeqCst);
+        self.pause_cvar.notify_all();
     }
 }
 
@@ -99,6 +130,60 @@ impl PrunerWorker {
     pub fn is_pruning_pending(&self) -> bool {
         self.inner.pruner.is_pruning_pending()
     }
+
+    pub fn progress(&self) -> Version {
+        self.inner.pruner.progress()
+    }
+
+    pub fn target_version(&self) -> Version {
+        self.inner.pruner.target_version()
+    }
+
+    pub fn versions_per_sec(&self) -> f64 {
+        self.inner.rate.lock().versions_per_sec
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.inner.paused.lock()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        info!(
+            pruner = %self.worker_name,
+            paused = paused,
+            "Pruner paused state changed."
+        );
+        self.inner.set_paused(paused);
+    }
+}
+
+/// Versions pruned per second, measured over at least `INTERVAL`.
+struct PruningRate {
+    since: Instant,
+    progress_since: Version,
+    versions_per_sec: f64,
+}
+
+impl PruningRate {
+    const INTERVAL: Duration = Duration::from_secs(10);
+
+    fn new(progress: Version) -> Self {
+        Self {
+            since: Instant::now(),
+            progress_since: progress,
+            versions_per_sec: 0.0,
+        }
+    }
+
+    fn update(&mut self, progress: Version) {
+        let elapsed = self.since.elapsed();
+        if elapsed >= Self::INTERVAL {
+            self.versions_per_sec =
+                progress.saturating_sub(self.progress_since) as f64 / elapsed.as_secs_f64();
+            self.since = Instant::now();
+            self.progress_since = progress;
+        }
+    }
 }
 
 impl Drop for PrunerWorker {
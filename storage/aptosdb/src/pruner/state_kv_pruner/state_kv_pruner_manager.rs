@@ -4,13 +4,15 @@
 use crate::{
     metrics::{PRUNER_BATCH_SIZE, PRUNER_VERSIONS, PRUNER_WINDOW},
     pruner::{
-        pruner_manager::PrunerManager, pruner_utils, pruner_worker::PrunerWorker,
-        state_kv_pruner::StateKvPruner,
+        pruner_manager::PrunerManager,
+        pruner_utils,
+        pruner_worker::PrunerWorker,
+        state_kv_pruner::{StateKvPruner, STATE_KV_PRUNER_NAME},
     },
     state_kv_db::StateKvDb,
 };
 use aptos_config::config::LedgerPrunerConfig;
-use aptos_storage_interface::Result;
+use aptos_storage_interface::{pruner_status::PrunerStatus, Result};
 use aptos_types::transaction::{AtomicVersion, Version};
 use std::sync::{atomic::Ordering, Arc};
 
@@ -34,6 +36,12 @@ impl PrunerManager for StateKvPrunerManager {
         self.pruner_worker.is_some()
     }
 
+    fn is_pruner_running(&self) -> bool {
+        self.pruner_worker
+            .as_ref()
+            .map_or(false, |w| !w.is_paused())
+    }
+
     fn get_prune_window(&self) -> Version {
         self.prune_window
     }
@@ -46,7 +54,7 @@ impl PrunerManager for StateKvPrunerManager {
     fn maybe_set_pruner_target_db_version(&self, latest_version: Version) {
         let min_readable_version = self.get_min_readable_version();
         // Only wake up the state kv pruner if there are `ledger_pruner_pruning_batch_size` pending
-        if self.is_pruner_enabled()
+        if self.is_pruner_running()
             && latest_version
                 >= min_readable_version + self.pruning_batch_size as u64 + self.prune_window
         {
@@ -71,6 +79,37 @@ impl PrunerManager for StateKvPrunerManager {
             .map_or(false, |w| w.is_pruning_pending())
     }
 
+    fn get_status(&self) -> PrunerStatus {
+        pruner_utils::get_pruner_status(
+            STATE_KV_PRUNER_NAME,
+            self.pruner_worker.as_ref(),
+            self.prune_window,
+            self.get_min_readable_version(),
+        )
+    }
+
+    fn set_paused(&self, paused: bool) -> bool {
+        match &self.pruner_worker {
+            Some(worker) => {
+                worker.set_paused(paused);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn reload_min_readable_version(&self) -> Result<()> {
+        let min_readable_version = pruner_utils::get_state_kv_pruner_progress(&self.state_kv_db)?;
+        self.min_readable_version
+            .store(min_readable_version, Ordering::SeqCst);
+
+        PRUNER_VERSIONS
+            .with_label_values(&["state_kv_pruner", "min_readable"])
+            .set(min_readable_version as i64);
+
+        Ok(())
+    }
+
     #[cfg(test)]
     fn set_worker_target_version(&self, target_version: Version) {
         self.pruner_worker
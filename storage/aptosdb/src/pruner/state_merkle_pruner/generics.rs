@@ -8,6 +8,9 @@ use crate::schema::{
 use aptos_jellyfish_merkle::StaleNodeIndex;
 use aptos_schemadb::schema::{KeyCodec, Schema};
 
+pub const STATE_MERKLE_PRUNER_NAME: &str = "state_merkle_pruner";
+pub const EPOCH_SNAPSHOT_PRUNER_NAME: &str = "epoch_snapshot_pruner";
+
 pub trait StaleNodeIndexSchemaTrait: Schema<Key = StaleNodeIndex>
 where
     StaleNodeIndex: KeyCodec<Self>,
@@ -26,7 +29,7 @@ impl StaleNodeIndexSchemaTrait for StaleNodeIndexSchema {
     }
 
     fn name() -> &'static str {
-        "state_merkle_pruner"
+        STATE_MERKLE_PRUNER_NAME
     }
 }
 
@@ -40,6 +43,6 @@ impl StaleNodeIndexSchemaTrait for StaleNodeIndexCrossEpochSchema {
     }
 
     fn name() -> &'static str {
-        "epoch_snapshot_pruner"
+        EPOCH_SNAPSHOT_PRUNER_NAME
     }
 }
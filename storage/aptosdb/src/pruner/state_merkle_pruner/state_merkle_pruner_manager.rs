@@ -17,7 +17,7 @@ use crate::{
 use aptos_config::config::StateMerklePrunerConfig;
 use aptos_jellyfish_merkle::StaleNodeIndex;
 use aptos_schemadb::schema::KeyCodec;
-use aptos_storage_interface::Result;
+use aptos_storage_interface::{pruner_status::PrunerStatus, Result};
 use aptos_types::transaction::{AtomicVersion, Version};
 use std::{
     marker::PhantomData,
@@ -55,6 +55,12 @@ where
         self.pruner_worker.is_some()
     }
 
+    fn is_pruner_running(&self) -> bool {
+        self.pruner_worker
+            .as_ref()
+            .map_or(false, |w| !w.is_paused())
+    }
+
     fn get_prune_window(&self) -> Version {
         self.prune_window
     }
@@ -66,7 +72,7 @@ where
     /// Sets pruner target version when necessary.
     fn maybe_set_pruner_target_db_version(&self, latest_version: Version) {
         let min_readable_version = self.get_min_readable_version();
-        if self.is_pruner_enabled() && latest_version >= min_readable_version + self.prune_window {
+        if self.is_pruner_running() && latest_version >= min_readable_version + self.prune_window {
             self.set_pruner_target_db_version(latest_version);
         }
     }
@@ -89,6 +95,38 @@ where
             .map_or(false, |w| w.is_pruning_pending())
     }
 
+    fn get_status(&self) -> PrunerStatus {
+        pruner_utils::get_pruner_status(
+            S::name(),
+            self.pruner_worker.as_ref(),
+            self.prune_window,
+            self.get_min_readable_version(),
+        )
+    }
+
+    fn set_paused(&self, paused: bool) -> bool {
+        match &self.pruner_worker {
+            Some(worker) => {
+                worker.set_paused(paused);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn reload_min_readable_version(&self) -> Result<()> {
+        let min_readable_version =
+            pruner_utils::get_state_merkle_pruner_progress::<S>(&self.state_merkle_db)?;
+        self.min_readable_version
+            .store(min_readable_version, Ordering::SeqCst);
+
+        PRUNER_VERSIONS
+            .with_label_values(&[S::name(), "min_readable"])
+            .set(min_readable_version as i64);
+
+        Ok(())
+    }
+
     #[cfg(test)]
     fn set_worker_target_version(&self, target_version: Version) {
         self.pruner_worker
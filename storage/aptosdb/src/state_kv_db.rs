@@ -13,7 +13,7 @@ use crate::{
 use aptos_config::config::{RocksdbConfig, RocksdbConfigs, StorageDirPaths};
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_logger::prelude::info;
-use aptos_rocksdb_options::gen_rocksdb_options;
+use aptos_rocksdb_options::{gen_rocksdb_options, gen_secondary_rocksdb_options};
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_storage_interface::Result;
 use aptos_types::transaction::Version;
@@ -37,6 +37,7 @@ impl StateKvDb {
         db_paths: &StorageDirPaths,
         rocksdb_configs: RocksdbConfigs,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
         ledger_db: Arc<DB>,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
@@ -49,13 +50,21 @@ impl StateKvDb {
             });
         }
 
-        Self::open(db_paths, rocksdb_configs.state_kv_db_config, readonly)
+        Self::open(
+            db_paths,
+            rocksdb_configs.state_kv_db_config,
+            readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
+        )
     }
 
     pub(crate) fn open(
         db_paths: &StorageDirPaths,
         state_kv_db_config: RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<Self> {
         let state_kv_metadata_db_path =
             Self::metadata_db_path(db_paths.state_kv_db_metadata_root_path());
@@ -65,6 +74,8 @@ impl StateKvDb {
             STATE_KV_METADATA_DB_NAME,
             &state_kv_db_config,
             readonly,
+            secondary_db_root_path,
+            in_memory,
         )?);
 
         info!(
@@ -76,7 +87,7 @@ impl StateKvDb {
         let state_kv_db_shards = {
             arr![{
                 let shard_root_path = db_paths.state_kv_db_shard_root_path(shard_id as u8);
-                let db = Self::open_shard(shard_root_path, shard_id as u8, &state_kv_db_config, readonly)?;
+                let db = Self::open_shard(shard_root_path, shard_id as u8, &state_kv_db_config, readonly, secondary_db_root_path, in_memory)?;
                 shard_id += 1;
                 Arc::new(db)
             }; 16]
@@ -88,8 +99,11 @@ impl StateKvDb {
             enabled_sharding: true,
         };
 
-        if let Some(overall_kv_commit_progress) = get_state_kv_commit_progress(&state_kv_db)? {
-            truncate_state_kv_db_shards(&state_kv_db, overall_kv_commit_progress, None)?;
+        // Read-only instances can't write, and may be opened alongside a live node.
+        if !readonly {
+            if let Some(overall_kv_commit_progress) = get_state_kv_commit_progress(&state_kv_db)? {
+                truncate_state_kv_db_shards(&state_kv_db, overall_kv_commit_progress, None)?;
+            }
         }
 
         Ok(state_kv_db)
@@ -149,12 +163,17 @@ impl StateKvDb {
     pub(crate) fn create_checkpoint(
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
+        in_memory: bool,
     ) -> Result<()> {
         // TODO(grao): Support path override here.
         let state_kv_db = Self::open(
             &StorageDirPaths::from_path(db_root_path),
             RocksdbConfig::default(),
-            false,
+            // Opening an in-memory DB that doesn't exist would create an empty one.
+            /*readonly=*/
+            in_memory,
+            /*secondary_db_root_path=*/ None,
+            in_memory,
         )?;
         let cp_state_kv_db_path = cp_root_path.as_ref().join(STATE_KV_DB_FOLDER_NAME);
 
@@ -176,6 +195,18 @@ impl StateKvDb {
         Ok(())
     }
 
+    /// Catches up with the primary, for DBs opened as secondary instances. Without sharding the
+    /// data is in the ledger DB, which catches up on its own.
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        if self.enabled_sharding {
+            for shard in &self.state_kv_db_shards {
+                shard.try_catch_up_with_primary()?;
+            }
+            self.state_kv_metadata_db.try_catch_up_with_primary()?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn metadata_db(&self) -> &DB {
         &self.state_kv_metadata_db
     }
@@ -214,6 +245,8 @@ impl StateKvDb {
         shard_id: u8,
         state_kv_db_config: &RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<DB> {
         let db_name = format!("state_kv_db_shard_{}", shard_id);
         Self::open_db(
@@ -221,6 +254,8 @@ impl StateKvDb {
             &db_name,
             state_kv_db_config,
             readonly,
+            secondary_db_root_path,
+            in_memory,
         )
     }
 
@@ -229,8 +264,20 @@ impl StateKvDb {
         name: &str,
         state_kv_db_config: &RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<DB> {
-        Ok(if readonly {
+        Ok(if in_memory {
+            DB::open_in_memory(path, name, state_kv_db_column_families(), readonly)?
+        } else if let Some(secondary_db_root_path) = secondary_db_root_path {
+            DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(state_kv_db_config),
+                path,
+                secondary_db_root_path.join(name),
+                name,
+                state_kv_db_column_families(),
+            )?
+        } else if readonly {
             DB::open_cf_readonly(
                 &gen_rocksdb_options(state_kv_db_config, true),
                 path,
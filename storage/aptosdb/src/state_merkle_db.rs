@@ -23,7 +23,7 @@ use aptos_jellyfish_merkle::{
     JellyfishMerkleTree, TreeReader, TreeUpdateBatch, TreeWriter,
 };
 use aptos_logger::prelude::*;
-use aptos_rocksdb_options::gen_rocksdb_options;
+use aptos_rocksdb_options::{gen_rocksdb_options, gen_secondary_rocksdb_options};
 use aptos_schemadb::{SchemaBatch, DB};
 #[cfg(test)]
 use aptos_scratchpad::get_state_shard_id;
@@ -69,6 +69,7 @@ impl StateMerkleDb {
         db_paths: &StorageDirPaths,
         rocksdb_configs: RocksdbConfigs,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
         max_nodes_per_lru_cache_shard: usize,
     ) -> Result<Self> {
         let sharding = rocksdb_configs.enable_storage_sharding;
@@ -90,6 +91,8 @@ impl StateMerkleDb {
                 STATE_MERKLE_DB_NAME,
                 &state_merkle_db_config,
                 readonly,
+                secondary_db_root_path,
+                rocksdb_configs.enable_in_memory_backend,
             )?);
             return Ok(Self {
                 state_merkle_metadata_db: Arc::clone(&db),
@@ -105,6 +108,8 @@ impl StateMerkleDb {
             db_paths,
             state_merkle_db_config,
             readonly,
+            secondary_db_root_path,
+            rocksdb_configs.enable_in_memory_backend,
             enable_cache,
             version_caches,
             lru_cache,
@@ -167,16 +172,21 @@ impl StateMerkleDb {
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
         sharding: bool,
+        in_memory: bool,
     ) -> Result<()> {
         let rocksdb_configs = RocksdbConfigs {
             enable_storage_sharding: sharding,
+            enable_in_memory_backend: in_memory,
             ..Default::default()
         };
         // TODO(grao): Support path override here.
         let state_merkle_db = Self::new(
             &StorageDirPaths::from_path(db_root_path),
             rocksdb_configs,
-            /*readonly=*/ false,
+            // Opening an in-memory DB that doesn't exist would create an empty one.
+            /*readonly=*/
+            in_memory,
+            /*secondary_db_root_path=*/ None,
             /*max_nodes_per_lru_cache_shard=*/ 0,
         )?;
         let cp_state_merkle_db_path = cp_root_path.as_ref().join(STATE_MERKLE_DB_FOLDER_NAME);
@@ -206,6 +216,16 @@ impl StateMerkleDb {
         Ok(())
     }
 
+    /// Catches up with the primary, for DBs opened as secondary instances.
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<()> {
+        if self.enable_sharding {
+            for shard in &self.state_merkle_db_shards {
+                shard.try_catch_up_with_primary()?;
+            }
+        }
+        self.state_merkle_metadata_db.try_catch_up_with_primary()
+    }
+
     pub(crate) fn metadata_db(&self) -> &DB {
         &self.state_merkle_metadata_db
     }
@@ -379,7 +399,6 @@ impl StateMerkleDb {
     }
 
     // A non-sharded helper function accepting KV updates from all shards.
-    #[cfg(test)]
     pub fn merklize_value_set(
         &self,
         value_set: Vec<(HashValue, Option<&(HashValue, StateKey)>)>,
@@ -405,9 +424,8 @@ impl StateMerkleDb {
                     base_version,
                     previous_epoch_ending_version,
                 )
-                .unwrap()
             })
-            .collect::<Vec<_>>()
+            .collect::<Result<Vec<_>>>()?
             .into_iter()
             .unzip();
 
@@ -562,6 +580,8 @@ impl StateMerkleDb {
         db_paths: &StorageDirPaths,
         state_merkle_db_config: RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
         enable_cache: bool,
         version_caches: HashMap<Option<u8>, VersionedNodeCache>,
         lru_cache: LruNodeCache,
@@ -576,6 +596,8 @@ impl StateMerkleDb {
             STATE_MERKLE_METADATA_DB_NAME,
             &state_merkle_db_config,
             readonly,
+            secondary_db_root_path,
+            in_memory,
         )?);
 
         info!(
@@ -586,7 +608,7 @@ impl StateMerkleDb {
         let mut shard_id: usize = 0;
         let state_merkle_db_shards = arr![{
             let shard_root_path = db_paths.state_merkle_db_shard_root_path(shard_id as u8);
-            let db = Self::open_shard(shard_root_path, shard_id as u8, &state_merkle_db_config, readonly)?;
+            let db = Self::open_shard(shard_root_path, shard_id as u8, &state_merkle_db_config, readonly, secondary_db_root_path, in_memory)?;
             shard_id += 1;
             Arc::new(db)
         }; 16];
@@ -600,13 +622,16 @@ impl StateMerkleDb {
             lru_cache,
         };
 
-        if let Some(overall_state_merkle_commit_progress) =
-            get_state_merkle_commit_progress(&state_merkle_db)?
-        {
-            truncate_state_merkle_db_shards(
-                &state_merkle_db,
-                overall_state_merkle_commit_progress,
-            )?;
+        // Read-only instances can't write, and may be opened alongside a live node.
+        if !readonly {
+            if let Some(overall_state_merkle_commit_progress) =
+                get_state_merkle_commit_progress(&state_merkle_db)?
+            {
+                truncate_state_merkle_db_shards(
+                    &state_merkle_db,
+                    overall_state_merkle_commit_progress,
+                )?;
+            }
         }
 
         Ok(state_merkle_db)
@@ -617,6 +642,8 @@ impl StateMerkleDb {
         shard_id: u8,
         state_merkle_db_config: &RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<DB> {
         let db_name = format!("state_merkle_db_shard_{}", shard_id);
         Self::open_db(
@@ -624,6 +651,8 @@ impl StateMerkleDb {
             &db_name,
             state_merkle_db_config,
             readonly,
+            secondary_db_root_path,
+            in_memory,
         )
     }
 
@@ -632,8 +661,20 @@ impl StateMerkleDb {
         name: &str,
         state_merkle_db_config: &RocksdbConfig,
         readonly: bool,
+        secondary_db_root_path: Option<&Path>,
+        in_memory: bool,
     ) -> Result<DB> {
-        Ok(if readonly {
+        Ok(if in_memory {
+            DB::open_in_memory(path, name, state_merkle_db_column_families(), readonly)?
+        } else if let Some(secondary_db_root_path) = secondary_db_root_path {
+            DB::open_cf_as_secondary(
+                &gen_secondary_rocksdb_options(state_merkle_db_config),
+                path,
+                secondary_db_root_path.join(name),
+                name,
+                state_merkle_db_column_families(),
+            )?
+        } else if readonly {
             DB::open_cf_readonly(
                 &gen_rocksdb_options(state_merkle_db_config, true),
                 path,
@@ -41,14 +41,14 @@ use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_infallible::Mutex;
 use aptos_jellyfish_merkle::iterator::JellyfishMerkleIterator;
 use aptos_logger::info;
-use aptos_schemadb::{ReadOptions, SchemaBatch};
+use aptos_schemadb::{schema::KeyCodec, ReadOptions, SchemaBatch};
 use aptos_scratchpad::{SmtAncestors, SparseMerkleTree};
 use aptos_storage_interface::{
     async_proof_fetcher::AsyncProofFetcher,
     cached_state_view::{CachedStateView, ShardedStateCache},
     db_ensure as ensure,
     state_delta::StateDelta,
-    AptosDbError, DbReader, Result, StateSnapshotReceiver,
+    AptosDbError, DbReader, Order, Result, StateSnapshotReceiver,
 };
 use aptos_types::{
     proof::{definition::LeafCount, SparseMerkleProofExt, SparseMerkleRangeProof},
@@ -65,7 +65,12 @@ use aptos_types::{
 };
 use claims::{assert_ge, assert_le};
 use rayon::prelude::*;
-use std::{collections::HashSet, ops::Deref, sync::Arc};
+use std::{
+    cmp::{max, min},
+    collections::HashSet,
+    ops::Deref,
+    sync::Arc,
+};
 
 pub(crate) mod buffered_state;
 mod state_merkle_batch_committer;
@@ -150,6 +155,14 @@ impl DbReader for StateDb {
         let mut read_opts = ReadOptions::default();
         // We want `None` if the state_key changes in iteration.
         read_opts.set_prefix_same_as_start(true);
+        // The same for the backends without prefix extractors: the versions are stored inverted,
+        // so the oldest version of the key is its last entry.
+        let mut upper_bound = <(StateKey, Version) as KeyCodec<StateValueSchema>>::encode_key(&(
+            state_key.clone(),
+            0,
+        ))?;
+        upper_bound.push(0);
+        read_opts.set_iterate_upper_bound(upper_bound);
         let mut iter = self
             .state_kv_db
             .db_shard(state_key.get_shard_id())
@@ -261,6 +274,47 @@ impl DbReader for StateStore {
 }
 
 impl StateDb {
+    /// Returns the versions at which the value of the state key changed along with the value
+    /// written at each, `None` meaning it was deleted, starting at `start_version` in the given
+    /// order. Changes after `ledger_version` are ignored.
+    pub fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        min_version: Version,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<StateValue>)>> {
+        let db = self.state_kv_db.db_shard(state_key.get_shard_id());
+        // Versions are encoded bitwise negated, so the versions of a key are in descending order.
+        let mut iter = match order {
+            Order::Ascending => {
+                let mut iter = db.rev_iter::<StateValueSchema>(ReadOptions::default())?;
+                iter.seek_for_prev(&(state_key.clone(), max(start_version, min_version)))?;
+                iter
+            },
+            Order::Descending => {
+                let mut iter = db.iter::<StateValueSchema>(ReadOptions::default())?;
+                iter.seek(&(state_key.clone(), min(start_version, ledger_version)))?;
+                iter
+            },
+        };
+
+        let mut history = Vec::new();
+        while (history.len() as u64) < limit {
+            match iter.next().transpose()? {
+                Some(((key, version), value_opt))
+                    if &key == state_key && (min_version..=ledger_version).contains(&version) =>
+                {
+                    history.push((version, value_opt))
+                },
+                _ => break,
+            }
+        }
+        Ok(history)
+    }
+
     fn expect_value_by_version(
         &self,
         state_key: &StateKey,
@@ -563,6 +617,20 @@ impl StateStore {
         *self.smt_ancestors.lock() = smt_ancestors;
     }
 
+    /// Like `reset`, but without replaying the write sets after the latest snapshot, for a DB
+    /// written by another process. The latest state checkpoint is then the latest snapshot.
+    pub fn reset_to_latest_snapshot(&self) -> Result<()> {
+        let (buffered_state, smt_ancestors) = Self::create_buffered_state_from_latest_snapshot(
+            &self.state_db,
+            self.buffered_state_target_items,
+            /*hack_for_tests=*/ true,
+            /*check_max_versions_after_snapshot=*/ false,
+        )?;
+        *self.buffered_state.lock() = buffered_state;
+        *self.smt_ancestors.lock() = smt_ancestors;
+        Ok(())
+    }
+
     pub fn buffered_state(&self) -> &Mutex<BufferedState> {
         &self.buffered_state
     }
@@ -969,6 +1037,85 @@ impl StateStore {
         Ok(root_hash)
     }
 
+    /// Saves a chunk of the state updates between the state snapshots at `base_version` and
+    /// `version`: the updated values at `version`, with their changes applied to `usage`. Returns
+    /// the key hashes of the updates along with the value hashes and keys of the updated values,
+    /// to be merklized by [`Self::commit_state_snapshot_diff`] once
+    /// all the chunks are saved.
+    pub fn save_state_snapshot_diff_values(
+        &self,
+        base_version: Version,
+        version: Version,
+        updates: Vec<(StateKey, Option<StateValue>)>,
+        usage: &mut StateStorageUsage,
+    ) -> Result<Vec<(HashValue, Option<(HashValue, StateKey)>)>> {
+        ensure!(
+            base_version < version,
+            "Base version {} is not older than version {}.",
+            base_version,
+            version,
+        );
+        let mut hashed_updates = Vec::with_capacity(updates.len());
+        let mut values = StateValueBatch::new();
+        for (key, value) in updates {
+            if let Some(old_value) = self.get_state_value_by_version(&key, base_version)? {
+                usage.remove_item(key.size() + old_value.size());
+            }
+            if let Some(value) = &value {
+                usage.add_item(key.size() + value.size());
+            }
+            hashed_updates.push((
+                key.hash(),
+                value.as_ref().map(|value| (value.hash(), key.clone())),
+            ));
+            values.insert((key, version), value);
+        }
+        let batch = SchemaBatch::new();
+        let sharded_schema_batch = new_sharded_kv_schema_batch();
+        self.shard_state_value_batch(&batch, &sharded_schema_batch, &values)?;
+        self.state_kv_db
+            .commit(version, batch, sharded_schema_batch)?;
+        Ok(hashed_updates)
+    }
+
+    /// Applies the hashed state updates between the state snapshots at `base_version` and
+    /// `version`, ordered by key hash, to the former, and saves the result as the state snapshot
+    /// at `version` along with the state usage. Nothing is written if the resulting root hash is
+    /// not `expected_root_hash`, and the values saved for the diff are overwritten once it's
+    /// applied again.
+    pub fn commit_state_snapshot_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+        hashed_updates: &[(HashValue, Option<(HashValue, StateKey)>)],
+        usage: StateStorageUsage,
+        expected_root_hash: HashValue,
+    ) -> Result<()> {
+        let (top_levels_batch, sharded_batch, root_hash) =
+            self.state_merkle_db.merklize_value_set(
+                hashed_updates
+                    .iter()
+                    .map(|(key_hash, value)| (*key_hash, value.as_ref()))
+                    .collect(),
+                version,
+                Some(base_version),
+                // The base is an epoch ending snapshot, the nodes it loses go to the epoch
+                // snapshot stale index so that the state merkle pruner keeps them.
+                /*previous_epoch_ending_version=*/
+                Some(base_version),
+            )?;
+        ensure!(
+            root_hash == expected_root_hash,
+            "Root hash mismatch after applying the state diff. root hash: {}, expected: {}",
+            root_hash,
+            expected_root_hash,
+        );
+
+        self.ledger_db.metadata_db().put_usage(version, usage)?;
+        self.state_merkle_db
+            .commit(version, top_levels_batch, sharded_batch)
+    }
+
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         self.state_merkle_db.get_root_hash(version)
     }
@@ -5,7 +5,10 @@
 use super::*;
 use crate::{
     db::test_helper::{arb_state_kv_sets, update_store},
-    schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    schema::{
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+    },
     state_restore::StateSnapshotRestore,
     utils::new_sharded_kv_schema_batch,
     AptosDB,
@@ -109,6 +112,17 @@ fn verify_value_index_in_store(
     assert_eq!(value.as_ref(), expected_value);
 }
 
+fn num_stale_node_indices<S: aptos_schemadb::schema::Schema>(db: &AptosDB) -> usize {
+    let state_merkle_db = db.state_merkle_db();
+    let mut dbs = vec![state_merkle_db.metadata_db()];
+    if state_merkle_db.sharding_enabled() {
+        dbs.extend((0..state_merkle_db.num_shards()).map(|i| state_merkle_db.db_shard(i)));
+    }
+    dbs.into_iter()
+        .map(|db| db.iter::<S>(ReadOptions::default()).unwrap().count())
+        .sum()
+}
+
 #[test]
 fn test_empty_store() {
     let tmp_dir = TempPath::new();
@@ -165,6 +179,81 @@ fn test_state_store_reader_writer() {
     verify_value_and_proof(store, key3, Some(&value3), 1, root);
 }
 
+#[test]
+fn test_get_state_value_history() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::raw(String::from("test_key2").into_bytes());
+
+    let values = (0..3)
+        .map(|i| StateValue::from(format!("test_val{}", i).into_bytes()))
+        .collect::<Vec<_>>();
+    let other_value = StateValue::from(String::from("other_val").into_bytes());
+
+    put_value_set(store, vec![(key1.clone(), values[0].clone())], 0, None);
+    put_value_set(store, vec![(key2, other_value)], 1, Some(0));
+    put_value_set(store, vec![(key1.clone(), values[1].clone())], 2, Some(1));
+    put_value_set(store, vec![(key1.clone(), values[2].clone())], 3, Some(2));
+
+    let history_since = |min_version, start_version, order, limit, ledger_version| {
+        store
+            .get_state_value_history(
+                &key1,
+                start_version,
+                order,
+                limit,
+                min_version,
+                ledger_version,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(version, value)| (version, value.unwrap()))
+            .collect::<Vec<_>>()
+    };
+    let history = |start_version, order, limit, ledger_version| {
+        history_since(0, start_version, order, limit, ledger_version)
+    };
+    let change = |version: Version, i: usize| (version, values[i].clone());
+
+    assert_eq!(history(0, Order::Ascending, 10, 3), vec![
+        change(0, 0),
+        change(2, 1),
+        change(3, 2)
+    ]);
+    assert_eq!(history(1, Order::Ascending, 10, 3), vec![
+        change(2, 1),
+        change(3, 2)
+    ]);
+    assert_eq!(history(0, Order::Ascending, 2, 3), vec![
+        change(0, 0),
+        change(2, 1)
+    ]);
+    assert_eq!(history(0, Order::Ascending, 10, 2), vec![
+        change(0, 0),
+        change(2, 1)
+    ]);
+    assert_eq!(history(3, Order::Descending, 10, 3), vec![
+        change(3, 2),
+        change(2, 1),
+        change(0, 0)
+    ]);
+    assert_eq!(history(3, Order::Descending, 10, 1), vec![change(0, 0)]);
+    assert_eq!(history(1, Order::Descending, 10, 3), vec![change(0, 0)]);
+    assert_eq!(history(3, Order::Descending, 1, 3), vec![change(3, 2)]);
+    assert!(history(4, Order::Ascending, 10, 4).is_empty());
+
+    // Changes before the min version are skipped before the limit is applied.
+    assert_eq!(history_since(1, 0, Order::Ascending, 1, 3), vec![change(
+        2, 1
+    )]);
+    assert_eq!(history_since(1, 3, Order::Descending, 10, 3), vec![
+        change(3, 2),
+        change(2, 1)
+    ]);
+}
+
 fn traverse_values(
     store: &StateStore,
     prefix: &StateKeyPrefix,
@@ -265,6 +354,58 @@ fn test_get_values_by_key_prefix() {
     assert_eq!(*key_value_map.get(&key5).unwrap(), value5_v2);
 }
 
+#[test]
+fn test_commit_state_snapshot_diff() {
+    let key1 = StateKey::raw(b"key1".to_vec());
+    let key2 = StateKey::raw(b"key2".to_vec());
+    let value1 = StateValue::from(b"value1".to_vec());
+    let value1_update = StateValue::from(b"value1_update".to_vec());
+    let value2 = StateValue::from(b"value2".to_vec());
+
+    // The state a regular commit ends up with.
+    let expected_tmp_dir = TempPath::new();
+    let expected_db = AptosDB::new_for_test(&expected_tmp_dir);
+    put_value_set(
+        &expected_db.state_store,
+        vec![(key1.clone(), value1.clone())],
+        0,
+        None,
+    );
+    let expected_root_hash = put_value_set(
+        &expected_db.state_store,
+        vec![
+            (key1.clone(), value1_update.clone()),
+            (key2.clone(), value2.clone()),
+        ],
+        1,
+        Some(0),
+    );
+
+    // Apply the same updates as a diff from the (epoch ending) snapshot at version 0.
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    put_value_set(store, vec![(key1.clone(), value1)], 0, None);
+    let mut updates = vec![
+        (key1.clone(), Some(value1_update.clone())),
+        (key2.clone(), Some(value2.clone())),
+    ];
+    updates.sort_by_key(|(key, _)| key.hash());
+    let mut usage = store.get_usage(Some(0)).unwrap();
+    let hashed_updates = store
+        .save_state_snapshot_diff_values(0, 1, updates, &mut usage)
+        .unwrap();
+    store
+        .commit_state_snapshot_diff(0, 1, &hashed_updates, usage, expected_root_hash)
+        .unwrap();
+    verify_value_and_proof(store, key1, Some(&value1_update), 1, expected_root_hash);
+    verify_value_and_proof(store, key2, Some(&value2), 1, expected_root_hash);
+
+    // The nodes of the base snapshot replaced by the diff are left to the epoch snapshot pruner.
+    assert_eq!(num_stale_node_indices::<StaleNodeIndexSchema>(&db), 0);
+    assert!(num_stale_node_indices::<StaleNodeIndexCrossEpochSchema>(&db) > 0);
+}
+
 #[test]
 pub fn test_get_state_snapshot_before() {
     let tmp_dir = TempPath::new();
@@ -266,7 +266,8 @@ impl PreheatedEpochEndingRestore {
     }
 }
 
-/// Represents a history of epoch changes since epoch 0.
+/// Represents a history of epoch changes since epoch 0, or since the first epoch kept by the
+/// backup retention, whose LedgerInfo is then trusted by a waypoint.
 #[derive(Clone)]
 pub struct EpochHistory {
     pub epoch_endings: Vec<LedgerInfo>,
@@ -277,20 +278,22 @@ impl EpochHistory {
     pub fn verify_ledger_info(&self, li_with_sigs: &LedgerInfoWithSignatures) -> Result<()> {
         let epoch = li_with_sigs.ledger_info().epoch();
         ensure!(!self.epoch_endings.is_empty(), "Empty epoch history.",);
-        if epoch > self.epoch_endings.len() as u64 {
+        let first_epoch = self.epoch_endings[0].epoch();
+        if epoch > first_epoch + self.epoch_endings.len() as u64 {
             // TODO(aldenhu): fix this from upper level
             warn!(
                 epoch = epoch,
-                epoch_history_until = self.epoch_endings.len(),
+                epoch_history_until = first_epoch + self.epoch_endings.len() as u64,
                 "Epoch is too new and can't be verified. Previous chunks are verified and node \
                 won't be able to start if this data is malicious."
             );
             return Ok(());
         }
-        if epoch == 0 {
+        if epoch == first_epoch {
             ensure!(
                 li_with_sigs.ledger_info() == &self.epoch_endings[0],
-                "Genesis epoch LedgerInfo info doesn't match.",
+                "{} epoch LedgerInfo info doesn't match.",
+                if epoch == 0 { "Genesis" } else { "First" },
             );
         } else if let Some(wp_trusted) = self
             .trusted_waypoints
@@ -304,7 +307,13 @@ impl EpochHistory {
                 wp_trusted,
             );
         } else {
-            self.epoch_endings[epoch as usize - 1]
+            ensure!(
+                epoch > first_epoch,
+                "LedgerInfo at epoch {} is before the epoch history, which starts at epoch {}.",
+                epoch,
+                first_epoch,
+            );
+            self.epoch_endings[(epoch - first_epoch) as usize - 1]
                 .next_epoch_state()
                 .ok_or_else(|| anyhow!("Shouldn't contain non- epoch bumping LIs."))?
                 .verify(li_with_sigs)?;
@@ -385,6 +394,22 @@ impl EpochHistoryRestoreController {
                 "No epochs restored from {}",
                 manifest_handle,
             );
+            if previous_li.is_none() {
+                // The history doesn't start at genesis if retention removed the earlier epochs,
+                // its first LedgerInfo is then checked against a trusted waypoint in preheat.
+                next_epoch = lis[0].epoch();
+                ensure!(
+                    next_epoch == 0
+                        || self
+                            .global_opt
+                            .trusted_waypoints
+                            .contains_key(&lis[0].version()),
+                    "Epoch history starts at epoch {}, trust the waypoint of its first \
+                    LedgerInfo (version {}) to restore from it.",
+                    next_epoch,
+                    lis[0].version(),
+                );
+            }
             for li in &lis {
                 ensure!(
                     li.epoch() == next_epoch,
@@ -229,13 +229,13 @@ async fn test_trusted_waypoints_impl(
     assert_eq!(should_fail_without, res_without_waypoints.is_err());
 
     let restored = EpochHistoryRestoreController::new(
-        manifests,
+        manifests.clone(),
         GlobalRestoreOpt {
             db_dir: None,
             dry_run: true,
             target_version: None,
             trusted_waypoints: TrustedWaypointOpt {
-                trust_waypoint: trusted_waypoints,
+                trust_waypoint: trusted_waypoints.clone(),
             },
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
@@ -249,11 +249,62 @@ async fn test_trusted_waypoints_impl(
     .await
     .unwrap();
     assert_eq!(
-        lis.into_iter()
+        lis.iter()
             .map(|li| li.ledger_info().clone())
             .collect::<Vec<_>>(),
         restored.epoch_endings,
     );
+
+    // Once retention removed the first range, the history is restorable from the next one only
+    // if its first LedgerInfo is trusted.
+    if manifests.len() < 2 {
+        return;
+    }
+    let first_li = &lis[2];
+    let first_waypoint = Waypoint::new_epoch_boundary(first_li.ledger_info()).unwrap();
+    let restore_from_second_range = |trust_waypoint: Vec<Waypoint>| {
+        EpochHistoryRestoreController::new(
+            manifests[1..].to_vec(),
+            GlobalRestoreOpt {
+                db_dir: None,
+                dry_run: true,
+                target_version: None,
+                trusted_waypoints: TrustedWaypointOpt { trust_waypoint },
+                rocksdb_opt: RocksdbOpt::default(),
+                concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            Arc::clone(&store),
+        )
+        .run()
+    };
+    let untrusted = trusted_waypoints
+        .iter()
+        .filter(|w| **w != first_waypoint)
+        .cloned()
+        .collect();
+    assert!(restore_from_second_range(untrusted).await.is_err());
+
+    let mut trust_waypoint = trusted_waypoints;
+    trust_waypoint.push(first_waypoint);
+    let restored = restore_from_second_range(trust_waypoint).await.unwrap();
+    assert_eq!(
+        lis[2..]
+            .iter()
+            .map(|li| li.ledger_info().clone())
+            .collect::<Vec<_>>(),
+        restored.epoch_endings,
+    );
+    restored.verify_ledger_info(first_li).unwrap();
+    // Earlier LedgerInfos can only be verified by trusted waypoints.
+    assert_eq!(
+        restored.verify_ledger_info(&lis[1]).is_ok(),
+        restored
+            .trusted_waypoints
+            .contains_key(&lis[1].ledger_info().version()),
+    );
 }
 
 proptest! {
@@ -4,6 +4,7 @@
 
 pub mod epoch_ending;
 pub mod state_snapshot;
+pub mod state_snapshot_diff;
 pub mod transaction;
 
 #[cfg(test)]
@@ -0,0 +1,286 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::state_snapshot_diff::manifest::{
+        StateSnapshotDiffBackup, StateSnapshotDiffChunk,
+    },
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::{TransactionWrite, WriteSet},
+};
+use clap::Parser;
+use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, convert::TryInto, str::FromStr, sync::Arc};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Parser)]
+pub struct StateSnapshotDiffBackupOpt {
+    #[clap(
+        long = "state-snapshot-base-epoch",
+        help = "Epoch at the end of which the state snapshot the diff applies to is taken."
+    )]
+    pub base_epoch: u64,
+
+    #[clap(
+        long = "state-snapshot-epoch",
+        help = "Epoch at the end of which the state snapshot resulting from the diff is taken."
+    )]
+    pub epoch: u64,
+}
+
+pub struct StateSnapshotDiffBackupController {
+    base_epoch: u64,
+    epoch: u64,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl StateSnapshotDiffBackupController {
+    pub fn new(
+        opt: StateSnapshotDiffBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            base_epoch: opt.base_epoch,
+            epoch: opt.epoch,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "State snapshot diff backup started, from epoch {} to epoch {}.",
+            self.base_epoch, self.epoch
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("State snapshot diff backup failed: {}", e))?;
+        info!("State snapshot diff backup succeeded. Manifest: {}", ret);
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<FileHandle> {
+        ensure!(
+            self.base_epoch < self.epoch,
+            "Base epoch {} is not older than epoch {}.",
+            self.base_epoch,
+            self.epoch,
+        );
+        let base_version = self.get_version_for_epoch_ending(self.base_epoch).await?;
+        let version = self.get_version_for_epoch_ending(self.epoch).await?;
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&Self::backup_name(base_version, version))
+            .await?;
+
+        let updates = self.get_updates(base_version, version).await?;
+        info!(
+            num_updates = updates.len(),
+            "Collected state updates from version {} to version {}.", base_version, version
+        );
+
+        let mut chunks = vec![];
+        let mut chunk_bytes = vec![];
+        let mut chunk_first_key = None;
+        let mut prev_key = None;
+        // The updates are serialized as their chunks are written, so they are held only once.
+        for (key_hash, update) in updates {
+            let record_bytes = bcs::to_bytes(&update)?;
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        chunk_first_key.take().expect("Chunk is not empty."),
+                        prev_key.expect("Chunk is not empty."),
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+            }
+
+            chunk_first_key.get_or_insert(key_hash);
+            prev_key = Some(key_hash);
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+        }
+        if let (Some(first_key), Some(last_key)) = (chunk_first_key, prev_key) {
+            let chunk = self
+                .write_chunk(&backup_handle, &chunk_bytes, first_key, last_key)
+                .await?;
+            chunks.push(chunk);
+        }
+
+        self.write_manifest(&backup_handle, base_version, version, chunks)
+            .await
+    }
+}
+
+impl StateSnapshotDiffBackupController {
+    fn backup_name(base_version: Version, version: Version) -> String {
+        format!("state_diff_ver_{}-{}", base_version, version)
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state_diff.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state_diff.proof").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_key: HashValue) -> ShellSafeName {
+        format!("{:x}-.chunk", first_key).try_into().unwrap()
+    }
+
+    async fn get_version_for_epoch_ending(&self, epoch: u64) -> Result<u64> {
+        let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
+            self.client
+                .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+                .await?
+                .read_record_bytes()
+                .await?
+                .ok_or_else(|| {
+                    anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch)
+                })?
+                .as_ref(),
+        )?;
+        Ok(ledger_info.ledger_info().version())
+    }
+
+    /// Derives the updates between the state snapshots at `base_version` and `version` from the
+    /// write sets of the transactions in between, and returns them ordered by key hash.
+    async fn get_updates(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<BTreeMap<HashValue, (StateKey, Option<StateValue>)>> {
+        let mut updates = BTreeMap::new();
+        let mut txns_file = self
+            .client
+            .get_transactions(base_version + 1, (version - base_version) as usize)
+            .await?;
+        let mut next_version = base_version + 1;
+        while let Some(record_bytes) = txns_file.read_record_bytes().await? {
+            let (_txn, _txn_info, _events, write_set): (
+                Transaction,
+                TransactionInfo,
+                Vec<ContractEvent>,
+                WriteSet,
+            ) = bcs::from_bytes(&record_bytes)?;
+            for (key, op) in write_set.iter() {
+                updates.insert(key.hash(), (key.clone(), op.as_state_value()));
+            }
+            next_version += 1;
+        }
+        ensure!(
+            next_version == version + 1,
+            "Transactions end at version {}, expecting {}.",
+            next_version - 1,
+            version,
+        );
+        Ok(updates)
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_key: HashValue,
+        last_key: HashValue,
+    ) -> Result<StateSnapshotDiffChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_key))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(StateSnapshotDiffChunk {
+            first_key,
+            last_key,
+            updates: chunk_handle,
+        })
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        base_version: Version,
+        version: Version,
+        chunks: Vec<StateSnapshotDiffChunk>,
+    ) -> Result<FileHandle> {
+        let base_proof_bytes = self.client.get_state_root_proof(base_version).await?;
+        let (base_txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&base_proof_bytes)?;
+
+        let proof_bytes = self.client.get_state_root_proof(version).await?;
+        let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::proof_name())
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        let manifest = StateSnapshotDiffBackup {
+            base_version,
+            base_root_hash: base_txn_info
+                .transaction_info()
+                .ensure_state_checkpoint_hash()?,
+            version,
+            epoch: self.epoch,
+            root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
+            chunks,
+            proof: proof_handle,
+        };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_state_snapshot_diff_backup(
+            base_version,
+            self.epoch,
+            version,
+            manifest_handle.clone(),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of a state snapshot diff manifest, representing the updates to keys in the key range
+/// [`first_key`, `last_key`] (right side inclusive).
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDiffChunk {
+    /// key of the first updated state key in this chunk.
+    pub first_key: HashValue,
+    /// key of the last updated state key in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, with `None` for a deleted key.
+    pub updates: FileHandle,
+}
+
+/// Differential state snapshot backup manifest, representing the state keys changed between the
+/// state snapshots at `base_version` and `version`. Applying it to the former yields the latter.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDiffBackup {
+    /// Version of the state snapshot this diff applies to.
+    pub base_version: Version,
+    /// Hash of the state tree root at `base_version`.
+    pub base_root_hash: HashValue,
+    /// Version of the state snapshot resulting from applying this diff.
+    pub version: Version,
+    /// Epoch in which the state snapshot at `version` is taken.
+    pub epoch: u64,
+    /// Hash of the state tree root at `version`.
+    pub root_hash: HashValue,
+    /// All updates in chunks, ordered by key.
+    pub chunks: Vec<StateSnapshotDiffChunk>,
+    /// BCS serialized `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)` that proves
+    /// `root_hash` at `version`, the same as `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
@@ -0,0 +1,232 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistory,
+        state_snapshot::{
+            manifest::StateSnapshotBackup,
+            restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
+        state_snapshot_diff::manifest::StateSnapshotDiffBackup,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
+        RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use clap::Parser;
+use std::sync::Arc;
+use tokio::time::Instant;
+
+#[derive(Parser)]
+pub struct StateSnapshotDiffRestoreOpt {
+    #[clap(
+        long = "state-base-manifest",
+        help = "Manifest of the state snapshot the first diff applies to. If not set, that \
+        state snapshot must already be in the DB."
+    )]
+    pub base_manifest_handle: Option<FileHandle>,
+    #[clap(
+        long = "state-diff-manifest",
+        help = "Manifests of the state snapshot diffs, applied in the order given."
+    )]
+    pub manifest_handles: Vec<FileHandle>,
+}
+
+pub struct StateSnapshotDiffRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    global_opt: GlobalRestoreOptions,
+    base_manifest_handle: Option<FileHandle>,
+    manifest_handles: Vec<FileHandle>,
+    epoch_history: Option<Arc<EpochHistory>>,
+}
+
+impl StateSnapshotDiffRestoreController {
+    pub fn new(
+        opt: StateSnapshotDiffRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Self {
+        Self {
+            storage,
+            global_opt,
+            base_manifest_handle: opt.base_manifest_handle,
+            manifest_handles: opt.manifest_handles,
+            epoch_history,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        let start = Instant::now();
+        info!("{} started. Manifests: {:?}", name, self.manifest_handles);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!(time = start.elapsed().as_secs(), "{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl StateSnapshotDiffRestoreController {
+    fn name(&self) -> String {
+        format!("state snapshot diff {}", self.global_opt.run_mode.name())
+    }
+
+    fn run_mode(&self) -> &RestoreRunMode {
+        &self.global_opt.run_mode
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let mut prev_version = None;
+        if let Some(base_manifest_handle) = &self.base_manifest_handle {
+            prev_version = Some(self.restore_base(base_manifest_handle.clone()).await?);
+        }
+
+        for manifest_handle in &self.manifest_handles {
+            let manifest: StateSnapshotDiffBackup =
+                self.storage.load_json_file(manifest_handle).await?;
+            if manifest.version > self.global_opt.target_version {
+                warn!(
+                    "Trying to apply state snapshot diff to version {}, which is newer than the target version {}, stopping.",
+                    manifest.version,
+                    self.global_opt.target_version,
+                );
+                break;
+            }
+            if let Some(prev_version) = prev_version {
+                ensure!(
+                    manifest.base_version == prev_version,
+                    "State snapshot diffs not continuous, expecting base version {}, got {}.",
+                    prev_version,
+                    manifest.base_version,
+                );
+            }
+            prev_version = Some(manifest.version);
+            self.apply_diff(manifest).await?;
+        }
+
+        self.run_mode().finish();
+        Ok(())
+    }
+
+    /// Restores the base state snapshot unless it's in the DB already, and returns its version.
+    async fn restore_base(&self, manifest_handle: FileHandle) -> Result<Version> {
+        let manifest: StateSnapshotBackup = self.storage.load_json_file(&manifest_handle).await?;
+        if self
+            .run_mode()
+            .get_state_snapshot_before(manifest.version + 1)
+            != Some((manifest.version, manifest.root_hash))
+        {
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle,
+                    version: manifest.version,
+                    validate_modules: false,
+                    restore_mode: StateSnapshotRestoreMode::Default,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                self.epoch_history.clone(),
+            )
+            .run()
+            .await?;
+        }
+        Ok(manifest.version)
+    }
+
+    async fn apply_diff(&self, manifest: StateSnapshotDiffBackup) -> Result<()> {
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            state_root_hash,
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+
+        if !self.run_mode().is_verify() {
+            let latest_snapshot = self
+                .run_mode()
+                .get_state_snapshot_before(manifest.version + 1);
+            if latest_snapshot == Some((manifest.version, manifest.root_hash)) {
+                info!(
+                    version = manifest.version,
+                    "State snapshot diff already applied, skipping."
+                );
+                return Ok(());
+            }
+            ensure!(
+                latest_snapshot == Some((manifest.base_version, manifest.base_root_hash)),
+                "State snapshot at base version {} with root hash {} not found in the DB, latest: {:?}",
+                manifest.base_version,
+                manifest.base_root_hash,
+                latest_snapshot,
+            );
+        }
+
+        // Chunks are applied one at a time, so that the values of the updates aren't held, only
+        // their hashes and keys.
+        let mut diff_restore = self
+            .run_mode()
+            .get_state_snapshot_diff_restore(manifest.base_version, manifest.version)?;
+        let mut num_updates = 0;
+        for chunk in &manifest.chunks {
+            let updates = self.read_updates(&chunk.updates).await?;
+            num_updates += updates.len();
+            if let Some(mut restore) = diff_restore.take() {
+                diff_restore = Some(
+                    tokio::task::spawn_blocking(move || {
+                        restore.add_chunk(updates)?;
+                        Ok::<_, anyhow::Error>(restore)
+                    })
+                    .await??,
+                );
+            }
+        }
+        if let Some(restore) = diff_restore {
+            let root_hash = manifest.root_hash;
+            tokio::task::spawn_blocking(move || restore.finish(root_hash)).await??;
+        }
+        info!(
+            base_version = manifest.base_version,
+            version = manifest.version,
+            num_updates = num_updates,
+            "State snapshot diff applied.",
+        );
+        Ok(())
+    }
+
+    async fn read_updates(
+        &self,
+        file_handle: &FileHandle,
+    ) -> Result<Vec<(StateKey, Option<StateValue>)>> {
+        let mut file = self.storage.open_for_read(file_handle).await?;
+
+        let mut updates = vec![];
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            updates.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(updates)
+    }
+}
@@ -11,7 +11,10 @@ use crate::{
         },
     },
     metrics::{
-        restore::{TRANSACTION_REPLAY_VERSION, TRANSACTION_SAVE_VERSION},
+        restore::{
+            TRANSACTION_REPLAY_ETA_S, TRANSACTION_REPLAY_TPS, TRANSACTION_REPLAY_VERSION,
+            TRANSACTION_SAVE_VERSION,
+        },
         verify::VERIFY_TRANSACTION_VERSION,
         OTHER_TIMERS_SECONDS,
     },
@@ -231,6 +234,8 @@ pub struct TransactionRestoreBatchController {
     verify_execution_mode: VerifyExecutionMode,
     output_transaction_analysis: Option<PathBuf>,
     first_version: Option<Version>,
+    /// Number of transactions replayed and committed together.
+    replay_chunk_size: usize,
 }
 
 impl TransactionRestoreBatchController {
@@ -253,9 +258,15 @@ impl TransactionRestoreBatchController {
             verify_execution_mode,
             output_transaction_analysis,
             first_version,
+            replay_chunk_size: BATCH_SIZE,
         }
     }
 
+    pub fn with_replay_chunk_size(mut self, replay_chunk_size: usize) -> Self {
+        self.replay_chunk_size = replay_chunk_size;
+        self
+    }
+
     pub async fn run(self) -> Result<()> {
         let name = self.name();
         info!("{} started.", name);
@@ -512,13 +523,14 @@ impl TransactionRestoreBatchController {
         >,
     ) -> Result<()> {
         let (first_version, _) = self.replay_from_version.unwrap();
+        let target_version = self.global_opt.target_version;
         let mut base_version = first_version;
         let mut offset = 0u64;
         let replay_start = Instant::now();
         let arc_restore_handler = Arc::new(restore_handler.clone());
 
         let db_commit_stream = txns_to_execute_stream
-            .try_chunks(BATCH_SIZE)
+            .try_chunks(self.replay_chunk_size)
             .err_into::<anyhow::Error>()
             .map_ok(|chunk| {
                 let (txns, txn_infos, write_sets, events): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) =
@@ -556,12 +568,16 @@ impl TransactionRestoreBatchController {
                     .start_timer();
                 tokio::task::spawn_blocking(move || {
                     // version is the latest version finishing the KV replaying
-                    let total_replayed = version - first_version;
-                    TRANSACTION_REPLAY_VERSION.set(version as i64);
+                    let (accumulative_tps, eta_secs) = update_replay_progress(
+                        first_version,
+                        version,
+                        target_version,
+                        replay_start,
+                    );
                     info!(
                         version = version,
-                        accumulative_tps =
-                            (total_replayed as f64 / replay_start.elapsed().as_secs_f64()) as u64,
+                        accumulative_tps = accumulative_tps,
+                        eta_secs = ?eta_secs,
                         "KV replayed."
                     );
                     Ok(version)
@@ -588,12 +604,13 @@ impl TransactionRestoreBatchController {
         >,
     ) -> Result<()> {
         let (first_version, _) = self.replay_from_version.unwrap();
+        let target_version = self.global_opt.target_version;
         restore_handler.reset_state_store();
         let replay_start = Instant::now();
         let db = DbReaderWriter::from_arc(Arc::clone(&restore_handler.aptosdb));
         let chunk_replayer = Arc::new(ChunkExecutor::<AptosVM>::new(db));
         let db_commit_stream = txns_to_execute_stream
-            .try_chunks(BATCH_SIZE)
+            .try_chunks(self.replay_chunk_size)
             .err_into::<anyhow::Error>()
             .map_ok(|chunk| {
                 let (txns, txn_infos, write_sets, events): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) =
@@ -631,13 +648,12 @@ impl TransactionRestoreBatchController {
                     tokio::task::spawn_blocking(move || {
                         let committed_chunk = chunk_replayer.commit()?;
                         let v = committed_chunk.result_state.current_version.unwrap_or(0);
-                        let total_replayed = v - first_version + 1;
-                        TRANSACTION_REPLAY_VERSION.set(v as i64);
+                        let (accumulative_tps, eta_secs) =
+                            update_replay_progress(first_version, v, target_version, replay_start);
                         info!(
                             version = v,
-                            accumulative_tps = (total_replayed as f64
-                                / replay_start.elapsed().as_secs_f64())
-                                as u64,
+                            accumulative_tps = accumulative_tps,
+                            eta_secs = ?eta_secs,
                             "Transactions replayed."
                         );
                         Ok(v)
@@ -693,3 +709,24 @@ impl TransactionRestoreBatchController {
         Ok(())
     }
 }
+
+/// Updates the replay progress metrics, returning the average TPS so far and the estimated
+/// seconds to reach the target version, if the target version is known.
+fn update_replay_progress(
+    first_version: Version,
+    version: Version,
+    target_version: Version,
+    replay_start: Instant,
+) -> (u64, Option<u64>) {
+    let total_replayed = version + 1 - first_version;
+    let tps = (total_replayed as f64 / replay_start.elapsed().as_secs_f64()) as u64;
+    TRANSACTION_REPLAY_VERSION.set(version as i64);
+    TRANSACTION_REPLAY_TPS.set(tps as i64);
+
+    let eta_secs = (target_version != Version::MAX && tps > 0)
+        .then(|| target_version.saturating_sub(version) / tps);
+    if let Some(eta_secs) = eta_secs {
+        TRANSACTION_REPLAY_ETA_S.set(eta_secs as i64);
+    }
+    (tps, eta_secs)
+}
@@ -5,4 +5,6 @@
 pub mod backup;
 pub mod replay_verify;
 pub mod restore;
+pub mod restore_planner;
+pub mod retention;
 pub mod verify;
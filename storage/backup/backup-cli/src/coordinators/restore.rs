@@ -6,12 +6,17 @@ use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        state_snapshot_diff::restore::{
+            StateSnapshotDiffRestoreController, StateSnapshotDiffRestoreOpt,
+        },
         transaction::restore::TransactionRestoreBatchController,
     },
+    coordinators::restore_planner::RestorePlanner,
     metadata,
     metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
+        PLAN_STATE_CHUNKS, PLAN_TRANSACTIONS_TO_REPLAY,
     },
     storage::BackupStorage,
     utils::{unix_timestamp_sec, GlobalRestoreOptions},
@@ -42,6 +47,29 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Option<Version>,
     #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[clap(
+        long,
+        default_value_t = 20000,
+        help = "How many transactions can be replayed in the time it takes to restore one chunk \
+        of a state snapshot or diff. Used to pick the cheapest combination of state snapshot, diffs \
+        and transactions to replay when restoring to a target version in a new DB."
+    )]
+    pub state_chunk_cost_in_txns: u64,
+    #[clap(
+        long,
+        default_value_t = 10000,
+        help = "Number of transactions replayed and committed together. Bigger chunks make better \
+        use of --replay-concurrency-level when verifying execution, at the cost of memory."
+    )]
+    pub replay_chunk_size: usize,
+    #[clap(
+        long,
+        help = "Execute the replayed transactions and check the results against the backup, \
+        instead of applying the write sets in the backup. Chunks are executed one after another, \
+        as each one needs the state left by the previous one, and the block executor runs the \
+        transactions of a chunk in parallel with --replay-concurrency-level threads."
+    )]
+    pub replay_verify_execution: bool,
 }
 
 pub struct RestoreCoordinator {
@@ -51,6 +79,9 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Option<Version>,
     skip_epoch_endings: bool,
+    state_chunk_cost_in_txns: u64,
+    replay_chunk_size: usize,
+    replay_verify_execution: bool,
 }
 
 impl RestoreCoordinator {
@@ -66,6 +97,9 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            state_chunk_cost_in_txns: opt.state_chunk_cost_in_txns,
+            replay_chunk_size: opt.replay_chunk_size,
+            replay_verify_execution: opt.replay_verify_execution,
         }
     }
 
@@ -90,7 +124,7 @@ impl RestoreCoordinator {
     }
 
     /// Support two modes
-    /// 1. restore to target version when do_phase_1 is false. We restore a closest snapshot and replay txns till the target version
+    /// 1. restore to target version when do_phase_1 is false. We restore the snapshot (and diffs) picked by `RestorePlanner` and replay txns till the target version
     /// 2. restore a DB with all data ranging from start_version to target_version with all KV restored between ledger_history_start_version and target_version along with the latest tree at target version.
 
     /// The overall flow is as follows:
@@ -152,7 +186,52 @@ impl RestoreCoordinator {
             .run_mode
             .get_next_expected_transaction_version()?;
 
+        // Restoring a new DB to the target version with no earlier ledger history asked for, we
+        // are free to pick the cheapest way there. If a previous run restored the state snapshot
+        // and maybe some diffs before being interrupted, the plan goes on from that state.
+        let plan = if db_next_version == 0 && lhs >= target_version {
+            let planner =
+                RestorePlanner::new(Arc::clone(&self.storage), self.state_chunk_cost_in_txns);
+            let plan = match latest_tree_version {
+                Some((version, _)) => {
+                    planner
+                        .plan_from_state(&metadata_view, version, target_version)
+                        .await?
+                },
+                None => planner.plan(&metadata_view, target_version).await?,
+            };
+            if let (None, Ok(Some(version))) = (
+                latest_tree_version,
+                self.global_opt.run_mode.get_in_progress_state_kv_snapshot(),
+            ) {
+                ensure!(
+                    plan.base_version == version,
+                    "Interrupted restore of state snapshot at version {} doesn't match the plan \
+                    starting at version {}, restore into a new DB.",
+                    version,
+                    plan.base_version,
+                );
+            }
+            info!(
+                base_version = plan.base_version,
+                restore_state_snapshot = plan.state_snapshot.is_some(),
+                state_snapshot_diffs = plan.state_snapshot_diffs.len(),
+                state_version = plan.state_version(),
+                transaction_backups = plan.transaction_backups.len(),
+                transactions_to_replay = plan.num_transactions_to_replay(),
+                estimated_cost = plan.estimated_cost,
+                "Restore planned."
+            );
+            PLAN_STATE_CHUNKS.set(plan.num_state_chunks as i64);
+            PLAN_TRANSACTIONS_TO_REPLAY.set(plan.num_transactions_to_replay() as i64);
+            Some(plan)
+        } else {
+            None
+        };
+
         let kv_snapshot = match self.global_opt.run_mode.get_in_progress_state_kv_snapshot() {
+            // The planned state snapshot is restored with both the tree and KV in phase 2.
+            _ if plan.is_some() => None,
             Ok(Some(ver)) => {
                 if db_next_version >= ver {
                     // already restored the kv snapshot, no need to restore again
@@ -178,7 +257,10 @@ impl RestoreCoordinator {
             },
         };
 
-        let tree_snapshot = if let Some((latest_tree_version, _)) = latest_tree_version {
+        let tree_snapshot = if let Some(plan) = &plan {
+            // Only restored if not in the DB yet, see phase 2.a.
+            plan.state_snapshot.clone()
+        } else if let Some((latest_tree_version, _)) = latest_tree_version {
             let snapshot = metadata_view.select_state_snapshot(latest_tree_version)?;
 
             ensure!(
@@ -186,28 +268,39 @@ impl RestoreCoordinator {
                 "cannot find tree snapshot {}",
                 latest_tree_version
             );
-            snapshot.unwrap()
+            snapshot
         } else {
-            metadata_view
-                .select_state_snapshot(target_version)?
-                .expect("Cannot find tree snapshot before target version")
+            Some(
+                metadata_view
+                    .select_state_snapshot(target_version)?
+                    .expect("Cannot find tree snapshot before target version"),
+            )
+        };
+        let tree_snapshot_version = match &plan {
+            Some(plan) => plan.base_version,
+            None => tree_snapshot.as_ref().expect("Selected above.").version,
         };
 
-        let do_phase_1 = if let Some(kv_snapshot) = kv_snapshot.as_ref() {
+        let do_phase_1 = if plan.is_some() {
+            // Nothing before the planned state is restored.
+            false
+        } else if let Some(kv_snapshot) = kv_snapshot.as_ref() {
             // if we have a kv snapshot, we need to restore the state between lhs and rs
             // if the version are equal, we don't need to restore phase 1. we can directly restore a snapshot with both tree and KV, and then replay txn till the target_version
-            kv_snapshot.version < tree_snapshot.version
+            kv_snapshot.version < tree_snapshot_version
         } else {
             // if we don't have a kv snapshot, we need to restore the state between db_next_version and rs
-            db_next_version < tree_snapshot.version
+            db_next_version < tree_snapshot_version
         };
         let txn_start_version = if let Some(kv_snapshot) = kv_snapshot.as_ref() {
             kv_snapshot.version
         } else {
             db_next_version
         };
-        let transaction_backups =
-            metadata_view.select_transaction_backups(txn_start_version, target_version)?;
+        let transaction_backups = match &plan {
+            Some(plan) => plan.transaction_backups.clone(),
+            None => metadata_view.select_transaction_backups(txn_start_version, target_version)?,
+        };
         let epoch_ending_backups = metadata_view.select_epoch_ending_backups(target_version)?;
         let epoch_handles = epoch_ending_backups
             .iter()
@@ -232,7 +325,7 @@ impl RestoreCoordinator {
         if do_phase_1 {
             info!(
                 "Start restoring DB from version {} to tree snapshot version {}",
-                txn_start_version, tree_snapshot.version,
+                txn_start_version, tree_snapshot_version,
             );
 
             // phase 1.a: restore the kv snapshot
@@ -259,7 +352,7 @@ impl RestoreCoordinator {
             let txn_manifests = transaction_backups
                 .iter()
                 .filter(|e| {
-                    e.first_version <= tree_snapshot.version && e.last_version >= db_next_version
+                    e.first_version <= tree_snapshot_version && e.last_version >= db_next_version
                 })
                 .map(|e| e.manifest.clone())
                 .collect();
@@ -281,7 +374,7 @@ impl RestoreCoordinator {
             } else {
                 db_next_version
             };
-            transaction_restore_opt.target_version = tree_snapshot.version;
+            transaction_restore_opt.target_version = tree_snapshot_version;
             TransactionRestoreBatchController::new(
                 transaction_restore_opt,
                 Arc::clone(&self.storage),
@@ -295,7 +388,7 @@ impl RestoreCoordinator {
             .run()
             .await?;
             // update the expected version for the first phase restore
-            db_next_version = tree_snapshot.version;
+            db_next_version = tree_snapshot_version;
         }
 
         // Phase 2: restore the full tree snapshot and replay till the target version
@@ -308,8 +401,59 @@ impl RestoreCoordinator {
                 "Starting restore DB from version {} to target version {}",
                 db_next_version, target_version,
             );
-            // phase 2.a: if the tree is not completed, we directly restore from the latest snapshot before target
-            if !tree_completed {
+            // phase 2.a: if the tree is not completed, we directly restore from the latest snapshot before target,
+            // or follow the plan
+            if let Some(plan) = &plan {
+                if let Some(tree_snapshot) = tree_snapshot {
+                    info!(
+                        "Start restoring planned state snapshot at {}",
+                        tree_snapshot.version
+                    );
+                    StateSnapshotRestoreController::new(
+                        StateSnapshotRestoreOpt {
+                            manifest_handle: tree_snapshot.manifest,
+                            version: tree_snapshot.version,
+                            validate_modules: false,
+                            restore_mode: StateSnapshotRestoreMode::Default,
+                        },
+                        self.global_opt.clone(),
+                        Arc::clone(&self.storage),
+                        epoch_history.clone(),
+                    )
+                    .run()
+                    .await?;
+                }
+
+                // phase 2.a.1: apply state snapshot diffs on top of the state snapshot
+                if !plan.state_snapshot_diffs.is_empty() {
+                    info!(
+                        "Start applying {} state snapshot diffs on top of version {}",
+                        plan.state_snapshot_diffs.len(),
+                        plan.base_version,
+                    );
+                    StateSnapshotDiffRestoreController::new(
+                        StateSnapshotDiffRestoreOpt {
+                            base_manifest_handle: None,
+                            manifest_handles: plan
+                                .state_snapshot_diffs
+                                .iter()
+                                .map(|d| d.manifest.clone())
+                                .collect(),
+                        },
+                        self.global_opt.clone(),
+                        Arc::clone(&self.storage),
+                        epoch_history.clone(),
+                    )
+                    .run()
+                    .await?;
+                }
+
+                replay_version = Some((
+                    plan.state_version() + 1,
+                    false, /*replay entire txn including update tree and KV*/
+                ));
+            } else if !tree_completed {
+                let tree_snapshot = tree_snapshot.expect("Selected above.");
                 // For boostrap DB to latest version, we want to use default mode
                 let restore_mode = if db_next_version > 0 {
                     StateSnapshotRestoreMode::TreeOnly
@@ -323,7 +467,7 @@ impl RestoreCoordinator {
 
                 StateSnapshotRestoreController::new(
                     StateSnapshotRestoreOpt {
-                        manifest_handle: tree_snapshot.manifest.clone(),
+                        manifest_handle: tree_snapshot.manifest,
                         version: tree_snapshot.version,
                         validate_modules: false,
                         restore_mode,
@@ -346,16 +490,25 @@ impl RestoreCoordinator {
                 .filter(|e| e.last_version >= db_next_version)
                 .map(|e| e.manifest.clone())
                 .collect();
+            let verify_execution_mode = if self.replay_verify_execution {
+                VerifyExecutionMode::verify_all()
+            } else {
+                VerifyExecutionMode::NoVerify
+            };
+            let mut global_opt = self.global_opt;
+            // So the replay progress knows where it ends.
+            global_opt.target_version = target_version;
             TransactionRestoreBatchController::new(
-                self.global_opt,
+                global_opt,
                 self.storage,
                 txn_manifests,
                 first_version,
                 replay_version,
                 epoch_history,
-                VerifyExecutionMode::NoVerify,
+                verify_execution_mode,
                 None,
             )
+            .with_replay_chunk_size(self.replay_chunk_size)
             .run()
             .await?;
         }
@@ -0,0 +1,384 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata::{
+        view::MetadataView, StateSnapshotBackupMeta, StateSnapshotDiffBackupMeta,
+        TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::storage_ext::BackupStorageExt,
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_types::transaction::Version;
+use serde::{de::IgnoredAny, Deserialize};
+use std::{future::Future, sync::Arc};
+
+/// How to get the DB to a target version: restore a state snapshot, apply state snapshot diffs on
+/// top of it, and replay transactions from there on.
+#[derive(Debug)]
+pub struct RestorePlan {
+    /// Version of the state the diffs apply to.
+    pub base_version: Version,
+    /// The state snapshot at `base_version` to restore, `None` if the DB has that state already,
+    /// when resuming an interrupted restore.
+    pub state_snapshot: Option<StateSnapshotBackupMeta>,
+    pub state_snapshot_diffs: Vec<StateSnapshotDiffBackupMeta>,
+    /// Transaction backups from the base version to the target version.
+    pub transaction_backups: Vec<TransactionBackupMeta>,
+    pub target_version: Version,
+    /// Chunks in the state snapshot and the diffs.
+    pub num_state_chunks: usize,
+    /// In the number of transactions that can be replayed in the same time.
+    pub estimated_cost: u64,
+}
+
+impl RestorePlan {
+    /// Version of the state after the state snapshot and diffs are restored.
+    pub fn state_version(&self) -> Version {
+        self.state_snapshot_diffs
+            .last()
+            .map_or(self.base_version, |diff| diff.version)
+    }
+
+    pub fn num_transactions_to_replay(&self) -> u64 {
+        self.target_version - self.state_version()
+    }
+
+    fn with_estimated_cost(&self, state_chunk_cost_in_txns: u64) -> Self {
+        Self {
+            base_version: self.base_version,
+            state_snapshot: self.state_snapshot.clone(),
+            state_snapshot_diffs: self.state_snapshot_diffs.clone(),
+            transaction_backups: Vec::new(),
+            target_version: self.target_version,
+            num_state_chunks: self.num_state_chunks,
+            estimated_cost: self.num_state_chunks as u64 * state_chunk_cost_in_txns
+                + self.num_transactions_to_replay(),
+        }
+    }
+}
+
+/// Only to count chunks of either a state snapshot or a state snapshot diff manifest.
+#[derive(Deserialize)]
+struct ManifestChunks {
+    chunks: Vec<IgnoredAny>,
+}
+
+/// Picks the cheapest `RestorePlan`. A later state snapshot means fewer transactions to replay,
+/// but the state can be bigger, and there can be diffs that are cheaper than either.
+pub struct RestorePlanner {
+    storage: Arc<dyn BackupStorage>,
+    state_chunk_cost_in_txns: u64,
+}
+
+impl RestorePlanner {
+    pub fn new(storage: Arc<dyn BackupStorage>, state_chunk_cost_in_txns: u64) -> Self {
+        Self {
+            storage,
+            state_chunk_cost_in_txns,
+        }
+    }
+
+    pub async fn plan(&self, view: &MetadataView, target_version: Version) -> Result<RestorePlan> {
+        let storage = &self.storage;
+        cheapest_plan(
+            view,
+            target_version,
+            self.state_chunk_cost_in_txns,
+            |manifest| num_chunks(storage, manifest),
+        )
+        .await
+    }
+
+    /// Plans the rest of an interrupted restore, from the state at `base_version` in the DB.
+    /// Only the diffs and transactions after it are left to pick.
+    pub async fn plan_from_state(
+        &self,
+        view: &MetadataView,
+        base_version: Version,
+        target_version: Version,
+    ) -> Result<RestorePlan> {
+        let storage = &self.storage;
+        cheapest_plan_from_state(
+            view,
+            base_version,
+            target_version,
+            self.state_chunk_cost_in_txns,
+            |manifest| num_chunks(storage, manifest),
+        )
+        .await
+    }
+}
+
+async fn num_chunks(storage: &Arc<dyn BackupStorage>, manifest: FileHandle) -> Result<usize> {
+    Ok(storage
+        .load_json_file::<ManifestChunks>(&manifest)
+        .await?
+        .chunks
+        .len())
+}
+
+/// `num_chunks` returns the number of chunks in a state snapshot or diff manifest, which is only
+/// called for snapshots that could be in the cheapest plan, from the latest on.
+async fn cheapest_plan<F, Fut>(
+    view: &MetadataView,
+    target_version: Version,
+    state_chunk_cost_in_txns: u64,
+    num_chunks: F,
+) -> Result<RestorePlan>
+where
+    F: Fn(FileHandle) -> Fut,
+    Fut: Future<Output = Result<usize>>,
+{
+    let mut best: Option<RestorePlan> = None;
+    for snapshot in view
+        .state_snapshot_backups()
+        .iter()
+        .rev()
+        .filter(|s| s.version <= target_version)
+    {
+        // Replaying from an earlier snapshot alone costs more than the best plan.
+        if let Some(best) = &best {
+            if target_version - snapshot.version >= best.estimated_cost {
+                break;
+            }
+        }
+
+        let candidate = RestorePlan {
+            base_version: snapshot.version,
+            state_snapshot: Some(snapshot.clone()),
+            state_snapshot_diffs: Vec::new(),
+            transaction_backups: Vec::new(),
+            target_version,
+            num_state_chunks: num_chunks(snapshot.manifest.clone()).await?,
+            estimated_cost: 0,
+        };
+        keep_cheapest_with_diffs(
+            &mut best,
+            candidate,
+            view,
+            state_chunk_cost_in_txns,
+            &num_chunks,
+        )
+        .await?;
+    }
+
+    let plan = best.ok_or_else(|| {
+        anyhow!(
+            "No state snapshot found at or before target version {}.",
+            target_version
+        )
+    })?;
+    with_transaction_backups(plan, view)
+}
+
+/// Like `cheapest_plan`, but with the state at `base_version` in the DB already.
+async fn cheapest_plan_from_state<F, Fut>(
+    view: &MetadataView,
+    base_version: Version,
+    target_version: Version,
+    state_chunk_cost_in_txns: u64,
+    num_chunks: F,
+) -> Result<RestorePlan>
+where
+    F: Fn(FileHandle) -> Fut,
+    Fut: Future<Output = Result<usize>>,
+{
+    ensure!(
+        base_version <= target_version,
+        "State in the DB at version {} is after target version {}.",
+        base_version,
+        target_version,
+    );
+    let candidate = RestorePlan {
+        base_version,
+        state_snapshot: None,
+        state_snapshot_diffs: Vec::new(),
+        transaction_backups: Vec::new(),
+        target_version,
+        num_state_chunks: 0,
+        estimated_cost: 0,
+    };
+    let mut best = None;
+    keep_cheapest_with_diffs(
+        &mut best,
+        candidate,
+        view,
+        state_chunk_cost_in_txns,
+        &num_chunks,
+    )
+    .await?;
+    with_transaction_backups(best.expect("At least one candidate."), view)
+}
+
+/// Considers `candidate` with each prefix of the chain of diffs from its base version.
+async fn keep_cheapest_with_diffs<F, Fut>(
+    best: &mut Option<RestorePlan>,
+    mut candidate: RestorePlan,
+    view: &MetadataView,
+    state_chunk_cost_in_txns: u64,
+    num_chunks: &F,
+) -> Result<()>
+where
+    F: Fn(FileHandle) -> Fut,
+    Fut: Future<Output = Result<usize>>,
+{
+    for diff in
+        view.select_state_snapshot_diffs(candidate.base_version, candidate.target_version)?
+    {
+        let plan_without_diff = candidate.with_estimated_cost(state_chunk_cost_in_txns);
+        candidate.num_state_chunks += num_chunks(diff.manifest.clone()).await?;
+        candidate.state_snapshot_diffs.push(diff);
+        keep_cheaper(best, plan_without_diff);
+    }
+    keep_cheaper(
+        best,
+        candidate.with_estimated_cost(state_chunk_cost_in_txns),
+    );
+    Ok(())
+}
+
+fn with_transaction_backups(mut plan: RestorePlan, view: &MetadataView) -> Result<RestorePlan> {
+    plan.transaction_backups =
+        view.select_transaction_backups(plan.base_version, plan.target_version)?;
+    Ok(plan)
+}
+
+fn keep_cheaper(best: &mut Option<RestorePlan>, candidate: RestorePlan) {
+    if best
+        .as_ref()
+        .map_or(true, |best| candidate.estimated_cost < best.estimated_cost)
+    {
+        *best = Some(candidate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coordinators::restore_planner::{cheapest_plan, cheapest_plan_from_state},
+        metadata::{view::MetadataView, Metadata},
+    };
+    use futures::{executor::block_on, future};
+    use std::collections::HashMap;
+
+    /// Transactions from 0 to 10000 in one backup, and state snapshots with the number of chunks
+    /// at each version.
+    fn view_and_chunks(
+        snapshots: &[(u64, usize)],
+        diffs: &[(u64, u64, usize)],
+    ) -> (MetadataView, HashMap<String, usize>) {
+        let mut metadata = vec![Metadata::new_transaction_backup(
+            0,
+            10000,
+            "transaction".into(),
+        )];
+        let mut chunks = HashMap::new();
+        for (version, num_chunks) in snapshots {
+            let manifest = format!("state_snapshot_{}", version);
+            chunks.insert(manifest.clone(), *num_chunks);
+            metadata.push(Metadata::new_state_snapshot_backup(0, *version, manifest));
+        }
+        for (base_version, version, num_chunks) in diffs {
+            let manifest = format!("diff_{}_{}", base_version, version);
+            chunks.insert(manifest.clone(), *num_chunks);
+            metadata.push(Metadata::new_state_snapshot_diff_backup(
+                *base_version,
+                0,
+                *version,
+                manifest,
+            ));
+        }
+        (MetadataView::new(metadata, Vec::new()), chunks)
+    }
+
+    fn plan(
+        view: &MetadataView,
+        chunks: &HashMap<String, usize>,
+        target_version: u64,
+    ) -> (u64, Vec<u64>, u64) {
+        let plan = block_on(cheapest_plan(view, target_version, 100, |manifest| {
+            future::ready(Ok(chunks[&manifest]))
+        }))
+        .unwrap();
+        (
+            plan.base_version,
+            plan.state_snapshot_diffs
+                .iter()
+                .map(|d| d.version)
+                .collect(),
+            plan.estimated_cost,
+        )
+    }
+
+    #[test]
+    fn test_latest_snapshot() {
+        let (view, chunks) = view_and_chunks(&[(0, 1), (1000, 2), (2000, 2)], &[]);
+        assert_eq!(plan(&view, &chunks, 2500), (2000, vec![], 700));
+        assert_eq!(plan(&view, &chunks, 1999), (1000, vec![], 1199));
+    }
+
+    #[test]
+    fn test_earlier_smaller_snapshot() {
+        // The latest snapshot is too big to be worth it.
+        let (view, chunks) = view_and_chunks(&[(1000, 2), (2000, 20)], &[]);
+        assert_eq!(plan(&view, &chunks, 2500), (1000, vec![], 1700));
+    }
+
+    #[test]
+    fn test_diffs() {
+        let (view, chunks) = view_and_chunks(&[(1000, 10), (2000, 20)], &[
+            (1000, 1500, 1),
+            (1500, 1900, 1),
+        ]);
+        assert_eq!(plan(&view, &chunks, 2500), (1000, vec![1500, 1900], 1800));
+        // The second diff doesn't pay off when replaying to the version before it.
+        assert_eq!(plan(&view, &chunks, 1600), (1000, vec![1500], 1200));
+    }
+
+    #[test]
+    fn test_resume_from_state() {
+        let (view, chunks) = view_and_chunks(&[(1000, 10), (2000, 20)], &[
+            (1000, 1500, 1),
+            (1500, 1900, 1),
+        ]);
+        let plan = block_on(cheapest_plan_from_state(
+            &view,
+            1500,
+            2500,
+            100,
+            |manifest| future::ready(Ok(chunks[&manifest])),
+        ))
+        .unwrap();
+        // The state in the DB is not restored again, only the diff after it.
+        assert!(plan.state_snapshot.is_none());
+        assert_eq!(
+            plan.state_snapshot_diffs
+                .iter()
+                .map(|d| d.version)
+                .collect::<Vec<_>>(),
+            vec![1900]
+        );
+        assert_eq!(plan.estimated_cost, 700);
+        assert_eq!(plan.transaction_backups.len(), 1);
+
+        assert!(block_on(cheapest_plan_from_state(
+            &view,
+            2600,
+            2500,
+            100,
+            |manifest| { future::ready(Ok(chunks[&manifest])) }
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_no_snapshot() {
+        let (view, chunks) = view_and_chunks(&[(1000, 1)], &[]);
+        assert!(block_on(cheapest_plan(&view, 999, 100, |manifest| {
+            future::ready(Ok(chunks[&manifest]))
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,700 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup, state_snapshot::manifest::StateSnapshotBackup,
+        state_snapshot_diff::manifest::StateSnapshotDiffBackup,
+        transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{
+        cache::MetadataCacheOpt, view::MetadataView, CompactionTimestampsMeta,
+        EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, StateSnapshotDiffBackupMeta,
+        TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle, TextLine},
+    utils::{storage_ext::BackupStorageExt, stream::StreamX},
+};
+use anyhow::{ensure, format_err, Context, Result};
+use aptos_infallible::duration_since_epoch;
+use aptos_logger::prelude::*;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, proof::TransactionInfoWithProof};
+use clap::Parser;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio_stream::StreamExt;
+
+const SECS_PER_DAY: u64 = 86400;
+const SECS_PER_WEEK: u64 = SECS_PER_DAY * 7;
+
+/// Which state snapshots to keep, everything else in the backup storage is kept only if it's
+/// needed to restore to or after one of them.
+///
+/// Epoch ending backups are only removed if they are redundant, or if they are older than the
+/// latest `keep_epoch_ending_ranges` ones and no kept state snapshot needs them to be verified.
+/// Restoring then verifies the epoch history from the first kept epoch ending, whose waypoint
+/// has to be trusted.
+#[derive(Clone, Debug, Parser)]
+pub struct RetentionPolicyOpt {
+    #[clap(
+        long,
+        default_value_t = 1,
+        help = "Number of latest state snapshots to keep regardless of their age. At least 1."
+    )]
+    pub keep_latest_state_snapshots: usize,
+    #[clap(
+        long,
+        default_value_t = 30,
+        help = "Keep the first state snapshot of each day (UTC) in this many days."
+    )]
+    pub keep_daily_state_snapshots_days: u64,
+    #[clap(
+        long,
+        default_value_t = 52,
+        help = "Keep the first state snapshot of each week in this many weeks."
+    )]
+    pub keep_weekly_state_snapshots_weeks: u64,
+    #[clap(
+        long,
+        help = "[default to keep the whole epoch history] Number of latest epoch ending backups \
+        to keep. Older ones are removed unless needed to verify a kept state snapshot, and \
+        restores need to trust the waypoint of the first kept epoch ending. At least 1."
+    )]
+    pub keep_epoch_ending_ranges: Option<usize>,
+}
+
+/// Backups of each type, sorted.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct BackupSet {
+    pub epoch_ending_backups: Vec<EpochEndingBackupMeta>,
+    pub state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    pub state_snapshot_diff_backups: Vec<StateSnapshotDiffBackupMeta>,
+    pub transaction_backups: Vec<TransactionBackupMeta>,
+}
+
+impl BackupSet {
+    fn len(&self) -> usize {
+        self.epoch_ending_backups.len()
+            + self.state_snapshot_backups.len()
+            + self.state_snapshot_diff_backups.len()
+            + self.transaction_backups.len()
+    }
+
+    fn manifests(&self) -> impl Iterator<Item = &FileHandle> {
+        self.epoch_ending_backups
+            .iter()
+            .map(|b| &b.manifest)
+            .chain(self.state_snapshot_backups.iter().map(|b| &b.manifest))
+            .chain(self.state_snapshot_diff_backups.iter().map(|b| &b.manifest))
+            .chain(self.transaction_backups.iter().map(|b| &b.manifest))
+    }
+
+    /// Metadata of the backups, grouped by type.
+    fn to_metadata_groups(&self) -> Vec<(&'static str, Vec<Metadata>)> {
+        vec![
+            (
+                "epoch_ending",
+                self.epoch_ending_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::EpochEndingBackup)
+                    .collect(),
+            ),
+            (
+                "state_snapshot",
+                self.state_snapshot_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::StateSnapshotBackup)
+                    .collect(),
+            ),
+            (
+                "state_snapshot_diff",
+                self.state_snapshot_diff_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::StateSnapshotDiffBackup)
+                    .collect(),
+            ),
+            (
+                "transaction",
+                self.transaction_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::TransactionBackup)
+                    .collect(),
+            ),
+        ]
+    }
+
+    fn to_metadata(&self) -> Vec<Metadata> {
+        self.to_metadata_groups()
+            .into_iter()
+            .flat_map(|(_, metadata)| metadata)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RetentionPlan {
+    pub retained: BackupSet,
+    pub garbage: BackupSet,
+}
+
+impl RetentionPlan {
+    /// Decides what to keep according to the policy.
+    ///
+    /// `state_snapshot_timestamps` maps the manifest of each state snapshot backup to the time
+    /// (in seconds since unix epoch) the snapshot was taken.
+    pub fn new(
+        policy: &RetentionPolicyOpt,
+        view: &MetadataView,
+        state_snapshot_timestamps: &HashMap<FileHandle, u64>,
+        now_secs: u64,
+    ) -> Result<Self> {
+        ensure!(
+            policy.keep_latest_state_snapshots >= 1,
+            "Must keep at least one latest state snapshot."
+        );
+        ensure!(
+            policy.keep_epoch_ending_ranges != Some(0),
+            "Must keep at least one epoch ending backup."
+        );
+        let mut retained = BackupSet::default();
+        let mut garbage = BackupSet::default();
+
+        // State snapshots: the latest ones, and the first in each daily / weekly bucket within
+        // the window.
+        let snapshots = view.state_snapshot_backups();
+        let num_latest = snapshots
+            .len()
+            .saturating_sub(policy.keep_latest_state_snapshots);
+        let mut seen_days = HashSet::new();
+        let mut seen_weeks = HashSet::new();
+        for (idx, snapshot) in snapshots.iter().enumerate() {
+            let ts = *state_snapshot_timestamps
+                .get(&snapshot.manifest)
+                .ok_or_else(|| {
+                    format_err!("Unknown timestamp of state snapshot {}.", snapshot.manifest)
+                })?;
+            let age = now_secs.saturating_sub(ts);
+            let first_in_day = seen_days.insert(ts / SECS_PER_DAY);
+            let first_in_week = seen_weeks.insert(ts / SECS_PER_WEEK);
+            let keep = idx >= num_latest
+                || (first_in_day && age < policy.keep_daily_state_snapshots_days * SECS_PER_DAY)
+                || (first_in_week
+                    && age < policy.keep_weekly_state_snapshots_weeks * SECS_PER_WEEK);
+            if keep {
+                retained.state_snapshot_backups.push(snapshot.clone());
+            } else {
+                garbage.state_snapshot_backups.push(snapshot.clone());
+            }
+        }
+
+        // State snapshot diffs: those on a chain starting from a kept snapshot. Diffs are sorted
+        // by base version, which is before the version a diff leads to.
+        let mut reachable: HashSet<_> = retained
+            .state_snapshot_backups
+            .iter()
+            .map(|s| s.version)
+            .collect();
+        for diff in view.state_snapshot_diff_backups() {
+            if reachable.contains(&diff.base_version) {
+                reachable.insert(diff.version);
+                retained.state_snapshot_diff_backups.push(diff.clone());
+            } else {
+                garbage.state_snapshot_diff_backups.push(diff.clone());
+            }
+        }
+
+        // Transactions: those needed to replay from the earliest kept snapshot on. Also remove
+        // backups covered entirely by a previous one.
+        let min_version = retained
+            .state_snapshot_backups
+            .iter()
+            .map(|s| s.version)
+            .min()
+            .unwrap_or(0);
+        let mut next_version = 0;
+        for backup in view.transaction_backups() {
+            if backup.last_version < next_version || backup.last_version < min_version {
+                garbage.transaction_backups.push(backup.clone());
+            } else {
+                retained.transaction_backups.push(backup.clone());
+            }
+            next_version = next_version.max(backup.last_version + 1);
+        }
+
+        // Epoch endings: remove backups covered entirely by a previous one, and the ranges
+        // older than the latest ones to keep, up to the first one needed by a kept snapshot.
+        // The LedgerInfo of a snapshot at epoch e is verified by the one ending epoch e - 1.
+        let mut next_epoch = 0;
+        for backup in view.epoch_ending_backups() {
+            if backup.last_epoch < next_epoch {
+                garbage.epoch_ending_backups.push(backup.clone());
+            } else {
+                retained.epoch_ending_backups.push(backup.clone());
+            }
+            next_epoch = next_epoch.max(backup.last_epoch + 1);
+        }
+        if let Some(num_ranges) = policy.keep_epoch_ending_ranges {
+            let min_epoch = retained
+                .state_snapshot_backups
+                .iter()
+                .map(|s| s.epoch.saturating_sub(1))
+                .min()
+                .unwrap_or(u64::MAX);
+            let num_old = retained
+                .epoch_ending_backups
+                .len()
+                .saturating_sub(num_ranges);
+            let num_removed = retained.epoch_ending_backups[..num_old]
+                .iter()
+                .take_while(|b| b.last_epoch < min_epoch)
+                .count();
+            garbage
+                .epoch_ending_backups
+                .extend(retained.epoch_ending_backups.drain(..num_removed));
+            garbage.epoch_ending_backups.sort();
+        }
+
+        let plan = Self { retained, garbage };
+        plan.verify()?;
+        Ok(plan)
+    }
+
+    /// Makes sure each kept state snapshot can still be verified, restored to and replayed from,
+    /// and that no file of a kept backup is removed.
+    fn verify(&self) -> Result<()> {
+        let view = MetadataView::new(self.retained.to_metadata(), Vec::new());
+        let epoch_endings = view
+            .select_epoch_ending_backups(u64::MAX)
+            .context("Epoch history not restorable after retention.")?;
+        let first_epoch = epoch_endings.first().map_or(0, |b| b.first_epoch);
+        for snapshot in &self.retained.state_snapshot_backups {
+            ensure!(
+                snapshot.epoch.saturating_sub(1) >= first_epoch,
+                "State snapshot at epoch {} not verifiable after retention, the epoch history \
+                starts at epoch {}.",
+                snapshot.epoch,
+                first_epoch,
+            );
+            view.select_transaction_backups(snapshot.version, u64::MAX)
+                .with_context(|| {
+                    format!(
+                        "Transactions after state snapshot at version {} not restorable after retention.",
+                        snapshot.version
+                    )
+                })?;
+        }
+
+        let retained_manifests: HashSet<_> = self.retained.manifests().collect();
+        for manifest in self.garbage.manifests() {
+            ensure!(
+                !retained_manifests.contains(manifest),
+                "Manifest {} is referenced by a kept backup.",
+                manifest,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Applies a `RetentionPolicyOpt` to the backup storage.
+///
+/// Without `delete`, only reports what is garbage. Otherwise, metadata files are first rewritten
+/// to only have the kept backups, so nothing will try to restore from the garbage, and then the
+/// garbage files are deleted.
+pub struct RetentionCoordinator {
+    policy: RetentionPolicyOpt,
+    metadata_cache_opt: MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+    delete: bool,
+}
+
+impl RetentionCoordinator {
+    pub fn new(
+        policy: RetentionPolicyOpt,
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrent_downloads: usize,
+        delete: bool,
+    ) -> Self {
+        Self {
+            policy,
+            metadata_cache_opt,
+            storage,
+            concurrent_downloads,
+            delete,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Backup retention started.");
+        // Listed before loading, so a metadata file that shows up in between is not moved away
+        // before its content is carried over.
+        let metadata_files = self.storage.list_metadata_files().await?;
+        let view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let timestamps = self
+            .load_state_snapshot_timestamps(view.state_snapshot_backups())
+            .await?;
+        let now = duration_since_epoch().as_secs();
+        let plan = RetentionPlan::new(&self.policy, &view, &timestamps, now)?;
+
+        let garbage_files = self.list_garbage_files(&plan.garbage).await?;
+        for backup in plan.garbage.to_metadata() {
+            info!(
+                "Garbage backup: {}",
+                backup.to_text_line()?.as_ref().trim_end()
+            );
+        }
+        for file in &garbage_files {
+            info!(file = file, "Garbage file.");
+        }
+        info!(
+            retained_backups = plan.retained.len(),
+            garbage_backups = plan.garbage.len(),
+            garbage_files = garbage_files.len(),
+            "Retention planned."
+        );
+        if let Some(backup) = plan
+            .retained
+            .epoch_ending_backups
+            .first()
+            .filter(|b| b.first_epoch > 0)
+        {
+            let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+            warn!(
+                "Epoch history starts at epoch {} after retention, restores need to trust waypoint {}.",
+                backup.first_epoch,
+                manifest
+                    .waypoints
+                    .first()
+                    .ok_or_else(|| format_err!("No waypoints in {}.", backup.manifest))?,
+            );
+        }
+        if !self.delete || garbage_files.is_empty() {
+            return Ok(());
+        }
+
+        self.rewrite_metadata(&plan.retained, metadata_files, now)
+            .await?;
+        for file in &garbage_files {
+            self.storage.delete_file(file).await?;
+        }
+        info!(
+            garbage_files = garbage_files.len(),
+            "Garbage files deleted."
+        );
+
+        Ok(())
+    }
+
+    async fn load_state_snapshot_timestamps(
+        &self,
+        snapshots: &[StateSnapshotBackupMeta],
+    ) -> Result<HashMap<FileHandle, u64>> {
+        let futs = snapshots.iter().map(|snapshot| async move {
+            let manifest: StateSnapshotBackup =
+                self.storage.load_json_file(&snapshot.manifest).await?;
+            let (_txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+                self.storage.load_bcs_file(&manifest.proof).await?;
+            Result::<_>::Ok((
+                snapshot.manifest.clone(),
+                li.ledger_info().timestamp_usecs() / 1_000_000,
+            ))
+        });
+        Ok(futures::stream::iter(futs)
+            .buffered_x(self.concurrent_downloads * 2, self.concurrent_downloads)
+            .collect::<Result<Vec<_>>>()
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// All files of the backups, manifests last.
+    async fn list_garbage_files(&self, garbage: &BackupSet) -> Result<Vec<FileHandle>> {
+        let mut files = Vec::new();
+        for backup in &garbage.epoch_ending_backups {
+            let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+            files.extend(manifest.chunks.into_iter().map(|c| c.ledger_infos));
+        }
+        for backup in &garbage.state_snapshot_backups {
+            let manifest: StateSnapshotBackup =
+                self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                files.push(chunk.blobs);
+                files.push(chunk.proof);
+            }
+            files.push(manifest.proof);
+        }
+        for backup in &garbage.state_snapshot_diff_backups {
+            let manifest: StateSnapshotDiffBackup =
+                self.storage.load_json_file(&backup.manifest).await?;
+            files.extend(manifest.chunks.into_iter().map(|c| c.updates));
+            files.push(manifest.proof);
+        }
+        for backup in &garbage.transaction_backups {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                files.push(chunk.transactions);
+                files.push(chunk.proof);
+            }
+        }
+        files.extend(garbage.manifests().cloned());
+        Ok(files)
+    }
+
+    async fn rewrite_metadata(
+        &self,
+        retained: &BackupSet,
+        metadata_files: Vec<FileHandle>,
+        now: u64,
+    ) -> Result<()> {
+        for (kind, metadata) in retained.to_metadata_groups() {
+            if metadata.is_empty() {
+                continue;
+            }
+            let lines = metadata
+                .iter()
+                .map(Metadata::to_text_line)
+                .collect::<Result<Vec<TextLine>>>()?;
+            let name = format!("{}_retained_{}.meta", kind, now).parse()?;
+            let file_handle = self.storage.save_metadata_lines(&name, &lines).await?;
+            info!(file_handle = file_handle, "Saved retained backups.");
+        }
+
+        let identity_name = Metadata::new_random_identity().name();
+        for file in metadata_files {
+            // The identity doesn't change.
+            if file.ends_with(identity_name.as_ref()) {
+                continue;
+            }
+            info!(file = file, "Backup metadata file.");
+            self.storage.backup_metadata_file(&file).await?;
+        }
+
+        // Timestamps of compaction refer to files just moved away.
+        let compaction_meta =
+            Metadata::new_compaction_timestamps(CompactionTimestampsMeta::new(HashMap::new(), now));
+        self.storage
+            .save_metadata_line(&compaction_meta.name(), &compaction_meta.to_text_line()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coordinators::retention::{RetentionPlan, RetentionPolicyOpt, SECS_PER_DAY, SECS_PER_WEEK},
+        metadata::{view::MetadataView, Metadata},
+    };
+    use std::collections::HashMap;
+
+    const NOW: u64 = 1000 * SECS_PER_WEEK;
+
+    fn policy(latest: usize, days: u64, weeks: u64) -> RetentionPolicyOpt {
+        RetentionPolicyOpt {
+            keep_latest_state_snapshots: latest,
+            keep_daily_state_snapshots_days: days,
+            keep_weekly_state_snapshots_weeks: weeks,
+            keep_epoch_ending_ranges: None,
+        }
+    }
+
+    /// Epoch endings in one backup, transactions in backups of 100 versions each, and a state
+    /// snapshot at the first version of each transaction backup, taken at `timestamps`.
+    fn backups(timestamps: &[u64]) -> (Vec<Metadata>, HashMap<String, u64>) {
+        let num = timestamps.len() as u64;
+        let mut metadata = vec![Metadata::new_epoch_ending_backup(
+            0,
+            num,
+            0,
+            num * 100,
+            "epoch_ending".into(),
+        )];
+        let mut snapshot_timestamps = HashMap::new();
+        for (i, ts) in timestamps.iter().enumerate() {
+            let version = i as u64 * 100;
+            let manifest = format!("state_snapshot_{}", version);
+            snapshot_timestamps.insert(manifest.clone(), *ts);
+            metadata.push(Metadata::new_state_snapshot_backup(
+                i as u64, version, manifest,
+            ));
+            metadata.push(Metadata::new_transaction_backup(
+                version,
+                version + 99,
+                format!("transaction_{}", version),
+            ));
+        }
+        (metadata, snapshot_timestamps)
+    }
+
+    fn retained_snapshot_versions(plan: &RetentionPlan) -> Vec<u64> {
+        plan.retained
+            .state_snapshot_backups
+            .iter()
+            .map(|s| s.version)
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_latest() {
+        let (metadata, timestamps) = backups(&[0, 1, 2, 3]);
+        let view = MetadataView::new(metadata, Vec::new());
+        let plan = RetentionPlan::new(&policy(2, 0, 0), &view, &timestamps, NOW).unwrap();
+
+        assert_eq!(retained_snapshot_versions(&plan), vec![200, 300]);
+        assert_eq!(plan.garbage.state_snapshot_backups.len(), 2);
+        assert_eq!(
+            plan.retained
+                .transaction_backups
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![200, 300]
+        );
+        assert_eq!(plan.retained.epoch_ending_backups.len(), 1);
+        assert!(plan.garbage.epoch_ending_backups.is_empty());
+
+        assert!(RetentionPlan::new(&policy(0, 30, 52), &view, &timestamps, NOW).is_err());
+    }
+
+    #[test]
+    fn test_keep_daily_and_weekly() {
+        let timestamps = [
+            // Out of the weekly window.
+            NOW - 60 * SECS_PER_WEEK,
+            // Weekly, and another one in the same week.
+            NOW - 20 * SECS_PER_WEEK,
+            NOW - 20 * SECS_PER_WEEK + SECS_PER_DAY,
+            // Daily, and another one in the same day.
+            NOW - 10 * SECS_PER_DAY,
+            NOW - 10 * SECS_PER_DAY + 1,
+            // Latest.
+            NOW - 1,
+        ];
+        let (metadata, snapshot_timestamps) = backups(&timestamps);
+        let view = MetadataView::new(metadata, Vec::new());
+        let plan =
+            RetentionPlan::new(&policy(1, 30, 52), &view, &snapshot_timestamps, NOW).unwrap();
+
+        assert_eq!(retained_snapshot_versions(&plan), vec![100, 300, 500]);
+        // Transactions are kept from the earliest kept snapshot on.
+        assert_eq!(
+            plan.garbage
+                .transaction_backups
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn test_state_snapshot_diffs() {
+        let (mut metadata, timestamps) = backups(&[0, 1, 2]);
+        metadata.extend([
+            // From a removed snapshot.
+            Metadata::new_state_snapshot_diff_backup(0, 1, 150, "diff_0_150".into()),
+            // From the kept snapshot, and chained on it.
+            Metadata::new_state_snapshot_diff_backup(200, 2, 250, "diff_200_250".into()),
+            Metadata::new_state_snapshot_diff_backup(250, 2, 280, "diff_250_280".into()),
+        ]);
+        let view = MetadataView::new(metadata, Vec::new());
+        let plan = RetentionPlan::new(&policy(1, 0, 0), &view, &timestamps, NOW).unwrap();
+
+        assert_eq!(
+            plan.retained
+                .state_snapshot_diff_backups
+                .iter()
+                .map(|d| d.manifest.as_str())
+                .collect::<Vec<_>>(),
+            vec!["diff_200_250", "diff_250_280"]
+        );
+        assert_eq!(plan.garbage.state_snapshot_diff_backups.len(), 1);
+    }
+
+    #[test]
+    fn test_redundant_backups() {
+        let (mut metadata, timestamps) = backups(&[0, 1]);
+        metadata.extend([
+            Metadata::new_epoch_ending_backup(1, 2, 50, 200, "epoch_ending_redundant".into()),
+            Metadata::new_transaction_backup(150, 199, "transaction_redundant".into()),
+        ]);
+        let view = MetadataView::new(metadata, Vec::new());
+        let plan = RetentionPlan::new(&policy(2, 0, 0), &view, &timestamps, NOW).unwrap();
+
+        assert_eq!(plan.garbage.manifests().collect::<Vec<_>>(), vec![
+            "epoch_ending_redundant",
+            "transaction_redundant"
+        ]);
+    }
+
+    #[test]
+    fn test_keep_epoch_ending_ranges() {
+        // Snapshots at epochs 0 to 4, and epoch endings in a backup per epoch.
+        let (mut metadata, timestamps) = backups(&[0, 1, 2, 3, 4]);
+        metadata.retain(|m| !matches!(m, Metadata::EpochEndingBackup(_)));
+        metadata.extend((0..=5).map(|epoch| {
+            Metadata::new_epoch_ending_backup(
+                epoch,
+                epoch,
+                epoch * 100,
+                epoch * 100 + 99,
+                format!("epoch_ending_{}", epoch),
+            )
+        }));
+        let view = MetadataView::new(metadata, Vec::new());
+        let garbage_epoch_endings = |num_ranges| {
+            let policy = RetentionPolicyOpt {
+                keep_epoch_ending_ranges: num_ranges,
+                ..policy(2, 0, 0)
+            };
+            RetentionPlan::new(&policy, &view, &timestamps, NOW)
+                .unwrap()
+                .garbage
+                .epoch_ending_backups
+                .into_iter()
+                .map(|b| b.manifest)
+                .collect::<Vec<_>>()
+        };
+
+        // The whole history is kept by default.
+        assert!(garbage_epoch_endings(None).is_empty());
+        // Older ranges are removed.
+        assert_eq!(garbage_epoch_endings(Some(5)), vec!["epoch_ending_0"]);
+        // Except those needed to verify the kept snapshot at epoch 3.
+        assert_eq!(garbage_epoch_endings(Some(1)), vec![
+            "epoch_ending_0",
+            "epoch_ending_1"
+        ]);
+
+        let policy = RetentionPolicyOpt {
+            keep_epoch_ending_ranges: Some(0),
+            ..policy(2, 0, 0)
+        };
+        assert!(RetentionPlan::new(&policy, &view, &timestamps, NOW).is_err());
+    }
+
+    #[test]
+    fn test_refuse_unrestorable() {
+        let (mut metadata, timestamps) = backups(&[0, 1, 2]);
+        // Transactions after the second snapshot are missing.
+        metadata.retain(|m| !matches!(m, Metadata::TransactionBackup(t) if t.first_version == 100));
+        let view = MetadataView::new(metadata, Vec::new());
+
+        assert!(RetentionPlan::new(&policy(2, 0, 0), &view, &timestamps, NOW).is_err());
+    }
+}
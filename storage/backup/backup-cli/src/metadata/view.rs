@@ -5,7 +5,7 @@
 use crate::{
     metadata::{
         CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta, Metadata,
-        StateSnapshotBackupMeta, TransactionBackupMeta,
+        StateSnapshotBackupMeta, StateSnapshotDiffBackupMeta, TransactionBackupMeta,
     },
     metrics::backup::COMPACTED_TXN_VERSION,
     storage::FileHandle,
@@ -20,6 +20,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    state_snapshot_diff_backups: Vec<StateSnapshotDiffBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
     _identity: Option<IdentityMeta>,
     // The compaction timestamps of the file handles producing this view
@@ -30,6 +31,7 @@ impl MetadataView {
     pub(crate) fn new(metadata_vec: Vec<Metadata>, file_handles: Vec<FileHandle>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut state_snapshot_diff_backups = Vec::new();
         let mut transaction_backups = Vec::new();
         let mut identity = None;
         let mut compaction_timestamps = Vec::new();
@@ -38,6 +40,7 @@ impl MetadataView {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::StateSnapshotDiffBackup(d) => state_snapshot_diff_backups.push(d),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
                 Metadata::Identity(i) => identity = Some(i),
                 Metadata::CompactionTimestamps(t) => compaction_timestamps.push(t),
@@ -47,6 +50,8 @@ impl MetadataView {
         epoch_ending_backups.dedup();
         state_snapshot_backups.sort_unstable();
         state_snapshot_backups.dedup();
+        state_snapshot_diff_backups.sort_unstable();
+        state_snapshot_diff_backups.dedup();
         transaction_backups.sort_unstable();
         transaction_backups.dedup();
 
@@ -72,6 +77,7 @@ impl MetadataView {
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            state_snapshot_diff_backups,
             transaction_backups,
             _identity: identity,
             compaction_timestamps: compaction_meta_opt,
@@ -126,31 +132,64 @@ impl MetadataView {
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    /// Selects a chain of state snapshot diffs that leads from the state snapshot at
+    /// `base_version` to the latest state snapshot at or before `target_version`, taking the
+    /// longest diff at each step.
+    pub fn select_state_snapshot_diffs(
+        &self,
+        base_version: Version,
+        target_version: Version,
+    ) -> Result<Vec<StateSnapshotDiffBackupMeta>> {
+        let mut version = base_version;
+        let mut res = Vec::new();
+        while let Some(diff) = self
+            .state_snapshot_diff_backups
+            .iter()
+            .filter(|d| d.base_version == version && d.version <= target_version)
+            .max_by_key(|d| d.version)
+        {
+            version = diff.version;
+            res.push(diff.clone());
+        }
+
+        Ok(res)
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // range (which is always true when we backup from a single backup coordinator).
+        // Backups entirely before `start_version` are not looked at, because they could've been
+        // removed by a `RetentionCoordinator`.
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
-            ensure!(
-                backup.first_version == next_ver,
-                "Transaction backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
-                backup.first_version,
-            );
-
-            if backup.last_version >= start_version {
-                res.push(backup.clone());
+            if backup.last_version < start_version {
+                continue;
+            }
+            match next_ver {
+                None => ensure!(
+                    backup.first_version <= start_version,
+                    "Transaction backups missing, expecting version {}, got {}.",
+                    start_version,
+                    backup.first_version,
+                ),
+                Some(next_ver) => ensure!(
+                    backup.first_version == next_ver,
+                    "Transaction backup ranges not continuous, expecting version {}, got {}.",
+                    next_ver,
+                    backup.first_version,
+                ),
             }
 
-            next_ver = backup.last_version + 1;
+            res.push(backup.clone());
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
@@ -170,23 +209,26 @@ impl MetadataView {
         target_version: Version,
     ) -> Result<Vec<EpochEndingBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_epoch = 0;
+        // range (which is always true when we backup from a single backup coordinator). The
+        // ranges start at epoch 0 unless retention removed the earlier ones.
+        let mut next_epoch = None;
         let mut res = Vec::new();
         for backup in self.epoch_ending_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
 
-            ensure!(
-                backup.first_epoch == next_epoch,
-                "Epoch ending backup ranges not continuous, expecting epoch {}, got {}.",
-                next_epoch,
-                backup.first_epoch,
-            );
+            if let Some(next_epoch) = next_epoch {
+                ensure!(
+                    backup.first_epoch == next_epoch,
+                    "Epoch ending backup ranges not continuous, expecting epoch {}, got {}.",
+                    next_epoch,
+                    backup.first_epoch,
+                );
+            }
             res.push(backup.clone());
 
-            next_epoch = backup.last_epoch + 1;
+            next_epoch = Some(backup.last_epoch + 1);
         }
 
         Ok(res)
@@ -235,6 +277,29 @@ impl MetadataView {
         Self::compact_backups(&self.state_snapshot_backups, compaction_cnt)
     }
 
+    pub fn compact_state_snapshot_diff_backups(
+        &mut self,
+        compaction_cnt: usize,
+    ) -> Result<Vec<&[StateSnapshotDiffBackupMeta]>> {
+        Self::compact_backups(&self.state_snapshot_diff_backups, compaction_cnt)
+    }
+
+    pub(crate) fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub(crate) fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub(crate) fn state_snapshot_diff_backups(&self) -> &[StateSnapshotDiffBackupMeta] {
+        &self.state_snapshot_diff_backups
+    }
+
+    pub(crate) fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     pub fn get_file_handles(&self) -> Vec<FileHandle> {
         self.select_latest_compaction_timestamps()
             .as_ref()
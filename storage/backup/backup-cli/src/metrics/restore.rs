@@ -69,6 +69,38 @@ pub static TRANSACTION_REPLAY_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static TRANSACTION_REPLAY_TPS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_restore_transaction_replay_tps",
+        "Average number of transactions replayed per second since the replay started."
+    )
+    .unwrap()
+});
+
+pub static TRANSACTION_REPLAY_ETA_S: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_restore_transaction_replay_eta_s",
+        "Estimated seconds until the transaction replay reaches the target version."
+    )
+    .unwrap()
+});
+
+pub static PLAN_STATE_CHUNKS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_restore_plan_state_chunks",
+        "Number of state snapshot and diff chunks the restore plan restores."
+    )
+    .unwrap()
+});
+
+pub static PLAN_TRANSACTIONS_TO_REPLAY: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_restore_plan_transactions_to_replay",
+        "Number of transactions the restore plan replays on top of the restored state."
+    )
+    .unwrap()
+});
+
 pub static COORDINATOR_START_TS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "aptos_db_restore_coordinator_start_timestamp_s",
@@ -22,7 +22,7 @@ use std::{
     str::FromStr,
 };
 use tokio::{
-    fs::{create_dir_all, read_dir, rename, OpenOptions},
+    fs::{create_dir_all, read_dir, remove_dir, remove_file, rename, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -180,4 +180,17 @@ impl BackupStorage for LocalFs {
             .path_to_string()?;
         Ok(fh)
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+
+        // Remove the backup folder as well once it's emptied, which fails otherwise.
+        if let Some(dir) = path.parent() {
+            if dir != self.dir && remove_dir(dir).await.is_ok() {
+                info!("Removed emptied backup folder {:?}.", dir);
+            }
+        }
+        Ok(())
+    }
 }
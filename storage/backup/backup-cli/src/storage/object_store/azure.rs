@@ -0,0 +1,232 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{
+    client::{
+        content_md5, http_client, into_reader, send_with_retry, uri_encode, uri_encode_key,
+        Checksum, ObjectClient,
+    },
+    config::AzureBlobConfig,
+    xml,
+};
+use anyhow::{ensure, format_err, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{
+    header::{HeaderName, HeaderValue, CONTENT_MD5},
+    Method, Request, Url,
+};
+use std::env;
+use tokio::io::AsyncRead;
+
+/// Talks the Azure Blob Storage REST API, authenticating with a SAS token.
+/// Multipart uploads are done by staging blocks and committing them as a block list.
+pub(super) struct AzureBlobClient {
+    http: reqwest::Client,
+    /// Where the container is, without a trailing slash. Blob names are appended to it.
+    container_url: String,
+    /// Query string of the SAS token, without the leading '?'.
+    sas_token: String,
+    max_retries: usize,
+}
+
+impl AzureBlobClient {
+    const API_VERSION: &'static str = "2021-08-06";
+
+    pub fn new(config: &AzureBlobConfig, sas_token: String, max_retries: usize) -> Self {
+        Self {
+            http: http_client(),
+            container_url: format!(
+                "{}/{}",
+                config.account_url.trim_end_matches('/'),
+                uri_encode(&config.container)
+            ),
+            sas_token: sas_token.trim_start_matches('?').to_string(),
+            max_retries,
+        }
+    }
+
+    pub fn sas_token_from_env(config: &AzureBlobConfig) -> Result<String> {
+        env::var(&config.sas_token_env)
+            .map_err(|e| format_err!("Failed reading env var {}: {}", config.sas_token_env, e))
+    }
+
+    fn url(&self, key: Option<&str>, query: &[(&str, String)]) -> Result<Url> {
+        let mut url = match key {
+            Some(key) => format!("{}/{}", self.container_url, uri_encode_key(key)),
+            None => self.container_url.clone(),
+        };
+        url.push('?');
+        url.push_str(&self.sas_token);
+        for (name, value) in query {
+            url.push_str(&format!("&{}={}", uri_encode(name), uri_encode(value)));
+        }
+        Ok(Url::parse(&url)?)
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Request> {
+        let mut request = Request::new(method, self.url(key, query)?);
+        let request_headers = request.headers_mut();
+        request_headers.insert("x-ms-version", HeaderValue::from_static(Self::API_VERSION));
+        for (name, value) in headers {
+            request_headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        *request.body_mut() = Some(body.into());
+        Ok(request)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<reqwest::Response> {
+        send_with_retry(&self.http, self.max_retries, || {
+            self.request(method.clone(), key, query, headers, body.clone())
+        })
+        .await
+    }
+
+    /// Block IDs in a blob must be of the same length.
+    fn block_id(part_number: usize) -> String {
+        base64::encode(format!("{:010}", part_number))
+    }
+}
+
+#[async_trait]
+impl ObjectClient for AzureBlobClient {
+    async fn put_object(&self, key: &str, data: Bytes) -> Result<()> {
+        let headers = [
+            ("x-ms-blob-type", "BlockBlob".to_string()),
+            ("content-md5", content_md5(&data)),
+        ];
+        self.send(Method::PUT, Some(key), &[], &headers, data)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_multipart_upload(&self, _key: &str, _part_size: usize) -> Result<String> {
+        // Blocks are staged against the blob itself, there's no upload to create.
+        Ok(String::new())
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        _upload_id: &str,
+        part_number: usize,
+        data: Bytes,
+    ) -> Result<String> {
+        let block_id = Self::block_id(part_number);
+        let query = [("comp", "block".to_string()), ("blockid", block_id.clone())];
+        let headers = [("content-md5", content_md5(&data))];
+        self.send(Method::PUT, Some(key), &query, &headers, data)
+            .await?;
+        Ok(block_id)
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        _upload_id: &str,
+        parts: Vec<String>,
+        blob_md5: String,
+    ) -> Result<()> {
+        let query = [("comp", "blocklist".to_string())];
+        let mut body = r#"<?xml version="1.0" encoding="utf-8"?><BlockList>"#.to_string();
+        for block_id in &parts {
+            body.push_str(&format!("<Latest>{}</Latest>", xml::escape(block_id)));
+        }
+        body.push_str("</BlockList>");
+        let headers = [
+            ("content-md5", content_md5(body.as_bytes())),
+            // Returned as the Content-MD5 of the blob, like with a blob put in one go.
+            ("x-ms-blob-content-md5", blob_md5),
+        ];
+        self.send(Method::PUT, Some(key), &query, &headers, body.into())
+            .await?;
+        Ok(())
+    }
+
+    async fn abort_multipart_upload(&self, _key: &str, _upload_id: &str) -> Result<()> {
+        // Uncommitted blocks are garbage collected by the service.
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let response = self
+            .send(Method::GET, Some(key), &[], &[], Bytes::new())
+            .await?;
+        // Recorded for blobs put in one go, and set when committing the blocks of the others.
+        let checksum = match response.headers().get(CONTENT_MD5) {
+            Some(content_md5) => Some(Checksum::Md5(base64::decode(content_md5.to_str()?)?)),
+            None => None,
+        };
+        Ok(into_reader(response, checksum))
+    }
+
+    async fn list_objects(
+        &self,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let mut query = vec![
+            ("restype", "container".to_string()),
+            ("comp", "list".to_string()),
+            ("prefix", prefix.to_string()),
+        ];
+        if let Some(marker) = continuation_token {
+            query.push(("marker", marker));
+        }
+        let body = self
+            .send(Method::GET, None, &query, &[], Bytes::new())
+            .await?
+            .text()
+            .await?;
+
+        // An empty <NextMarker /> marks the last page.
+        let next = xml::element(&body, "NextMarker").filter(|marker| !marker.is_empty());
+        Ok((xml::elements(&body, "Name"), next))
+    }
+
+    async fn copy_object(&self, from_key: &str, to_key: &str) -> Result<()> {
+        let headers = [(
+            "x-ms-copy-source",
+            self.url(Some(from_key), &[])?.to_string(),
+        )];
+        let response = self
+            .send(Method::PUT, Some(to_key), &[], &headers, Bytes::new())
+            .await?;
+        // Copying within a storage account finishes synchronously.
+        let status = response
+            .headers()
+            .get("x-ms-copy-status")
+            .map(|status| status.to_str().unwrap_or_default().to_string());
+        ensure!(
+            status.as_deref() == Some("success"),
+            "Copying {} to {} didn't finish, status: {:?}",
+            from_key,
+            to_key,
+            status,
+        );
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, Some(key), &[], &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+}
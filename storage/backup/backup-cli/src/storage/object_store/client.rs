@@ -0,0 +1,280 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    ready,
+    task::{Context, Poll},
+    TryStreamExt,
+};
+use md5::{Digest, Md5};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{Request, Response, StatusCode};
+use std::{io, pin::Pin};
+use tokio::{
+    io::{AsyncRead, ReadBuf},
+    time::Duration,
+};
+use tokio_io_timeout::TimeoutReader;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// The subset of an object store API the `ObjectStore` is built on top of. Keys are full object
+/// keys, prefix included.
+#[async_trait]
+pub(super) trait ObjectClient: Send + Sync {
+    async fn put_object(&self, key: &str, data: Bytes) -> Result<()>;
+
+    /// Starts a multipart upload of parts of `part_size` bytes, except for the last one, and
+    /// returns its ID.
+    async fn create_multipart_upload(&self, key: &str, part_size: usize) -> Result<String>;
+
+    /// Uploads a part, numbered from 1, and returns what identifies it when completing the upload.
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        data: Bytes,
+    ) -> Result<String>;
+
+    /// `parts` are what `upload_part()` returned, in part number order. `content_md5` is the
+    /// `content_md5()` of the whole object.
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<String>,
+        content_md5: String,
+    ) -> Result<()>;
+
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()>;
+
+    /// The returned reader fails at the end of the object if the content doesn't match the
+    /// checksum recorded when uploading it.
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+
+    /// Returns one page of keys starting with `prefix` and the token to get the next page, if
+    /// there's one.
+    async fn list_objects(
+        &self,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)>;
+
+    async fn copy_object(&self, from_key: &str, to_key: &str) -> Result<()>;
+
+    async fn delete_object(&self, key: &str) -> Result<()>;
+}
+
+/// The HTTP client of the object store clients. It goes through the proxies set in the
+/// environment, like `HTTPS_PROXY`, except for the hosts in `NO_PROXY`.
+pub(super) fn http_client() -> reqwest::Client {
+    reqwest::Client::new()
+}
+
+/// Sends a request built by `make_request`, rebuilding and resending it with exponential backoff
+/// on connection errors, timeouts and responses indicating the service is temporarily unable to
+/// serve it.
+pub(super) async fn send_with_retry(
+    http: &reqwest::Client,
+    max_retries: usize,
+    mut make_request: impl FnMut() -> Result<Request>,
+) -> Result<Response> {
+    const BASE_DELAY: Duration = Duration::from_millis(100);
+    const MAX_DELAY: Duration = Duration::from_secs(10);
+
+    let mut attempt = 0;
+    loop {
+        let request = make_request()?;
+        let description = format!("{} {}", request.method(), request.url().path());
+        let error = match http.execute(request).await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                if !is_retryable(status) || attempt >= max_retries {
+                    bail!("{} failed with status {}: {}", description, status, body);
+                }
+                format!("status {}: {}", status, body)
+            },
+            Err(e) => {
+                if attempt >= max_retries {
+                    bail!("{} failed: {}", description, e);
+                }
+                e.to_string()
+            },
+        };
+
+        let delay = std::cmp::min(BASE_DELAY * 2u32.pow(attempt as u32), MAX_DELAY);
+        warn!(
+            attempt = attempt,
+            error = %error,
+            "{} failed, retrying in {:?}.",
+            description,
+            delay,
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// Turns the body of a download into a reader that errors out if the connection stalls, or at
+/// the end of the content if it doesn't match `checksum`.
+pub(super) fn into_reader(
+    response: Response,
+    checksum: Option<Checksum>,
+) -> Box<dyn AsyncRead + Send + Unpin> {
+    const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+    let reader = response
+        .bytes_stream()
+        .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+        .into_async_read()
+        .compat();
+    let mut reader_with_read_timeout = TimeoutReader::new(reader);
+    reader_with_read_timeout.set_timeout(Some(READ_TIMEOUT));
+
+    let reader = Box::pin(reader_with_read_timeout);
+    match checksum {
+        Some(checksum) => Box::new(ChecksumReader::new(reader, checksum)),
+        None => Box::new(reader),
+    }
+}
+
+/// What the content of an object is verified against once downloaded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum Checksum {
+    /// MD5 digest of the content.
+    Md5(Vec<u8>),
+    /// MD5 digest of the concatenated MD5 digests of the `num_parts` parts of `part_size` bytes
+    /// the content was uploaded in, which is what the ETag of an S3 multipart upload is.
+    PartsMd5 {
+        part_size: usize,
+        num_parts: usize,
+        digest: Vec<u8>,
+    },
+}
+
+/// Computes the checksum of what's read through it, and fails the read hitting the end of the
+/// content if it doesn't match the expected one.
+struct ChecksumReader<R> {
+    inner: R,
+    expected: Checksum,
+    part_hasher: Md5,
+    part_len: usize,
+    /// Concatenated digests of the complete parts.
+    part_digests: Vec<u8>,
+    num_parts: usize,
+    verified: bool,
+}
+
+impl<R> ChecksumReader<R> {
+    fn new(inner: R, expected: Checksum) -> Self {
+        Self {
+            inner,
+            expected,
+            part_hasher: Md5::new(),
+            part_len: 0,
+            part_digests: Vec::new(),
+            num_parts: 0,
+            verified: false,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        let part_size = match self.expected {
+            Checksum::Md5(_) => usize::MAX,
+            Checksum::PartsMd5 { part_size, .. } => part_size,
+        };
+        while !data.is_empty() {
+            let len = std::cmp::min(data.len(), part_size - self.part_len);
+            self.part_hasher.update(&data[..len]);
+            self.part_len += len;
+            data = &data[len..];
+            if self.part_len == part_size {
+                self.finish_part();
+            }
+        }
+    }
+
+    fn finish_part(&mut self) {
+        self.part_digests
+            .extend(self.part_hasher.finalize_reset().as_slice());
+        self.part_len = 0;
+        self.num_parts += 1;
+    }
+
+    fn verify(&mut self) -> io::Result<()> {
+        let matches = match &self.expected {
+            Checksum::Md5(digest) => self.part_hasher.finalize_reset().as_slice() == digest,
+            Checksum::PartsMd5 {
+                num_parts, digest, ..
+            } => {
+                let (num_parts, digest) = (*num_parts, digest.clone());
+                if self.part_len > 0 {
+                    self.finish_part();
+                }
+                self.num_parts == num_parts
+                    && Md5::digest(&self.part_digests).as_slice() == digest.as_slice()
+            },
+        };
+        if !matches {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Checksum mismatch, expecting {:?}.", self.expected),
+            ));
+        }
+        self.verified = true;
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ChecksumReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        let data = &buf.filled()[filled..];
+        if !data.is_empty() {
+            self.update(data);
+        } else if buf.remaining() > 0 && !self.verified {
+            // Nothing read into a buffer with room left is the end of the content.
+            self.verify()?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Base64 encoded MD5 digest, as expected in the Content-MD5 header, with which the service
+/// verifies the payload it received.
+pub(super) fn content_md5(data: &[u8]) -> String {
+    base64::encode(Md5::digest(data))
+}
+
+/// Everything except the unreserved characters in RFC 3986.
+const URI_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+pub(super) fn uri_encode(s: &str) -> String {
+    utf8_percent_encode(s, URI_ENCODE_SET).to_string()
+}
+
+/// Encodes an object key for use in a URL path, keeping the slashes.
+pub(super) fn uri_encode_key(key: &str) -> String {
+    key.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+}
@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::{ensure, Result};
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ObjectStoreConfig {
+    /// The object store service to talk to.
+    pub backend: BackendConfig,
+    /// Key prefix under which everything is stored, like a sub directory in the bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// Files bigger than this are uploaded in parts of this size.
+    #[serde(default = "ObjectStoreConfig::default_part_size")]
+    pub part_size: usize,
+    /// Max number of parts of a file being uploaded at the same time.
+    #[serde(default = "ObjectStoreConfig::default_concurrency")]
+    pub concurrency: usize,
+    /// Times a failed request is retried before giving up, with exponential backoff.
+    #[serde(default = "ObjectStoreConfig::default_max_retries")]
+    pub max_retries: usize,
+}
+
+impl ObjectStoreConfig {
+    /// S3 rejects parts smaller than this, except for the last one.
+    pub const MIN_PART_SIZE: usize = 5 << 20;
+
+    fn default_part_size() -> usize {
+        16 << 20
+    }
+
+    fn default_concurrency() -> usize {
+        8
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        serde_yaml::from_slice::<Self>(&content)?.validated()
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        serde_yaml::from_str::<Self>(content)?.validated()
+    }
+
+    fn validated(self) -> Result<Self> {
+        ensure!(
+            self.part_size >= Self::MIN_PART_SIZE,
+            "part_size {} is smaller than the minimum {}.",
+            self.part_size,
+            Self::MIN_PART_SIZE,
+        );
+        ensure!(self.concurrency > 0, "concurrency must be positive.");
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// Anything speaking the S3 API: AWS S3, MinIO, or GCS through its XML API with HMAC keys.
+    S3(S3Config),
+    /// Azure Blob Storage, authenticated with a SAS token.
+    AzureBlob(AzureBlobConfig),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct S3Config {
+    /// Like "https://s3.us-west-2.amazonaws.com", "https://storage.googleapis.com" or
+    /// "http://localhost:9000".
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Address the bucket as the first path segment instead of as a sub-domain of the endpoint,
+    /// which is what MinIO and other self-hosted services usually expect.
+    #[serde(default)]
+    pub path_style: bool,
+    /// Names of the environment variables holding the credentials.
+    #[serde(default = "S3Config::default_access_key_id_env")]
+    pub access_key_id_env: String,
+    #[serde(default = "S3Config::default_secret_access_key_env")]
+    pub secret_access_key_env: String,
+    #[serde(default)]
+    pub session_token_env: Option<String>,
+    /// Verify the downloads of objects uploaded in parts against their ETags, which are the MD5
+    /// digests of the MD5 digests of the parts, except for objects encrypted with SSE-KMS or
+    /// SSE-C. Objects uploaded in one go are always verified.
+    #[serde(default = "S3Config::default_verify_multipart_etags")]
+    pub verify_multipart_etags: bool,
+}
+
+impl S3Config {
+    fn default_access_key_id_env() -> String {
+        "AWS_ACCESS_KEY_ID".to_string()
+    }
+
+    fn default_secret_access_key_env() -> String {
+        "AWS_SECRET_ACCESS_KEY".to_string()
+    }
+
+    fn default_verify_multipart_etags() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AzureBlobConfig {
+    /// Like "https://myaccount.blob.core.windows.net".
+    pub account_url: String,
+    pub container: String,
+    /// Name of the environment variable holding the SAS token.
+    #[serde(default = "AzureBlobConfig::default_sas_token_env")]
+    pub sas_token_env: String,
+}
+
+impl AzureBlobConfig {
+    fn default_sas_token_env() -> String {
+        "AZURE_STORAGE_SAS_TOKEN".to_string()
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod azure;
+mod client;
+pub mod config;
+mod s3;
+mod sink;
+mod xml;
+
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{
+    object_store::{
+        azure::AzureBlobClient,
+        client::ObjectClient,
+        config::{BackendConfig, ObjectStoreConfig},
+        s3::{S3Client, S3Credentials},
+        sink::ObjectSink,
+    },
+    BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+    TextLine,
+};
+use anyhow::{format_err, Result};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectStoreOpt {
+    #[clap(
+        long = "config",
+        help = "Config file for the object store backup store."
+    )]
+    config: PathBuf,
+}
+
+impl FromStr for ObjectStoreOpt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ObjectStoreOpt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+/// A BackupStorage that talks to an object store service directly, see `ObjectStoreConfig`.
+/// File handles are object keys relative to the configured prefix, laid out the same way as in
+/// `LocalFs`.
+pub struct ObjectStore {
+    client: Arc<dyn ObjectClient>,
+    prefix: String,
+    part_size: usize,
+    concurrency: usize,
+}
+
+impl ObjectStore {
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(config: ObjectStoreConfig) -> Result<Self> {
+        let client: Arc<dyn ObjectClient> = match &config.backend {
+            BackendConfig::S3(s3_config) => Arc::new(S3Client::new(
+                s3_config,
+                S3Credentials::from_env(s3_config)?,
+                config.max_retries,
+            )?),
+            BackendConfig::AzureBlob(azure_config) => Arc::new(AzureBlobClient::new(
+                azure_config,
+                AzureBlobClient::sas_token_from_env(azure_config)?,
+                config.max_retries,
+            )),
+        };
+        Ok(Self::new_with_client(
+            client,
+            &config.prefix,
+            config.part_size,
+            config.concurrency,
+        ))
+    }
+
+    pub async fn new_with_opt(opt: ObjectStoreOpt) -> Result<Self> {
+        let config = ObjectStoreConfig::load_from_file(&opt.config).await?;
+
+        Self::new(config)
+    }
+
+    fn new_with_client(
+        client: Arc<dyn ObjectClient>,
+        prefix: &str,
+        part_size: usize,
+        concurrency: usize,
+    ) -> Self {
+        let prefix = prefix.trim_matches('/');
+        Self {
+            client,
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", prefix)
+            },
+            part_size,
+            concurrency,
+        }
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        format!("{}{}", self.prefix, file_handle)
+    }
+
+    fn file_handle(&self, key: &str) -> Result<FileHandle> {
+        key.strip_prefix(&self.prefix)
+            .map(str::to_string)
+            .ok_or_else(|| format_err!("Key {} is not under prefix {}.", key, self.prefix))
+    }
+
+    fn metadata_file_handle(name: &str) -> FileHandle {
+        format!("{}/{}", Self::METADATA_DIR, name)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStore {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no directories to create in an object store.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let sink = ObjectSink::new(
+            Arc::clone(&self.client),
+            self.key(&file_handle),
+            self.part_size,
+            self.concurrency,
+        );
+        Ok((file_handle, Box::new(sink)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.client.get_object(&self.key(file_handle)).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&Self::metadata_file_handle(""));
+
+        let mut res = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let (keys, next) = self
+                .client
+                .list_objects(&prefix, continuation_token)
+                .await?;
+            for key in keys {
+                res.push(self.file_handle(&key)?);
+            }
+            match next {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(res)
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let name = file_handle
+            .rsplit('/')
+            .next()
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        let backup_key = self.key(&format!("{}/{}", Self::METADATA_BACKUP_DIR, name));
+
+        let key = self.key(file_handle);
+        self.client.copy_object(&key, &backup_key).await?;
+        self.client.delete_object(&key).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let file_handle = Self::metadata_file_handle(name.as_ref());
+        self.client
+            .put_object(&self.key(&file_handle), content.into())
+            .await?;
+        Ok(file_handle)
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.client.delete_object(&self.key(file_handle)).await
+    }
+}
@@ -0,0 +1,429 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::{
+    client::{
+        content_md5, http_client, into_reader, send_with_retry, uri_encode, uri_encode_key,
+        Checksum, ObjectClient,
+    },
+    config::S3Config,
+    xml,
+};
+use anyhow::{bail, format_err, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, ETAG, HOST},
+    Method, Request, Url,
+};
+use ring::{digest, hmac};
+use std::env;
+use tokio::io::AsyncRead;
+
+#[derive(Clone)]
+pub(super) struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl S3Credentials {
+    pub fn from_env(config: &S3Config) -> Result<Self> {
+        let var = |name: &str| {
+            env::var(name).map_err(|e| format_err!("Failed reading env var {}: {}", name, e))
+        };
+        Ok(Self {
+            access_key_id: var(&config.access_key_id_env)?,
+            secret_access_key: var(&config.secret_access_key_env)?,
+            session_token: config.session_token_env.as_deref().map(var).transpose()?,
+        })
+    }
+}
+
+/// Talks the S3 REST API, signing requests with AWS Signature Version 4.
+pub(super) struct S3Client {
+    http: reqwest::Client,
+    /// Where the bucket is, without a trailing slash. Object keys are appended to it.
+    bucket_url: Url,
+    bucket: String,
+    region: String,
+    credentials: S3Credentials,
+    verify_multipart_etags: bool,
+    max_retries: usize,
+}
+
+impl S3Client {
+    /// User metadata recording the checksums of uploads, see `checksum()`.
+    const CONTENT_MD5_HEADER: &'static str = "x-amz-meta-content-md5";
+    const PART_SIZE_HEADER: &'static str = "x-amz-meta-part-size";
+    const SERVICE: &'static str = "s3";
+
+    pub fn new(config: &S3Config, credentials: S3Credentials, max_retries: usize) -> Result<Self> {
+        let endpoint = Url::parse(config.endpoint.trim_end_matches('/'))?;
+        let bucket_url = if config.path_style {
+            endpoint.join(&format!("/{}", uri_encode(&config.bucket)))?
+        } else {
+            let host = endpoint
+                .host_str()
+                .ok_or_else(|| format_err!("No host in endpoint {}.", config.endpoint))?;
+            let mut url = endpoint.clone();
+            url.set_host(Some(&format!("{}.{}", config.bucket, host)))?;
+            url
+        };
+
+        Ok(Self {
+            http: http_client(),
+            bucket_url,
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            credentials,
+            verify_multipart_etags: config.verify_multipart_etags,
+            max_retries,
+        })
+    }
+
+    fn url(&self, key: Option<&str>, query: &[(&str, String)]) -> Result<Url> {
+        let mut url = self.bucket_url.clone();
+        let path = match key {
+            Some(key) => format!(
+                "{}/{}",
+                self.bucket_url.path().trim_end_matches('/'),
+                uri_encode_key(key)
+            ),
+            None => format!("{}/", self.bucket_url.path().trim_end_matches('/')),
+        };
+        url.set_path(&path);
+        if !query.is_empty() {
+            url.set_query(Some(&canonical_query(query)));
+        }
+        Ok(url)
+    }
+
+    /// Builds a signed request.
+    fn request(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Request> {
+        let url = self.url(key, query)?;
+        let now = Utc::now();
+        let payload_hash = hex::encode(digest::digest(&digest::SHA256, &body));
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let mut all_headers = vec![
+            (HOST.as_str().to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            (
+                "x-amz-date".to_string(),
+                now.format(AMZ_DATE_FORMAT).to_string(),
+            ),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            all_headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        all_headers.extend(
+            headers
+                .iter()
+                .map(|(name, value)| (name.to_lowercase(), value.clone())),
+        );
+        all_headers.sort();
+        let authorization = sign_v4(
+            &self.credentials,
+            &self.region,
+            Self::SERVICE,
+            &method,
+            &url,
+            &all_headers,
+            &payload_hash,
+            now,
+        );
+
+        let mut request = Request::new(method, url);
+        let request_headers = request.headers_mut();
+        for (name, value) in all_headers {
+            // reqwest sets the Host header from the URL.
+            if name != HOST.as_str() {
+                request_headers.insert(
+                    HeaderName::from_bytes(name.as_bytes())?,
+                    HeaderValue::from_str(&value)?,
+                );
+            }
+        }
+        request_headers.insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+        *request.body_mut() = Some(body.into());
+        Ok(request)
+    }
+
+    /// The checksum recorded when uploading an object, from the headers of a download.
+    fn checksum(&self, headers: &HeaderMap) -> Result<Option<Checksum>> {
+        if let Some(content_md5) = headers.get(Self::CONTENT_MD5_HEADER) {
+            return Ok(Some(Checksum::Md5(base64::decode(content_md5.to_str()?)?)));
+        }
+        let (Some(part_size), Some(etag)) =
+            (headers.get(Self::PART_SIZE_HEADER), headers.get(ETAG))
+        else {
+            // Not uploaded by us.
+            return Ok(None);
+        };
+        if !self.verify_multipart_etags {
+            return Ok(None);
+        }
+        // Like "<hex digest>-<number of parts>", quoted.
+        let etag = etag.to_str()?.trim_matches('"');
+        let (digest, num_parts) = etag
+            .split_once('-')
+            .ok_or_else(|| format_err!("Not the ETag of a multipart upload: {}", etag))?;
+        Ok(Some(Checksum::PartsMd5 {
+            part_size: part_size.to_str()?.parse()?,
+            num_parts: num_parts.parse()?,
+            digest: hex::decode(digest)?,
+        }))
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<reqwest::Response> {
+        send_with_retry(&self.http, self.max_retries, || {
+            self.request(method.clone(), key, query, headers, body.clone())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ObjectClient for S3Client {
+    async fn put_object(&self, key: &str, data: Bytes) -> Result<()> {
+        let content_md5 = content_md5(&data);
+        let headers = [
+            ("content-md5", content_md5.clone()),
+            (Self::CONTENT_MD5_HEADER, content_md5),
+        ];
+        self.send(Method::PUT, Some(key), &[], &headers, data)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_multipart_upload(&self, key: &str, part_size: usize) -> Result<String> {
+        let query = [("uploads", String::new())];
+        let headers = [(Self::PART_SIZE_HEADER, part_size.to_string())];
+        let body = self
+            .send(Method::POST, Some(key), &query, &headers, Bytes::new())
+            .await?
+            .text()
+            .await?;
+        xml::element(&body, "UploadId")
+            .ok_or_else(|| format_err!("No UploadId in response: {}", body))
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        data: Bytes,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let headers = [("content-md5", content_md5(&data))];
+        let response = self
+            .send(Method::PUT, Some(key), &query, &headers, data)
+            .await?;
+        Ok(response
+            .headers()
+            .get(ETAG)
+            .ok_or_else(|| format_err!("No ETag for part {} of {}.", part_number, key))?
+            .to_str()?
+            .to_string())
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: Vec<String>,
+        // Can't be recorded once the upload started, the ETag of the object is verified instead.
+        _content_md5: String,
+    ) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        let mut body = "<CompleteMultipartUpload>".to_string();
+        for (idx, etag) in parts.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                idx + 1,
+                xml::escape(etag),
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let response_body = self
+            .send(Method::POST, Some(key), &query, &[], body.into())
+            .await?
+            .text()
+            .await?;
+        // The service can fail the request after responding with 200.
+        if let Some(error) = xml::element(&response_body, "Code") {
+            bail!("Failed completing upload of {}: {}", key, error);
+        }
+        Ok(())
+    }
+
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        self.send(Method::DELETE, Some(key), &query, &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let response = self
+            .send(Method::GET, Some(key), &[], &[], Bytes::new())
+            .await?;
+        let checksum = self.checksum(response.headers())?;
+        Ok(into_reader(response, checksum))
+    }
+
+    async fn list_objects(
+        &self,
+        prefix: &str,
+        continuation_token: Option<String>,
+    ) -> Result<(Vec<String>, Option<String>)> {
+        let mut query = vec![
+            ("list-type", "2".to_string()),
+            ("prefix", prefix.to_string()),
+        ];
+        if let Some(token) = continuation_token {
+            query.push(("continuation-token", token));
+        }
+        let body = self
+            .send(Method::GET, None, &query, &[], Bytes::new())
+            .await?
+            .text()
+            .await?;
+
+        let next = if xml::element(&body, "IsTruncated").as_deref() == Some("true") {
+            Some(
+                xml::element(&body, "NextContinuationToken")
+                    .ok_or_else(|| format_err!("No NextContinuationToken in response: {}", body))?,
+            )
+        } else {
+            None
+        };
+        Ok((xml::elements(&body, "Key"), next))
+    }
+
+    async fn copy_object(&self, from_key: &str, to_key: &str) -> Result<()> {
+        let headers = [(
+            "x-amz-copy-source",
+            format!("/{}/{}", uri_encode(&self.bucket), uri_encode_key(from_key)),
+        )];
+        let body = self
+            .send(Method::PUT, Some(to_key), &[], &headers, Bytes::new())
+            .await?
+            .text()
+            .await?;
+        // The service can fail the request after responding with 200.
+        if let Some(error) = xml::element(&body, "Code") {
+            bail!("Failed copying {} to {}: {}", from_key, to_key, error);
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, Some(key), &[], &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Query string with parameters sorted by name and URI encoded, as required for signing.
+pub(super) fn canonical_query(query: &[(&str, String)]) -> String {
+    let mut pairs = query
+        .iter()
+        .map(|(name, value)| (uri_encode(name), uri_encode(value)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+const AMZ_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Returns the Authorization header of a request signed with AWS Signature Version 4 at `now`.
+/// `headers` are the headers to sign, `host` and `x-amz-date` included, with lowercase names and
+/// sorted by name. The query of `url` must be in canonical form, see `canonical_query()`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sign_v4(
+    credentials: &S3Credentials,
+    region: &str,
+    service: &str,
+    method: &Method,
+    url: &Url,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let amz_date = now.format(AMZ_DATE_FORMAT).to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        url.path(),
+        url.query().unwrap_or_default(),
+        canonical_headers,
+        signed_headers,
+        payload_hash,
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(digest::digest(
+            &digest::SHA256,
+            canonical_request.as_bytes()
+        )),
+    );
+    let signing_key = [date.as_str(), region, service, "aws4_request"]
+        .iter()
+        .fold(
+            format!("AWS4{}", credentials.secret_access_key).into_bytes(),
+            |key, data| hmac_sha256(&key, data.as_bytes()),
+        );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature,
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
@@ -0,0 +1,232 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::client::ObjectClient;
+use anyhow::{bail, format_err, Result};
+use aptos_logger::prelude::*;
+use bytes::{Bytes, BytesMut};
+use futures::{
+    ready,
+    stream::{FuturesUnordered, StreamExt},
+    task::{Context, Poll},
+    Future,
+};
+use md5::{Digest, Md5};
+use std::{io, pin::Pin, sync::Arc};
+use tokio::{io::AsyncWrite, sync::mpsc, task::JoinHandle};
+use tokio_util::sync::PollSender;
+
+/// What's written to it is uploaded to an object in the background, in parts uploaded in parallel
+/// if it's big. The object is complete only after a successful `shutdown()`; if the sink is
+/// dropped before that the upload is abandoned.
+pub(super) struct ObjectSink {
+    /// `None` marks the end of the content.
+    sender: PollSender<Option<Bytes>>,
+    end_sent: bool,
+    upload: JoinHandle<Result<()>>,
+}
+
+impl ObjectSink {
+    const CHANNEL_SIZE: usize = 64;
+
+    pub fn new(
+        client: Arc<dyn ObjectClient>,
+        key: String,
+        part_size: usize,
+        concurrency: usize,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(Self::CHANNEL_SIZE);
+        let upload = tokio::spawn(upload(client, key, receiver, part_size, concurrency));
+        Self {
+            sender: PollSender::new(sender),
+            end_sent: false,
+            upload,
+        }
+    }
+
+    /// Waits for the upload to finish, which is an error if it finished before being told the
+    /// content ended.
+    fn poll_upload(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let res = match ready!(Pin::new(&mut self.upload).poll(cx)) {
+            Ok(Ok(())) if self.end_sent => Ok(()),
+            Ok(Ok(())) => Err(io::ErrorKind::BrokenPipe.into()),
+            Ok(Err(e)) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+        };
+        Poll::Ready(res)
+    }
+
+    fn poll_send(&mut self, cx: &mut Context<'_>, item: Option<Bytes>) -> Poll<io::Result<()>> {
+        if ready!(self.sender.poll_reserve(cx)).is_err() || self.sender.send_item(item).is_err() {
+            // The upload quit, find out why.
+            return self.poll_upload(cx);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for ObjectSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.end_sent {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        ready!(self.poll_send(cx, Some(Bytes::copy_from_slice(buf))))?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Content is durable only after shutdown.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.end_sent {
+            ready!(self.poll_send(cx, None))?;
+            self.end_sent = true;
+            self.sender.close();
+        }
+        self.poll_upload(cx)
+    }
+}
+
+async fn upload(
+    client: Arc<dyn ObjectClient>,
+    key: String,
+    mut receiver: mpsc::Receiver<Option<Bytes>>,
+    part_size: usize,
+    concurrency: usize,
+) -> Result<()> {
+    let mut buf = BytesMut::new();
+    let mut multipart = None;
+    // Of the whole content, recorded with a multipart upload for verifying downloads.
+    let mut hasher = Md5::new();
+
+    let res = async {
+        loop {
+            match receiver.recv().await {
+                Some(Some(bytes)) => {
+                    hasher.update(&bytes);
+                    buf.extend_from_slice(&bytes);
+                    // Holding back a full part until more comes, so small files end up in one
+                    // simple upload.
+                    while buf.len() > part_size {
+                        let part = buf.split_to(part_size).freeze();
+                        if multipart.is_none() {
+                            multipart =
+                                Some(MultipartUpload::start(&client, &key, part_size).await?);
+                        }
+                        multipart
+                            .as_mut()
+                            .expect("Started above.")
+                            .upload_part(part, concurrency)
+                            .await?;
+                    }
+                },
+                Some(None) => break,
+                None => bail!("Writer dropped before shutdown."),
+            }
+        }
+
+        match multipart.as_mut() {
+            None => client.put_object(&key, buf.split().freeze()).await,
+            Some(multipart) => {
+                multipart
+                    .upload_part(buf.split().freeze(), concurrency)
+                    .await?;
+                multipart
+                    .complete(base64::encode(hasher.finalize_reset()))
+                    .await
+            },
+        }
+    }
+    .await;
+
+    if let Err(e) = &res {
+        if let Some(multipart) = multipart {
+            multipart.abort().await;
+        }
+        warn!(error = ?e, "Failed uploading {}.", key);
+    }
+    res.map_err(|e| format_err!("Failed uploading {}: {}", key, e))
+}
+
+struct MultipartUpload {
+    client: Arc<dyn ObjectClient>,
+    key: String,
+    upload_id: String,
+    in_flight: FuturesUnordered<JoinHandle<Result<(usize, String)>>>,
+    /// (part number, part ID) of uploaded parts, in random order.
+    uploaded: Vec<(usize, String)>,
+    next_part_number: usize,
+}
+
+impl MultipartUpload {
+    async fn start(client: &Arc<dyn ObjectClient>, key: &str, part_size: usize) -> Result<Self> {
+        let upload_id = client.create_multipart_upload(key, part_size).await?;
+        Ok(Self {
+            client: Arc::clone(client),
+            key: key.to_string(),
+            upload_id,
+            in_flight: FuturesUnordered::new(),
+            uploaded: Vec::new(),
+            next_part_number: 1,
+        })
+    }
+
+    /// Starts uploading the part after waiting for one to finish if too many are in flight.
+    async fn upload_part(&mut self, data: Bytes, concurrency: usize) -> Result<()> {
+        while self.in_flight.len() >= concurrency {
+            self.wait_one().await?;
+        }
+
+        let part_number = self.next_part_number;
+        self.next_part_number += 1;
+        let client = Arc::clone(&self.client);
+        let key = self.key.clone();
+        let upload_id = self.upload_id.clone();
+        self.in_flight.push(tokio::spawn(async move {
+            let part_id = client
+                .upload_part(&key, &upload_id, part_number, data)
+                .await?;
+            Ok((part_number, part_id))
+        }));
+        Ok(())
+    }
+
+    async fn wait_one(&mut self) -> Result<()> {
+        if let Some(res) = self.in_flight.next().await {
+            self.uploaded.push(res??);
+        }
+        Ok(())
+    }
+
+    /// `content_md5` is the `content_md5()` of the whole object.
+    async fn complete(&mut self, content_md5: String) -> Result<()> {
+        while !self.in_flight.is_empty() {
+            self.wait_one().await?;
+        }
+        self.uploaded.sort_unstable();
+        let parts = self.uploaded.iter().map(|(_, id)| id.clone()).collect();
+        self.client
+            .complete_multipart_upload(&self.key, &self.upload_id, parts, content_md5)
+            .await
+    }
+
+    /// Best effort, so the service doesn't keep the parts around.
+    async fn abort(self) {
+        for handle in &self.in_flight {
+            handle.abort();
+        }
+        if let Err(e) = self
+            .client
+            .abort_multipart_upload(&self.key, &self.upload_id)
+            .await
+        {
+            warn!(error = ?e, "Failed aborting upload of {}.", self.key);
+        }
+    }
+}
@@ -17,7 +17,7 @@ use aptos_config::config::{
 };
 use aptos_crypto::HashValue;
 use aptos_db::{
-    backup::restore_handler::RestoreHandler,
+    backup::restore_handler::{RestoreHandler, StateSnapshotDiffRestore},
     db::AptosDB,
     get_restore_handler::GetRestoreHandler,
     state_restore::{
@@ -97,6 +97,8 @@ impl From<RocksdbOpt> for RocksdbConfigs {
                 ..Default::default()
             },
             enable_storage_sharding: opt.enable_storage_sharding,
+            enable_account_transactions_index: false,
+            enable_in_memory_backend: false,
             state_kv_db_config: RocksdbConfig {
                 max_open_files: opt.state_kv_db_max_open_files,
                 max_total_wal_size: opt.state_kv_db_max_total_wal_size,
@@ -224,6 +226,20 @@ impl RestoreRunMode {
         }
     }
 
+    pub fn get_state_snapshot_diff_restore(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<Option<StateSnapshotDiffRestore>> {
+        match self {
+            Self::Restore { restore_handler } => Ok(Some(
+                restore_handler.get_state_snapshot_diff_restore(base_version, version)?,
+            )),
+            // Without the base state there is nothing to apply the diff to.
+            Self::Verify => Ok(None),
+        }
+    }
+
     pub fn finish(&self) {
         match self {
             Self::Restore { restore_handler } => {
@@ -2,8 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::{
+        backup::BackupCompactor,
+        retention::{RetentionCoordinator, RetentionPolicyOpt},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::ConcurrentDownloadsOpt,
 };
 use clap::{Parser, Subcommand};
 
@@ -12,7 +17,10 @@ use clap::{Parser, Subcommand};
 pub enum Command {
     #[clap(about = "Compact metdata files")]
     Compact(CompactionOpt),
-    #[clap(about = "Cleanup the backup metadata files")]
+    #[clap(
+        about = "Cleanup backups no longer needed according to the retention policy, only \
+        reporting them unless --delete is given"
+    )]
     Cleanup(CleanupOpt),
 }
 
@@ -21,7 +29,8 @@ pub struct CompactionOpt {
     /// Specify how many epoch files to be merged in one compacted epoch ending metadata file
     #[clap(long, default_value_t = 1)]
     pub epoch_ending_file_compact_factor: usize,
-    /// Specify how many state snapshot files to be merged in one compacted state snapshot metadata file
+    /// Specify how many state snapshot files to be merged in one compacted state snapshot metadata file,
+    /// likewise for the state snapshot diff files
     #[clap(long, default_value_t = 1)]
     pub state_snapshot_file_compact_factor: usize,
     /// Specify how many transaction files to be merged in one transaction metadata file
@@ -44,8 +53,17 @@ pub struct CompactionOpt {
 
 #[derive(Parser)]
 pub struct CleanupOpt {
+    #[clap(flatten)]
+    pub retention_policy: RetentionPolicyOpt,
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
     #[clap(flatten)]
     pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+    /// Actually delete the backups no longer needed, instead of only reporting them
+    #[clap(long)]
+    pub delete: bool,
 }
 
 impl Command {
@@ -63,8 +81,15 @@ impl Command {
                 );
                 compactor.run().await?
             },
-            Command::Cleanup(_) => {
-                // TODO: add cleanup logic for removing obsolete metadata files
+            Command::Cleanup(opt) => {
+                let coordinator = RetentionCoordinator::new(
+                    opt.retention_policy,
+                    opt.metadata_cache_opt,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                    opt.delete,
+                );
+                coordinator.run().await?
             },
         }
         Ok(())
@@ -0,0 +1,370 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod tables;
+
+use crate::export::tables::{Table, TransactionRow};
+use anyhow::{ensure, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::transaction::Version;
+use arrow::{datatypes::SchemaRef, ipc::writer::FileWriter, record_batch::RecordBatch};
+use clap::{Parser, ValueEnum};
+use itertools::multizip;
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use std::{
+    cmp::min,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+/// Export transactions, events, write sets and state changes in a version range to columnar files.
+///
+/// Each table goes to its own sub-directory of the output dir, split into one file per version
+/// range. Ranges start at multiples of `--versions-per-file` (`--start-version` is rounded down to
+/// one), and ranges whose files all exist are skipped, so an interrupted export can be resumed by
+/// rerunning the command. A file left partial by an earlier `--end-version` is replaced by the
+/// longer one once the range grows, so that no version is exported twice.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    #[clap(long, value_parser)]
+    output_dir: PathBuf,
+
+    #[clap(long, default_value_t = 0)]
+    start_version: Version,
+
+    #[clap(
+        long,
+        help = "The last version to export (inclusive). [Defaults to the latest committed version]"
+    )]
+    end_version: Option<Version>,
+
+    #[clap(
+        long = "table",
+        value_enum,
+        help = "The tables to export, can be repeated. [Defaults to all tables]"
+    )]
+    tables: Vec<Table>,
+
+    #[clap(long, value_enum, default_value_t = Format::Parquet)]
+    format: Format,
+
+    #[clap(long, default_value_t = 1_000_000)]
+    versions_per_file: u64,
+
+    #[clap(
+        long,
+        default_value_t = 4,
+        help = "Number of version ranges exported in parallel."
+    )]
+    concurrency: usize,
+
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Parquet,
+    Arrow,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Parquet => "parquet",
+            Format::Arrow => "arrow",
+        }
+    }
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        ensure!(
+            self.versions_per_file > 0,
+            "--versions-per-file must be positive."
+        );
+        ensure!(self.concurrency > 0, "--concurrency must be positive.");
+
+        let db = AptosDB::open(
+            StorageDirPaths::from_path(&self.db_dir),
+            true,                        /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.clone().into(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?;
+        let latest_version = db.get_latest_version()?;
+        let end_version = self.end_version.unwrap_or(latest_version);
+        ensure!(
+            end_version <= latest_version,
+            "End version {} is beyond the latest version in the DB {}.",
+            end_version,
+            latest_version,
+        );
+        ensure!(
+            self.start_version <= end_version,
+            "Start version {} is larger than end version {}.",
+            self.start_version,
+            end_version,
+        );
+
+        let mut tables = if self.tables.is_empty() {
+            Table::ALL.to_vec()
+        } else {
+            self.tables.clone()
+        };
+        tables.sort();
+        tables.dedup();
+        for table in &tables {
+            let dir = self.output_dir.join(table.name());
+            fs::create_dir_all(&dir)?;
+            // Left behind by an interrupted export.
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().map_or(false, |ext| ext == "tmp") {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        let ranges = self.version_ranges(end_version);
+        info!(
+            start_version = self.start_version,
+            end_version = end_version,
+            num_files_per_table = ranges.len(),
+            "Exporting."
+        );
+
+        let next_range = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        thread::scope(|s| {
+            let workers: Vec<_> = (0..self.concurrency)
+                .map(|_| {
+                    s.spawn(|| -> Result<()> {
+                        while !failed.load(Ordering::Relaxed) {
+                            let Some(&(first, last)) =
+                                ranges.get(next_range.fetch_add(1, Ordering::Relaxed))
+                            else {
+                                break;
+                            };
+                            if let Err(err) = self.export_range(&db, &tables, first, last) {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().expect("Export worker panicked."))
+        })?;
+
+        info!("Export finished.");
+        Ok(())
+    }
+
+    /// Splits [start_version, end_version] at multiples of `versions_per_file`, with the start
+    /// rounded down to one, so that ranges don't depend on where a (resumed) export started.
+    fn version_ranges(&self, end_version: Version) -> Vec<(Version, Version)> {
+        let mut ranges = Vec::new();
+        let mut first = self.start_version / self.versions_per_file * self.versions_per_file;
+        loop {
+            let last = min(
+                end_version,
+                (first / self.versions_per_file + 1) * self.versions_per_file - 1,
+            );
+            ranges.push((first, last));
+            if last == end_version {
+                break;
+            }
+            first = last + 1;
+        }
+        ranges
+    }
+
+    fn file_path(&self, table: Table, first: Version, last: Version) -> PathBuf {
+        self.output_dir.join(table.name()).join(format!(
+            "{:020}-{:020}.{}",
+            first,
+            last,
+            self.format.extension()
+        ))
+    }
+
+    /// Returns the last version and path of the files of `table` exported for the range starting
+    /// at `first`, which are for shorter ranges if the end version was within the range then.
+    fn exported_files(&self, table: Table, first: Version) -> Result<Vec<(Version, PathBuf)>> {
+        let prefix = format!("{:020}-", first);
+        let mut files = Vec::new();
+        for entry in fs::read_dir(self.output_dir.join(table.name()))? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(true, |ext| ext != self.format.extension())
+            {
+                continue;
+            }
+            let last = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix(&prefix))
+                .and_then(|last| last.parse::<Version>().ok());
+            if let Some(last) = last {
+                files.push((last, path));
+            }
+        }
+        Ok(files)
+    }
+
+    fn export_range(
+        &self,
+        db: &AptosDB,
+        tables: &[Table],
+        first: Version,
+        last: Version,
+    ) -> Result<()> {
+        let mut pending = Vec::new();
+        for table in tables {
+            let exported = self.exported_files(*table, first)?;
+            if exported
+                .iter()
+                .all(|(exported_last, _path)| *exported_last < last)
+            {
+                let partial_files: Vec<_> =
+                    exported.into_iter().map(|(_last, path)| path).collect();
+                pending.push((*table, partial_files));
+            }
+        }
+        if pending.is_empty() {
+            info!(
+                first_version = first,
+                last_version = last,
+                "Already exported, skipping."
+            );
+            return Ok(());
+        }
+
+        // Files are written under a temporary name and renamed once complete, so that a file
+        // with the final name is always a complete one.
+        let mut outputs = pending
+            .iter()
+            .map(|(table, partial_files)| {
+                let path = self.file_path(*table, first, last);
+                let tmp_path = path.with_extension("tmp");
+                let writer = TableWriter::create(self.format, &tmp_path, table.schema())?;
+                Ok((table.builder(), writer, tmp_path, path, partial_files))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut version = first;
+        while version <= last {
+            let limit = min(last - version + 1, MAX_REQUEST_LIMIT);
+            let rows = multizip((
+                db.get_transaction_iterator(version, limit)?,
+                db.get_transaction_info_iterator(version, limit)?,
+                db.get_events_iterator(version, limit)?,
+                db.get_write_set_iterator(version, limit)?,
+            ));
+
+            let mut num_rows = 0;
+            for (transaction, info, events, write_set) in rows {
+                let row = TransactionRow {
+                    version: version + num_rows,
+                    transaction: transaction?,
+                    info: info?,
+                    events: events?,
+                    write_set: write_set?,
+                };
+                for (builder, ..) in outputs.iter_mut() {
+                    builder.append(&row)?;
+                }
+                num_rows += 1;
+            }
+            ensure!(
+                num_rows == limit,
+                "Expecting {} versions starting at {}, got {}.",
+                limit,
+                version,
+                num_rows,
+            );
+
+            for (builder, writer, ..) in outputs.iter_mut() {
+                writer.write(&builder.finish()?)?;
+            }
+            version += limit;
+        }
+
+        for (_builder, writer, tmp_path, path, partial_files) in outputs {
+            writer.finish()?;
+            fs::rename(tmp_path, path)?;
+            // Superseded by the file just written, which covers their versions as well.
+            for partial_file in partial_files {
+                fs::remove_file(partial_file)?;
+            }
+        }
+        info!(
+            "Exported versions [{}, {}] of tables {:?}.",
+            first,
+            last,
+            pending.iter().map(|(table, _)| table).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+}
+
+enum TableWriter {
+    Parquet(ArrowWriter<File>),
+    Arrow(FileWriter<File>),
+}
+
+impl TableWriter {
+    fn create(format: Format, path: &Path, schema: SchemaRef) -> Result<Self> {
+        let file = File::create(path)?;
+        Ok(match format {
+            Format::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .build();
+                Self::Parquet(ArrowWriter::try_new(file, schema, Some(props))?)
+            },
+            Format::Arrow => Self::Arrow(FileWriter::try_new(file, &schema)?),
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => writer.write(batch)?,
+            Self::Arrow(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => {
+                writer.close()?;
+            },
+            Self::Arrow(mut writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    access_path::Path,
+    contract_event::{ContractEvent, ContractEventV1},
+    state_store::state_key::StateKeyInner,
+    transaction::{ExecutionStatus, Transaction, TransactionInfo, Version},
+    vm_status::AbortLocation,
+    write_set::{WriteOp, WriteSet},
+};
+use arrow::{
+    array::{
+        ArrayRef, BinaryBuilder, BooleanBuilder, StringBuilder, UInt16Builder, UInt32Builder,
+        UInt64Builder,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use clap::ValueEnum;
+use std::sync::Arc;
+
+/// Everything the exported tables know about a single version.
+pub struct TransactionRow {
+    pub version: Version,
+    pub transaction: Transaction,
+    pub info: TransactionInfo,
+    pub events: Vec<ContractEvent>,
+    pub write_set: WriteSet,
+}
+
+/// One table per data type, written to a directory of the same name. The schemas are part of the
+/// output format: columns can be appended, but existing ones must not be renamed or retyped.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, ValueEnum)]
+pub enum Table {
+    /// One row per transaction.
+    Transactions,
+    /// One row per event emitted.
+    Events,
+    /// One row per transaction, holding the BCS of its whole write set.
+    WriteSets,
+    /// One row per state key written, decoded.
+    StateChanges,
+}
+
+impl Table {
+    pub const ALL: [Table; 4] = [
+        Table::Transactions,
+        Table::Events,
+        Table::WriteSets,
+        Table::StateChanges,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Table::Transactions => "transactions",
+            Table::Events => "events",
+            Table::WriteSets => "write_sets",
+            Table::StateChanges => "state_changes",
+        }
+    }
+
+    pub fn schema(self) -> SchemaRef {
+        let fields = match self {
+            Table::Transactions => vec![
+                Field::new("version", DataType::UInt64, false),
+                Field::new("hash", DataType::Utf8, false),
+                Field::new("type", DataType::Utf8, false),
+                Field::new("sender", DataType::Utf8, true),
+                Field::new("sequence_number", DataType::UInt64, true),
+                Field::new("success", DataType::Boolean, false),
+                Field::new("status", DataType::Utf8, false),
+                Field::new("abort_location", DataType::Utf8, true),
+                Field::new("abort_code", DataType::UInt64, true),
+                Field::new("abort_reason", DataType::Utf8, true),
+                Field::new("function_index", DataType::UInt16, true),
+                Field::new("code_offset", DataType::UInt16, true),
+                Field::new("status_code", DataType::UInt64, true),
+                Field::new("gas_used", DataType::UInt64, false),
+                Field::new("state_change_hash", DataType::Utf8, false),
+                Field::new("event_root_hash", DataType::Utf8, false),
+                Field::new("state_checkpoint_hash", DataType::Utf8, true),
+                Field::new("num_events", DataType::UInt64, false),
+                Field::new("num_write_set_changes", DataType::UInt64, false),
+                Field::new("transaction_bcs", DataType::Binary, false),
+            ],
+            Table::Events => vec![
+                Field::new("version", DataType::UInt64, false),
+                Field::new("event_index", DataType::UInt32, false),
+                Field::new("type_tag", DataType::Utf8, false),
+                Field::new("account_address", DataType::Utf8, true),
+                Field::new("creation_number", DataType::UInt64, true),
+                Field::new("sequence_number", DataType::UInt64, true),
+                Field::new("data", DataType::Binary, false),
+            ],
+            Table::WriteSets => vec![
+                Field::new("version", DataType::UInt64, false),
+                Field::new("write_set_bcs", DataType::Binary, false),
+            ],
+            Table::StateChanges => vec![
+                Field::new("version", DataType::UInt64, false),
+                Field::new("change_index", DataType::UInt32, false),
+                Field::new("state_key_hash", DataType::Utf8, false),
+                Field::new("state_key_bcs", DataType::Binary, false),
+                Field::new("key_type", DataType::Utf8, false),
+                Field::new("address", DataType::Utf8, true),
+                Field::new("path", DataType::Utf8, true),
+                Field::new("table_handle", DataType::Utf8, true),
+                Field::new("table_key", DataType::Binary, true),
+                Field::new("op", DataType::Utf8, false),
+                Field::new("value", DataType::Binary, true),
+            ],
+        };
+        Arc::new(Schema::new(fields))
+    }
+
+    pub fn builder(self) -> Box<dyn TableBuilder> {
+        match self {
+            Table::Transactions => Box::<TransactionsBuilder>::default(),
+            Table::Events => Box::<EventsBuilder>::default(),
+            Table::WriteSets => Box::<WriteSetsBuilder>::default(),
+            Table::StateChanges => Box::<StateChangesBuilder>::default(),
+        }
+    }
+}
+
+/// Accumulates the rows of one table, to be flushed as record batches.
+pub trait TableBuilder {
+    fn append(&mut self, row: &TransactionRow) -> Result<()>;
+
+    /// Returns the rows appended since the last call as a record batch.
+    fn finish(&mut self) -> Result<RecordBatch>;
+}
+
+#[derive(Default)]
+struct TransactionsBuilder {
+    version: UInt64Builder,
+    hash: StringBuilder,
+    txn_type: StringBuilder,
+    sender: StringBuilder,
+    sequence_number: UInt64Builder,
+    success: BooleanBuilder,
+    status: StringBuilder,
+    abort_location: StringBuilder,
+    abort_code: UInt64Builder,
+    abort_reason: StringBuilder,
+    function_index: UInt16Builder,
+    code_offset: UInt16Builder,
+    status_code: UInt64Builder,
+    gas_used: UInt64Builder,
+    state_change_hash: StringBuilder,
+    event_root_hash: StringBuilder,
+    state_checkpoint_hash: StringBuilder,
+    num_events: UInt64Builder,
+    num_write_set_changes: UInt64Builder,
+    transaction_bcs: BinaryBuilder,
+}
+
+impl TableBuilder for TransactionsBuilder {
+    fn append(&mut self, row: &TransactionRow) -> Result<()> {
+        let (sender, sequence_number) = match &row.transaction {
+            Transaction::UserTransaction(signed_txn) => (
+                Some(signed_txn.sender().to_hex_literal()),
+                Some(signed_txn.sequence_number()),
+            ),
+            _ => (None, None),
+        };
+
+        self.version.append_value(row.version);
+        self.hash
+            .append_value(row.info.transaction_hash().to_hex_literal());
+        self.txn_type.append_value(row.transaction.type_name());
+        self.sender.append_option(sender);
+        self.sequence_number.append_option(sequence_number);
+        self.success.append_value(row.info.status().is_success());
+        self.append_status(row.info.status());
+        self.gas_used.append_value(row.info.gas_used());
+        self.state_change_hash
+            .append_value(row.info.state_change_hash().to_hex_literal());
+        self.event_root_hash
+            .append_value(row.info.event_root_hash().to_hex_literal());
+        self.state_checkpoint_hash.append_option(
+            row.info
+                .state_checkpoint_hash()
+                .map(|hash| hash.to_hex_literal()),
+        );
+        self.num_events.append_value(row.events.len() as u64);
+        self.num_write_set_changes
+            .append_value(row.write_set.iter().len() as u64);
+        self.transaction_bcs
+            .append_value(bcs::to_bytes(&row.transaction)?);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.version.finish()),
+            Arc::new(self.hash.finish()),
+            Arc::new(self.txn_type.finish()),
+            Arc::new(self.sender.finish()),
+            Arc::new(self.sequence_number.finish()),
+            Arc::new(self.success.finish()),
+            Arc::new(self.status.finish()),
+            Arc::new(self.abort_location.finish()),
+            Arc::new(self.abort_code.finish()),
+            Arc::new(self.abort_reason.finish()),
+            Arc::new(self.function_index.finish()),
+            Arc::new(self.code_offset.finish()),
+            Arc::new(self.status_code.finish()),
+            Arc::new(self.gas_used.finish()),
+            Arc::new(self.state_change_hash.finish()),
+            Arc::new(self.event_root_hash.finish()),
+            Arc::new(self.state_checkpoint_hash.finish()),
+            Arc::new(self.num_events.finish()),
+            Arc::new(self.num_write_set_changes.finish()),
+            Arc::new(self.transaction_bcs.finish()),
+        ];
+        Ok(RecordBatch::try_new(Table::Transactions.schema(), columns)?)
+    }
+}
+
+impl TransactionsBuilder {
+    /// Spreads the execution status over a column per field, so that it can be queried without
+    /// parsing.
+    fn append_status(&mut self, status: &ExecutionStatus) {
+        let location = |location: &AbortLocation| match location {
+            AbortLocation::Module(module_id) => module_id.to_string(),
+            AbortLocation::Script => "script".to_string(),
+        };
+        let (kind, abort_location, abort_code, abort_reason, function_index, code_offset) =
+            match status {
+                ExecutionStatus::Success => ("success", None, None, None, None, None),
+                ExecutionStatus::OutOfGas => ("out_of_gas", None, None, None, None, None),
+                ExecutionStatus::MoveAbort {
+                    location: abort_location,
+                    code,
+                    info,
+                } => (
+                    "move_abort",
+                    Some(location(abort_location)),
+                    Some(*code),
+                    info.as_ref().map(|info| info.reason_name.as_str()),
+                    None,
+                    None,
+                ),
+                ExecutionStatus::ExecutionFailure {
+                    location: abort_location,
+                    function,
+                    code_offset,
+                } => (
+                    "execution_failure",
+                    Some(location(abort_location)),
+                    None,
+                    None,
+                    Some(*function),
+                    Some(*code_offset),
+                ),
+                ExecutionStatus::MiscellaneousError(_) => {
+                    ("miscellaneous_error", None, None, None, None, None)
+                },
+            };
+        let status_code = match status {
+            ExecutionStatus::MiscellaneousError(status_code) => status_code.map(|code| code as u64),
+            _ => None,
+        };
+
+        self.status.append_value(kind);
+        self.abort_location.append_option(abort_location);
+        self.abort_code.append_option(abort_code);
+        self.abort_reason.append_option(abort_reason);
+        self.function_index.append_option(function_index);
+        self.code_offset.append_option(code_offset);
+        self.status_code.append_option(status_code);
+    }
+}
+
+#[derive(Default)]
+struct EventsBuilder {
+    version: UInt64Builder,
+    event_index: UInt32Builder,
+    type_tag: StringBuilder,
+    account_address: StringBuilder,
+    creation_number: UInt64Builder,
+    sequence_number: UInt64Builder,
+    data: BinaryBuilder,
+}
+
+impl TableBuilder for EventsBuilder {
+    fn append(&mut self, row: &TransactionRow) -> Result<()> {
+        for (idx, event) in row.events.iter().enumerate() {
+            let v1 = match event {
+                ContractEvent::V1(v1) => Some(v1),
+                ContractEvent::V2(_) => None,
+            };
+
+            self.version.append_value(row.version);
+            self.event_index.append_value(idx as u32);
+            self.type_tag
+                .append_value(event.type_tag().to_canonical_string());
+            self.account_address
+                .append_option(v1.map(|v1| v1.key().get_creator_address().to_hex_literal()));
+            self.creation_number
+                .append_option(v1.map(|v1| v1.key().get_creation_number()));
+            self.sequence_number
+                .append_option(v1.map(ContractEventV1::sequence_number));
+            self.data.append_value(event.event_data());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.version.finish()),
+            Arc::new(self.event_index.finish()),
+            Arc::new(self.type_tag.finish()),
+            Arc::new(self.account_address.finish()),
+            Arc::new(self.creation_number.finish()),
+            Arc::new(self.sequence_number.finish()),
+            Arc::new(self.data.finish()),
+        ];
+        Ok(RecordBatch::try_new(Table::Events.schema(), columns)?)
+    }
+}
+
+#[derive(Default)]
+struct WriteSetsBuilder {
+    version: UInt64Builder,
+    write_set_bcs: BinaryBuilder,
+}
+
+impl TableBuilder for WriteSetsBuilder {
+    fn append(&mut self, row: &TransactionRow) -> Result<()> {
+        self.version.append_value(row.version);
+        self.write_set_bcs
+            .append_value(bcs::to_bytes(&row.write_set)?);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.version.finish()),
+            Arc::new(self.write_set_bcs.finish()),
+        ];
+        Ok(RecordBatch::try_new(Table::WriteSets.schema(), columns)?)
+    }
+}
+
+#[derive(Default)]
+struct StateChangesBuilder {
+    version: UInt64Builder,
+    change_index: UInt32Builder,
+    state_key_hash: StringBuilder,
+    state_key_bcs: BinaryBuilder,
+    key_type: StringBuilder,
+    address: StringBuilder,
+    path: StringBuilder,
+    table_handle: StringBuilder,
+    table_key: BinaryBuilder,
+    op: StringBuilder,
+    value: BinaryBuilder,
+}
+
+impl TableBuilder for StateChangesBuilder {
+    fn append(&mut self, row: &TransactionRow) -> Result<()> {
+        for (idx, (state_key, write_op)) in row.write_set.iter().enumerate() {
+            let (key_type, address, path, table_handle, table_key) = match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) => {
+                    let path = match access_path.get_path() {
+                        Path::Code(module_id) => module_id.to_string(),
+                        Path::Resource(struct_tag) | Path::ResourceGroup(struct_tag) => {
+                            struct_tag.to_canonical_string()
+                        },
+                    };
+                    (
+                        "access_path",
+                        Some(access_path.address.to_hex_literal()),
+                        Some(path),
+                        None,
+                        None,
+                    )
+                },
+                StateKeyInner::TableItem { handle, key } => (
+                    "table_item",
+                    None,
+                    None,
+                    Some(handle.0.to_hex_literal()),
+                    Some(key.as_slice()),
+                ),
+                StateKeyInner::Raw(_) => ("raw", None, None, None, None),
+            };
+            let op = match write_op {
+                WriteOp::Creation { .. } => "creation",
+                WriteOp::Modification { .. } => "modification",
+                WriteOp::Deletion { .. } => "deletion",
+            };
+
+            self.version.append_value(row.version);
+            self.change_index.append_value(idx as u32);
+            self.state_key_hash
+                .append_value(CryptoHash::hash(state_key).to_hex_literal());
+            self.state_key_bcs.append_value(bcs::to_bytes(state_key)?);
+            self.key_type.append_value(key_type);
+            self.address.append_option(address);
+            self.path.append_option(path);
+            self.table_handle.append_option(table_handle);
+            self.table_key.append_option(table_key);
+            self.op.append_value(op);
+            self.value.append_option(write_op.bytes());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.version.finish()),
+            Arc::new(self.change_index.finish()),
+            Arc::new(self.state_key_hash.finish()),
+            Arc::new(self.state_key_bcs.finish()),
+            Arc::new(self.key_type.finish()),
+            Arc::new(self.address.finish()),
+            Arc::new(self.path.finish()),
+            Arc::new(self.table_handle.finish()),
+            Arc::new(self.table_key.finish()),
+            Arc::new(self.op.finish()),
+            Arc::new(self.value.finish()),
+        ];
+        Ok(RecordBatch::try_new(Table::StateChanges.schema(), columns)?)
+    }
+}
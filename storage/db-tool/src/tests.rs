@@ -46,6 +46,21 @@ fn test_various_cmd_parsing() {
         ".",
     ]);
 
+    run_cmd(&[
+        "aptos-db-tool",
+        "export",
+        "--db-dir",
+        ".",
+        "--output-dir",
+        ".",
+        "--table",
+        "transactions",
+        "--table",
+        "state-changes",
+        "--format",
+        "arrow",
+    ]);
+
     run_cmd(&["aptos-db-tool", "backup", "verify", "--local-fs-dir", "."]);
     run_cmd(&[
         "aptos-db-tool",
@@ -76,7 +91,7 @@ mod dbtool_tests {
     use aptos_backup_cli::{
         coordinators::backup::BackupCompactor,
         metadata,
-        metadata::{cache::MetadataCacheOpt, view::MetadataView},
+        metadata::{cache::MetadataCacheOpt, view::MetadataView, StateSnapshotDiffBackupMeta},
         storage::{local_fs::LocalFs, BackupStorage},
         utils::test_utils::start_local_backup_service,
     };
@@ -109,7 +124,9 @@ mod dbtool_tests {
                 && view1.select_epoch_ending_backups(Version::MAX).unwrap()
                     == view2.select_epoch_ending_backups(Version::MAX).unwrap()
                 && view1.select_state_snapshot(Version::MAX).unwrap()
-                    == view2.select_state_snapshot(Version::MAX).unwrap(),
+                    == view2.select_state_snapshot(Version::MAX).unwrap()
+                && view1.select_state_snapshot_diffs(0, Version::MAX).unwrap()
+                    == view2.select_state_snapshot_diffs(0, Version::MAX).unwrap(),
             "Metadata views are not equal"
         );
     }
@@ -279,6 +296,320 @@ mod dbtool_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
+    #[test]
+    fn test_backup_compaction_with_state_snapshot_diffs() {
+        let db = test_execution_with_storage_impl();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let local_fs = LocalFs::new(backup_dir.path().to_path_buf());
+        let store: Arc<dyn BackupStorage> = Arc::new(local_fs);
+        let (rt, port) = start_local_backup_service(Arc::clone(&db));
+        let server_addr = format!(" http://localhost:{}", port);
+        let backup = |args: &[&str]| {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "backup",
+                "oneoff",
+                "--backup-service-address",
+                server_addr.as_str(),
+            ];
+            cmd.extend(args);
+            cmd.extend(["--local-fs-dir", backup_dir.path().to_str().unwrap()]);
+            rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+                .unwrap();
+        };
+
+        // Backup the state snapshot at the end of epoch 0 and the diffs up to epoch 2
+        backup(&["state-snapshot", "--state-snapshot-epoch", "0"]);
+        backup(&[
+            "state-snapshot-diff",
+            "--state-snapshot-base-epoch",
+            "0",
+            "--state-snapshot-epoch",
+            "1",
+        ]);
+        backup(&[
+            "state-snapshot-diff",
+            "--state-snapshot-base-epoch",
+            "1",
+            "--state-snapshot-epoch",
+            "2",
+        ]);
+
+        // Compact twice, so that the original metadata files expire
+        let metadata_cache_dir = TempPath::new();
+        let metadata_opt = MetadataCacheOpt::new(Some(metadata_cache_dir.path().to_path_buf()));
+        let old_metaview = rt
+            .block_on(metadata::cache::sync_and_load(
+                &metadata_opt,
+                Arc::clone(&store),
+                1,
+            ))
+            .unwrap();
+        let og_list = rt.block_on(store.list_metadata_files()).unwrap();
+        let compactor =
+            BackupCompactor::new(2, 2, 2, metadata_opt.clone(), Arc::clone(&store), 1, 1);
+        rt.block_on(compactor.run()).unwrap();
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        let compactor =
+            BackupCompactor::new(2, 2, 2, metadata_opt.clone(), Arc::clone(&store), 1, 1);
+        rt.block_on(compactor.run()).unwrap();
+        let final_list = rt.block_on(store.list_metadata_files()).unwrap();
+        assert!(!og_list.iter().any(|x| final_list.contains(x)));
+
+        // The diffs are still found after the compaction
+        let new_metaview = rt
+            .block_on(metadata::cache::sync_and_load(
+                &metadata_opt,
+                Arc::clone(&store),
+                1,
+            ))
+            .unwrap();
+        assert_metadata_view_eq(&old_metaview, &new_metaview);
+        let base = new_metaview.select_state_snapshot(0).unwrap().unwrap();
+        let diffs = new_metaview
+            .select_state_snapshot_diffs(base.version, Version::MAX)
+            .unwrap();
+        assert_eq!(diffs.len(), 2);
+
+        // And the state snapshot at epoch 2 can be restored through them
+        let new_db_dir = TempPath::new();
+        let mut restore_args = vec![
+            "aptos-db-tool".to_string(),
+            "restore".to_string(),
+            "oneoff".to_string(),
+            "state-snapshot-diff".to_string(),
+            "--state-base-manifest".to_string(),
+            base.manifest.clone(),
+        ];
+        for diff in &diffs {
+            restore_args.extend(["--state-diff-manifest".to_string(), diff.manifest.clone()]);
+        }
+        restore_args.extend([
+            "--target-db-dir".to_string(),
+            new_db_dir.path().to_str().unwrap().to_string(),
+            "--local-fs-dir".to_string(),
+            backup_dir.path().to_str().unwrap().to_string(),
+        ]);
+        rt.block_on(DBTool::try_parse_from(restore_args).unwrap().run())
+            .unwrap();
+
+        let version = diffs.last().unwrap().version;
+        let (_ledger_db, tree_db, _state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(new_db_dir.path()),
+            RocksdbConfigs::default(),
+            false,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            db.get_state_snapshot_before(version + 1).unwrap(),
+            Some((version, tree_db.get_root_hash(version).unwrap())),
+        );
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    /// Backs up the epoch endings, the state snapshot at the end of epoch 0, the diffs up to
+    /// epoch 2 and all the transactions of `db`, returning the metadata of the diffs.
+    fn backup_with_state_snapshot_diffs(
+        db: &Arc<AptosDB>,
+        rt: &Runtime,
+        port: u16,
+        backup_dir: &Path,
+    ) -> Vec<StateSnapshotDiffBackupMeta> {
+        let server_addr = format!(" http://localhost:{}", port);
+        let backup = |args: &[&str]| {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "backup",
+                "oneoff",
+                "--backup-service-address",
+                server_addr.as_str(),
+            ];
+            cmd.extend(args);
+            cmd.extend(["--local-fs-dir", backup_dir.to_str().unwrap()]);
+            rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+                .unwrap();
+        };
+        let latest_version = db.get_latest_version().unwrap();
+        let end_epoch = db
+            .get_latest_ledger_info()
+            .unwrap()
+            .ledger_info()
+            .next_block_epoch()
+            .to_string();
+        let num_transactions = (latest_version + 1).to_string();
+        backup(&[
+            "epoch-ending",
+            "--start-epoch",
+            "0",
+            "--end-epoch",
+            end_epoch.as_str(),
+        ]);
+        backup(&["state-snapshot", "--state-snapshot-epoch", "0"]);
+        backup(&[
+            "state-snapshot-diff",
+            "--state-snapshot-base-epoch",
+            "0",
+            "--state-snapshot-epoch",
+            "1",
+        ]);
+        backup(&[
+            "state-snapshot-diff",
+            "--state-snapshot-base-epoch",
+            "1",
+            "--state-snapshot-epoch",
+            "2",
+        ]);
+        backup(&[
+            "transaction",
+            "--start-version",
+            "0",
+            "--num_transactions",
+            num_transactions.as_str(),
+        ]);
+
+        let metadata_cache_dir = TempPath::new();
+        let view = rt
+            .block_on(metadata::cache::sync_and_load(
+                &MetadataCacheOpt::new(Some(metadata_cache_dir.path().to_path_buf())),
+                Arc::new(LocalFs::new(backup_dir.to_path_buf())),
+                1,
+            ))
+            .unwrap();
+        let diffs = view.select_state_snapshot_diffs(0, Version::MAX).unwrap();
+        assert_eq!(diffs.len(), 2);
+        diffs
+    }
+
+    /// Restores the latest version of `db` through `bootstrap-db`, letting the planner use all
+    /// the diffs, and checks the result.
+    fn bootstrap_db_through_diffs(
+        db: &Arc<AptosDB>,
+        rt: &Runtime,
+        backup_dir: &Path,
+        new_db_dir: &Path,
+        diffs: &[StateSnapshotDiffBackupMeta],
+    ) {
+        use aptos_db::utils::iterators::PrefixedStateValueIterator;
+        use itertools::zip_eq;
+
+        let latest_version = db.get_latest_version().unwrap();
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "restore",
+                "bootstrap-db",
+                "--target-version",
+                latest_version.to_string().as_str(),
+                // Restoring state chunks is free, so the diffs are always worth it.
+                "--state-chunk-cost-in-txns",
+                "0",
+                "--target-db-dir",
+                new_db_dir.to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.to_str().unwrap(),
+            ])
+            .unwrap()
+            .run(),
+        )
+        .unwrap();
+
+        let (_ledger_db, tree_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(new_db_dir),
+            RocksdbConfigs::default(),
+            false,
+            0,
+        )
+        .unwrap();
+        // The trees of the diffs were restored, rather than replayed to.
+        for diff in diffs {
+            assert_eq!(
+                db.get_state_snapshot_before(diff.version + 1).unwrap(),
+                Some((diff.version, tree_db.get_root_hash(diff.version).unwrap())),
+            );
+        }
+        // And the state at the target version is the same.
+        let new_iter = PrefixedStateValueIterator::new(
+            &state_kv_db,
+            StateKeyPrefix::new(AccessPath, b"".to_vec()),
+            None,
+            latest_version,
+            false,
+        )
+        .unwrap();
+        let old_iter = db
+            .deref()
+            .get_prefixed_state_value_iterator(
+                &StateKeyPrefix::new(AccessPath, b"".to_vec()),
+                None,
+                latest_version,
+            )
+            .unwrap();
+        zip_eq(new_iter, old_iter).for_each(|(new, old)| {
+            assert_eq!(new.unwrap(), old.unwrap());
+        });
+    }
+
+    #[test]
+    fn test_restore_db_through_state_snapshot_diffs() {
+        let db = test_execution_with_storage_impl();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let (rt, port) = start_local_backup_service(Arc::clone(&db));
+
+        let diffs = backup_with_state_snapshot_diffs(&db, &rt, port, backup_dir.path());
+        bootstrap_db_through_diffs(&db, &rt, backup_dir.path(), new_db_dir.path(), &diffs);
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_resume_restore_db_through_state_snapshot_diffs() {
+        let db = test_execution_with_storage_impl();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let (rt, port) = start_local_backup_service(Arc::clone(&db));
+        let diffs = backup_with_state_snapshot_diffs(&db, &rt, port, backup_dir.path());
+
+        // As if a restore was interrupted after the state snapshot and the first diff.
+        let metadata_cache_dir = TempPath::new();
+        let base = rt
+            .block_on(metadata::cache::sync_and_load(
+                &MetadataCacheOpt::new(Some(metadata_cache_dir.path().to_path_buf())),
+                Arc::new(LocalFs::new(backup_dir.path().to_path_buf())),
+                1,
+            ))
+            .unwrap()
+            .select_state_snapshot(0)
+            .unwrap()
+            .unwrap();
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "restore",
+                "oneoff",
+                "state-snapshot-diff",
+                "--state-base-manifest",
+                base.manifest.as_str(),
+                "--state-diff-manifest",
+                diffs[0].manifest.as_str(),
+                "--target-db-dir",
+                new_db_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.path().to_str().unwrap(),
+            ])
+            .unwrap()
+            .run(),
+        )
+        .unwrap();
+
+        // The restore goes on from the state in the DB.
+        bootstrap_db_through_diffs(&db, &rt, backup_dir.path(), new_db_dir.path(), &diffs);
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
     #[cfg(test)]
     fn db_restore_test_setup(
         start: Version,
@@ -623,6 +954,96 @@ mod dbtool_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
+    #[test]
+    fn test_export() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let db_dir = TempPath::new();
+        let output_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let latest_version = db.get_latest_version().unwrap();
+        let num_events: usize = db
+            .get_events_iterator(0, latest_version + 1)
+            .unwrap()
+            .map(|events| events.unwrap().len())
+            .sum();
+
+        let export = |extra_args: &[&str]| {
+            let mut args = vec![
+                "aptos-db-tool",
+                "export",
+                "--db-dir",
+                db_dir.path().to_str().unwrap(),
+                "--output-dir",
+                output_dir.path().to_str().unwrap(),
+                "--versions-per-file",
+                "10",
+            ];
+            args.extend_from_slice(extra_args);
+            DBTool::try_parse_from(args).unwrap().run()
+        };
+        let rt = Runtime::new().unwrap();
+
+        // Export up to the first version of the last range, then resume after the end version
+        // grows, which must replace the partial last range instead of overlapping it.
+        let partial_first = (latest_version - 1) / 10 * 10;
+        rt.block_on(export(&["--end-version", &partial_first.to_string()]))
+            .unwrap();
+        let partial_file = output_dir.path().join("transactions").join(format!(
+            "{:020}-{:020}.parquet",
+            partial_first, partial_first
+        ));
+        assert!(partial_file.exists());
+        let stale_tmp_file = output_dir.path().join("transactions").join(format!(
+            "{:020}-{:020}.tmp",
+            partial_first,
+            partial_first + 9
+        ));
+        fs::write(&stale_tmp_file, b"interrupted").unwrap();
+        rt.block_on(export(&[])).unwrap();
+        assert!(!partial_file.exists());
+        assert!(!stale_tmp_file.exists());
+
+        let num_rows = |table: &str| -> usize {
+            let mut num_rows = 0;
+            for entry in fs::read_dir(output_dir.path().join(table)).unwrap() {
+                let reader =
+                    SerializedFileReader::new(fs::File::open(entry.unwrap().path()).unwrap())
+                        .unwrap();
+                num_rows += reader.metadata().file_metadata().num_rows() as usize;
+            }
+            num_rows
+        };
+        let num_files = fs::read_dir(output_dir.path().join("transactions"))
+            .unwrap()
+            .count();
+        assert_eq!(num_files as u64, latest_version / 10 + 1);
+        assert_eq!(num_rows("transactions") as u64, latest_version + 1);
+        assert_eq!(num_rows("write_sets") as u64, latest_version + 1);
+        assert_eq!(num_rows("events"), num_events);
+
+        // Files already exported are skipped on a rerun.
+        let first_file = output_dir
+            .path()
+            .join("transactions")
+            .join(format!("{:020}-{:020}.parquet", 0, 9));
+        let modified = fs::metadata(&first_file).unwrap().modified().unwrap();
+        rt.block_on(export(&[])).unwrap();
+        assert_eq!(
+            fs::metadata(&first_file).unwrap().modified().unwrap(),
+            modified
+        );
+        assert_eq!(num_rows("transactions") as u64, latest_version + 1);
+
+        // Starting mid-range maps to the same aligned ranges, so nothing is exported twice.
+        rt.block_on(export(&["--start-version", "5"])).unwrap();
+        assert_eq!(
+            fs::metadata(&first_file).unwrap().modified().unwrap(),
+            modified
+        );
+        assert_eq!(num_rows("transactions") as u64, latest_version + 1);
+    }
+
     fn dir_size<P: AsRef<Path>>(path: P) -> u64 {
         let mut size = 0;
 
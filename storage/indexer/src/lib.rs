@@ -8,29 +8,34 @@ pub mod db_v2;
 mod metadata;
 mod schema;
 pub mod table_info_reader;
+mod touched_accounts;
 
 use crate::{
     db::INDEX_DB_NAME,
     metadata::{MetadataKey, MetadataValue},
     schema::{
         column_families, indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
+        transaction_by_touched_account::TransactionByTouchedAccountSchema,
     },
+    touched_accounts::touched_accounts,
 };
 use aptos_config::config::RocksdbConfig;
 use aptos_logger::warn;
 use aptos_rocksdb_options::gen_rocksdb_options;
-use aptos_schemadb::{SchemaBatch, DB};
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_storage_interface::{
-    db_ensure, db_other_bail, state_view::DbStateViewAtVersion, AptosDbError, DbReader, Result,
+    db_ensure, db_other_bail, state_view::DbStateViewAtVersion, AptosDbError, DbReader, Order,
+    Result,
 };
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
+    contract_event::ContractEvent,
     state_store::{
         state_key::{StateKey, StateKeyInner},
         table::{TableHandle, TableInfo},
     },
-    transaction::{AtomicVersion, Version},
+    transaction::{AtomicVersion, Transaction, Version},
     write_set::{WriteOp, WriteSet},
 };
 use aptos_vm::data_cache::AsMoveResolver;
@@ -51,29 +56,45 @@ use std::{
 pub struct Indexer {
     db: DB,
     next_version: AtomicVersion,
+    account_transactions_next_version: Option<AtomicVersion>,
 }
 
 impl Indexer {
     pub fn open(
         db_root_path: impl AsRef<std::path::Path>,
         rocksdb_config: RocksdbConfig,
+        enable_account_transactions_index: bool,
+        in_memory: bool,
     ) -> Result<Self> {
         let db_path = db_root_path.as_ref().join(INDEX_DB_NAME);
 
-        let db = DB::open(
-            db_path,
-            "index_db",
-            column_families(),
-            &gen_rocksdb_options(&rocksdb_config, false),
-        )?;
+        let db = if in_memory {
+            DB::open_in_memory(db_path, "index_db", column_families(), false)?
+        } else {
+            DB::open(
+                db_path,
+                "index_db",
+                column_families(),
+                &gen_rocksdb_options(&rocksdb_config, false),
+            )?
+        };
 
         let next_version = db
             .get::<IndexerMetadataSchema>(&MetadataKey::LatestVersion)?
             .map_or(0, |v| v.expect_version());
+        let account_transactions_next_version = if enable_account_transactions_index {
+            let next_version = db
+                .get::<IndexerMetadataSchema>(&MetadataKey::AccountTransactionsLatestVersion)?
+                .map_or(0, |v| v.expect_version() + 1);
+            Some(AtomicVersion::new(next_version))
+        } else {
+            None
+        };
 
         Ok(Self {
             db,
             next_version: AtomicVersion::new(next_version),
+            account_transactions_next_version,
         })
     }
 
@@ -152,6 +173,104 @@ impl Indexer {
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    /// Indexes the given transactions by the accounts they touch. Transactions below the next
+    /// version of the index are skipped, while gaps are tolerated so that the index can start
+    /// after the pruned part of the ledger.
+    pub fn index_account_transactions<'a>(
+        &self,
+        first_version: Version,
+        txns: impl IntoIterator<Item = (&'a Transaction, &'a [ContractEvent], &'a WriteSet)>,
+    ) -> Result<()> {
+        let next_version = match self.next_account_transactions_version() {
+            Some(next_version) => next_version,
+            None => return Ok(()),
+        };
+
+        let mut batch = SchemaBatch::new();
+        let mut end_version = next_version;
+        for (version, (txn, events, write_set)) in (first_version..).zip(txns) {
+            if version < next_version {
+                continue;
+            }
+            for address in touched_accounts(txn, events, write_set) {
+                batch.put::<TransactionByTouchedAccountSchema>(&(address, version), &())?;
+            }
+            end_version = version + 1;
+        }
+        if end_version == next_version {
+            return Ok(());
+        }
+
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataKey::AccountTransactionsLatestVersion,
+            &MetadataValue::Version(end_version - 1),
+        )?;
+        self.db.write_schemas(batch)?;
+        if let Some(next_version) = &self.account_transactions_next_version {
+            next_version.store(end_version, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the next version to be indexed by the account transactions index, or `None` if
+    /// the index is not enabled.
+    pub fn next_account_transactions_version(&self) -> Option<Version> {
+        self.account_transactions_next_version
+            .as_ref()
+            .map(|v| v.load(Ordering::Relaxed))
+    }
+
+    /// Returns the versions of up to `limit` transactions touching `address`, starting from
+    /// `start_version` in the given order and bounded by `min_version` and `ledger_version`.
+    pub fn get_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        min_version: Version,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        db_ensure!(
+            self.account_transactions_next_version.is_some(),
+            "Account transactions index is not enabled."
+        );
+        if (order == Order::Ascending && start_version > ledger_version)
+            || (order == Order::Descending && start_version < min_version)
+        {
+            return Ok(vec![]);
+        }
+
+        // Seek within the bounds, so that the limit only counts readable transactions
+        let iter = match order {
+            Order::Ascending => {
+                let mut iter = self
+                    .db
+                    .iter::<TransactionByTouchedAccountSchema>(ReadOptions::default())?;
+                iter.seek(&(address, start_version.max(min_version)))?;
+                iter
+            },
+            Order::Descending => {
+                let mut iter = self
+                    .db
+                    .rev_iter::<TransactionByTouchedAccountSchema>(ReadOptions::default())?;
+                iter.seek_for_prev(&(address, start_version.min(ledger_version)))?;
+                iter
+            },
+        };
+
+        let mut versions = Vec::new();
+        for item in iter.take(limit as usize) {
+            let ((touched_address, version), ()) = item?;
+            if touched_address != address || version > ledger_version || version < min_version {
+                break;
+            }
+            versions.push(version);
+        }
+        Ok(versions)
+    }
 }
 
 struct TableInfoParser<'a, R> {
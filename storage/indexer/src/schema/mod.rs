@@ -8,17 +8,32 @@
 
 pub(crate) mod indexer_metadata;
 pub(crate) mod table_info;
+pub(crate) mod transaction_by_touched_account;
 
+use anyhow::{ensure, Result};
 use aptos_schemadb::ColumnFamilyName;
 
 pub const DEFAULT_COLUMN_FAMILY_NAME: ColumnFamilyName = "default";
 pub const INDEXER_METADATA_CF_NAME: ColumnFamilyName = "indexer_metadata";
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
+pub const TRANSACTION_BY_TOUCHED_ACCOUNT_CF_NAME: ColumnFamilyName =
+    "transaction_by_touched_account";
 
 pub fn column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         INDEXER_METADATA_CF_NAME,
         TABLE_INFO_CF_NAME,
+        TRANSACTION_BY_TOUCHED_ACCOUNT_CF_NAME,
     ]
 }
+
+fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
+    ensure!(
+        data.len() == len,
+        "Unexpected data len {}, expected {}.",
+        data.len(),
+        len,
+    );
+    Ok(())
+}
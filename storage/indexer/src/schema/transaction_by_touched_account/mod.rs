@@ -0,0 +1,65 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of the transactions touching an
+//! account, i.e. the transactions in which the account is a signer, owns the key of an emitted
+//! event or owns a resource in the write set. With the version one can resort to the ledger DB
+//! for the transaction content.
+//!
+//! ```text
+//! |<-------key------->|<-value->|
+//! | address | txn_ver |  empty  |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, TRANSACTION_BY_TOUCHED_ACCOUNT_CF_NAME};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    TransactionByTouchedAccountSchema,
+    Key,
+    (),
+    TRANSACTION_BY_TOUCHED_ACCOUNT_CF_NAME
+);
+
+type Key = (AccountAddress, Version);
+
+impl KeyCodec<TransactionByTouchedAccountSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref account_address, version) = *self;
+
+        let mut encoded = account_address.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version))
+    }
+}
+
+impl ValueCodec<TransactionByTouchedAccountSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,211 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Finds the accounts touched by a transaction, for the account transactions index.
+//!
+//! Besides the signers of a transaction, an account is touched when the transaction emits an
+//! event under one of its event keys, emits a module event about it, or writes one of its
+//! resources. Fungible asset stores live at object addresses, so writes to a store and the
+//! events of a store are also attributed to the owner of the store object.
+
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    account_config::CORE_CODE_ADDRESS,
+    contract_event::{ContractEvent, ContractEventV2},
+    event::EventHandle,
+    state_store::state_key::StateKeyInner,
+    transaction::{Transaction, TransactionPayload},
+    write_set::WriteSet,
+};
+use bytes::Bytes;
+use move_core_types::{
+    ident_str,
+    identifier::IdentStr,
+    language_storage::{StructTag, TypeTag},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+type ResourceGroup = BTreeMap<StructTag, Bytes>;
+
+/// Returns the accounts touched by a transaction: the signers of a user transaction, the
+/// creators of the keys of the emitted events, the accounts referred to by the emitted module
+/// events of the framework and the owners of the resources and modules in the write set.
+pub(crate) fn touched_accounts(
+    txn: &Transaction,
+    events: &[ContractEvent],
+    write_set: &WriteSet,
+) -> BTreeSet<AccountAddress> {
+    let mut accounts = BTreeSet::new();
+    if let Some(signed_txn) = txn.try_as_signed_user_txn() {
+        accounts.insert(signed_txn.sender());
+        let authenticator = signed_txn.authenticator_ref();
+        accounts.extend(authenticator.secondary_signer_addresses());
+        accounts.extend(authenticator.fee_payer_address());
+        if let TransactionPayload::Multisig(multisig) = signed_txn.payload() {
+            accounts.insert(multisig.multisig_address);
+        }
+    }
+
+    let store_owners = fungible_store_owners(write_set);
+    for (state_key, _write_op) in write_set.iter() {
+        if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+            accounts.insert(access_path.address);
+            accounts.extend(store_owners.get(&access_path.address));
+        }
+    }
+    for event in events {
+        match event {
+            ContractEvent::V1(event) => {
+                accounts.insert(event.key().get_creator_address());
+            },
+            ContractEvent::V2(event) => {
+                accounts.extend(module_event_accounts(event, &store_owners));
+            },
+        }
+    }
+    accounts
+}
+
+/// Returns the owners of the fungible asset stores written by the transaction, by the address
+/// of their store object. Only the stores whose object is written can be resolved, which is
+/// always the case for the stores whose balance changes.
+fn fungible_store_owners(write_set: &WriteSet) -> HashMap<AccountAddress, AccountAddress> {
+    let mut owners = HashMap::new();
+    for (state_key, write_op) in write_set.iter() {
+        let (StateKeyInner::AccessPath(access_path), Some(bytes)) =
+            (state_key.inner(), write_op.bytes())
+        else {
+            continue;
+        };
+        let Ok(Path::ResourceGroup(group_tag)) = Path::try_from(&access_path.path) else {
+            continue;
+        };
+        if !is_framework_struct(&group_tag, ident_str!("object"), ident_str!("ObjectGroup")) {
+            continue;
+        }
+        let Ok(group) = bcs::from_bytes::<ResourceGroup>(bytes) else {
+            continue;
+        };
+        if !group.keys().any(|tag| {
+            is_framework_struct(
+                tag,
+                ident_str!("fungible_asset"),
+                ident_str!("FungibleStore"),
+            )
+        }) {
+            continue;
+        }
+        let object_core = group.iter().find_map(|(tag, bytes)| {
+            is_framework_struct(tag, ident_str!("object"), ident_str!("ObjectCore"))
+                .then(|| bcs::from_bytes::<ObjectCore>(bytes).ok())
+                .flatten()
+        });
+        if let Some(object_core) = object_core {
+            owners.insert(access_path.address, object_core.owner);
+        }
+    }
+    owners
+}
+
+/// Returns the accounts a module event of the framework is about. Module events have no event
+/// key, so the accounts are read from the event data of the events that are known to refer to
+/// accounts or fungible asset stores.
+fn module_event_accounts(
+    event: &ContractEventV2,
+    store_owners: &HashMap<AccountAddress, AccountAddress>,
+) -> Vec<AccountAddress> {
+    let TypeTag::Struct(struct_tag) = event.type_tag() else {
+        return vec![];
+    };
+    if struct_tag.address != CORE_CODE_ADDRESS {
+        return vec![];
+    }
+    let data = event.event_data();
+    let accounts = match (struct_tag.module.as_str(), struct_tag.name.as_str()) {
+        ("coin", "Deposit") | ("coin", "Withdraw") => bcs::from_bytes::<CoinEvent>(data)
+            .map(|event| vec![event.account])
+            .ok(),
+        ("account", "KeyRotation") => bcs::from_bytes::<KeyRotation>(data)
+            .map(|event| vec![event.account])
+            .ok(),
+        ("object", "TransferEvent") => bcs::from_bytes::<TransferEvent>(data)
+            .map(|event| vec![event.object, event.from, event.to])
+            .ok(),
+        ("fungible_asset", "Deposit") | ("fungible_asset", "Withdraw") => {
+            bcs::from_bytes::<FungibleStoreEvent>(data)
+                .map(|event| store_and_owner(event.store, store_owners))
+                .ok()
+        },
+        ("fungible_asset", "Frozen") => bcs::from_bytes::<Frozen>(data)
+            .map(|event| store_and_owner(event.store, store_owners))
+            .ok(),
+        _ => None,
+    };
+    accounts.unwrap_or_default()
+}
+
+fn store_and_owner(
+    store: AccountAddress,
+    store_owners: &HashMap<AccountAddress, AccountAddress>,
+) -> Vec<AccountAddress> {
+    std::iter::once(store)
+        .chain(store_owners.get(&store).copied())
+        .collect()
+}
+
+fn is_framework_struct(struct_tag: &StructTag, module: &IdentStr, name: &IdentStr) -> bool {
+    struct_tag.address == CORE_CODE_ADDRESS
+        && struct_tag.module.as_ident_str() == module
+        && struct_tag.name.as_ident_str() == name
+}
+
+/// `0x1::object::ObjectCore`
+#[derive(Debug, Serialize, Deserialize)]
+struct ObjectCore {
+    guid_creation_num: u64,
+    owner: AccountAddress,
+    allow_ungated_transfer: bool,
+    transfer_events: EventHandle,
+}
+
+/// `0x1::coin::Deposit` and `0x1::coin::Withdraw`
+#[derive(Debug, Serialize, Deserialize)]
+struct CoinEvent {
+    account: AccountAddress,
+    amount: u64,
+}
+
+/// `0x1::account::KeyRotation`
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyRotation {
+    account: AccountAddress,
+    old_authentication_key: Vec<u8>,
+    new_authentication_key: Vec<u8>,
+}
+
+/// `0x1::object::TransferEvent`
+#[derive(Debug, Serialize, Deserialize)]
+struct TransferEvent {
+    object: AccountAddress,
+    from: AccountAddress,
+    to: AccountAddress,
+}
+
+/// `0x1::fungible_asset::Deposit` and `0x1::fungible_asset::Withdraw`
+#[derive(Debug, Serialize, Deserialize)]
+struct FungibleStoreEvent {
+    store: AccountAddress,
+    amount: u64,
+}
+
+/// `0x1::fungible_asset::Frozen`
+#[derive(Debug, Serialize, Deserialize)]
+struct Frozen {
+    store: AccountAddress,
+    frozen: bool,
+}
+
+#[cfg(test)]
+mod test;
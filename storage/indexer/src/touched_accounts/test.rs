@@ -0,0 +1,143 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_crypto::HashValue;
+use aptos_types::{
+    access_path::AccessPath,
+    event::EventKey,
+    state_store::state_key::StateKey,
+    write_set::{WriteOp, WriteSetMut},
+};
+
+fn framework_struct(module: &str, name: &str) -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: module.parse().unwrap(),
+        name: name.parse().unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn fungible_store_write_set(store: AccountAddress, owner: AccountAddress) -> WriteSet {
+    let object_core = ObjectCore {
+        guid_creation_num: 0,
+        owner,
+        allow_ungated_transfer: false,
+        transfer_events: EventHandle::new(EventKey::new(0, store), 0),
+    };
+    let group: ResourceGroup = [
+        (
+            framework_struct("object", "ObjectCore"),
+            bcs::to_bytes(&object_core).unwrap().into(),
+        ),
+        (
+            framework_struct("fungible_asset", "FungibleStore"),
+            Bytes::from_static(b"store"),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let state_key = StateKey::access_path(AccessPath::resource_group_access_path(
+        store,
+        framework_struct("object", "ObjectGroup"),
+    ));
+    WriteSetMut::new(vec![(
+        state_key,
+        WriteOp::legacy_modification(bcs::to_bytes(&group).unwrap().into()),
+    )])
+    .freeze()
+    .unwrap()
+}
+
+fn module_event<T: Serialize>(module: &str, name: &str, data: &T) -> ContractEvent {
+    ContractEvent::new_v2(
+        TypeTag::Struct(Box::new(framework_struct(module, name))),
+        bcs::to_bytes(data).unwrap(),
+    )
+}
+
+fn state_checkpoint() -> Transaction {
+    Transaction::StateCheckpoint(HashValue::zero())
+}
+
+#[test]
+fn test_module_events_touch_accounts() {
+    let account = AccountAddress::random();
+    let from = AccountAddress::random();
+    let to = AccountAddress::random();
+    let object = AccountAddress::random();
+    let events = vec![
+        module_event("coin", "Deposit", &CoinEvent { account, amount: 1 }),
+        module_event("object", "TransferEvent", &TransferEvent {
+            object,
+            from,
+            to,
+        }),
+        // Events of other modules aren't known to refer to accounts
+        ContractEvent::new_v2(
+            TypeTag::Struct(Box::new(StructTag {
+                address: AccountAddress::random(),
+                ..framework_struct("coin", "Deposit")
+            })),
+            bcs::to_bytes(&CoinEvent {
+                account: AccountAddress::random(),
+                amount: 1,
+            })
+            .unwrap(),
+        ),
+    ];
+
+    let accounts = touched_accounts(&state_checkpoint(), &events, &WriteSet::default());
+    assert_eq!(accounts, BTreeSet::from([account, from, to, object]));
+}
+
+#[test]
+fn test_fungible_stores_touch_their_owner() {
+    let store = AccountAddress::random();
+    let owner = AccountAddress::random();
+    let write_set = fungible_store_write_set(store, owner);
+
+    // Writing the store touches the store object and its owner
+    let accounts = touched_accounts(&state_checkpoint(), &[], &write_set);
+    assert_eq!(accounts, BTreeSet::from([store, owner]));
+
+    // So do the events of the store
+    let events = vec![module_event(
+        "fungible_asset",
+        "Deposit",
+        &FungibleStoreEvent { store, amount: 1 },
+    )];
+    let accounts = touched_accounts(&state_checkpoint(), &events, &write_set);
+    assert_eq!(accounts, BTreeSet::from([store, owner]));
+}
+
+#[test]
+fn test_objects_without_fungible_store_are_not_attributed() {
+    let object = AccountAddress::random();
+    let owner = AccountAddress::random();
+    let object_core = ObjectCore {
+        guid_creation_num: 0,
+        owner,
+        allow_ungated_transfer: true,
+        transfer_events: EventHandle::new(EventKey::new(0, object), 0),
+    };
+    let group: ResourceGroup = [(
+        framework_struct("object", "ObjectCore"),
+        bcs::to_bytes(&object_core).unwrap().into(),
+    )]
+    .into_iter()
+    .collect();
+    let write_set = WriteSetMut::new(vec![(
+        StateKey::access_path(AccessPath::resource_group_access_path(
+            object,
+            framework_struct("object", "ObjectGroup"),
+        )),
+        WriteOp::legacy_modification(bcs::to_bytes(&group).unwrap().into()),
+    )])
+    .freeze()
+    .unwrap();
+
+    let accounts = touched_accounts(&state_checkpoint(), &[], &write_set);
+    assert_eq!(accounts, BTreeSet::from([object]));
+}
@@ -20,3 +20,13 @@ pub fn gen_rocksdb_options(config: &RocksdbConfig, readonly: bool) -> Options {
 
     db_opts
 }
+
+/// Options for opening a DB as a secondary instance following a primary one.
+pub fn gen_secondary_rocksdb_options(config: &RocksdbConfig) -> Options {
+    let mut db_opts = gen_rocksdb_options(config, /*readonly=*/ true);
+    // Recommended for secondary instances, which would otherwise have to reopen table files
+    // removed by the primary's compactions.
+    db_opts.set_max_open_files(-1);
+
+    db_opts
+}
@@ -0,0 +1,385 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backend::{KvBackend, KvIterator},
+    ColumnFamilyName, ReadOptions, WriteOp,
+};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_storage_interface::{db_ensure, AptosDbError, Result as DbResult};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound::{self, Excluded, Included, Unbounded},
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+};
+
+type ColumnFamily = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The in-memory DBs of the process by path, so that opening a path or a checkpoint while
+/// another handle to it is alive finds the data written there, like with RocksDB.
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| Mutex::new(Registry::default()));
+
+#[derive(Default)]
+struct Registry {
+    dbs: HashMap<PathBuf, Weak<InMemoryDb>>,
+    /// Number of live [`RetainedInMemoryDbs`].
+    num_retainers: usize,
+    /// The DBs kept alive by the retainers.
+    retained: Vec<Arc<InMemoryDb>>,
+}
+
+impl Registry {
+    fn contains(&self, path: &Path) -> bool {
+        self.dbs.get(path).map_or(false, |db| db.strong_count() > 0)
+    }
+
+    fn insert(&mut self, db: Arc<InMemoryDb>) {
+        self.dbs.insert(db.path.clone(), Arc::downgrade(&db));
+        if self.num_retainers > 0 {
+            self.retained.push(db);
+        }
+    }
+}
+
+/// The data of an in-memory DB, shared by all the handles opened at its path. It's dropped,
+/// and the path evicted from the registry, with the last handle.
+#[derive(Debug)]
+struct InMemoryDb {
+    path: PathBuf,
+    column_families: RwLock<HashMap<String, ColumnFamily>>,
+    /// The checkpoints created from the DB, which live at least as long as the DB does.
+    checkpoints: Mutex<Vec<Arc<InMemoryDb>>>,
+}
+
+impl InMemoryDb {
+    fn new(path: &Path, column_families: HashMap<String, ColumnFamily>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            column_families: RwLock::new(column_families),
+            checkpoints: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Drop for InMemoryDb {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock();
+        // The path may have been opened again since the last handle was released.
+        if !registry.contains(&self.path) {
+            registry.dbs.remove(&self.path);
+        }
+    }
+}
+
+/// Keeps all the in-memory DBs of the process alive, including the ones opened after it was
+/// created, until it's dropped. For processes dropping their DBs and opening them again, e.g.
+/// to bootstrap a DB and then create a checkpoint of it.
+#[must_use]
+pub struct RetainedInMemoryDbs {
+    _private: (),
+}
+
+impl Drop for RetainedInMemoryDbs {
+    fn drop(&mut self) {
+        let released = {
+            let mut registry = REGISTRY.lock();
+            registry.num_retainers -= 1;
+            if registry.num_retainers == 0 {
+                std::mem::take(&mut registry.retained)
+            } else {
+                Vec::new()
+            }
+        };
+        // Dropping the last handle of a DB locks the registry.
+        drop(released);
+    }
+}
+
+/// A [`KvBackend`] keeping each column family in a [`BTreeMap`].
+///
+/// Iterators are not snapshots: they see the writes committed after they were created. They
+/// honor the iterate bounds of the [`ReadOptions`], but not `prefix_same_as_start`, as there
+/// are no prefix extractors.
+#[derive(Debug)]
+pub struct InMemoryBackend {
+    db: Arc<InMemoryDb>,
+    readonly: bool,
+}
+
+impl InMemoryBackend {
+    /// Opens the in-memory DB at `path`, creating it unless `readonly`, and creating the missing
+    /// column families.
+    pub fn open(
+        path: &Path,
+        column_families: &[ColumnFamilyName],
+        readonly: bool,
+    ) -> DbResult<Self> {
+        let db = {
+            let mut registry = REGISTRY.lock();
+            match registry.dbs.get(path).and_then(Weak::upgrade) {
+                Some(db) => db,
+                None => {
+                    db_ensure!(!readonly, "No in-memory DB at {:?}.", path);
+                    let db = Arc::new(InMemoryDb::new(path, HashMap::new()));
+                    registry.insert(Arc::clone(&db));
+                    db
+                },
+            }
+        };
+        {
+            let mut cfs = db.column_families.write();
+            for cf_name in column_families {
+                cfs.entry(cf_name.to_string()).or_default();
+            }
+        }
+
+        Ok(Self { db, readonly })
+    }
+
+    /// Keeps the in-memory DBs alive while the returned value is, instead of dropping them with
+    /// their last handle.
+    pub fn retain_dbs() -> RetainedInMemoryDbs {
+        let mut registry = REGISTRY.lock();
+        if registry.num_retainers == 0 {
+            registry.retained = registry.dbs.values().filter_map(Weak::upgrade).collect();
+        }
+        registry.num_retainers += 1;
+        RetainedInMemoryDbs { _private: () }
+    }
+
+    fn ensure_cf_exists(cfs: &HashMap<String, ColumnFamily>, cf_name: &str) -> DbResult<()> {
+        db_ensure!(
+            cfs.contains_key(cf_name),
+            "Column family not found in in-memory DB: {}",
+            cf_name
+        );
+        Ok(())
+    }
+}
+
+impl KvBackend for InMemoryBackend {
+    fn get(&self, cf_name: &str, key: &[u8]) -> DbResult<Option<Vec<u8>>> {
+        let cfs = self.db.column_families.read();
+        Self::ensure_cf_exists(&cfs, cf_name)?;
+        Ok(cfs[cf_name].get(key).cloned())
+    }
+
+    fn raw_iter(&self, cf_name: &str, opts: ReadOptions) -> DbResult<Box<dyn KvIterator + '_>> {
+        Self::ensure_cf_exists(&self.db.column_families.read(), cf_name)?;
+        Ok(Box::new(InMemoryIterator {
+            column_families: &self.db.column_families,
+            cf_name: cf_name.to_string(),
+            opts,
+            current: None,
+        }))
+    }
+
+    fn write_batch(&self, rows: &HashMap<ColumnFamilyName, Vec<WriteOp>>) -> DbResult<usize> {
+        db_ensure!(!self.readonly, "Cannot write to a read-only in-memory DB.");
+
+        let mut cfs = self.db.column_families.write();
+        // Check all the column families first, so that the batch is written entirely or not at
+        // all.
+        for cf_name in rows.keys() {
+            Self::ensure_cf_exists(&cfs, cf_name)?;
+        }
+        let mut size = 0;
+        for (cf_name, rows) in rows.iter() {
+            let cf = cfs
+                .get_mut(*cf_name)
+                .expect("Column families have been checked.");
+            for write_op in rows {
+                match write_op {
+                    WriteOp::Value { key, value } => {
+                        size += key.len() + value.len();
+                        cf.insert(key.clone(), value.clone());
+                    },
+                    WriteOp::Deletion { key } => {
+                        size += key.len();
+                        cf.remove(key);
+                    },
+                }
+            }
+        }
+        Ok(size)
+    }
+
+    fn flush_cf(&self, cf_name: &str) -> DbResult<()> {
+        Self::ensure_cf_exists(&self.db.column_families.read(), cf_name)
+    }
+
+    fn get_property(&self, cf_name: &str, property_name: &str) -> DbResult<Option<u64>> {
+        let cfs = self.db.column_families.read();
+        Self::ensure_cf_exists(&cfs, cf_name)?;
+        let cf = &cfs[cf_name];
+        // Everything is in the "active memtable", and there are no files, compactions etc.
+        Ok(match property_name {
+            "rocksdb.estimate-num-keys" | "rocksdb.num-entries-active-mem-table" => {
+                Some(cf.len() as u64)
+            },
+            "rocksdb.estimate-live-data-size"
+            | "rocksdb.cur-size-active-mem-table"
+            | "rocksdb.cur-size-all-mem-tables"
+            | "rocksdb.size-all-mem-tables" => Some(
+                cf.iter()
+                    .map(|(key, value)| (key.len() + value.len()) as u64)
+                    .sum(),
+            ),
+            _ if property_name.starts_with("rocksdb.") => Some(0),
+            _ => None,
+        })
+    }
+
+    fn create_checkpoint(&self, path: &Path) -> DbResult<()> {
+        let checkpoint = {
+            let mut registry = REGISTRY.lock();
+            db_ensure!(
+                !registry.contains(path),
+                "Checkpoint path {:?} already exists.",
+                path
+            );
+            let checkpoint = Arc::new(InMemoryDb::new(
+                path,
+                self.db.column_families.read().clone(),
+            ));
+            registry.insert(Arc::clone(&checkpoint));
+            checkpoint
+        };
+        self.db.checkpoints.lock().push(checkpoint);
+        Ok(())
+    }
+
+    fn try_catch_up_with_primary(&self) -> DbResult<()> {
+        // Every instance opened at the same path shares the data, so there is nothing to catch
+        // up with.
+        Ok(())
+    }
+}
+
+/// Iterates by looking up the key following the current one on each step, which keeps it valid
+/// across writes without holding the lock in between.
+struct InMemoryIterator<'a> {
+    column_families: &'a RwLock<HashMap<String, ColumnFamily>>,
+    cf_name: String,
+    opts: ReadOptions,
+    current: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<'a> InMemoryIterator<'a> {
+    /// Returns the first key, or the last one if `last`, in the range within the iterate bounds.
+    fn find(&self, from: Bound<&[u8]>, to: Bound<&[u8]>, last: bool) -> Option<(Vec<u8>, Vec<u8>)> {
+        let from = max_lower_bound(
+            from,
+            self.opts.iterate_lower_bound().map_or(Unbounded, Included),
+        );
+        let to = min_upper_bound(
+            to,
+            self.opts.iterate_upper_bound().map_or(Unbounded, Excluded),
+        );
+        if is_empty_range(from, to) {
+            return None;
+        }
+
+        let cfs = self.column_families.read();
+        let mut range = cfs.get(&self.cf_name)?.range::<[u8], _>((from, to));
+        let entry = if last {
+            range.next_back()
+        } else {
+            range.next()
+        };
+        entry.map(|(key, value)| (key.clone(), value.clone()))
+    }
+
+    fn current_key(&self) -> &[u8] {
+        &self
+            .current
+            .as_ref()
+            .expect("Iterator must be positioned.")
+            .0
+    }
+}
+
+impl<'a> KvIterator for InMemoryIterator<'a> {
+    fn seek_to_first(&mut self) {
+        self.current = self.find(Unbounded, Unbounded, false);
+    }
+
+    fn seek_to_last(&mut self) {
+        self.current = self.find(Unbounded, Unbounded, true);
+    }
+
+    fn seek(&mut self, key: &[u8]) {
+        self.current = self.find(Included(key), Unbounded, false);
+    }
+
+    fn seek_for_prev(&mut self, key: &[u8]) {
+        self.current = self.find(Unbounded, Included(key), true);
+    }
+
+    fn next(&mut self) {
+        self.current = self.find(Excluded(self.current_key()), Unbounded, false);
+    }
+
+    fn prev(&mut self) {
+        self.current = self.find(Unbounded, Excluded(self.current_key()), true);
+    }
+
+    fn valid(&self) -> bool {
+        self.current.is_some()
+    }
+
+    fn key(&self) -> Option<&[u8]> {
+        self.current.as_ref().map(|(key, _)| key.as_slice())
+    }
+
+    fn value(&self) -> Option<&[u8]> {
+        self.current.as_ref().map(|(_, value)| value.as_slice())
+    }
+
+    fn status(&self) -> DbResult<()> {
+        Ok(())
+    }
+}
+
+fn max_lower_bound<'k>(a: Bound<&'k [u8]>, b: Bound<&'k [u8]>) -> Bound<&'k [u8]> {
+    match (a, b) {
+        (Unbounded, bound) | (bound, Unbounded) => bound,
+        (Included(a), Included(b)) => Included(a.max(b)),
+        (Excluded(a), Excluded(b)) => Excluded(a.max(b)),
+        (Included(included), Excluded(excluded)) | (Excluded(excluded), Included(included)) => {
+            if included > excluded {
+                Included(included)
+            } else {
+                Excluded(excluded)
+            }
+        },
+    }
+}
+
+fn min_upper_bound<'k>(a: Bound<&'k [u8]>, b: Bound<&'k [u8]>) -> Bound<&'k [u8]> {
+    match (a, b) {
+        (Unbounded, bound) | (bound, Unbounded) => bound,
+        (Included(a), Included(b)) => Included(a.min(b)),
+        (Excluded(a), Excluded(b)) => Excluded(a.min(b)),
+        (Included(included), Excluded(excluded)) | (Excluded(excluded), Included(included)) => {
+            if included < excluded {
+                Included(included)
+            } else {
+                Excluded(excluded)
+            }
+        },
+    }
+}
+
+/// Whether no key is within the bounds, in which case [`BTreeMap::range`] may panic.
+fn is_empty_range(from: Bound<&[u8]>, to: Bound<&[u8]>) -> bool {
+    match (from, to) {
+        (Included(from), Included(to)) => from > to,
+        (Included(from), Excluded(to))
+        | (Excluded(from), Included(to))
+        | (Excluded(from), Excluded(to)) => from >= to,
+        (Unbounded, _) | (_, Unbounded) => false,
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Key-value engines a [`DB`](crate::DB) can store its column families in. [`rocksdb::DB`] is
+//! the one used by nodes, [`InMemoryBackend`] keeps everything in memory for tests and
+//! benchmarks.
+
+mod in_memory;
+mod rocks;
+
+use crate::{ColumnFamilyName, ReadOptions, WriteOp};
+use aptos_storage_interface::Result as DbResult;
+pub use in_memory::{InMemoryBackend, RetainedInMemoryDbs};
+use std::{collections::HashMap, fmt::Debug, path::Path};
+
+/// An ordered key-value store with column families, on top of which [`DB`](crate::DB) encodes
+/// and decodes schemas.
+pub trait KvBackend: Debug + Send + Sync {
+    /// Reads the value under `key` in the column family.
+    fn get(&self, cf_name: &str, key: &[u8]) -> DbResult<Option<Vec<u8>>>;
+
+    /// Returns an iterator over the column family. It's not positioned until one of the seek
+    /// methods is called.
+    fn raw_iter(&self, cf_name: &str, opts: ReadOptions) -> DbResult<Box<dyn KvIterator + '_>>;
+
+    /// Applies all the writes atomically, in order within each column family, and returns the
+    /// size of the written batch in bytes.
+    fn write_batch(&self, rows: &HashMap<ColumnFamilyName, Vec<WriteOp>>) -> DbResult<usize>;
+
+    /// Flushes memtable data of the column family, if the backend has any.
+    fn flush_cf(&self, cf_name: &str) -> DbResult<()>;
+
+    /// Returns the value of an integer property of the column family, e.g.
+    /// `rocksdb.estimate-num-keys`, or `None` if the backend doesn't know the property.
+    fn get_property(&self, cf_name: &str, property_name: &str) -> DbResult<Option<u64>>;
+
+    /// Creates a consistent copy of the whole DB at `path`, which can be opened as a DB with the
+    /// same backend.
+    fn create_checkpoint(&self, path: &Path) -> DbResult<()>;
+
+    /// Makes what the primary instance wrote so far visible, if this is opened as a secondary
+    /// instance of it.
+    fn try_catch_up_with_primary(&self) -> DbResult<()>;
+}
+
+/// A raw iterator over the keys of a column family, in the byte-wise order of the keys. Mirrors
+/// [`rocksdb::DBRawIterator`].
+pub trait KvIterator {
+    /// Positions at the first key.
+    fn seek_to_first(&mut self);
+
+    /// Positions at the last key.
+    fn seek_to_last(&mut self);
+
+    /// Positions at the first key equal to or greater than `key`.
+    fn seek(&mut self, key: &[u8]);
+
+    /// Positions at the last key equal to or less than `key`.
+    fn seek_for_prev(&mut self, key: &[u8]);
+
+    /// Moves to the next key. Must only be called while positioned.
+    fn next(&mut self);
+
+    /// Moves to the previous key. Must only be called while positioned.
+    fn prev(&mut self);
+
+    /// Returns whether the iterator is positioned at a key.
+    fn valid(&self) -> bool;
+
+    /// Returns the key the iterator is positioned at.
+    fn key(&self) -> Option<&[u8]>;
+
+    /// Returns the value the iterator is positioned at.
+    fn value(&self) -> Option<&[u8]>;
+
+    /// Returns the error encountered while iterating, if any. Only meaningful when the iterator
+    /// is not valid.
+    fn status(&self) -> DbResult<()>;
+}
@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backend::{KvBackend, KvIterator},
+    ColumnFamilyName, ReadOptions, WriteOp,
+};
+use anyhow::format_err;
+use aptos_storage_interface::Result as DbResult;
+use rocksdb::DBRawIterator;
+use std::{collections::HashMap, path::Path};
+
+impl KvBackend for rocksdb::DB {
+    fn get(&self, cf_name: &str, key: &[u8]) -> DbResult<Option<Vec<u8>>> {
+        Ok(self.get_cf(get_cf_handle(self, cf_name)?, key)?)
+    }
+
+    fn raw_iter(&self, cf_name: &str, opts: ReadOptions) -> DbResult<Box<dyn KvIterator + '_>> {
+        let cf_handle = get_cf_handle(self, cf_name)?;
+        Ok(Box::new(
+            self.raw_iterator_cf_opt(cf_handle, opts.to_rocksdb()),
+        ))
+    }
+
+    fn write_batch(&self, rows: &HashMap<ColumnFamilyName, Vec<WriteOp>>) -> DbResult<usize> {
+        let mut db_batch = rocksdb::WriteBatch::default();
+        for (cf_name, rows) in rows.iter() {
+            let cf_handle = get_cf_handle(self, cf_name)?;
+            for write_op in rows {
+                match write_op {
+                    WriteOp::Value { key, value } => db_batch.put_cf(cf_handle, key, value),
+                    WriteOp::Deletion { key } => db_batch.delete_cf(cf_handle, key),
+                }
+            }
+        }
+        let serialized_size = db_batch.size_in_bytes();
+
+        self.write_opt(db_batch, &default_write_options())?;
+        Ok(serialized_size)
+    }
+
+    fn flush_cf(&self, cf_name: &str) -> DbResult<()> {
+        Ok(rocksdb::DB::flush_cf(self, get_cf_handle(self, cf_name)?)?)
+    }
+
+    fn get_property(&self, cf_name: &str, property_name: &str) -> DbResult<Option<u64>> {
+        Ok(self.property_int_value_cf(get_cf_handle(self, cf_name)?, property_name)?)
+    }
+
+    fn create_checkpoint(&self, path: &Path) -> DbResult<()> {
+        rocksdb::checkpoint::Checkpoint::new(self)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    fn try_catch_up_with_primary(&self) -> DbResult<()> {
+        Ok(rocksdb::DB::try_catch_up_with_primary(self)?)
+    }
+}
+
+impl<'a> KvIterator for DBRawIterator<'a> {
+    fn seek_to_first(&mut self) {
+        DBRawIterator::seek_to_first(self)
+    }
+
+    fn seek_to_last(&mut self) {
+        DBRawIterator::seek_to_last(self)
+    }
+
+    fn seek(&mut self, key: &[u8]) {
+        DBRawIterator::seek(self, key)
+    }
+
+    fn seek_for_prev(&mut self, key: &[u8]) {
+        DBRawIterator::seek_for_prev(self, key)
+    }
+
+    fn next(&mut self) {
+        DBRawIterator::next(self)
+    }
+
+    fn prev(&mut self) {
+        DBRawIterator::prev(self)
+    }
+
+    fn valid(&self) -> bool {
+        DBRawIterator::valid(self)
+    }
+
+    fn key(&self) -> Option<&[u8]> {
+        DBRawIterator::key(self)
+    }
+
+    fn value(&self) -> Option<&[u8]> {
+        DBRawIterator::value(self)
+    }
+
+    fn status(&self) -> DbResult<()> {
+        Ok(DBRawIterator::status(self)?)
+    }
+}
+
+fn get_cf_handle<'a>(db: &'a rocksdb::DB, cf_name: &str) -> DbResult<&'a rocksdb::ColumnFamily> {
+    db.cf_handle(cf_name)
+        .ok_or_else(|| {
+            format_err!(
+                "DB::cf_handle not found for column family name: {}",
+                cf_name
+            )
+        })
+        .map_err(Into::into)
+}
+
+/// For now we always use synchronous writes. This makes sure that once the operation returns
+/// `Ok(())` the data is persisted even if the machine crashes. In the future we might consider
+/// selectively turning this off for some non-critical writes to improve performance.
+fn default_write_options() -> rocksdb::WriteOptions {
+    let mut opts = rocksdb::WriteOptions::default();
+    opts.set_sync(true);
+    opts
+}
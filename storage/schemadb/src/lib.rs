@@ -9,17 +9,23 @@
 //! access to raw keys and values. This library also enforces a set of specific DB options,
 //! like custom comparators and schema-to-column-family mapping.
 //!
+//! The key-value engine is abstracted by [`KvBackend`], so a DB can also be kept in memory, see
+//! [`DB::open_in_memory`].
+//!
 //! It requires that different kinds of key-value pairs be stored in separate column
 //! families.  To use this library to store a kind of key-value pairs, the user needs to use the
 //! [`define_schema!`] macro to define the schema name, the types of key and value, and name of the
 //! column family.
 
+pub mod backend;
 mod metrics;
+mod read_options;
 #[macro_use]
 pub mod schema;
 pub mod iterator;
 
 use crate::{
+    backend::{InMemoryBackend, KvBackend},
     metrics::{
         APTOS_SCHEMADB_BATCH_COMMIT_BYTES, APTOS_SCHEMADB_BATCH_COMMIT_LATENCY_SECONDS,
         APTOS_SCHEMADB_DELETES_SAMPLED, APTOS_SCHEMADB_GET_BYTES,
@@ -29,23 +35,23 @@ use crate::{
     },
     schema::{KeyCodec, Schema, SeekKeyCodec, ValueCodec},
 };
-use anyhow::format_err;
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
 use aptos_storage_interface::Result as DbResult;
 use iterator::{ScanDirection, SchemaIterator};
 use rand::Rng;
-/// Type alias to `rocksdb::ReadOptions`. See [`rocksdb doc`](https://github.com/pingcap/rust-rocksdb/blob/master/src/rocksdb_options.rs)
+pub use read_options::ReadOptions;
 pub use rocksdb::{
-    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompressionType, Options, ReadOptions,
-    SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBCompressionType, Options, SliceTransform,
+    DEFAULT_COLUMN_FAMILY_NAME,
 };
 use std::{collections::HashMap, iter::Iterator, path::Path};
 
 pub type ColumnFamilyName = &'static str;
 
+/// A write of a [`SchemaBatch`] to a column family, with the key and value encoded.
 #[derive(Debug)]
-enum WriteOp {
+pub enum WriteOp {
     Value { key: Vec<u8>, value: Vec<u8> },
     Deletion { key: Vec<u8> },
 }
@@ -106,7 +112,7 @@ impl SchemaBatch {
 #[derive(Debug)]
 pub struct DB {
     name: String, // for logging
-    inner: rocksdb::DB,
+    inner: Box<dyn KvBackend>,
 }
 
 impl DB {
@@ -139,7 +145,7 @@ impl DB {
         cfds: Vec<rocksdb::ColumnFamilyDescriptor>,
     ) -> DbResult<DB> {
         let inner = rocksdb::DB::open_cf_descriptors(db_opts, path.de_unc(), cfds)?;
-        Ok(Self::log_construct(name, inner))
+        Ok(Self::open_with_backend(name, Box::new(inner)))
     }
 
     /// Open db in readonly mode
@@ -155,7 +161,7 @@ impl DB {
         let inner =
             rocksdb::DB::open_cf_for_read_only(opts, path.de_unc(), cfs, error_if_log_file_exists)?;
 
-        Ok(Self::log_construct(name, inner))
+        Ok(Self::open_with_backend(name, Box::new(inner)))
     }
 
     pub fn open_cf_as_secondary<P: AsRef<Path>>(
@@ -171,11 +177,25 @@ impl DB {
             secondary_path.de_unc(),
             cfs,
         )?;
-        Ok(Self::log_construct(name, inner))
+        Ok(Self::open_with_backend(name, Box::new(inner)))
+    }
+
+    /// Opens a DB kept in memory instead of RocksDB, for tests and benchmarks. The data lives as
+    /// long as a DB is open at the path, or while the DBs are retained with
+    /// [`InMemoryBackend::retain_dbs`], so opening the same path again meanwhile finds it.
+    pub fn open_in_memory(
+        path: impl AsRef<Path>,
+        name: &str,
+        cfs: Vec<ColumnFamilyName>,
+        readonly: bool,
+    ) -> DbResult<DB> {
+        let inner = InMemoryBackend::open(path.as_ref(), &cfs, readonly)?;
+        Ok(Self::open_with_backend(name, Box::new(inner)))
     }
 
-    fn log_construct(name: &str, inner: rocksdb::DB) -> DB {
-        info!(rocksdb_name = name, "Opened RocksDB.");
+    /// Opens a DB on top of any key-value backend.
+    pub fn open_with_backend(name: &str, inner: Box<dyn KvBackend>) -> DB {
+        info!(rocksdb_name = name, "Opened DB.");
         DB {
             name: name.to_string(),
             inner,
@@ -189,9 +209,8 @@ impl DB {
             .start_timer();
 
         let k = <S::Key as KeyCodec<S>>::encode_key(schema_key)?;
-        let cf_handle = self.get_cf_handle(S::COLUMN_FAMILY_NAME)?;
 
-        let result = self.inner.get_cf(cf_handle, k)?;
+        let result = self.inner.get(S::COLUMN_FAMILY_NAME, &k)?;
         APTOS_SCHEMADB_GET_BYTES
             .with_label_values(&[S::COLUMN_FAMILY_NAME])
             .observe(result.as_ref().map_or(0.0, |v| v.len() as f64));
@@ -215,9 +234,8 @@ impl DB {
         opts: ReadOptions,
         direction: ScanDirection,
     ) -> DbResult<SchemaIterator<S>> {
-        let cf_handle = self.get_cf_handle(S::COLUMN_FAMILY_NAME)?;
         Ok(SchemaIterator::new(
-            self.inner.raw_iterator_cf_opt(cf_handle, opts),
+            self.inner.raw_iter(S::COLUMN_FAMILY_NAME, opts)?,
             direction,
         ))
     }
@@ -250,19 +268,7 @@ impl DB {
         let sampling_rate_pct = 1;
         let sampled_kv_bytes = should_sample(sampling_rate_pct);
 
-        let mut db_batch = rocksdb::WriteBatch::default();
-        for (cf_name, rows) in rows_locked.iter() {
-            let cf_handle = self.get_cf_handle(cf_name)?;
-            for write_op in rows {
-                match write_op {
-                    WriteOp::Value { key, value } => db_batch.put_cf(cf_handle, key, value),
-                    WriteOp::Deletion { key } => db_batch.delete_cf(cf_handle, key),
-                }
-            }
-        }
-        let serialized_size = db_batch.size_in_bytes();
-
-        self.inner.write_opt(db_batch, &default_write_options())?;
+        let serialized_size = self.inner.write_batch(&rows_locked)?;
 
         // Bump counters only after DB write succeeds.
         if sampled_kv_bytes {
@@ -291,27 +297,15 @@ impl DB {
         Ok(())
     }
 
-    fn get_cf_handle(&self, cf_name: &str) -> DbResult<&rocksdb::ColumnFamily> {
-        self.inner
-            .cf_handle(cf_name)
-            .ok_or_else(|| {
-                format_err!(
-                    "DB::cf_handle not found for column family name: {}",
-                    cf_name
-                )
-            })
-            .map_err(Into::into)
-    }
-
     /// Flushes memtable data. This is only used for testing `get_approximate_sizes_cf` in unit
     /// tests.
     pub fn flush_cf(&self, cf_name: &str) -> DbResult<()> {
-        Ok(self.inner.flush_cf(self.get_cf_handle(cf_name)?)?)
+        self.inner.flush_cf(cf_name)
     }
 
     pub fn get_property(&self, cf_name: &str, property_name: &str) -> DbResult<u64> {
         self.inner
-            .property_int_value_cf(self.get_cf_handle(cf_name)?, property_name)?
+            .get_property(cf_name, property_name)?
             .ok_or_else(|| {
                 aptos_storage_interface::AptosDbError::Other(
                     format!(
@@ -325,26 +319,21 @@ impl DB {
 
     /// Creates new physical DB checkpoint in directory specified by `path`.
     pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> DbResult<()> {
-        rocksdb::checkpoint::Checkpoint::new(&self.inner)?.create_checkpoint(path)?;
-        Ok(())
+        self.inner.create_checkpoint(path.as_ref())
+    }
+
+    /// Catches up with the primary instance, for a DB opened with `open_cf_as_secondary`.
+    pub fn try_catch_up_with_primary(&self) -> DbResult<()> {
+        self.inner.try_catch_up_with_primary()
     }
 }
 
 impl Drop for DB {
     fn drop(&mut self) {
-        info!(rocksdb_name = self.name, "Dropped RocksDB.");
+        info!(rocksdb_name = self.name, "Dropped DB.");
     }
 }
 
-/// For now we always use synchronous writes. This makes sure that once the operation returns
-/// `Ok(())` the data is persisted even if the machine crashes. In the future we might consider
-/// selectively turning this off for some non-critical writes to improve performance.
-fn default_write_options() -> rocksdb::WriteOptions {
-    let mut opts = rocksdb::WriteOptions::default();
-    opts.set_sync(true);
-    opts
-}
-
 trait DeUnc: AsRef<Path> {
     fn de_unc(&self) -> &Path {
         // `dunce` is needed to "de-UNC" because rocksdb doesn't take Windows UNC paths like `\\?\C:\`
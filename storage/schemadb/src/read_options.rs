@@ -0,0 +1,59 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+/// Options of the iterators of a [`DB`](crate::DB), understood by all the backends. Mirrors the
+/// subset of [`rocksdb::ReadOptions`] in use.
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptions {
+    prefix_same_as_start: bool,
+    total_order_seek: bool,
+    iterate_lower_bound: Option<Vec<u8>>,
+    iterate_upper_bound: Option<Vec<u8>>,
+}
+
+impl ReadOptions {
+    /// Stops the iteration once the prefix of the keys differs from the one of the key the
+    /// iterator was positioned at. Only backends configured with a prefix extractor for the
+    /// column family, i.e. RocksDB, know the prefix of a key, the others ignore the option. Set
+    /// the iterate bounds as well for the same behavior with all the backends.
+    pub fn set_prefix_same_as_start(&mut self, v: bool) {
+        self.prefix_same_as_start = v;
+    }
+
+    /// Seeks and iterates over all the keys in order, even in column families with a prefix
+    /// extractor. Backends without prefix extractors always do.
+    pub fn set_total_order_seek(&mut self, v: bool) {
+        self.total_order_seek = v;
+    }
+
+    /// Sets the smallest key the iterator can be positioned at, inclusive.
+    pub fn set_iterate_lower_bound<K: Into<Vec<u8>>>(&mut self, key: K) {
+        self.iterate_lower_bound = Some(key.into());
+    }
+
+    /// Sets the key the iterator stops before, exclusive.
+    pub fn set_iterate_upper_bound<K: Into<Vec<u8>>>(&mut self, key: K) {
+        self.iterate_upper_bound = Some(key.into());
+    }
+
+    pub(crate) fn iterate_lower_bound(&self) -> Option<&[u8]> {
+        self.iterate_lower_bound.as_deref()
+    }
+
+    pub(crate) fn iterate_upper_bound(&self) -> Option<&[u8]> {
+        self.iterate_upper_bound.as_deref()
+    }
+
+    pub(crate) fn to_rocksdb(&self) -> rocksdb::ReadOptions {
+        let mut opts = rocksdb::ReadOptions::default();
+        opts.set_prefix_same_as_start(self.prefix_same_as_start);
+        opts.set_total_order_seek(self.total_order_seek);
+        if let Some(key) = &self.iterate_lower_bound {
+            opts.set_iterate_lower_bound(key.clone());
+        }
+        if let Some(key) = &self.iterate_upper_bound {
+            opts.set_iterate_upper_bound(key.clone());
+        }
+        opts
+    }
+}
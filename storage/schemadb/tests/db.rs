@@ -4,6 +4,7 @@
 
 use anyhow::Result;
 use aptos_schemadb::{
+    backend::InMemoryBackend,
     define_schema,
     schema::{KeyCodec, Schema, ValueCodec},
     ColumnFamilyName, SchemaBatch, DB,
@@ -88,6 +89,11 @@ fn open_db(dir: &aptos_temppath::TempPath) -> DB {
     DB::open(dir.path(), "test", get_column_families(), &db_opts).expect("Failed to open DB.")
 }
 
+fn open_db_in_memory(dir: &aptos_temppath::TempPath, readonly: bool) -> DB {
+    DB::open_in_memory(dir.path(), "test", get_column_families(), readonly)
+        .expect("Failed to open DB.")
+}
+
 fn open_db_read_only(dir: &aptos_temppath::TempPath) -> DB {
     DB::open_cf_readonly(
         &rocksdb::Options::default(),
@@ -174,7 +180,7 @@ fn test_schema_put_get() {
     );
 }
 
-fn collect_values<S: Schema>(db: &TestDB) -> Vec<(S::Key, S::Value)> {
+fn collect_values<S: Schema>(db: &DB) -> Vec<(S::Key, S::Value)> {
     let mut iter = db
         .iter::<S>(Default::default())
         .expect("Failed to create iterator.");
@@ -323,6 +329,14 @@ fn test_open_as_secondary() {
         db_sec.get::<TestSchema1>(&TestField(0)).unwrap(),
         Some(TestField(0)),
     );
+
+    db.put::<TestSchema1>(&TestField(1), &TestField(1)).unwrap();
+    assert_eq!(db_sec.get::<TestSchema1>(&TestField(1)).unwrap(), None);
+    db_sec.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        db_sec.get::<TestSchema1>(&TestField(1)).unwrap(),
+        Some(TestField(1)),
+    );
 }
 
 #[test]
@@ -389,3 +403,132 @@ fn test_checkpoint() {
         assert_eq!(db.get::<TestSchema1>(&TestField(1)).unwrap(), None);
     }
 }
+
+#[test]
+fn test_in_memory_schema_batch() {
+    let tmpdir = aptos_temppath::TempPath::new();
+    let db = open_db_in_memory(&tmpdir, false);
+
+    let db_batch = SchemaBatch::new();
+    db_batch
+        .put::<TestSchema1>(&TestField(0), &TestField(0))
+        .unwrap();
+    db_batch
+        .put::<TestSchema1>(&TestField(1), &TestField(1))
+        .unwrap();
+    db_batch
+        .put::<TestSchema2>(&TestField(3), &TestField(3))
+        .unwrap();
+    db_batch.delete::<TestSchema2>(&TestField(3)).unwrap();
+    db_batch
+        .put::<TestSchema2>(&TestField(4), &TestField(4))
+        .unwrap();
+    db.write_schemas(db_batch).unwrap();
+
+    assert_eq!(
+        db.get::<TestSchema1>(&TestField(1)).unwrap(),
+        Some(TestField(1)),
+    );
+    assert_eq!(db.get::<TestSchema2>(&TestField(3)).unwrap(), None);
+    assert_eq!(
+        collect_values::<TestSchema1>(&db),
+        gen_expected_values(&[(0, 0), (1, 1)]),
+    );
+    assert_eq!(
+        collect_values::<TestSchema2>(&db),
+        gen_expected_values(&[(4, 4)]),
+    );
+}
+
+#[test]
+fn test_in_memory_reopen() {
+    let tmpdir = aptos_temppath::TempPath::new();
+    assert!(DB::open_in_memory(tmpdir.path(), "test", get_column_families(), true).is_err());
+    {
+        let db = open_db_in_memory(&tmpdir, false);
+        db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+
+        // Other handles share the data while one is alive
+        let reopened = open_db_in_memory(&tmpdir, true);
+        assert_eq!(
+            reopened.get::<TestSchema1>(&TestField(0)).unwrap(),
+            Some(TestField(0)),
+        );
+        assert!(reopened
+            .put::<TestSchema1>(&TestField(1), &TestField(1))
+            .is_err());
+    }
+    // The data is dropped with the last handle
+    assert!(DB::open_in_memory(tmpdir.path(), "test", get_column_families(), true).is_err());
+
+    // Unless the DBs are retained. Retaining in the same test, as it affects the whole process.
+    let retained = InMemoryBackend::retain_dbs();
+    {
+        let db = open_db_in_memory(&tmpdir, false);
+        db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    }
+    {
+        let db = open_db_in_memory(&tmpdir, true);
+        assert_eq!(
+            db.get::<TestSchema1>(&TestField(0)).unwrap(),
+            Some(TestField(0)),
+        );
+    }
+    drop(retained);
+    assert!(DB::open_in_memory(tmpdir.path(), "test", get_column_families(), true).is_err());
+}
+
+#[test]
+fn test_in_memory_report_size() {
+    let tmpdir = aptos_temppath::TempPath::new();
+    let db = open_db_in_memory(&tmpdir, false);
+    for i in 0..10 {
+        db.put::<TestSchema1>(&TestField(i), &TestField(i)).unwrap();
+    }
+
+    assert_eq!(
+        db.get_property("TestCF1", "rocksdb.estimate-num-keys")
+            .unwrap(),
+        10
+    );
+    assert_eq!(
+        db.get_property("TestCF1", "rocksdb.estimate-live-data-size")
+            .unwrap(),
+        80
+    );
+    assert_eq!(
+        db.get_property("TestCF2", "rocksdb.estimate-live-data-size")
+            .unwrap(),
+        0
+    );
+    assert!(db.get_property("TestCF1", "unknown-property").is_err());
+}
+
+#[test]
+fn test_in_memory_checkpoint() {
+    let tmpdir = aptos_temppath::TempPath::new();
+    let checkpoint = aptos_temppath::TempPath::new();
+    let db = open_db_in_memory(&tmpdir, false);
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    db.create_checkpoint(&checkpoint).unwrap();
+    assert!(db.create_checkpoint(&checkpoint).is_err());
+
+    let cp = open_db_in_memory(&checkpoint, false);
+    assert_eq!(
+        cp.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+    cp.put::<TestSchema1>(&TestField(1), &TestField(1)).unwrap();
+    assert_eq!(db.get::<TestSchema1>(&TestField(1)).unwrap(), None);
+
+    // The checkpoint lives as long as the DB it was created from
+    drop(cp);
+    assert_eq!(
+        open_db_in_memory(&checkpoint, true)
+            .get::<TestSchema1>(&TestField(1))
+            .unwrap(),
+        Some(TestField(1)),
+    );
+    drop(db);
+    assert!(DB::open_in_memory(checkpoint.path(), "test", get_column_families(), true).is_err());
+}
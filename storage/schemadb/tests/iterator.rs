@@ -7,7 +7,7 @@ use aptos_schemadb::{
     define_schema,
     iterator::SchemaIterator,
     schema::{KeyCodec, Schema, SeekKeyCodec, ValueCodec},
-    DB,
+    ReadOptions, DB,
 };
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use rocksdb::DEFAULT_COLUMN_FAMILY_NAME;
@@ -86,7 +86,22 @@ impl TestDB {
         db_opts.create_if_missing(true);
         db_opts.create_missing_column_families(true);
         let db = DB::open(tmpdir.path(), "test", column_families, &db_opts).unwrap();
+        Self::populate(tmpdir, db)
+    }
+
+    fn new_in_memory() -> Self {
+        let tmpdir = aptos_temppath::TempPath::new();
+        let column_families = vec![DEFAULT_COLUMN_FAMILY_NAME, TestSchema::COLUMN_FAMILY_NAME];
+        let db = DB::open_in_memory(tmpdir.path(), "test", column_families, false).unwrap();
+        Self::populate(tmpdir, db)
+    }
 
+    /// Returns a DB of each backend, with the same content.
+    fn with_all_backends() -> Vec<Self> {
+        vec![Self::new(), Self::new_in_memory()]
+    }
+
+    fn populate(tmpdir: aptos_temppath::TempPath, db: DB) -> Self {
         db.put::<TestSchema>(&TestKey(1, 0, 0), &TestValue(100))
             .unwrap();
         db.put::<TestSchema>(&TestKey(1, 0, 2), &TestValue(102))
@@ -135,134 +150,159 @@ impl std::ops::Deref for TestDB {
 
 #[test]
 fn test_seek_to_first() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_to_first();
-    assert_eq!(collect_values(iter), [
-        100, 102, 104, 110, 112, 114, 200, 202
-    ]);
-
-    let mut iter = db.rev_iter();
-    iter.seek_to_first();
-    assert_eq!(collect_values(iter), [100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_to_first();
+        assert_eq!(collect_values(iter), [
+            100, 102, 104, 110, 112, 114, 200, 202
+        ]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_to_first();
+        assert_eq!(collect_values(iter), [100]);
+    }
 }
 
 #[test]
 fn test_seek_to_last() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_to_last();
-    assert_eq!(collect_values(iter), [202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek_to_last();
-    assert_eq!(collect_values(iter), [
-        202, 200, 114, 112, 110, 104, 102, 100
-    ]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_to_last();
+        assert_eq!(collect_values(iter), [202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_to_last();
+        assert_eq!(collect_values(iter), [
+            202, 200, 114, 112, 110, 104, 102, 100
+        ]);
+    }
 }
 
 #[test]
 fn test_seek_by_existing_key() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek(&TestKey(1, 1, 0)).unwrap();
-    assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek(&TestKey(1, 1, 0)).unwrap();
-    assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek(&TestKey(1, 1, 0)).unwrap();
+        assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek(&TestKey(1, 1, 0)).unwrap();
+        assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_by_nonexistent_key() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek(&TestKey(1, 1, 1)).unwrap();
-    assert_eq!(collect_values(iter), [112, 114, 200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek(&TestKey(1, 1, 1)).unwrap();
-    assert_eq!(collect_values(iter), [112, 110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek(&TestKey(1, 1, 1)).unwrap();
+        assert_eq!(collect_values(iter), [112, 114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek(&TestKey(1, 1, 1)).unwrap();
+        assert_eq!(collect_values(iter), [112, 110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_for_prev_by_existing_key() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_for_prev(&TestKey(1, 1, 0)).unwrap();
-    assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek_for_prev(&TestKey(1, 1, 0)).unwrap();
-    assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_for_prev(&TestKey(1, 1, 0)).unwrap();
+        assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_for_prev(&TestKey(1, 1, 0)).unwrap();
+        assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_for_prev_by_nonexistent_key() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_for_prev(&TestKey(1, 1, 1)).unwrap();
-    assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek_for_prev(&TestKey(1, 1, 1)).unwrap();
-    assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_for_prev(&TestKey(1, 1, 1)).unwrap();
+        assert_eq!(collect_values(iter), [110, 112, 114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_for_prev(&TestKey(1, 1, 1)).unwrap();
+        assert_eq!(collect_values(iter), [110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_by_1prefix() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek(&KeyPrefix1(2)).unwrap();
-    assert_eq!(collect_values(iter), [200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek(&KeyPrefix1(2)).unwrap();
-    assert_eq!(collect_values(iter), [200, 114, 112, 110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek(&KeyPrefix1(2)).unwrap();
+        assert_eq!(collect_values(iter), [200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek(&KeyPrefix1(2)).unwrap();
+        assert_eq!(collect_values(iter), [200, 114, 112, 110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_for_prev_by_1prefix() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_for_prev(&KeyPrefix1(2)).unwrap();
-    assert_eq!(collect_values(iter), [114, 200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek_for_prev(&KeyPrefix1(2)).unwrap();
-    assert_eq!(collect_values(iter), [114, 112, 110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_for_prev(&KeyPrefix1(2)).unwrap();
+        assert_eq!(collect_values(iter), [114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_for_prev(&KeyPrefix1(2)).unwrap();
+        assert_eq!(collect_values(iter), [114, 112, 110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_by_2prefix() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek(&KeyPrefix2(2, 0)).unwrap();
-    assert_eq!(collect_values(iter), [200, 202]);
-
-    let mut iter = db.rev_iter();
-    iter.seek(&KeyPrefix2(2, 0)).unwrap();
-    assert_eq!(collect_values(iter), [200, 114, 112, 110, 104, 102, 100]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek(&KeyPrefix2(2, 0)).unwrap();
+        assert_eq!(collect_values(iter), [200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek(&KeyPrefix2(2, 0)).unwrap();
+        assert_eq!(collect_values(iter), [200, 114, 112, 110, 104, 102, 100]);
+    }
 }
 
 #[test]
 fn test_seek_for_prev_by_2prefix() {
-    let db = TestDB::new();
-
-    let mut iter = db.iter();
-    iter.seek_for_prev(&KeyPrefix2(2, 0)).unwrap();
-    assert_eq!(collect_values(iter), [114, 200, 202]);
+    for db in TestDB::with_all_backends() {
+        let mut iter = db.iter();
+        iter.seek_for_prev(&KeyPrefix2(2, 0)).unwrap();
+        assert_eq!(collect_values(iter), [114, 200, 202]);
+
+        let mut iter = db.rev_iter();
+        iter.seek_for_prev(&KeyPrefix2(2, 0)).unwrap();
+        assert_eq!(collect_values(iter), [114, 112, 110, 104, 102, 100]);
+    }
+}
 
-    let mut iter = db.rev_iter();
-    iter.seek_for_prev(&KeyPrefix2(2, 0)).unwrap();
-    assert_eq!(collect_values(iter), [114, 112, 110, 104, 102, 100]);
+#[test]
+fn test_iterate_bounds() {
+    for db in TestDB::with_all_backends() {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_lower_bound(KeyPrefix2(1, 1).encode_seek_key().unwrap());
+        opts.set_iterate_upper_bound(KeyPrefix1(2).encode_seek_key().unwrap());
+
+        let mut iter = db.db.iter::<TestSchema>(opts.clone()).unwrap();
+        iter.seek_to_first();
+        assert_eq!(collect_values(iter), [110, 112, 114]);
+
+        let mut iter = db.db.iter::<TestSchema>(opts.clone()).unwrap();
+        iter.seek(&KeyPrefix2(1, 1)).unwrap();
+        assert_eq!(collect_values(iter), [110, 112, 114]);
+
+        let mut iter = db.db.rev_iter::<TestSchema>(opts.clone()).unwrap();
+        iter.seek_to_last();
+        assert_eq!(collect_values(iter), [114, 112, 110]);
+
+        let mut iter = db.db.rev_iter::<TestSchema>(opts).unwrap();
+        iter.seek_for_prev(&TestKey(1, 1, 2)).unwrap();
+        assert_eq!(collect_values(iter), [112, 110]);
+    }
 }
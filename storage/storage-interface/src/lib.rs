@@ -48,10 +48,11 @@ mod executed_trees;
 mod metrics;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock;
+pub mod pruner_status;
 pub mod state_delta;
 pub mod state_view;
 
-use crate::state_delta::StateDelta;
+use crate::{pruner_status::PrunerStatus, state_delta::StateDelta};
 use aptos_scratchpad::SparseMerkleTree;
 pub use aptos_types::block_info::BlockHeight;
 pub use errors::AptosDbError;
@@ -325,6 +326,22 @@ pub trait DbReader: Send + Sync {
             ledger_version: Version,
         ) -> Result<AccountTransactionsWithProof>;
 
+        /// Returns the list of transactions touching an account with `address`, i.e. signed
+        /// by it, emitting events under its event keys or writing its resources, starting at
+        /// version `start_version` in the given `order`. Will return no more than `limit`
+        /// transactions. Will ignore transactions with `txn.version > ledger_version`.
+        /// Optionally fetch events for each transaction when `include_events` is `true`.
+        /// Requires the account transactions index of the internal indexer.
+        fn get_account_touched_transactions(
+            &self,
+            address: AccountAddress,
+            start_version: Version,
+            order: Order,
+            limit: u64,
+            include_events: bool,
+            ledger_version: Version,
+        ) -> Result<AccountTransactionsWithProof>;
+
         /// Returns proof of new state for a given ledger info with signatures relative to version known
         /// to client
         fn get_state_proof_with_ledger_info(
@@ -359,6 +376,24 @@ pub trait DbReader: Send + Sync {
             version: Version,
         ) -> Result<Option<(Version, StateValue)>>;
 
+        /// Returns the first version of the state values that is still available, e.g., for
+        /// [`DbReader::get_state_value_history`]. State values before it may have been pruned.
+        fn get_first_state_value_version(&self) -> Result<Option<Version>>;
+
+        /// Returns the versions at which the value of `state_key` changed, each with the value
+        /// written at that version or `None` if it was deleted, starting at version
+        /// `start_version` in the given `order`. Will return no more than `limit` changes. Will
+        /// ignore changes with `version > ledger_version`. Only changes at or after the min
+        /// readable version of the state kv pruner are available.
+        fn get_state_value_history(
+            &self,
+            state_key: &StateKey,
+            start_version: Version,
+            order: Order,
+            limit: u64,
+            ledger_version: Version,
+        ) -> Result<Vec<(Version, Option<StateValue>)>>;
+
         /// Returns the proof of the given state key and version.
         fn get_state_proof_by_version_ext(
             &self,
@@ -455,6 +490,13 @@ pub trait DbReader: Send + Sync {
         /// Returns whether the internal indexer DB has been enabled or not
         fn indexer_enabled(&self) -> bool;
 
+        /// Returns whether the account transactions index of the internal indexer has been
+        /// enabled or not
+        fn account_transactions_index_enabled(&self) -> bool;
+
+        /// Returns the status of each of the DB pruners.
+        fn get_pruner_statuses(&self) -> Result<Vec<PrunerStatus>>;
+
         /// Returns state storage usage at the end of an epoch.
         fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage>;
     ); // end delegated
@@ -572,6 +614,13 @@ pub trait DbWriter: Send + Sync {
     ) -> Result<()> {
         unimplemented!()
     }
+
+    /// Pauses or resumes deleting data by the pruner with the given name, or by all enabled
+    /// pruners if no name is given. The min readable versions of paused pruners are held, so
+    /// reads of the data they keep don't fail. Pausing doesn't survive a restart.
+    fn set_pruner_paused(&self, pruner_name: Option<&str>, paused: bool) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
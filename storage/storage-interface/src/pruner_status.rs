@@ -0,0 +1,25 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// The state of one of the DB pruners, e.g., the ledger pruner.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PrunerStatus {
+    pub name: String,
+    pub enabled: bool,
+    /// A paused pruner keeps advancing its target version but doesn't delete anything.
+    pub paused: bool,
+    pub prune_window: Version,
+    /// Data before this version is not readable, though it might not have been deleted yet.
+    pub min_readable_version: Version,
+    /// Data before this version has been deleted.
+    pub progress: Version,
+    /// The version the pruner is deleting data up to.
+    pub target_version: Version,
+    /// The number of versions between `progress` and `target_version`.
+    pub backlog: u64,
+    /// The number of versions pruned per second, measured over the last few seconds.
+    pub versions_per_sec: f64,
+}
@@ -369,6 +369,32 @@ pub enum ProposerElectionType {
     // or default proposer if round proposer not
     // specified
     RoundProposer(HashMap<Round, AccountAddress>),
+    // Stake weighted random selection, seeded from on-chain randomness,
+    // excluding validators that recently failed their rounds
+    StakeWeighted(StakeWeightedConfig),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StakeWeightedConfig {
+    // Number of most recent rounds whose failed proposers are considered
+    pub exclusion_window_rounds: u64,
+    // Number of failed rounds within the window after which a validator is
+    // excluded from proposing, until enough of them leave the window
+    pub exclusion_failure_threshold: u32,
+    // Maximum voting power that can be excluded at once, integer values
+    // representing percentages, i.e. 12 is 12%. Validators with the most
+    // failures are excluded first.
+    pub max_excluded_voting_power_percent: u32,
+}
+
+impl Default for StakeWeightedConfig {
+    fn default() -> Self {
+        Self {
+            exclusion_window_rounds: 100,
+            exclusion_failure_threshold: 3,
+            max_excluded_voting_power_percent: 20,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -428,6 +454,53 @@ pub struct ProposerAndVoterConfig {
 pub enum AnchorElectionMode {
     RoundRobin,
     LeaderReputation(LeaderReputationType),
+    // Anchor election based on DAG signals: failed anchor rounds, strong links
+    // from committed anchors, and how long the validator's anchors took.
+    DagReputation(DagReputationConfig),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DagReputationConfig {
+    // Selection weight for active validators with anchor failures below threshold
+    pub active_weight: u64,
+    // Selection weight for validators that neither committed an anchor nor were
+    // strongly linked in the window
+    pub inactive_weight: u64,
+    // Selection weight for validators with anchor failures above threshold
+    pub failed_weight: u64,
+    // Threshold of failures in the rounds validator was elected as anchor,
+    // integer values representing percentages, i.e. 12 is 12%.
+    pub failure_threshold_percent: u32,
+    // Percentage of the committed anchors in the window that must strongly link
+    // a validator's node for it to keep its full weight, below that the weight
+    // is scaled down proportionally.
+    pub min_strong_link_coverage_percent: u32,
+    // A validator whose anchors take longer than this percentage of the median
+    // anchor latency is considered slow.
+    pub slow_latency_threshold_percent: u32,
+    // Percentage of its weight a slow validator keeps.
+    pub slow_weight_percent: u32,
+    // Window into commit history considered, multiplier on top of number of
+    // validators
+    pub window_num_validators_multiplier: usize,
+    // Flag whether to use voting power as multiplier to the weights
+    pub weight_by_voting_power: bool,
+}
+
+impl Default for DagReputationConfig {
+    fn default() -> Self {
+        Self {
+            active_weight: 1000,
+            inactive_weight: 10,
+            failed_weight: 1,
+            failure_threshold_percent: 10,
+            min_strong_link_coverage_percent: 80,
+            slow_latency_threshold_percent: 150,
+            slow_weight_percent: 10,
+            window_num_validators_multiplier: 10,
+            weight_by_voting_power: true,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -478,6 +551,43 @@ mod test {
         bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap();
     }
 
+    #[test]
+    fn test_stake_weighted_config_serialization() {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type: ProposerElectionType::StakeWeighted(
+                StakeWeightedConfig::default(),
+            ),
+            ..ConsensusConfigV1::default()
+        });
+
+        let s = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+        let s = bcs::to_bytes(&config).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_dag_reputation_config_serialization() {
+        let config = DagConsensusConfigV1 {
+            anchor_election_mode: AnchorElectionMode::DagReputation(DagReputationConfig::default()),
+            ..DagConsensusConfigV1::default()
+        };
+
+        let s = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<DagConsensusConfigV1>(&s).unwrap(),
+            config
+        );
+        let s = bcs::to_bytes(&config).unwrap();
+        assert_eq!(bcs::from_bytes::<DagConsensusConfigV1>(&s).unwrap(), config);
+    }
+
     #[test]
     fn test_config_serialization_non_default() {
         let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {